@@ -4,31 +4,43 @@ use anyhow::Result;
 use breez_sdk_core::lnurl::pay::{LnUrlPayResult, LnUrlPaySuccessData};
 use breez_sdk_core::{
     error::*, mnemonic_to_seed as sdk_mnemonic_to_seed, parse as sdk_parse_input,
-    parse_invoice as sdk_parse_invoice, AesSuccessActionDataDecrypted, AesSuccessActionDataResult,
-    BackupFailedData, BackupStatus, BitcoinAddressData, BreezEvent, BreezServices,
-    BuyBitcoinProvider, BuyBitcoinRequest, BuyBitcoinResponse, ChannelState, CheckMessageRequest,
-    CheckMessageResponse, ClosedChannelPaymentDetails, Config, ConfigureNodeRequest,
-    ConnectRequest, CurrencyInfo, EnvironmentType, EventListener, FeeratePreset, FiatCurrency,
-    GreenlightCredentials, GreenlightDeviceCredentials, GreenlightNodeConfig, HealthCheckStatus,
-    InputType, InvoicePaidDetails, LNInvoice, ListPaymentsRequest, ListSwapsRequest,
-    LnPaymentDetails, LnUrlAuthError, LnUrlAuthRequestData, LnUrlCallbackStatus, LnUrlErrorData,
-    LnUrlPayError, LnUrlPayErrorData, LnUrlPayRequest, LnUrlPayRequestData, LnUrlWithdrawError,
-    LnUrlWithdrawRequest, LnUrlWithdrawRequestData, LnUrlWithdrawResult, LnUrlWithdrawSuccessData,
-    LocaleOverrides, LocalizedName, LogEntry, LogStream, LspInformation, MessageSuccessActionData,
-    MetadataFilter, MetadataItem, Network, NodeConfig, NodeCredentials, NodeState,
-    OnchainPaymentLimitsResponse, OpenChannelFeeRequest, OpenChannelFeeResponse, OpeningFeeParams,
-    OpeningFeeParamsMenu, PayOnchainRequest, PayOnchainResponse, Payment, PaymentDetails,
-    PaymentFailedData, PaymentStatus, PaymentType, PaymentTypeFilter, PrepareOnchainPaymentRequest,
-    PrepareOnchainPaymentResponse, PrepareRedeemOnchainFundsRequest,
-    PrepareRedeemOnchainFundsResponse, PrepareRefundRequest, PrepareRefundResponse, Rate,
-    ReceiveOnchainRequest, ReceivePaymentRequest, ReceivePaymentResponse, RecommendedFees,
-    RedeemOnchainFundsRequest, RedeemOnchainFundsResponse, RefundRequest, RefundResponse,
-    ReportIssueRequest, ReportPaymentFailureDetails, ReverseSwapFeesRequest, ReverseSwapInfo,
-    ReverseSwapPairInfo, ReverseSwapStatus, RouteHint, RouteHintHop, SendPaymentRequest,
-    SendPaymentResponse, SendSpontaneousPaymentRequest, ServiceHealthCheckResponse,
-    SignMessageRequest, SignMessageResponse, StaticBackupRequest, StaticBackupResponse,
-    SuccessActionProcessed, SwapAmountType, SwapInfo, SwapStatus, Symbol, TlvEntry,
-    UnspentTransactionOutput, UrlSuccessActionData,
+    parse_invoice as sdk_parse_invoice, AddContactRequest, AddRecurringPaymentRequest,
+    AesSuccessActionDataDecrypted, AesSuccessActionDataResult, AppKey, AppKeyEcdhRequest,
+    AppKeyEcdhResponse, AppPublicKey, AppSignatureScheme, BackupFailedData, BackupStatus,
+    BitcoinAddressData, BreezEvent, BreezServices, BuyBitcoinProvider, BuyBitcoinRequest,
+    BuyBitcoinResponse, ChannelState, CheckMessageRequest, CheckMessageResponse,
+    ClosedChannelPaymentDetails, Config, ConfigureNodeRequest, ConnectRequest,
+    ConnectWatchOnlyRequest, Contact, CreateHoldInvoiceRequest, CurrencyInfo, EnvironmentType,
+    EventListener, ExportFormat, ExportRequest, ExportResponse, FeeratePreset, FiatAmount,
+    FiatCurrency, FiatValuationRequest, FiatValuationResponse, GreenlightCredentials,
+    GreenlightDevice, GreenlightDeviceCredentials, GreenlightNodeConfig, HealthCheckStatus,
+    HistoricalRate, HoldInvoice, HoldInvoiceStatus, InputType, Invoice, InvoicePaidDetails,
+    InvoiceStatus, LNInvoice, LdkChainSource, LdkGossipSource, LdkLspConfig, LdkNodeConfig,
+    ListInvoicesRequest, ListPaymentsRequest, ListSwapsRequest, LnPaymentDetails, LnUrlAuthError,
+    LnUrlAuthRequestData, LnUrlCallbackStatus, LnUrlErrorData, LnUrlPayCurrency,
+    LnUrlPayCurrencyRequest, LnUrlPayError, LnUrlPayErrorData, LnUrlPayRequest,
+    LnUrlPayRequestData, LnUrlWithdrawError, LnUrlWithdrawRequest, LnUrlWithdrawRequestData,
+    LnUrlWithdrawResult, LnUrlWithdrawSuccessData, LnUrlZapRequest, LocaleOverrides, LocalizedName,
+    LogEntry, LogStream, LspInformation, MessageSuccessActionData, MetadataFilter, MetadataItem,
+    Network, NodeConfig, NodeCredentials, NodeState, OnchainPaymentLimitsResponse,
+    OpenChannelFeeRequest, OpenChannelFeeResponse, OpeningFeeParams, OpeningFeeParamsMenu,
+    PayOnchainRequest, PayOnchainResponse, Payment, PaymentCursor, PaymentDetails,
+    PaymentFailedData, PaymentFiatValue, PaymentStatsBucket, PaymentStatus, PaymentType,
+    PaymentTypeFilter, PrepareOnchainPaymentRequest, PrepareOnchainPaymentResponse,
+    PrepareRedeemOnchainFundsRequest, PrepareRedeemOnchainFundsResponse, PrepareRefundRequest,
+    PrepareRefundResponse, Rate, ReceiveFiatPaymentRequest, ReceiveOnchainRequest,
+    ReceivePaymentRequest, ReceivePaymentResponse, RecommendedFees, RecurringPayment,
+    RecurringPaymentAmount, RecurringPaymentAttemptedDetails, RecurringPaymentDestination,
+    RecurringPaymentInterval, RecurringPaymentRun, RedeemOnchainFundsRequest,
+    RedeemOnchainFundsResponse, RefundRequest, RefundResponse, RegisterDeviceRequest,
+    RegisterDeviceResponse, ReportIssueRequest, ReportPaymentFailureDetails,
+    ReverseSwapFeesRequest, ReverseSwapInfo, ReverseSwapPairInfo, ReverseSwapStatus, RouteHint,
+    RouteHintHop, SendPaymentRequest, SendPaymentResponse, SendSpontaneousPaymentRequest,
+    ServiceHealthCheckResponse, SignMessageRequest, SignMessageResponse, SignWithAppKeyRequest,
+    SignWithAppKeyResponse, SpendingPolicy, StaticBackupRequest, StaticBackupResponse,
+    StatsInterval, StatsRequest, StatsResponse, SuccessActionProcessed, SwapAmountType, SwapInfo,
+    SwapStatus, Symbol, TlvEntry, UnspentTransactionOutput, UpdateContactRequest,
+    UrlSuccessActionData, ZapRequestData,
 };
 use log::{Level, LevelFilter, Metadata, Record};
 use once_cell::sync::{Lazy, OnceCell};
@@ -107,6 +119,17 @@ pub fn connect(
     })
 }
 
+pub fn connect_watch_only(
+    req: ConnectWatchOnlyRequest,
+    event_listener: Box<dyn EventListener>,
+) -> Result<Arc<BlockingBreezServices>, ConnectError> {
+    rt().block_on(async move {
+        let breez_services = BreezServices::connect_watch_only(req, event_listener).await?;
+
+        Ok(Arc::new(BlockingBreezServices { breez_services }))
+    })
+}
+
 /// If used, this must be called before `connect`
 pub fn set_log_stream(log_stream: Box<dyn LogStream>) -> SdkResult<()> {
     LOG_INIT.set(true).map_err(|_| SdkError::Generic {
@@ -150,10 +173,60 @@ impl BlockingBreezServices {
         rt().block_on(self.breez_services.receive_payment(req))
     }
 
+    pub fn receive_fiat_payment(
+        &self,
+        req: ReceiveFiatPaymentRequest,
+    ) -> Result<ReceivePaymentResponse, ReceivePaymentError> {
+        rt().block_on(self.breez_services.receive_fiat_payment(req))
+    }
+
+    pub fn list_invoices(&self, req: ListInvoicesRequest) -> SdkResult<Vec<Invoice>> {
+        rt().block_on(self.breez_services.list_invoices(req))
+    }
+
+    pub fn create_hold_invoice(
+        &self,
+        req: CreateHoldInvoiceRequest,
+    ) -> Result<HoldInvoice, ReceivePaymentError> {
+        rt().block_on(self.breez_services.create_hold_invoice(req))
+    }
+
+    pub fn settle_invoice(&self, preimage: Vec<u8>) -> SdkResult<()> {
+        rt().block_on(self.breez_services.settle_invoice(preimage))
+    }
+
+    pub fn cancel_invoice(&self, payment_hash: String) -> SdkResult<()> {
+        rt().block_on(self.breez_services.cancel_invoice(payment_hash))
+    }
+
+    pub fn list_hold_invoices(&self) -> SdkResult<Vec<HoldInvoice>> {
+        rt().block_on(self.breez_services.list_hold_invoices())
+    }
+
+    pub fn take_over_node_lock(&self) -> SdkResult<()> {
+        rt().block_on(self.breez_services.take_over_node_lock())
+    }
+
     pub fn node_credentials(&self) -> SdkResult<Option<NodeCredentials>> {
         rt().block_on(self.breez_services.node_credentials())
     }
 
+    pub fn watch_only_credentials(&self) -> SdkResult<NodeCredentials> {
+        rt().block_on(self.breez_services.watch_only_credentials())
+    }
+
+    pub fn backup_encryption_key(&self) -> SdkResult<Vec<u8>> {
+        rt().block_on(self.breez_services.backup_encryption_key())
+    }
+
+    pub fn list_devices(&self) -> SdkResult<Vec<GreenlightDevice>> {
+        rt().block_on(self.breez_services.list_devices())
+    }
+
+    pub fn register_device(&self, req: RegisterDeviceRequest) -> SdkResult<RegisterDeviceResponse> {
+        rt().block_on(self.breez_services.register_device(req))
+    }
+
     pub fn node_info(&self) -> SdkResult<NodeState> {
         self.breez_services.node_info()
     }
@@ -166,6 +239,21 @@ impl BlockingBreezServices {
         rt().block_on(self.breez_services.check_message(req))
     }
 
+    pub fn app_public_key(&self, key: AppKey) -> SdkResult<AppPublicKey> {
+        rt().block_on(self.breez_services.app_public_key(key))
+    }
+
+    pub fn sign_with_app_key(
+        &self,
+        req: SignWithAppKeyRequest,
+    ) -> SdkResult<SignWithAppKeyResponse> {
+        rt().block_on(self.breez_services.sign_with_app_key(req))
+    }
+
+    pub fn app_key_ecdh(&self, req: AppKeyEcdhRequest) -> SdkResult<AppKeyEcdhResponse> {
+        rt().block_on(self.breez_services.app_key_ecdh(req))
+    }
+
     pub fn backup_status(&self) -> SdkResult<BackupStatus> {
         self.breez_services.backup_status()
     }
@@ -178,10 +266,77 @@ impl BlockingBreezServices {
         rt().block_on(self.breez_services.list_payments(req))
     }
 
+    pub fn payment_stats(&self, req: StatsRequest) -> SdkResult<StatsResponse> {
+        rt().block_on(self.breez_services.payment_stats(req))
+    }
+
+    pub fn export_payments(&self, req: ExportRequest) -> SdkResult<ExportResponse> {
+        rt().block_on(self.breez_services.export_payments(req))
+    }
+
+    pub fn add_contact(&self, req: AddContactRequest) -> SdkResult<Contact> {
+        rt().block_on(self.breez_services.add_contact(req))
+    }
+
+    pub fn update_contact(&self, req: UpdateContactRequest) -> SdkResult<Contact> {
+        rt().block_on(self.breez_services.update_contact(req))
+    }
+
+    pub fn delete_contact(&self, id: String) -> SdkResult<()> {
+        rt().block_on(self.breez_services.delete_contact(id))
+    }
+
+    pub fn get_contact(&self, id: String) -> SdkResult<Option<Contact>> {
+        rt().block_on(self.breez_services.get_contact(id))
+    }
+
+    pub fn list_contacts(&self) -> SdkResult<Vec<Contact>> {
+        rt().block_on(self.breez_services.list_contacts())
+    }
+
+    pub fn add_recurring_payment(
+        &self,
+        req: AddRecurringPaymentRequest,
+    ) -> SdkResult<RecurringPayment> {
+        rt().block_on(self.breez_services.add_recurring_payment(req))
+    }
+
+    pub fn list_recurring_payments(&self) -> SdkResult<Vec<RecurringPayment>> {
+        rt().block_on(self.breez_services.list_recurring_payments())
+    }
+
+    pub fn set_recurring_payment_paused(
+        &self,
+        id: String,
+        paused: bool,
+    ) -> SdkResult<RecurringPayment> {
+        rt().block_on(self.breez_services.set_recurring_payment_paused(id, paused))
+    }
+
+    pub fn delete_recurring_payment(&self, id: String) -> SdkResult<()> {
+        rt().block_on(self.breez_services.delete_recurring_payment(id))
+    }
+
+    pub fn list_recurring_payment_runs(&self, id: String) -> SdkResult<Vec<RecurringPaymentRun>> {
+        rt().block_on(self.breez_services.list_recurring_payment_runs(id))
+    }
+
+    pub fn set_spending_policy(&self, policy: Option<SpendingPolicy>) -> SdkResult<()> {
+        rt().block_on(self.breez_services.set_spending_policy(policy))
+    }
+
+    pub fn spending_policy(&self) -> SdkResult<Option<SpendingPolicy>> {
+        rt().block_on(self.breez_services.spending_policy())
+    }
+
     pub fn payment_by_hash(&self, hash: String) -> SdkResult<Option<Payment>> {
         rt().block_on(self.breez_services.payment_by_hash(hash))
     }
 
+    pub fn verify_lnurl_payment(&self, payment_hash: String) -> SdkResult<Payment> {
+        rt().block_on(self.breez_services.verify_lnurl_payment(payment_hash))
+    }
+
     pub fn set_payment_metadata(&self, hash: String, metadata: String) -> SdkResult<()> {
         rt().block_on(self.breez_services.set_payment_metadata(hash, metadata))
     }
@@ -190,6 +345,28 @@ impl BlockingBreezServices {
         rt().block_on(self.breez_services.lnurl_pay(req))
     }
 
+    pub fn pay_lnurl_in_currency(
+        &self,
+        req: LnUrlPayCurrencyRequest,
+    ) -> Result<LnUrlPayResult, LnUrlPayError> {
+        rt().block_on(self.breez_services.lnurl_pay_in_currency(req))
+    }
+
+    pub fn zap_lnurl(&self, req: LnUrlZapRequest) -> Result<LnUrlPayResult, LnUrlPayError> {
+        rt().block_on(self.breez_services.lnurl_zap(req))
+    }
+
+    pub fn link_zap_receipt(
+        &self,
+        payment_hash: String,
+        zap_receipt: String,
+    ) -> SdkResult<Payment> {
+        rt().block_on(
+            self.breez_services
+                .link_zap_receipt(payment_hash, zap_receipt),
+        )
+    }
+
     pub fn withdraw_lnurl(
         &self,
         req: LnUrlWithdrawRequest,
@@ -219,6 +396,18 @@ impl BlockingBreezServices {
         rt().block_on(self.breez_services.fetch_fiat_rates())
     }
 
+    pub fn payment_fiat_value(
+        &self,
+        payment_id: String,
+        currency: String,
+    ) -> SdkResult<Option<PaymentFiatValue>> {
+        rt().block_on(self.breez_services.payment_fiat_value(payment_id, currency))
+    }
+
+    pub fn fiat_valuation(&self, req: FiatValuationRequest) -> SdkResult<FiatValuationResponse> {
+        rt().block_on(self.breez_services.fiat_valuation(req))
+    }
+
     pub fn list_fiat_currencies(&self) -> SdkResult<Vec<FiatCurrency>> {
         rt().block_on(self.breez_services.list_fiat_currencies())
     }
@@ -791,11 +791,38 @@ pub struct LnUrlPayRequestData {
     /// See <https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki>
     pub nostr_pubkey: Option<String>,
 
+    /// Currencies the endpoint accepts amounts in, besides millisatoshis. See
+    /// [crate::prelude::LnUrlPayCurrencyRequest].
+    ///
+    /// See <https://github.com/lnurl/luds/pull/251>
+    pub currencies: Option<Vec<LnUrlPayCurrency>>,
+
     /// If sending to a LN Address, this will be filled.
     #[serde(skip)]
     pub ln_address: Option<String>,
 }
 
+/// A currency advertised by a LNURL-pay endpoint, in which the payer can denominate the amount
+#[derive(Clone, Deserialize, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnUrlPayCurrency {
+    /// The currency code, e.g. `USD`
+    pub code: String,
+    pub name: String,
+    pub symbol: String,
+    /// Number of decimal places of the currency, e.g. 2 for cents
+    pub decimals: u32,
+    /// Millisatoshis per smallest unit of the currency, at the endpoint's current rate
+    pub multiplier: f64,
+}
+
+impl LnUrlPayCurrency {
+    /// Converts an amount, in the smallest unit of the currency, to millisatoshis
+    pub fn to_msat(&self, amount: u64) -> u64 {
+        (amount as f64 * self.multiplier).round() as u64
+    }
+}
+
 impl LnUrlPayRequestData {
     /// The minimum amount, in sats, accepted by this LNURL-pay endpoint
     pub fn min_sendable_sats(&self) -> u64 {
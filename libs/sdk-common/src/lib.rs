@@ -26,6 +26,8 @@ pub use lightning;
 pub use lightning_invoice;
 #[cfg(feature = "liquid")]
 pub use lightning_with_bolt12;
+#[cfg(feature = "nwc")]
+pub use nostr_sdk;
 
 // We don't include grpc::* in the prelude exports, to force callers to use the grpc path prefix.
 #[rustfmt::skip]
@@ -42,14 +44,24 @@ pub mod prelude {
     pub use crate::liquid::*;
     #[cfg(feature = "nwc")]
     pub use crate::nwc::*;
+    #[cfg(feature = "nwc")]
+    pub use crate::nwc::client::*;
+    #[cfg(feature = "nwc")]
+    pub use crate::nwc::error::*;
+    #[cfg(feature = "nwc")]
+    pub use crate::nwc::model::*;
     pub use crate::lnurl::error::*;
     pub use crate::lnurl::model::*;
     pub use crate::lnurl::specs::auth::model::*;
     pub use crate::lnurl::specs::auth::*;
     pub use crate::lnurl::specs::pay::model::*;
     pub use crate::lnurl::specs::pay::*;
+    pub use crate::lnurl::specs::pay_server::model::*;
+    pub use crate::lnurl::specs::pay_server::*;
     pub use crate::lnurl::specs::withdraw::model::*;
     pub use crate::lnurl::specs::withdraw::*;
+    pub use crate::lnurl::specs::zap::model::*;
+    pub use crate::lnurl::specs::zap::*;
     pub use crate::lnurl::*;
     pub use crate::model::*;
     #[cfg(feature = "test-utils")]
@@ -1,3 +1,5 @@
 pub(crate) mod auth;
 pub(crate) mod pay;
+pub(crate) mod pay_server;
 pub(crate) mod withdraw;
+pub(crate) mod zap;
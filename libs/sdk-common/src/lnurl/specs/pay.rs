@@ -1,5 +1,8 @@
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
+use bitcoin::hashes::{sha256, Hash};
+
 use crate::prelude::*;
 
 pub type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
@@ -26,7 +29,73 @@ pub async fn validate_lnurl_pay<C: RestClient + ?Sized>(
     )?;
 
     let callback_url = build_pay_callback_url(user_amount_msat, comment, req_data)?;
-    let (response, _) = rest_client.get(&callback_url).await?;
+    validate_lnurl_pay_callback(
+        rest_client,
+        &callback_url,
+        user_amount_msat..=user_amount_msat,
+        req_data,
+        network,
+        validate_success_action_url,
+    )
+    .await
+}
+
+/// Like [validate_lnurl_pay], but with `amount` in the smallest unit of `currency`, one of the
+/// [LnUrlPayRequestData::currencies] advertised by the endpoint.
+///
+/// The endpoint converts the amount at its own rate, so the invoice amount may deviate from the
+/// advertised multiplier by up to `max_slippage_percent`.
+#[allow(clippy::too_many_arguments)]
+pub async fn validate_lnurl_pay_in_currency<C: RestClient + ?Sized>(
+    rest_client: &C,
+    amount: u64,
+    currency: &LnUrlPayCurrency,
+    max_slippage_percent: f64,
+    comment: &Option<String>,
+    req_data: &LnUrlPayRequestData,
+    network: Network,
+    validate_success_action_url: Option<bool>,
+) -> LnUrlResult<ValidatedCallbackResponse> {
+    ensure_sdk!(amount > 0, LnUrlError::generic("Amount must be positive"));
+    ensure_sdk!(
+        max_slippage_percent >= 0.0,
+        LnUrlError::generic("Slippage must not be negative")
+    );
+
+    let estimated_amount_msat = currency.to_msat(amount);
+    validate_user_input(
+        estimated_amount_msat,
+        comment,
+        req_data.min_sendable,
+        req_data.max_sendable,
+        req_data.comment_allowed,
+    )?;
+
+    let slippage_msat = (estimated_amount_msat as f64 * max_slippage_percent / 100.0) as u64;
+    let callback_url =
+        build_callback_url(&format!("{amount}.{}", currency.code), comment, req_data)?;
+    validate_lnurl_pay_callback(
+        rest_client,
+        &callback_url,
+        estimated_amount_msat.saturating_sub(slippage_msat)
+            ..=estimated_amount_msat.saturating_add(slippage_msat),
+        req_data,
+        network,
+        validate_success_action_url,
+    )
+    .await
+}
+
+/// Calls the LNURL-pay callback and validates its response, including the returned invoice.
+pub(crate) async fn validate_lnurl_pay_callback<C: RestClient + ?Sized>(
+    rest_client: &C,
+    callback_url: &str,
+    invoice_amount_msat: RangeInclusive<u64>,
+    req_data: &LnUrlPayRequestData,
+    network: Network,
+    validate_success_action_url: Option<bool>,
+) -> LnUrlResult<ValidatedCallbackResponse> {
+    let (response, _) = rest_client.get(callback_url).await?;
     if let Ok(err) = serde_json::from_str::<LnUrlErrorData>(&response) {
         Ok(ValidatedCallbackResponse::EndpointError { data: err })
     } else {
@@ -44,23 +113,67 @@ pub async fn validate_lnurl_pay<C: RestClient + ?Sized>(
             }
         }
 
-        validate_invoice(user_amount_msat, &callback_resp.pr, network)?;
+        match invoice_amount_msat.start() == invoice_amount_msat.end() {
+            true => validate_invoice(*invoice_amount_msat.start(), &callback_resp.pr, network)?,
+            false => validate_invoice_in_range(invoice_amount_msat, &callback_resp.pr, network)?,
+        }
         Ok(ValidatedCallbackResponse::EndpointSuccess {
             data: callback_resp,
         })
     }
 }
 
+/// Queries the LUD-21 verify URL returned by the LNURL-pay callback, as per
+/// <https://github.com/lnurl/luds/blob/luds/21.md>
+///
+/// The invoice returned by the endpoint must match `payment_hash` and, if the payment is reported
+/// as settled, the preimage must hash to it.
+pub async fn verify_lnurl_pay<C: RestClient + ?Sized>(
+    rest_client: &C,
+    verify_url: &str,
+    payment_hash: &str,
+) -> LnUrlResult<LnUrlVerifyData> {
+    let (response, _) = rest_client.get(verify_url).await?;
+    if let Ok(err) = serde_json::from_str::<LnUrlErrorData>(&response) {
+        return Err(LnUrlError::Generic(err.reason));
+    }
+
+    let verify_data: LnUrlVerifyData = serde_json::from_str(&response)?;
+    let invoice = parse_invoice(&verify_data.pr)?;
+    ensure_sdk!(
+        invoice.payment_hash == payment_hash,
+        LnUrlError::generic("Verify invoice does not match the payment hash")
+    );
+    if let Some(preimage) = &verify_data.preimage {
+        let preimage_hash = sha256::Hash::hash(
+            &hex::decode(preimage)
+                .map_err(|_| LnUrlError::generic("Verify preimage is not valid hex"))?,
+        );
+        ensure_sdk!(
+            preimage_hash.to_string() == payment_hash,
+            LnUrlError::generic("Verify preimage does not match the payment hash")
+        );
+    }
+    Ok(verify_data)
+}
+
 pub fn build_pay_callback_url(
     user_amount_msat: u64,
     user_comment: &Option<String>,
     data: &LnUrlPayRequestData,
 ) -> LnUrlResult<String> {
-    let amount_msat = user_amount_msat.to_string();
+    build_callback_url(&user_amount_msat.to_string(), user_comment, data)
+}
+
+fn build_callback_url(
+    amount: &str,
+    user_comment: &Option<String>,
+    data: &LnUrlPayRequestData,
+) -> LnUrlResult<String> {
     let mut url = reqwest::Url::from_str(&data.callback)
         .map_err(|e| LnUrlError::InvalidUri(e.to_string()))?;
 
-    url.query_pairs_mut().append_pair("amount", &amount_msat);
+    url.query_pairs_mut().append_pair("amount", amount);
     if let Some(comment) = user_comment {
         url.query_pairs_mut().append_pair("comment", comment);
     }
@@ -114,6 +227,24 @@ pub fn validate_invoice(user_amount_msat: u64, bolt11: &str, network: Network) -
     }
 }
 
+/// Checks the invoice amount is within the given range, for endpoints that set the amount themselves
+pub fn validate_invoice_in_range(
+    amount_msat: RangeInclusive<u64>,
+    bolt11: &str,
+    network: Network,
+) -> LnUrlResult<()> {
+    let invoice = parse_invoice(bolt11)?;
+    validate_network(invoice.clone(), network)?;
+
+    match invoice.amount_msat {
+        Some(invoice_amount_msat) if amount_msat.contains(&invoice_amount_msat) => Ok(()),
+        Some(_) => Err(LnUrlError::generic(
+            "Invoice amount deviates from the expected amount by more than the allowed slippage",
+        )),
+        None => Err(LnUrlError::generic("Invoice has no amount")),
+    }
+}
+
 pub mod model {
     use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
     use anyhow::Result;
@@ -145,6 +276,22 @@ pub mod model {
         pub validate_success_action_url: Option<bool>,
     }
 
+    /// Represents a LNURL-pay request with the amount denominated in one of the
+    /// [LnUrlPayRequestData::currencies] advertised by the endpoint
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LnUrlPayCurrencyRequest {
+        /// The underlying LNURL-pay request. Its `amount_msat` is ignored, as the endpoint
+        /// converts `amount` itself.
+        pub pay_request: LnUrlPayRequest,
+        /// The code of the currency, as advertised by the endpoint
+        pub currency: String,
+        /// The amount, in the smallest unit of the currency (e.g. cents)
+        pub amount: u64,
+        /// How far, in percent, the invoice amount may deviate from the amount converted at the
+        /// advertised multiplier. Defaults to 1%.
+        pub max_slippage_percent: Option<f64>,
+    }
+
     pub enum ValidatedCallbackResponse {
         EndpointSuccess { data: CallbackResponse },
         EndpointError { data: LnUrlErrorData },
@@ -166,6 +313,19 @@ pub mod model {
     pub struct CallbackResponse {
         pub pr: String,
         pub success_action: Option<SuccessAction>,
+        /// LUD-21 verify URL, see [super::verify_lnurl_pay]
+        pub verify: Option<String>,
+    }
+
+    /// Response of the LUD-21 verify endpoint
+    ///
+    /// See <https://github.com/lnurl/luds/blob/luds/21.md>
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    pub struct LnUrlVerifyData {
+        pub settled: bool,
+        /// The hex encoded preimage, only set once settled
+        pub preimage: Option<String>,
+        pub pr: String,
     }
 
     /// Payload of the AES success action, as received from the LNURL endpoint
@@ -443,6 +603,14 @@ pub mod model {
         /// This error is raised when the node does not have enough funds to make the payment.
         #[error("Insufficient balance: {err}")]
         InsufficientBalance { err: String },
+
+        /// This error is raised when the payment is not allowed by the spending policy.
+        #[error("Policy violation: {err}")]
+        PolicyViolation { err: String },
+
+        /// This error is raised when paying from a watch-only instance, which can't spend.
+        #[error("Read-only: {err}")]
+        ReadOnly { err: String },
     }
 
     impl From<anyhow::Error> for LnUrlPayError {
@@ -511,6 +679,7 @@ pub(crate) mod tests {
             domain: "localhost".into(),
             allows_nostr: false,
             nostr_pubkey: None,
+            currencies: None,
             ln_address: None,
         }
     }
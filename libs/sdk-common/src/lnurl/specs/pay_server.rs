@@ -0,0 +1,540 @@
+use maybe_sync::{MaybeSend, MaybeSync};
+
+use crate::prelude::*;
+
+use self::model::*;
+
+const WELL_KNOWN_PREFIX: &str = "/.well-known/lnurlp/";
+const LNURLP_PREFIX: &str = "/lnurlp/";
+
+/// Source of invoices for a [LnUrlPayServer].
+///
+/// The SDK implements this on top of its own `receive_payment` and payment store, but any node
+/// able to mint invoices committing to a description hash can back the server.
+#[sdk_macros::async_trait]
+pub trait LnUrlPayInvoiceProvider: MaybeSend + MaybeSync {
+    /// Creates an invoice for `amount_msat`, whose description hash commits to `metadata`.
+    ///
+    /// The LUD-12 `comment` of the payer, if any, is stored along with the invoice.
+    async fn create_invoice(
+        &self,
+        amount_msat: u64,
+        metadata: String,
+        comment: Option<String>,
+    ) -> LnUrlResult<LnUrlPayServerInvoice>;
+
+    /// Looks up the state of an invoice previously created by [Self::create_invoice].
+    ///
+    /// Returns `None` if the invoice was not issued by [Self::create_invoice], so the verify
+    /// endpoint can't be used to probe other payments of the node.
+    async fn invoice_status(
+        &self,
+        payment_hash: &str,
+    ) -> LnUrlResult<Option<LnUrlPayServerInvoiceStatus>>;
+}
+
+/// Transport-agnostic LNURL-pay endpoint, serving Lightning Addresses of the form `user@domain`
+/// as per <https://github.com/lnurl/luds/blob/luds/06.md> and
+/// <https://github.com/lnurl/luds/blob/luds/16.md>.
+///
+/// The server does not open any socket. The embedding HTTP server passes every GET request to
+/// [LnUrlPayServer::handle] and writes the returned [LnUrlServerResponse] back to the client.
+///
+/// The following routes are served:
+/// * `/.well-known/lnurlp/<username>`: the `payRequest` of the address
+/// * `/lnurlp/<username>/callback?amount=<msat>[&comment=<text>]`: the invoice callback
+/// * `/lnurlp/<username>/verify/<payment_hash>`: the LUD-21 verify endpoint, if enabled
+pub struct LnUrlPayServer<P: LnUrlPayInvoiceProvider + ?Sized> {
+    config: LnUrlPayServerConfig,
+    provider: std::sync::Arc<P>,
+}
+
+impl<P: LnUrlPayInvoiceProvider + ?Sized> LnUrlPayServer<P> {
+    pub fn new(config: LnUrlPayServerConfig, provider: std::sync::Arc<P>) -> LnUrlResult<Self> {
+        config.validate()?;
+        Ok(Self { config, provider })
+    }
+
+    /// Handles a single request and builds the response to be sent back to the client.
+    ///
+    /// LNURL errors are returned as `{"status": "ERROR", "reason": "..."}` as required by
+    /// LUD-06, unknown routes and users result in a 404.
+    pub async fn handle(&self, req: LnUrlServerRequest) -> LnUrlServerResponse {
+        let path = req.path.trim_end_matches('/');
+        let query = req.query_pairs();
+
+        if let Some(username) = path.strip_prefix(WELL_KNOWN_PREFIX) {
+            return match self.is_known_user(username) {
+                true => LnUrlServerResponse::ok(&self.pay_request(username)),
+                false => LnUrlServerResponse::not_found(),
+            };
+        }
+
+        let Some(rest) = path.strip_prefix(LNURLP_PREFIX) else {
+            return LnUrlServerResponse::not_found();
+        };
+        let parts: Vec<&str> = rest.split('/').collect();
+        match parts.as_slice() {
+            [username, "callback"] if self.is_known_user(username) => {
+                match self.callback(username, &query).await {
+                    Ok(res) => LnUrlServerResponse::ok(&res),
+                    Err(e) => LnUrlServerResponse::error(&e.to_string()),
+                }
+            }
+            [username, "verify", payment_hash]
+                if self.config.enable_verify && self.is_known_user(username) =>
+            {
+                match self.verify(payment_hash).await {
+                    Ok(Some(res)) => LnUrlServerResponse::ok(&res),
+                    Ok(None) => LnUrlServerResponse::not_found(),
+                    Err(e) => LnUrlServerResponse::error(&e.to_string()),
+                }
+            }
+            _ => LnUrlServerResponse::not_found(),
+        }
+    }
+
+    /// The raw metadata string advertised for `username`, whose SHA256 is committed to by the
+    /// description hash of every invoice issued for it.
+    pub fn metadata(&self, username: &str) -> String {
+        let identifier = format!("{username}@{}", self.config.domain);
+        let description = self
+            .config
+            .description
+            .clone()
+            .unwrap_or(format!("Payment to {identifier}"));
+        serde_json::json!([["text/plain", description], ["text/identifier", identifier]])
+            .to_string()
+    }
+
+    fn is_known_user(&self, username: &str) -> bool {
+        self.config.usernames.iter().any(|u| u == username)
+    }
+
+    fn pay_request(&self, username: &str) -> LnUrlPayServerPayRequest {
+        LnUrlPayServerPayRequest {
+            tag: "payRequest".to_string(),
+            callback: format!("{}{LNURLP_PREFIX}{username}/callback", self.base_url()),
+            min_sendable: self.config.min_sendable,
+            max_sendable: self.config.max_sendable,
+            metadata: self.metadata(username),
+            comment_allowed: self.config.comment_allowed,
+        }
+    }
+
+    async fn callback(
+        &self,
+        username: &str,
+        query: &[(String, String)],
+    ) -> LnUrlResult<LnUrlPayServerCallbackResponse> {
+        let amount_msat = query
+            .iter()
+            .find(|(k, _)| k == "amount")
+            .ok_or(LnUrlError::generic("Missing amount"))?
+            .1
+            .parse::<u64>()
+            .map_err(|_| LnUrlError::generic("Invalid amount"))?;
+        let comment = query
+            .iter()
+            .find(|(k, _)| k == "comment")
+            .map(|(_, v)| v.clone());
+        validate_user_input(
+            amount_msat,
+            &comment,
+            self.config.min_sendable,
+            self.config.max_sendable,
+            self.config.comment_allowed,
+        )?;
+
+        let invoice = self
+            .provider
+            .create_invoice(amount_msat, self.metadata(username), comment)
+            .await?;
+        let verify = match self.config.enable_verify {
+            true => Some(format!(
+                "{}{LNURLP_PREFIX}{username}/verify/{}",
+                self.base_url(),
+                invoice.payment_hash
+            )),
+            false => None,
+        };
+        Ok(LnUrlPayServerCallbackResponse {
+            pr: invoice.bolt11,
+            routes: vec![],
+            verify,
+        })
+    }
+
+    async fn verify(
+        &self,
+        payment_hash: &str,
+    ) -> LnUrlResult<Option<LnUrlPayServerVerifyResponse>> {
+        Ok(self
+            .provider
+            .invoice_status(payment_hash)
+            .await?
+            .map(|status| LnUrlPayServerVerifyResponse {
+                status: "OK".to_string(),
+                settled: status.settled,
+                preimage: status.preimage,
+                pr: status.bolt11,
+            }))
+    }
+
+    fn base_url(&self) -> String {
+        format!("https://{}", self.config.domain)
+    }
+}
+
+pub mod model {
+    use serde::{Deserialize, Serialize};
+
+    use crate::prelude::*;
+
+    /// Configuration of a [super::LnUrlPayServer]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LnUrlPayServerConfig {
+        /// The domain the addresses are served on, without scheme. For example `example.com`.
+        pub domain: String,
+        /// The usernames served, resulting in Lightning Addresses `username@domain`
+        pub usernames: Vec<String>,
+        /// The minimum amount, in millisats, accepted
+        pub min_sendable: u64,
+        /// The maximum amount, in millisats, accepted
+        pub max_sendable: u64,
+        /// The comment length accepted, as per LUD-12. Set to 0 to disable comments.
+        pub comment_allowed: u16,
+        /// Optional `text/plain` description. Defaults to `Payment to username@domain`.
+        pub description: Option<String>,
+        /// If set, the callback response includes a LUD-21 `verify` URL
+        pub enable_verify: bool,
+    }
+
+    impl LnUrlPayServerConfig {
+        pub(crate) fn validate(&self) -> LnUrlResult<()> {
+            ensure_sdk!(
+                !self.domain.is_empty() && !self.domain.contains('/'),
+                LnUrlError::generic("Invalid domain")
+            );
+            ensure_sdk!(
+                !self.usernames.is_empty(),
+                LnUrlError::generic("At least one username is required")
+            );
+            ensure_sdk!(
+                self.min_sendable > 0 && self.min_sendable <= self.max_sendable,
+                LnUrlError::generic("Invalid sendable range")
+            );
+            Ok(())
+        }
+    }
+
+    /// An invoice created by a [super::LnUrlPayInvoiceProvider]
+    #[derive(Clone, Debug)]
+    pub struct LnUrlPayServerInvoice {
+        pub bolt11: String,
+        pub payment_hash: String,
+    }
+
+    /// The settlement state of an invoice created by a [super::LnUrlPayInvoiceProvider]
+    #[derive(Clone, Debug)]
+    pub struct LnUrlPayServerInvoiceStatus {
+        pub bolt11: String,
+        pub settled: bool,
+        /// The hex encoded preimage, only set once settled
+        pub preimage: Option<String>,
+    }
+
+    /// A GET request received by the embedding HTTP server
+    #[derive(Clone, Debug)]
+    pub struct LnUrlServerRequest {
+        /// The request path, for example `/.well-known/lnurlp/alice`
+        pub path: String,
+        /// The raw, still url-encoded, query string without the leading `?`
+        pub query: Option<String>,
+    }
+
+    impl LnUrlServerRequest {
+        /// Builds a request from a full URL or an origin-form target like `/path?query`.
+        pub fn from_url(url: &str) -> LnUrlResult<Self> {
+            let parsed = match url.starts_with('/') {
+                true => url::Url::parse(&format!("http://localhost{url}")),
+                false => url::Url::parse(url),
+            }
+            .map_err(|e| LnUrlError::InvalidUri(e.to_string()))?;
+            Ok(Self {
+                path: parsed.path().to_string(),
+                query: parsed.query().map(|q| q.to_string()),
+            })
+        }
+
+        pub(crate) fn query_pairs(&self) -> Vec<(String, String)> {
+            self.query
+                .as_ref()
+                .map(|q| {
+                    url::form_urlencoded::parse(q.as_bytes())
+                        .into_owned()
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+    }
+
+    /// The response to be written back by the embedding HTTP server
+    #[derive(Clone, Debug)]
+    pub struct LnUrlServerResponse {
+        pub status_code: u16,
+        pub content_type: String,
+        pub body: String,
+    }
+
+    impl LnUrlServerResponse {
+        pub(crate) fn ok<T: Serialize>(body: &T) -> Self {
+            match serde_json::to_string(body) {
+                Ok(body) => Self::json(200, body),
+                Err(e) => Self::error(&e.to_string()),
+            }
+        }
+
+        pub(crate) fn error(reason: &str) -> Self {
+            let body = serde_json::to_string(&LnUrlCallbackStatus::ErrorStatus {
+                data: LnUrlErrorData {
+                    reason: reason.to_string(),
+                },
+            })
+            .unwrap_or_default();
+            Self::json(200, body)
+        }
+
+        pub(crate) fn not_found() -> Self {
+            let mut res = Self::error("Not found");
+            res.status_code = 404;
+            res
+        }
+
+        fn json(status_code: u16, body: String) -> Self {
+            Self {
+                status_code,
+                content_type: "application/json".to_string(),
+                body,
+            }
+        }
+    }
+
+    /// The `payRequest` served on `/.well-known/lnurlp/<username>`
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LnUrlPayServerPayRequest {
+        pub tag: String,
+        pub callback: String,
+        pub min_sendable: u64,
+        pub max_sendable: u64,
+        pub metadata: String,
+        pub comment_allowed: u16,
+    }
+
+    /// The callback response containing the invoice
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LnUrlPayServerCallbackResponse {
+        pub pr: String,
+        pub routes: Vec<String>,
+        /// LUD-21 verify URL
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub verify: Option<String>,
+    }
+
+    /// The LUD-21 verify response
+    ///
+    /// See <https://github.com/lnurl/luds/blob/luds/21.md>
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LnUrlPayServerVerifyResponse {
+        pub status: String,
+        pub settled: bool,
+        pub preimage: Option<String>,
+        pub pr: String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Result;
+
+    use crate::lnurl::specs::pay_server::*;
+
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[derive(Default)]
+    struct MockInvoiceProvider {
+        created: Mutex<Vec<(u64, String, Option<String>)>>,
+    }
+
+    #[sdk_macros::async_trait]
+    impl LnUrlPayInvoiceProvider for MockInvoiceProvider {
+        async fn create_invoice(
+            &self,
+            amount_msat: u64,
+            metadata: String,
+            comment: Option<String>,
+        ) -> LnUrlResult<LnUrlPayServerInvoice> {
+            self.created
+                .lock()
+                .unwrap()
+                .push((amount_msat, metadata, comment));
+            Ok(LnUrlPayServerInvoice {
+                bolt11: "lnbc1test".to_string(),
+                payment_hash: "abcd".to_string(),
+            })
+        }
+
+        async fn invoice_status(
+            &self,
+            payment_hash: &str,
+        ) -> LnUrlResult<Option<LnUrlPayServerInvoiceStatus>> {
+            Ok(
+                (payment_hash == "abcd").then(|| LnUrlPayServerInvoiceStatus {
+                    bolt11: "lnbc1test".to_string(),
+                    settled: true,
+                    preimage: Some("00".repeat(32)),
+                }),
+            )
+        }
+    }
+
+    fn test_server(
+        enable_verify: bool,
+    ) -> (
+        LnUrlPayServer<MockInvoiceProvider>,
+        Arc<MockInvoiceProvider>,
+    ) {
+        let provider = Arc::new(MockInvoiceProvider::default());
+        let server = LnUrlPayServer::new(
+            LnUrlPayServerConfig {
+                domain: "example.com".to_string(),
+                usernames: vec!["alice".to_string()],
+                min_sendable: 1_000,
+                max_sendable: 1_000_000,
+                comment_allowed: 10,
+                description: None,
+                enable_verify,
+            },
+            provider.clone(),
+        )
+        .unwrap();
+        (server, provider)
+    }
+
+    async fn get(server: &LnUrlPayServer<MockInvoiceProvider>, url: &str) -> LnUrlServerResponse {
+        server
+            .handle(LnUrlServerRequest::from_url(url).unwrap())
+            .await
+    }
+
+    #[sdk_macros::test_all]
+    fn test_config_validation() {
+        let (server, provider) = test_server(false);
+        let mut config = server.config.clone();
+        config.domain = "https://example.com".to_string();
+        assert!(LnUrlPayServer::new(config, provider.clone()).is_err());
+
+        let mut config = server.config.clone();
+        config.usernames = vec![];
+        assert!(LnUrlPayServer::new(config, provider.clone()).is_err());
+
+        let mut config = server.config.clone();
+        config.min_sendable = config.max_sendable + 1;
+        assert!(LnUrlPayServer::new(config, provider).is_err());
+    }
+
+    #[sdk_macros::async_test_all]
+    async fn test_pay_request() -> Result<()> {
+        let (server, _) = test_server(false);
+
+        let res = get(&server, "/.well-known/lnurlp/alice").await;
+        assert_eq!(res.status_code, 200);
+        let pay_req: LnUrlPayRequestData = serde_json::from_str(&res.body)?;
+        assert_eq!(
+            pay_req.callback,
+            "https://example.com/lnurlp/alice/callback"
+        );
+        assert_eq!(pay_req.min_sendable, 1_000);
+        assert_eq!(pay_req.max_sendable, 1_000_000);
+        assert_eq!(pay_req.comment_allowed, 10);
+        assert_eq!(pay_req.metadata_str, server.metadata("alice"));
+        assert!(pay_req
+            .metadata_vec()?
+            .iter()
+            .any(|m| m.key == "text/identifier" && m.value == "alice@example.com"));
+
+        let res = get(&server, "/.well-known/lnurlp/bob").await;
+        assert_eq!(res.status_code, 404);
+        Ok(())
+    }
+
+    #[sdk_macros::async_test_all]
+    async fn test_callback() -> Result<()> {
+        let (server, provider) = test_server(false);
+
+        let res = get(
+            &server,
+            "/lnurlp/alice/callback?amount=5000&comment=hi%20there",
+        )
+        .await;
+        let cb: LnUrlPayServerCallbackResponse = serde_json::from_str(&res.body)?;
+        assert_eq!(cb.pr, "lnbc1test");
+        assert!(cb.verify.is_none());
+        let created = provider.created.lock().unwrap().clone();
+        assert_eq!(
+            created,
+            vec![(5000, server.metadata("alice"), Some("hi there".to_string()))]
+        );
+
+        for url in [
+            "/lnurlp/alice/callback",
+            "/lnurlp/alice/callback?amount=500",
+            "/lnurlp/alice/callback?amount=5000000",
+            "/lnurlp/alice/callback?amount=5000&comment=this%20is%20too%20long",
+        ] {
+            let res = get(&server, url).await;
+            assert!(matches!(
+                serde_json::from_str::<LnUrlCallbackStatus>(&res.body)?,
+                LnUrlCallbackStatus::ErrorStatus { .. }
+            ));
+        }
+        assert_eq!(provider.created.lock().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[sdk_macros::async_test_all]
+    async fn test_verify() -> Result<()> {
+        let (server, _) = test_server(false);
+        let res = get(&server, "/lnurlp/alice/verify/abcd").await;
+        assert_eq!(res.status_code, 404);
+
+        let (server, _) = test_server(true);
+        let res = get(
+            &server,
+            "https://example.com/lnurlp/alice/callback?amount=5000",
+        )
+        .await;
+        let cb: LnUrlPayServerCallbackResponse = serde_json::from_str(&res.body)?;
+        assert_eq!(
+            cb.verify,
+            Some("https://example.com/lnurlp/alice/verify/abcd".to_string())
+        );
+
+        let res = get(&server, "/lnurlp/alice/verify/abcd").await;
+        let verify: LnUrlPayServerVerifyResponse = serde_json::from_str(&res.body)?;
+        assert!(verify.settled);
+        assert_eq!(verify.pr, "lnbc1test");
+
+        let res = get(&server, "/lnurlp/alice/verify/ffff").await;
+        assert_eq!(res.status_code, 404);
+        assert!(matches!(
+            serde_json::from_str::<LnUrlCallbackStatus>(&res.body)?,
+            LnUrlCallbackStatus::ErrorStatus { .. }
+        ));
+        Ok(())
+    }
+}
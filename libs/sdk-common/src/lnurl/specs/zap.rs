@@ -0,0 +1,347 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::{schnorr, KeyPair, Message, Secp256k1, XOnlyPublicKey};
+
+use crate::prelude::*;
+
+use super::pay::validate_lnurl_pay_callback;
+use model::*;
+
+/// Builds and signs a NIP-57 zap request (kind 9734 event) for `amount_msat`.
+///
+/// `secret_key` is the 32 byte Nostr secret key of the sender.
+///
+/// See <https://github.com/nostr-protocol/nips/blob/master/57.md>
+pub fn build_zap_request(
+    secret_key: &[u8],
+    amount_msat: u64,
+    zap: &ZapRequestData,
+) -> LnUrlResult<NostrEvent> {
+    ensure_sdk!(
+        !zap.relays.is_empty(),
+        LnUrlError::generic("At least one relay is required for the zap receipt")
+    );
+    XOnlyPublicKey::from_str(&zap.recipient_pubkey)
+        .map_err(|_| LnUrlError::generic("Invalid zap recipient pubkey"))?;
+
+    let mut relays = vec!["relays".to_string()];
+    relays.extend(zap.relays.iter().cloned());
+    let mut tags = vec![
+        vec!["p".to_string(), zap.recipient_pubkey.clone()],
+        vec!["amount".to_string(), amount_msat.to_string()],
+        relays,
+    ];
+    if let Some(event_id) = &zap.event_id {
+        tags.push(vec!["e".to_string(), event_id.clone()]);
+    }
+    if let Some(lnurl) = &zap.lnurl {
+        tags.push(vec!["lnurl".to_string(), lnurl.clone()]);
+    }
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| LnUrlError::Generic(e.to_string()))?
+        .as_secs();
+    NostrEvent::sign(
+        secret_key,
+        created_at,
+        ZAP_REQUEST_KIND,
+        tags,
+        zap.content.clone(),
+    )
+}
+
+/// Performs the LNURL-pay callback with a zap request attached, as per NIP-57.
+///
+/// On top of the [super::pay::validate_lnurl_pay] checks, the endpoint must support zaps and the
+/// returned invoice must commit to the zap request through its description hash.
+pub async fn validate_lnurl_zap<C: RestClient + ?Sized>(
+    rest_client: &C,
+    user_amount_msat: u64,
+    comment: &Option<String>,
+    zap_request: &NostrEvent,
+    req_data: &LnUrlPayRequestData,
+    network: Network,
+    validate_success_action_url: Option<bool>,
+) -> LnUrlResult<ValidatedCallbackResponse> {
+    ensure_sdk!(
+        req_data.allows_nostr && req_data.nostr_pubkey.is_some(),
+        LnUrlError::generic("The LNURL-pay endpoint does not support zaps")
+    );
+    ensure_sdk!(
+        zap_request.kind == ZAP_REQUEST_KIND,
+        LnUrlError::generic("Invalid zap request kind")
+    );
+    ensure_sdk!(
+        zap_request.tag("amount") == Some(user_amount_msat.to_string().as_str()),
+        LnUrlError::generic("Zap request amount does not match the payment amount")
+    );
+    zap_request.verify()?;
+    validate_user_input(
+        user_amount_msat,
+        comment,
+        req_data.min_sendable,
+        req_data.max_sendable,
+        req_data.comment_allowed,
+    )?;
+
+    let zap_request_json = serde_json::to_string(zap_request)?;
+    let mut callback_url = reqwest::Url::from_str(&build_pay_callback_url(
+        user_amount_msat,
+        comment,
+        req_data,
+    )?)
+    .map_err(|e| LnUrlError::InvalidUri(e.to_string()))?;
+    callback_url
+        .query_pairs_mut()
+        .append_pair("nostr", &zap_request_json);
+
+    let res = validate_lnurl_pay_callback(
+        rest_client,
+        callback_url.as_str(),
+        user_amount_msat..=user_amount_msat,
+        req_data,
+        network,
+        validate_success_action_url,
+    )
+    .await?;
+    if let ValidatedCallbackResponse::EndpointSuccess { data } = &res {
+        let invoice = parse_invoice(&data.pr)?;
+        ensure_sdk!(
+            invoice.description_hash
+                == Some(sha256::Hash::hash(zap_request_json.as_bytes()).to_string()),
+            LnUrlError::generic("Invoice description hash does not match the zap request")
+        );
+    }
+    Ok(res)
+}
+
+/// Validates that a zap receipt (kind 9735 event) was published by the LNURL provider of
+/// `nostr_pubkey` for the given zap request and invoice.
+pub fn validate_zap_receipt(
+    receipt: &NostrEvent,
+    zap_request: &NostrEvent,
+    bolt11: &str,
+    nostr_pubkey: Option<&str>,
+) -> LnUrlResult<()> {
+    ensure_sdk!(
+        receipt.kind == ZAP_RECEIPT_KIND,
+        LnUrlError::generic("Invalid zap receipt kind")
+    );
+    if let Some(nostr_pubkey) = nostr_pubkey {
+        ensure_sdk!(
+            receipt.pubkey == nostr_pubkey,
+            LnUrlError::generic("Zap receipt is not signed by the LNURL provider")
+        );
+    }
+    receipt.verify()?;
+    ensure_sdk!(
+        receipt.tag("bolt11") == Some(bolt11),
+        LnUrlError::generic("Zap receipt invoice does not match the payment")
+    );
+    let description: NostrEvent = serde_json::from_str(
+        receipt
+            .tag("description")
+            .ok_or(LnUrlError::generic("Zap receipt has no description"))?,
+    )?;
+    ensure_sdk!(
+        description.id == zap_request.id,
+        LnUrlError::generic("Zap receipt does not match the zap request")
+    );
+    Ok(())
+}
+
+pub mod model {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    pub const ZAP_REQUEST_KIND: u16 = 9734;
+    pub const ZAP_RECEIPT_KIND: u16 = 9735;
+
+    /// The zap specific parameters of a [LnUrlPayRequest]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ZapRequestData {
+        /// The hex encoded Nostr pubkey of the recipient
+        pub recipient_pubkey: String,
+        /// The relays the recipient's LNURL provider publishes the zap receipt to
+        pub relays: Vec<String>,
+        /// The hex encoded id of the event being zapped, if any
+        pub event_id: Option<String>,
+        /// The bech32 encoded LNURL of the recipient, if known
+        pub lnurl: Option<String>,
+        /// An optional message to send along with the zap
+        pub content: String,
+    }
+
+    /// Represents a LNURL-pay request carrying a NIP-57 zap request
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LnUrlZapRequest {
+        /// The underlying LNURL-pay request. The endpoint must allow Nostr, see
+        /// [LnUrlPayRequestData::allows_nostr]
+        pub pay_request: LnUrlPayRequest,
+        pub zap: ZapRequestData,
+        /// The hex encoded Nostr secret key signing the zap request. If not set, a key derived
+        /// from the wallet seed is used.
+        pub nostr_secret_key: Option<String>,
+    }
+
+    /// A signed Nostr event, as per NIP-01
+    ///
+    /// See <https://github.com/nostr-protocol/nips/blob/master/01.md>
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct NostrEvent {
+        pub id: String,
+        pub pubkey: String,
+        pub created_at: u64,
+        pub kind: u16,
+        pub tags: Vec<Vec<String>>,
+        pub content: String,
+        pub sig: String,
+    }
+
+    impl NostrEvent {
+        /// Builds and signs an event with the 32 byte Nostr `secret_key`
+        pub fn sign(
+            secret_key: &[u8],
+            created_at: u64,
+            kind: u16,
+            tags: Vec<Vec<String>>,
+            content: String,
+        ) -> LnUrlResult<Self> {
+            let secp = Secp256k1::new();
+            let key_pair = KeyPair::from_seckey_slice(&secp, secret_key)?;
+            let pubkey = XOnlyPublicKey::from_keypair(&key_pair).0.to_string();
+            let id = Self::compute_id(&pubkey, created_at, kind, &tags, &content)?;
+            let sig =
+                secp.sign_schnorr_no_aux_rand(&Message::from_slice(id.as_byte_array())?, &key_pair);
+            Ok(Self {
+                id: id.to_string(),
+                pubkey,
+                created_at,
+                kind,
+                tags,
+                content,
+                sig: sig.to_string(),
+            })
+        }
+
+        /// Checks the event id and signature
+        pub fn verify(&self) -> LnUrlResult<()> {
+            let id = Self::compute_id(
+                &self.pubkey,
+                self.created_at,
+                self.kind,
+                &self.tags,
+                &self.content,
+            )?;
+            ensure_sdk!(
+                id.to_string() == self.id,
+                LnUrlError::generic("Invalid Nostr event id")
+            );
+            let pubkey = XOnlyPublicKey::from_str(&self.pubkey)?;
+            let sig = schnorr::Signature::from_str(&self.sig)?;
+            Secp256k1::verification_only()
+                .verify_schnorr(&sig, &Message::from_slice(id.as_byte_array())?, &pubkey)
+                .map_err(|_| LnUrlError::generic("Invalid Nostr event signature"))
+        }
+
+        /// The value of the first tag named `name`
+        pub fn tag(&self, name: &str) -> Option<&str> {
+            self.tags
+                .iter()
+                .find(|tag| tag.first().map(String::as_str) == Some(name))
+                .and_then(|tag| tag.get(1))
+                .map(String::as_str)
+        }
+
+        fn compute_id(
+            pubkey: &str,
+            created_at: u64,
+            kind: u16,
+            tags: &[Vec<String>],
+            content: &str,
+        ) -> LnUrlResult<sha256::Hash> {
+            let serialized = serde_json::to_string(&serde_json::json!([
+                0, pubkey, created_at, kind, tags, content
+            ]))?;
+            Ok(sha256::Hash::hash(serialized.as_bytes()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::lnurl::specs::zap::*;
+
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    const TEST_PUBKEY: &str = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d";
+
+    fn get_test_zap_data() -> ZapRequestData {
+        ZapRequestData {
+            recipient_pubkey: TEST_PUBKEY.to_string(),
+            relays: vec!["wss://relay.example.com".to_string()],
+            event_id: Some("a".repeat(64)),
+            lnurl: None,
+            content: "Great post!".to_string(),
+        }
+    }
+
+    #[sdk_macros::test_all]
+    fn test_build_zap_request() -> Result<()> {
+        let zap_request = build_zap_request(&[1u8; 32], 21_000, &get_test_zap_data())?;
+        assert_eq!(zap_request.kind, ZAP_REQUEST_KIND);
+        assert_eq!(zap_request.content, "Great post!");
+        assert_eq!(zap_request.tag("p"), Some(TEST_PUBKEY));
+        assert_eq!(zap_request.tag("amount"), Some("21000"));
+        assert_eq!(zap_request.tag("relays"), Some("wss://relay.example.com"));
+        assert_eq!(zap_request.tag("e"), Some("a".repeat(64).as_str()));
+        assert!(zap_request.verify().is_ok());
+
+        let mut tampered = zap_request.clone();
+        tampered.content = "Bad post".to_string();
+        assert!(tampered.verify().is_err());
+
+        let mut zap = get_test_zap_data();
+        zap.relays = vec![];
+        assert!(build_zap_request(&[1u8; 32], 21_000, &zap).is_err());
+        Ok(())
+    }
+
+    #[sdk_macros::test_all]
+    fn test_validate_zap_receipt() -> Result<()> {
+        let bolt11 = "lnbc210n1...";
+        let zap_request = build_zap_request(&[1u8; 32], 21_000, &get_test_zap_data())?;
+        let provider_key = [2u8; 32];
+        let receipt = NostrEvent::sign(
+            &provider_key,
+            zap_request.created_at,
+            ZAP_RECEIPT_KIND,
+            vec![
+                vec!["p".to_string(), TEST_PUBKEY.to_string()],
+                vec!["bolt11".to_string(), bolt11.to_string()],
+                vec![
+                    "description".to_string(),
+                    serde_json::to_string(&zap_request)?,
+                ],
+            ],
+            String::new(),
+        )?;
+
+        assert!(validate_zap_receipt(&receipt, &zap_request, bolt11, None).is_ok());
+        assert!(
+            validate_zap_receipt(&receipt, &zap_request, bolt11, Some(&receipt.pubkey)).is_ok()
+        );
+        assert!(validate_zap_receipt(&receipt, &zap_request, bolt11, Some(TEST_PUBKEY)).is_err());
+        assert!(validate_zap_receipt(&receipt, &zap_request, "lnbc1...", None).is_err());
+
+        let other_request = build_zap_request(&[3u8; 32], 21_000, &get_test_zap_data())?;
+        assert!(validate_zap_receipt(&receipt, &other_request, bolt11, None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+use std::time::Duration;
+
+use bitcoin::hashes::{sha256, Hash};
+use log::{debug, warn};
+use nostr_sdk::{
+    Client, Event, EventBuilder, Filter, Keys, Kind, PublicKey, RelayPoolNotification, Tag, TagKind,
+};
+use serde::de::DeserializeOwned;
+use tokio::sync::{broadcast::error::RecvError, Mutex};
+
+use super::error::{NwcClientError, NwcClientResult};
+use super::model::*;
+use super::{nwc_decrypt, nwc_encrypt, NostrWalletConnectUri};
+use crate::ensure_sdk;
+use crate::prelude::{parse_invoice, LNInvoice};
+
+/// How long to wait for the remote wallet to answer a request
+const NWC_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long to wait for the relays to return the wallet info event
+const NWC_INFO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A NIP-47 client, using a remote wallet linked through a `nostr+walletconnect://` URI as a
+/// funding source.
+///
+/// See <https://github.com/nostr-protocol/nips/blob/master/47.md>
+pub struct NwcClient {
+    uri: NostrWalletConnectUri,
+    keys: Keys,
+    wallet_pubkey: PublicKey,
+    client: Client,
+    /// The encryption advertised by the wallet, resolved on first use
+    encryption: Mutex<Option<NwcEncryption>>,
+}
+
+impl NwcClient {
+    pub fn new(uri: NostrWalletConnectUri) -> NwcClientResult<Self> {
+        ensure_sdk!(
+            !uri.relays.is_empty(),
+            NwcClientError::InvalidUri("Missing relay".to_string())
+        );
+        let keys = Keys::parse(&uri.secret)?;
+        let wallet_pubkey = PublicKey::from_hex(&uri.public_key)?;
+        Ok(Self {
+            client: Client::new(keys.clone()),
+            uri,
+            keys,
+            wallet_pubkey,
+            encryption: Mutex::new(None),
+        })
+    }
+
+    /// Connects to the relays of the connection URI
+    pub async fn connect(&self) -> NwcClientResult<()> {
+        for relay in &self.uri.relays {
+            self.client.add_relay(relay.as_str()).await?;
+        }
+        self.client.connect().await;
+        Ok(())
+    }
+
+    pub async fn disconnect(&self) {
+        self.client.disconnect().await;
+    }
+
+    /// Pays a bolt11 invoice through the remote wallet.
+    ///
+    /// The returned preimage is checked against the invoice payment hash.
+    pub async fn pay_invoice(
+        &self,
+        bolt11: String,
+        amount_msat: Option<u64>,
+    ) -> NwcClientResult<NwcPayInvoiceResult> {
+        let invoice = parse_invoice(&bolt11)?;
+        let res: NwcPayInvoiceResult = self
+            .request(NwcRequest::PayInvoice {
+                invoice: bolt11,
+                amount: amount_msat,
+            })
+            .await?;
+        validate_preimage(&res.preimage, &invoice.payment_hash)?;
+        Ok(res)
+    }
+
+    /// Returns the balance of the remote wallet, in millisatoshis
+    pub async fn get_balance(&self) -> NwcClientResult<u64> {
+        let res: NwcGetBalanceResult = self.request(NwcRequest::GetBalance {}).await?;
+        Ok(res.balance)
+    }
+
+    /// Creates an invoice on the remote wallet
+    pub async fn make_invoice(
+        &self,
+        amount_msat: u64,
+        description: Option<String>,
+        expiry: Option<u64>,
+    ) -> NwcClientResult<LNInvoice> {
+        let tx: NwcTransaction = self
+            .request(NwcRequest::MakeInvoice {
+                amount: amount_msat,
+                description,
+                description_hash: None,
+                expiry,
+            })
+            .await?;
+        transaction_to_invoice(tx, amount_msat)
+    }
+
+    pub async fn get_info(&self) -> NwcClientResult<NwcGetInfoResult> {
+        self.request(NwcRequest::GetInfo {}).await
+    }
+
+    async fn request<T: DeserializeOwned>(&self, request: NwcRequest) -> NwcClientResult<T> {
+        let encryption = self.encryption().await;
+        let event = self.build_request_event(&request, encryption)?;
+
+        // Subscribe before publishing, so a fast response can't be missed
+        let mut notifications = self.client.notifications();
+        let filter = Filter::new()
+            .kind(Kind::WalletConnectResponse)
+            .author(self.wallet_pubkey)
+            .pubkey(self.keys.public_key())
+            .event(event.id);
+        let subscription = self.client.subscribe(filter, None).await?;
+        debug!("Sending NWC {} request", request.method().as_str());
+        self.client.send_event(&event).await?;
+
+        let response = async_utility::time::timeout(Some(NWC_REQUEST_TIMEOUT), async {
+            loop {
+                match notifications.recv().await {
+                    Ok(RelayPoolNotification::Event { event: res, .. }) => {
+                        if res.kind == Kind::WalletConnectResponse
+                            && res.tags.event_ids().any(|id| *id == event.id)
+                        {
+                            return self.parse_response_event(&res);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("NWC client skipped {skipped} relay notifications");
+                    }
+                    Err(RecvError::Closed) => {
+                        return Err(NwcClientError::ServiceConnectivity(
+                            "Relay connection closed".to_string(),
+                        ))
+                    }
+                }
+            }
+        })
+        .await;
+        self.client.unsubscribe(&subscription.val).await;
+
+        let response = response.ok_or(NwcClientError::Timeout)??;
+        ensure_sdk!(
+            response.result_type == request.method().as_str(),
+            NwcClientError::Generic(format!("Unexpected result type: {}", response.result_type))
+        );
+        Ok(response.into_result()?)
+    }
+
+    /// The encryption to use for requests: NIP-44 if the wallet info event advertises it,
+    /// NIP-04 otherwise, as the info event of legacy wallets has no encryption tag.
+    async fn encryption(&self) -> NwcEncryption {
+        let mut encryption = self.encryption.lock().await;
+        if let Some(encryption) = *encryption {
+            return encryption;
+        }
+
+        let filter = Filter::new()
+            .kind(Kind::WalletConnectInfo)
+            .author(self.wallet_pubkey)
+            .limit(1);
+        let resolved = match self.client.fetch_events(filter, NWC_INFO_TIMEOUT).await {
+            Ok(events) => events
+                .into_iter()
+                .next()
+                .map(|info| info_event_encryption(&info)),
+            Err(e) => {
+                warn!("Failed to fetch NWC info event: {e}");
+                None
+            }
+        };
+        // Only cache what the wallet actually advertised, so a transient relay failure doesn't
+        // pin the client to NIP-04
+        *encryption = resolved;
+        resolved.unwrap_or(NwcEncryption::Nip04)
+    }
+
+    pub(crate) fn build_request_event(
+        &self,
+        request: &NwcRequest,
+        encryption: NwcEncryption,
+    ) -> NwcClientResult<Event> {
+        let content = nwc_encrypt(
+            self.keys.secret_key(),
+            &self.wallet_pubkey,
+            &serde_json::to_string(request)?,
+            encryption,
+        )?;
+        let mut builder = EventBuilder::new(Kind::WalletConnectRequest, content)
+            .tag(Tag::public_key(self.wallet_pubkey));
+        if encryption == NwcEncryption::Nip44V2 {
+            builder = builder.tag(Tag::custom(TagKind::custom("encryption"), ["nip44_v2"]));
+        }
+        Ok(builder.sign_with_keys(&self.keys)?)
+    }
+
+    pub(crate) fn parse_response_event(&self, event: &Event) -> NwcClientResult<NwcResponse> {
+        ensure_sdk!(
+            event.pubkey == self.wallet_pubkey,
+            NwcClientError::generic("Response not signed by the wallet")
+        );
+        let (content, _) = nwc_decrypt(self.keys.secret_key(), &event.pubkey, &event.content)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+fn info_event_encryption(info: &Event) -> NwcEncryption {
+    let supports_nip44 = info
+        .tags
+        .iter()
+        .find(|tag| tag.kind() == TagKind::custom("encryption"))
+        .and_then(|tag| tag.content())
+        .is_some_and(|schemes| schemes.split_whitespace().any(|s| s == "nip44_v2"));
+    match supports_nip44 {
+        true => NwcEncryption::Nip44V2,
+        false => NwcEncryption::Nip04,
+    }
+}
+
+fn validate_preimage(preimage: &str, payment_hash: &str) -> NwcClientResult<()> {
+    let preimage = hex::decode(preimage)
+        .map_err(|_| NwcClientError::generic("Invalid preimage returned by the wallet"))?;
+    ensure_sdk!(
+        hex::encode(sha256::Hash::hash(&preimage)) == payment_hash,
+        NwcClientError::generic("Preimage returned by the wallet doesn't match the invoice")
+    );
+    Ok(())
+}
+
+fn transaction_to_invoice(tx: NwcTransaction, amount_msat: u64) -> NwcClientResult<LNInvoice> {
+    let bolt11 = tx
+        .invoice
+        .ok_or(NwcClientError::generic("No invoice returned by the wallet"))?;
+    let invoice = parse_invoice(&bolt11)?;
+    ensure_sdk!(
+        invoice.amount_msat == Some(amount_msat),
+        NwcClientError::generic("Invoice amount returned by the wallet doesn't match")
+    );
+    Ok(invoice)
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::{EventBuilder, Keys, Kind, Tag, TagKind};
+
+    use crate::nwc::client::*;
+    use crate::nwc::{nwc_decrypt, nwc_encrypt, NostrWalletConnectUri};
+
+    const TEST_INVOICE: &str = "lnbc110n1p38q3gtpp5ypz09jrd8p993snjwnm68cph4ftwp22le34xd4r8ftspwshxhmnsdqqxqyjw5qcqpxsp5htlg8ydpywvsa7h3u4hdn77ehs4z4e844em0apjyvmqfkzqhhd2q9qgsqqqyssqszpxzxt9uuqzymr7zxcdccj5g69s8q7zzjs7sgxn9ejhnvdh6gqjcy22mss2yexunagm5r2gqczh8k24cwrqml3njskm548aruhpwssq9nvrvz";
+
+    fn test_client(wallet: &Keys) -> NwcClient {
+        NwcClient::new(NostrWalletConnectUri {
+            public_key: wallet.public_key().to_hex(),
+            secret: Keys::generate().secret_key().to_secret_hex(),
+            relays: vec!["wss://relay.example.com".to_string()],
+            lud16: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_nwc_client_request_roundtrip() {
+        let wallet = Keys::generate();
+        let client = test_client(&wallet);
+
+        for encryption in [NwcEncryption::Nip04, NwcEncryption::Nip44V2] {
+            let request = NwcRequest::GetBalance {};
+            let event = client.build_request_event(&request, encryption).unwrap();
+            assert_eq!(event.kind, Kind::WalletConnectRequest);
+
+            // The wallet decrypts the request and answers with the same encryption
+            let (content, detected) =
+                nwc_decrypt(wallet.secret_key(), &event.pubkey, &event.content).unwrap();
+            assert_eq!(detected, encryption);
+            assert_eq!(
+                serde_json::from_str::<NwcRequest>(&content).unwrap(),
+                request
+            );
+
+            let response =
+                NwcResponse::success(NwcMethod::GetBalance, &NwcGetBalanceResult { balance: 21 });
+            let content = nwc_encrypt(
+                wallet.secret_key(),
+                &event.pubkey,
+                &serde_json::to_string(&response).unwrap(),
+                detected,
+            )
+            .unwrap();
+            let response_event = EventBuilder::new(Kind::WalletConnectResponse, content)
+                .tag(Tag::public_key(event.pubkey))
+                .tag(Tag::event(event.id))
+                .sign_with_keys(&wallet)
+                .unwrap();
+            let parsed = client.parse_response_event(&response_event).unwrap();
+            assert_eq!(
+                parsed.into_result::<NwcGetBalanceResult>().unwrap().balance,
+                21
+            );
+        }
+
+        // Responses from anyone but the wallet are rejected
+        let impostor = Keys::generate();
+        let event = EventBuilder::new(Kind::WalletConnectResponse, "")
+            .sign_with_keys(&impostor)
+            .unwrap();
+        assert!(client.parse_response_event(&event).is_err());
+    }
+
+    #[test]
+    fn test_nwc_client_info_encryption() {
+        let wallet = Keys::generate();
+        let info = EventBuilder::new(Kind::WalletConnectInfo, "pay_invoice get_balance")
+            .tag(Tag::custom(
+                TagKind::custom("encryption"),
+                ["nip44_v2 nip04"],
+            ))
+            .sign_with_keys(&wallet)
+            .unwrap();
+        assert_eq!(info_event_encryption(&info), NwcEncryption::Nip44V2);
+
+        let legacy_info = EventBuilder::new(Kind::WalletConnectInfo, "pay_invoice get_balance")
+            .sign_with_keys(&wallet)
+            .unwrap();
+        assert_eq!(info_event_encryption(&legacy_info), NwcEncryption::Nip04);
+    }
+
+    #[test]
+    fn test_nwc_client_results() {
+        let invoice = transaction_to_invoice(
+            NwcTransaction {
+                invoice: Some(TEST_INVOICE.to_string()),
+                ..Default::default()
+            },
+            11_000,
+        )
+        .unwrap();
+        assert_eq!(invoice.bolt11, TEST_INVOICE);
+        assert!(transaction_to_invoice(
+            NwcTransaction {
+                invoice: Some(TEST_INVOICE.to_string()),
+                ..Default::default()
+            },
+            12_000,
+        )
+        .is_err());
+        assert!(transaction_to_invoice(NwcTransaction::default(), 11_000).is_err());
+
+        let preimage = [1u8; 32];
+        let payment_hash = hex::encode(sha256::Hash::hash(&preimage));
+        assert!(validate_preimage(&hex::encode(preimage), &payment_hash).is_ok());
+        assert!(validate_preimage(&hex::encode([2u8; 32]), &payment_hash).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::prelude::InvoiceError;
+
+use super::model::{NwcError, NwcErrorCode};
+
+pub type NwcClientResult<T, E = NwcClientError> = Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum NwcClientError {
+    #[error("{0}")]
+    Generic(String),
+
+    #[error("{0}")]
+    InvalidUri(String),
+
+    #[error("{0}")]
+    ServiceConnectivity(String),
+
+    #[error("Timed out waiting for the wallet response")]
+    Timeout,
+
+    /// The remote wallet answered with a NIP-47 error
+    #[error("{code:?}: {message}")]
+    Wallet { code: NwcErrorCode, message: String },
+}
+
+impl NwcClientError {
+    pub fn generic(err: &str) -> Self {
+        Self::Generic(err.to_string())
+    }
+}
+
+impl From<NwcError> for NwcClientError {
+    fn from(err: NwcError) -> Self {
+        Self::Wallet {
+            code: err.code,
+            message: err.message,
+        }
+    }
+}
+
+impl From<anyhow::Error> for NwcClientError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::Generic(err.to_string())
+    }
+}
+
+impl From<InvoiceError> for NwcClientError {
+    fn from(err: InvoiceError) -> Self {
+        Self::Generic(err.to_string())
+    }
+}
+
+impl From<nostr_sdk::client::Error> for NwcClientError {
+    fn from(err: nostr_sdk::client::Error) -> Self {
+        Self::ServiceConnectivity(err.to_string())
+    }
+}
+
+impl From<nostr_sdk::event::builder::Error> for NwcClientError {
+    fn from(err: nostr_sdk::event::builder::Error) -> Self {
+        Self::Generic(err.to_string())
+    }
+}
+
+impl From<nostr_sdk::key::Error> for NwcClientError {
+    fn from(err: nostr_sdk::key::Error) -> Self {
+        Self::InvalidUri(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for NwcClientError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Generic(err.to_string())
+    }
+}
@@ -1,6 +1,16 @@
+use anyhow::Result;
+use nostr_sdk::nips::nip44::Version;
 use nostr_sdk::nips::nip47::{Error, NostrWalletConnectURI as _NostrWalletConnectURI};
+use nostr_sdk::nips::{nip04, nip44};
+use nostr_sdk::{PublicKey, SecretKey};
 use serde::{Deserialize, Serialize};
 
+use self::model::NwcEncryption;
+
+pub mod client;
+pub mod error;
+pub mod model;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NostrWalletConnectUri {
     pub public_key: String,
@@ -28,9 +38,45 @@ pub(crate) fn parse_nwc_uri(uri: &str) -> Result<NostrWalletConnectUri, Error> {
     _NostrWalletConnectURI::parse(uri).map(Into::into)
 }
 
+/// Encrypts the content of a NIP-47 event sent to `public_key`.
+pub fn nwc_encrypt(
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+    content: &str,
+    encryption: NwcEncryption,
+) -> Result<String> {
+    Ok(match encryption {
+        NwcEncryption::Nip04 => nip04::encrypt(secret_key, public_key, content)?,
+        NwcEncryption::Nip44V2 => nip44::encrypt(secret_key, public_key, content, Version::V2)?,
+    })
+}
+
+/// Decrypts the content of a NIP-47 event received from `public_key`, returning the plaintext and
+/// the encryption the counterparty used, so the reply can use the same.
+pub fn nwc_decrypt(
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+    content: &str,
+) -> Result<(String, NwcEncryption)> {
+    // NIP-04 payloads are `<ciphertext>?iv=<iv>`, while NIP-44 payloads are plain base64
+    Ok(match content.contains("?iv=") {
+        true => (
+            nip04::decrypt(secret_key, public_key, content)?,
+            NwcEncryption::Nip04,
+        ),
+        false => (
+            nip44::decrypt(secret_key, public_key, content)?,
+            NwcEncryption::Nip44V2,
+        ),
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::nwc::parse_nwc_uri;
+    use nostr_sdk::Keys;
+
+    use crate::nwc::model::*;
+    use crate::nwc::{nwc_decrypt, nwc_encrypt, parse_nwc_uri};
 
     #[test]
     fn test_parse_nwc_uri() {
@@ -47,4 +93,51 @@ mod tests {
         );
         assert_eq!(uri.lud16, None);
     }
+
+    #[test]
+    fn test_nwc_encryption_roundtrip() {
+        let client = Keys::generate();
+        let service = Keys::generate();
+        for encryption in [NwcEncryption::Nip04, NwcEncryption::Nip44V2] {
+            let encrypted =
+                nwc_encrypt(client.secret_key(), &service.public_key(), "{}", encryption).unwrap();
+            let (decrypted, detected) =
+                nwc_decrypt(service.secret_key(), &client.public_key(), &encrypted).unwrap();
+            assert_eq!(decrypted, "{}");
+            assert_eq!(detected, encryption);
+        }
+    }
+
+    #[test]
+    fn test_nwc_request_serde() {
+        let req: NwcRequest =
+            serde_json::from_str(r#"{"method":"pay_invoice","params":{"invoice":"lnbc50n1..."}}"#)
+                .unwrap();
+        assert_eq!(
+            req,
+            NwcRequest::PayInvoice {
+                invoice: "lnbc50n1...".to_string(),
+                amount: None
+            }
+        );
+        assert_eq!(req.method(), NwcMethod::PayInvoice);
+
+        let req: NwcRequest =
+            serde_json::from_str(r#"{"method":"get_balance","params":{}}"#).unwrap();
+        assert_eq!(req, NwcRequest::GetBalance {});
+        assert_eq!(
+            serde_json::to_string(&req).unwrap(),
+            r#"{"method":"get_balance","params":{}}"#
+        );
+
+        let res: NwcResponse = serde_json::from_str(
+            r#"{"result_type":"pay_invoice","error":{"code":"QUOTA_EXCEEDED","message":"budget"}}"#,
+        )
+        .unwrap();
+        let err = res.into_result::<NwcPayInvoiceResult>().unwrap_err();
+        assert_eq!(err.code, NwcErrorCode::QuotaExceeded);
+
+        let res = NwcResponse::success(NwcMethod::GetBalance, &NwcGetBalanceResult { balance: 5 });
+        assert_eq!(res.into_result::<NwcGetBalanceResult>().unwrap().balance, 5);
+    }
 }
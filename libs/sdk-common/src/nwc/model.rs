@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A NIP-47 request, as found in the decrypted content of a kind 23194 event.
+///
+/// See <https://github.com/nostr-protocol/nips/blob/master/47.md>
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum NwcRequest {
+    PayInvoice {
+        invoice: String,
+        /// Amount in millisatoshis, for amountless invoices
+        #[serde(skip_serializing_if = "Option::is_none")]
+        amount: Option<u64>,
+    },
+    MakeInvoice {
+        /// Amount in millisatoshis
+        amount: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description_hash: Option<String>,
+        /// Expiry in seconds
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expiry: Option<u64>,
+    },
+    LookupInvoice {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payment_hash: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        invoice: Option<String>,
+    },
+    ListTransactions {
+        /// Epoch time, in seconds
+        #[serde(skip_serializing_if = "Option::is_none")]
+        from: Option<u64>,
+        /// Epoch time, in seconds
+        #[serde(skip_serializing_if = "Option::is_none")]
+        until: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        limit: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        offset: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        unpaid: Option<bool>,
+        #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+        transaction_type: Option<NwcTransactionType>,
+    },
+    GetBalance {},
+    GetInfo {},
+}
+
+impl NwcRequest {
+    /// The NIP-47 method name, which is also the `result_type` of the matching [NwcResponse]
+    pub fn method(&self) -> NwcMethod {
+        match self {
+            NwcRequest::PayInvoice { .. } => NwcMethod::PayInvoice,
+            NwcRequest::MakeInvoice { .. } => NwcMethod::MakeInvoice,
+            NwcRequest::LookupInvoice { .. } => NwcMethod::LookupInvoice,
+            NwcRequest::ListTransactions { .. } => NwcMethod::ListTransactions,
+            NwcRequest::GetBalance {} => NwcMethod::GetBalance,
+            NwcRequest::GetInfo {} => NwcMethod::GetInfo,
+        }
+    }
+}
+
+/// The NIP-47 methods supported by the SDK
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NwcMethod {
+    PayInvoice,
+    MakeInvoice,
+    LookupInvoice,
+    ListTransactions,
+    GetBalance,
+    GetInfo,
+}
+
+impl NwcMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NwcMethod::PayInvoice => "pay_invoice",
+            NwcMethod::MakeInvoice => "make_invoice",
+            NwcMethod::LookupInvoice => "lookup_invoice",
+            NwcMethod::ListTransactions => "list_transactions",
+            NwcMethod::GetBalance => "get_balance",
+            NwcMethod::GetInfo => "get_info",
+        }
+    }
+}
+
+/// A NIP-47 response, as found in the decrypted content of a kind 23195 event.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct NwcResponse {
+    pub result_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<NwcError>,
+    #[serde(default)]
+    pub result: Option<Value>,
+}
+
+impl NwcResponse {
+    pub fn success<T: Serialize>(method: NwcMethod, result: &T) -> Self {
+        match serde_json::to_value(result) {
+            Ok(result) => Self {
+                result_type: method.as_str().to_string(),
+                error: None,
+                result: Some(result),
+            },
+            Err(e) => Self::error(method, NwcErrorCode::Internal, e.to_string()),
+        }
+    }
+
+    pub fn error(method: NwcMethod, code: NwcErrorCode, message: String) -> Self {
+        Self {
+            result_type: method.as_str().to_string(),
+            error: Some(NwcError { code, message }),
+            result: None,
+        }
+    }
+
+    /// Deserializes the result, or returns the error reported by the wallet service.
+    pub fn into_result<T: for<'de> Deserialize<'de>>(self) -> Result<T, NwcError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        serde_json::from_value(self.result.unwrap_or(Value::Null)).map_err(|e| NwcError {
+            code: NwcErrorCode::Other,
+            message: format!("Invalid {} result: {e}", self.result_type),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NwcError {
+    pub code: NwcErrorCode,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NwcErrorCode {
+    RateLimited,
+    NotImplemented,
+    InsufficientBalance,
+    QuotaExceeded,
+    Restricted,
+    Unauthorized,
+    Internal,
+    PaymentFailed,
+    NotFound,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NwcTransactionType {
+    Incoming,
+    Outgoing,
+}
+
+/// Result of the `pay_invoice` method
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NwcPayInvoiceResult {
+    pub preimage: String,
+    /// Fees paid, in millisatoshis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees_paid: Option<u64>,
+}
+
+/// Result of the `get_balance` method
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NwcGetBalanceResult {
+    /// Balance, in millisatoshis
+    pub balance: u64,
+}
+
+/// Result of the `get_info` method
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NwcGetInfoResult {
+    #[serde(default)]
+    pub alias: String,
+    #[serde(default)]
+    pub pubkey: String,
+    #[serde(default)]
+    pub network: String,
+    #[serde(default)]
+    pub block_height: u32,
+    #[serde(default)]
+    pub methods: Vec<String>,
+}
+
+/// A transaction as returned by `make_invoice`, `lookup_invoice` and `list_transactions`
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NwcTransaction {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<NwcTransactionType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invoice: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preimage: Option<String>,
+    pub payment_hash: String,
+    /// Amount, in millisatoshis
+    pub amount: u64,
+    /// Fees paid, in millisatoshis
+    #[serde(default)]
+    pub fees_paid: u64,
+    /// Epoch time, in seconds
+    pub created_at: u64,
+    /// Epoch time, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Epoch time, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settled_at: Option<u64>,
+}
+
+/// Result of the `list_transactions` method
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NwcListTransactionsResult {
+    pub transactions: Vec<NwcTransaction>,
+}
+
+/// The content encryption used for NIP-47 events
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum NwcEncryption {
+    Nip04,
+    Nip44V2,
+}
@@ -1,5 +1,6 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     set_git_revision_hash();
+    tonic_build::compile_protos("src/signer/proto/signer.proto")?;
     Ok(())
 }
 
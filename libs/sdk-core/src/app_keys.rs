@@ -0,0 +1,190 @@
+use crate::bitcoin::bip32::ChildNumber;
+use crate::bitcoin::hashes::{sha256, Hash};
+use crate::bitcoin::secp256k1::ecdh::{shared_secret_point, SharedSecret};
+use crate::bitcoin::secp256k1::{KeyPair, Message, PublicKey, Secp256k1, SecretKey};
+use crate::error::{SdkError, SdkResult};
+use crate::models::{AppKey, AppPublicKey, AppSignatureScheme};
+
+/// Hardened BIP32 index of the branch reserved for app keys. The keys of the node and the ones
+/// the SDK uses itself are derived elsewhere, so they can't be reached through an [AppKey].
+pub(crate) const APP_KEY_DERIVATION_INDEX: u32 = 143;
+
+const MAX_NAMESPACE_LEN: usize = 64;
+
+/// The path of an app key: `m/143'/<namespace hash>'/<namespace hash>'/<index>'`, where the
+/// namespace is hashed into two hardened indexes to keep collisions between apps unlikely.
+pub(crate) fn app_key_path(key: &AppKey) -> SdkResult<Vec<ChildNumber>> {
+    if key.namespace.is_empty() || key.namespace.len() > MAX_NAMESPACE_LEN {
+        return Err(SdkError::generic(&format!(
+            "The namespace must have between 1 and {MAX_NAMESPACE_LEN} bytes"
+        )));
+    }
+    let hash = sha256::Hash::hash(key.namespace.as_bytes()).to_byte_array();
+    let namespace_index =
+        |i: usize| u32::from_be_bytes([hash[i], hash[i + 1], hash[i + 2], hash[i + 3]]) >> 1;
+    Ok(vec![
+        ChildNumber::from_hardened_idx(APP_KEY_DERIVATION_INDEX)?,
+        ChildNumber::from_hardened_idx(namespace_index(0))?,
+        ChildNumber::from_hardened_idx(namespace_index(4))?,
+        ChildNumber::from_hardened_idx(key.index)?,
+    ])
+}
+
+pub(crate) fn public_key(secret_key: &SecretKey) -> AppPublicKey {
+    let public_key = secret_key.public_key(&Secp256k1::new());
+    AppPublicKey {
+        public_key: public_key.to_string(),
+        x_only_public_key: public_key.x_only_public_key().0.to_string(),
+    }
+}
+
+/// Signs the SHA256 digest of `message`, or `message` itself when `prehashed`, returns the hex
+/// encoded 64 bytes signature
+pub(crate) fn sign(
+    secret_key: &SecretKey,
+    message: &[u8],
+    scheme: AppSignatureScheme,
+    prehashed: bool,
+) -> SdkResult<String> {
+    let secp = Secp256k1::new();
+    let msg = match prehashed {
+        true => Message::from_slice(message)
+            .map_err(|_| SdkError::generic("A prehashed message must have 32 bytes"))?,
+        false => Message::from_slice(&sha256::Hash::hash(message).to_byte_array())
+            .expect("32 bytes digest"),
+    };
+    Ok(match scheme {
+        AppSignatureScheme::Ecdsa => {
+            hex::encode(secp.sign_ecdsa(&msg, secret_key).serialize_compact())
+        }
+        // BIP340, as used by Nostr
+        AppSignatureScheme::Schnorr => secp
+            .sign_schnorr(&msg, &KeyPair::from_secret_key(&secp, secret_key))
+            .to_string(),
+    })
+}
+
+/// The ECDH shared secret with `peer_public_key`, hex encoded. It's the SHA256 of the shared
+/// point as in libsecp256k1, or its bare x-coordinate when `raw`, as NIP-44 expects.
+pub(crate) fn ecdh(secret_key: &SecretKey, peer_public_key: &str, raw: bool) -> SdkResult<String> {
+    let peer_public_key: PublicKey = peer_public_key
+        .parse()
+        .map_err(|_| SdkError::generic("Invalid peer public key"))?;
+    Ok(match raw {
+        true => hex::encode(&shared_secret_point(&peer_public_key, secret_key)[..32]),
+        false => hex::encode(SharedSecret::new(&peer_public_key, secret_key).secret_bytes()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{app_key_path, ecdh, public_key, sign, APP_KEY_DERIVATION_INDEX};
+    use crate::bitcoin::bip32::ChildNumber;
+    use crate::bitcoin::hashes::{sha256, Hash};
+    use crate::bitcoin::secp256k1::ecdh::shared_secret_point;
+    use crate::bitcoin::secp256k1::{
+        ecdsa, schnorr, Message, PublicKey, Secp256k1, SecretKey, XOnlyPublicKey,
+    };
+    use crate::models::{AppKey, AppSignatureScheme};
+
+    fn key(namespace: &str, index: u32) -> AppKey {
+        AppKey {
+            namespace: namespace.to_string(),
+            index,
+        }
+    }
+
+    #[test]
+    fn test_app_key_path() {
+        let path = app_key_path(&key("nostr", 0)).unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(
+            path[0],
+            ChildNumber::Hardened {
+                index: APP_KEY_DERIVATION_INDEX
+            }
+        );
+        assert!(path.iter().all(ChildNumber::is_hardened));
+        assert_eq!(path, app_key_path(&key("nostr", 0)).unwrap());
+        assert_ne!(path, app_key_path(&key("nostr", 1)).unwrap());
+        assert_ne!(path, app_key_path(&key("chat", 0)).unwrap());
+
+        assert!(app_key_path(&key("", 0)).is_err());
+        assert!(app_key_path(&key(&"a".repeat(65), 0)).is_err());
+        assert!(app_key_path(&key("nostr", 1 << 31)).is_err());
+    }
+
+    #[test]
+    fn test_sign_and_ecdh() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let peer_secret_key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let app_public_key = public_key(&secret_key);
+        let msg = Message::from_slice(&sha256::Hash::hash(b"hello").to_byte_array()).unwrap();
+
+        let signature = sign(&secret_key, b"hello", AppSignatureScheme::Ecdsa, false).unwrap();
+        let signature = ecdsa::Signature::from_compact(&hex::decode(signature).unwrap()).unwrap();
+        let pubkey: PublicKey = app_public_key.public_key.parse().unwrap();
+        assert!(secp.verify_ecdsa(&msg, &signature, &pubkey).is_ok());
+
+        let signature = sign(&secret_key, b"hello", AppSignatureScheme::Schnorr, false).unwrap();
+        let signature = schnorr::Signature::from_slice(&hex::decode(signature).unwrap()).unwrap();
+        let pubkey: XOnlyPublicKey = app_public_key.x_only_public_key.parse().unwrap();
+        assert!(secp.verify_schnorr(&signature, &msg, &pubkey).is_ok());
+
+        // Both sides agree on the secret
+        let peer_public_key = public_key(&peer_secret_key).public_key;
+        assert_eq!(
+            ecdh(&secret_key, &peer_public_key, false).unwrap(),
+            ecdh(&peer_secret_key, &app_public_key.public_key, false).unwrap()
+        );
+        assert!(ecdh(&secret_key, "02", false).is_err());
+    }
+
+    #[test]
+    fn test_sign_prehashed() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let app_public_key = public_key(&secret_key);
+        let digest = sha256::Hash::hash(b"hello").to_byte_array();
+        let msg = Message::from_slice(&digest).unwrap();
+
+        // The digest is signed as is, ECDSA signatures are deterministic
+        assert_eq!(
+            sign(&secret_key, &digest, AppSignatureScheme::Ecdsa, true).unwrap(),
+            sign(&secret_key, b"hello", AppSignatureScheme::Ecdsa, false).unwrap()
+        );
+
+        // e.g. the id of a Nostr event
+        let signature = sign(&secret_key, &digest, AppSignatureScheme::Schnorr, true).unwrap();
+        let signature = schnorr::Signature::from_slice(&hex::decode(signature).unwrap()).unwrap();
+        let pubkey: XOnlyPublicKey = app_public_key.x_only_public_key.parse().unwrap();
+        assert!(secp.verify_schnorr(&signature, &msg, &pubkey).is_ok());
+
+        assert!(sign(&secret_key, b"hello", AppSignatureScheme::Ecdsa, true).is_err());
+        assert!(sign(&secret_key, &[0; 33], AppSignatureScheme::Schnorr, true).is_err());
+    }
+
+    #[test]
+    fn test_raw_ecdh() {
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let peer_secret_key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let peer_public_key = public_key(&peer_secret_key).public_key;
+
+        let raw = ecdh(&secret_key, &peer_public_key, true).unwrap();
+        assert_eq!(raw.len(), 64);
+        assert_eq!(
+            raw,
+            ecdh(&peer_secret_key, &public_key(&secret_key).public_key, true).unwrap()
+        );
+
+        // The hashed secret is the SHA256 of the compressed shared point
+        let point = shared_secret_point(&peer_public_key.parse().unwrap(), &secret_key);
+        let compressed = [[0x02 | (point[63] & 1)].as_slice(), &point[..32]].concat();
+        assert_eq!(&point[..32], hex::decode(&raw).unwrap());
+        assert_eq!(
+            ecdh(&secret_key, &peer_public_key, false).unwrap(),
+            hex::encode(sha256::Hash::hash(&compressed).to_byte_array())
+        );
+    }
+}
@@ -76,6 +76,7 @@ pub(crate) struct BackupWatcher {
     encryption_key: Vec<u8>,
     legacy_encryption_key: Vec<u8>,
     events_notifier: broadcast::Sender<BreezEvent>,
+    read_only: bool,
 }
 
 /// watches for sync requests and syncs the sdk state when a request is detected.
@@ -86,6 +87,7 @@ impl BackupWatcher {
         persister: Arc<SqliteStorage>,
         encryption_key: Vec<u8>,
         legacy_encryption_key: Vec<u8>,
+        read_only: bool,
     ) -> Self {
         let (events_notifier, _) = broadcast::channel::<BreezEvent>(100);
 
@@ -97,6 +99,7 @@ impl BackupWatcher {
             encryption_key,
             legacy_encryption_key,
             events_notifier,
+            read_only,
         }
     }
 
@@ -113,6 +116,7 @@ impl BackupWatcher {
             self.encryption_key.clone(),
             self.legacy_encryption_key.clone(),
             self.events_notifier.clone(),
+            self.read_only,
         );
 
         let mut hooks_subscription = self.persister.subscribe_hooks();
@@ -201,6 +205,9 @@ impl BackupWatcher {
 }
 
 /// BackupWorker is a worker that bidirectionally syncs the sdk state.
+///
+/// The worker of a watch-only instance is `read_only`: it only imports the remote changes, its
+/// local state is never pushed.
 #[derive(Clone)]
 struct BackupWorker {
     working_dir_path: String,
@@ -209,6 +216,7 @@ struct BackupWorker {
     encryption_key: Vec<u8>,
     legacy_encryption_key: Vec<u8>,
     events_notifier: broadcast::Sender<BreezEvent>,
+    read_only: bool,
 }
 
 impl BackupWorker {
@@ -219,6 +227,7 @@ impl BackupWorker {
         encryption_key: Vec<u8>,
         legacy_encryption_key: Vec<u8>,
         events_notifier: broadcast::Sender<BreezEvent>,
+        read_only: bool,
     ) -> Self {
         Self {
             working_dir_path,
@@ -227,6 +236,7 @@ impl BackupWorker {
             encryption_key,
             legacy_encryption_key,
             events_notifier,
+            read_only,
         }
     }
 
@@ -284,6 +294,15 @@ impl BackupWorker {
 
         self.notify(BreezEvent::BackupStarted).await?;
 
+        if self.read_only {
+            self.import_remote(sync_dir).await?;
+            // The local changes are never pushed, so they are dropped
+            self.persister
+                .delete_sync_requests_up_to(last_sync_request_id)?;
+            info!("Sync succeeded, without pushing the local changes");
+            return Ok(());
+        }
+
         // Backup the local sdk state
         let local_storage_file = tempfile::NamedTempFile::new_in(sync_dir.clone())?;
         self.persister.backup(local_storage_file.path())?;
@@ -385,6 +404,29 @@ impl BackupWorker {
         }
     }
 
+    /// Imports the remote changes into the local state, without pushing it back
+    async fn import_remote(&self, sync_dir: String) -> Result<()> {
+        let Some(state) = self.pull().await? else {
+            debug!("No remote state to import");
+            return Ok(());
+        };
+        let tmp_dir = tempdir_in(sync_dir)?;
+        let remote_storage_path = tmp_dir.path();
+        let mut remote_storage_file = File::create(remote_storage_path.join("sync_storage.sql"))?;
+        remote_storage_file.write_all(&state.data[..])?;
+        remote_storage_file.flush()?;
+        let remote_storage = SqliteStorage::new(
+            remote_storage_path
+                .as_os_str()
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        self.persister
+            .import_remote_changes(&remote_storage, true)?;
+        Ok(())
+    }
+
     async fn pull(&self) -> Result<Option<BackupState>> {
         let state = self.inner.pull().await?;
         match state {
@@ -463,6 +505,12 @@ mod tests {
     use super::BackupWatcher;
 
     async fn create_test_backup_watcher(
+    ) -> (watch::Sender<()>, BackupWatcher, Arc<MockBackupTransport>) {
+        create_test_backup_watcher_with(false).await
+    }
+
+    async fn create_test_backup_watcher_with(
+        read_only: bool,
     ) -> (watch::Sender<()>, BackupWatcher, Arc<MockBackupTransport>) {
         let config = create_test_config();
         let persister = Arc::new(create_test_persister(config.clone()));
@@ -474,6 +522,7 @@ mod tests {
             persister,
             vec![0; 32],
             vec![0; 32],
+            read_only,
         );
         let (quit_sender, receiver) = watch::channel(());
         watcher.start(receiver).await.unwrap();
@@ -555,6 +604,35 @@ mod tests {
         quit_sender.closed().await;
     }
 
+    // Test that a read-only watcher pulls the remote backup without ever pushing
+    #[tokio::test]
+    async fn test_read_only() {
+        let (quit_sender, watcher, transport) = create_test_backup_watcher_with(true).await;
+        let subscription = watcher.subscribe_events();
+        let expected_events = vec![
+            BreezEvent::BackupStarted,
+            BreezEvent::BackupSucceeded,
+            BreezEvent::BackupStarted,
+            BreezEvent::BackupSucceeded,
+        ];
+
+        let task_subscription = watcher.subscribe_events();
+        tokio::spawn(async move {
+            watcher
+                .request_backup(BackupRequest::new(true))
+                .await
+                .unwrap();
+            wait_for_backup_success(task_subscription).await;
+            watcher
+                .request_backup(BackupRequest::new(true))
+                .await
+                .unwrap();
+        });
+        test_expected_backup_events(subscription, transport, expected_events, 0, 2).await;
+        _ = quit_sender.send(());
+        quit_sender.closed().await;
+    }
+
     // Test case when remote backup is not available and we only push the local backup.
     #[tokio::test]
     async fn test_remote_not_exist() {
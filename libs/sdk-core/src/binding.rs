@@ -22,10 +22,11 @@ use sdk_common::invoice;
 pub use sdk_common::prelude::{
     parse, AesSuccessActionDataDecrypted, AesSuccessActionDataResult, BitcoinAddressData,
     CurrencyInfo, FiatCurrency, InputType, LNInvoice, LnUrlAuthRequestData, LnUrlCallbackStatus,
-    LnUrlError, LnUrlErrorData, LnUrlPayErrorData, LnUrlPayRequest, LnUrlPayRequestData,
-    LnUrlWithdrawRequest, LnUrlWithdrawRequestData, LnUrlWithdrawResult, LnUrlWithdrawSuccessData,
-    LocaleOverrides, LocalizedName, MessageSuccessActionData, Network, Rate, RouteHint,
-    RouteHintHop, SuccessActionProcessed, Symbol, UrlSuccessActionData,
+    LnUrlError, LnUrlErrorData, LnUrlPayCurrency, LnUrlPayCurrencyRequest, LnUrlPayErrorData,
+    LnUrlPayRequest, LnUrlPayRequestData, LnUrlWithdrawRequest, LnUrlWithdrawRequestData,
+    LnUrlWithdrawResult, LnUrlWithdrawSuccessData, LnUrlZapRequest, LocaleOverrides, LocalizedName,
+    MessageSuccessActionData, Network, Rate, RouteHint, RouteHintHop, SuccessActionProcessed,
+    Symbol, UrlSuccessActionData, ZapRequestData,
 };
 use sdk_common::prelude::{LnUrlPayError, LnUrlWithdrawError};
 use tokio::sync::Mutex;
@@ -39,18 +40,24 @@ use crate::error::{
 use crate::lsp::LspInformation;
 use crate::models::{Config, LogEntry, NodeState, Payment, SwapInfo};
 use crate::{
-    BackupStatus, BuyBitcoinRequest, BuyBitcoinResponse, CheckMessageRequest, CheckMessageResponse,
-    ConfigureNodeRequest, ConnectRequest, EnvironmentType, ListPaymentsRequest, ListSwapsRequest,
-    LnUrlAuthError, NodeConfig, NodeCredentials, OnchainPaymentLimitsResponse,
-    OpenChannelFeeRequest, OpenChannelFeeResponse, PayOnchainRequest, PayOnchainResponse,
-    PrepareOnchainPaymentRequest, PrepareOnchainPaymentResponse, PrepareRedeemOnchainFundsRequest,
+    AddContactRequest, AddRecurringPaymentRequest, AppKey, AppKeyEcdhRequest, AppKeyEcdhResponse,
+    AppPublicKey, BackupStatus, BuyBitcoinRequest, BuyBitcoinResponse, CheckMessageRequest,
+    CheckMessageResponse, ConfigureNodeRequest, ConnectRequest, ConnectWatchOnlyRequest, Contact,
+    CreateHoldInvoiceRequest, EnvironmentType, ExportRequest, ExportResponse, FiatValuationRequest,
+    FiatValuationResponse, GreenlightDevice, HoldInvoice, Invoice, ListInvoicesRequest,
+    ListPaymentsRequest, ListSwapsRequest, LnUrlAuthError, NodeConfig, NodeCredentials,
+    OnchainPaymentLimitsResponse, OpenChannelFeeRequest, OpenChannelFeeResponse, PayOnchainRequest,
+    PayOnchainResponse, PaymentFiatValue, PrepareOnchainPaymentRequest,
+    PrepareOnchainPaymentResponse, PrepareRedeemOnchainFundsRequest,
     PrepareRedeemOnchainFundsResponse, PrepareRefundRequest, PrepareRefundResponse,
-    ReceiveOnchainRequest, ReceivePaymentRequest, ReceivePaymentResponse,
-    RedeemOnchainFundsRequest, RedeemOnchainFundsResponse, RefundRequest, RefundResponse,
-    ReportIssueRequest, ReverseSwapFeesRequest, ReverseSwapInfo, ReverseSwapPairInfo,
-    SendPaymentRequest, SendPaymentResponse, SendSpontaneousPaymentRequest,
-    ServiceHealthCheckResponse, SignMessageRequest, SignMessageResponse, StaticBackupRequest,
-    StaticBackupResponse,
+    ReceiveFiatPaymentRequest, ReceiveOnchainRequest, ReceivePaymentRequest,
+    ReceivePaymentResponse, RecurringPayment, RecurringPaymentRun, RedeemOnchainFundsRequest,
+    RedeemOnchainFundsResponse, RefundRequest, RefundResponse, RegisterDeviceRequest,
+    RegisterDeviceResponse, ReportIssueRequest, ReverseSwapFeesRequest, ReverseSwapInfo,
+    ReverseSwapPairInfo, SendPaymentRequest, SendPaymentResponse, SendSpontaneousPaymentRequest,
+    ServiceHealthCheckResponse, SignMessageRequest, SignMessageResponse, SignWithAppKeyRequest,
+    SignWithAppKeyResponse, SpendingPolicy, StaticBackupRequest, StaticBackupResponse,
+    StatsRequest, StatsResponse, UpdateContactRequest,
 };
 
 // === FRB mirroring
@@ -131,6 +138,30 @@ pub struct _LnUrlPayRequest {
     pub validate_success_action_url: Option<bool>,
 }
 
+#[frb(mirror(LnUrlPayCurrencyRequest))]
+pub struct _LnUrlPayCurrencyRequest {
+    pub pay_request: LnUrlPayRequest,
+    pub currency: String,
+    pub amount: u64,
+    pub max_slippage_percent: Option<f64>,
+}
+
+#[frb(mirror(LnUrlZapRequest))]
+pub struct _LnUrlZapRequest {
+    pub pay_request: LnUrlPayRequest,
+    pub zap: ZapRequestData,
+    pub nostr_secret_key: Option<String>,
+}
+
+#[frb(mirror(ZapRequestData))]
+pub struct _ZapRequestData {
+    pub recipient_pubkey: String,
+    pub relays: Vec<String>,
+    pub event_id: Option<String>,
+    pub lnurl: Option<String>,
+    pub content: String,
+}
+
 #[frb(mirror(LnUrlPayRequestData))]
 pub struct _LnUrlPayRequestData {
     pub callback: String,
@@ -141,9 +172,19 @@ pub struct _LnUrlPayRequestData {
     pub domain: String,
     pub allows_nostr: bool,
     pub nostr_pubkey: Option<String>,
+    pub currencies: Option<Vec<LnUrlPayCurrency>>,
     pub ln_address: Option<String>,
 }
 
+#[frb(mirror(LnUrlPayCurrency))]
+pub struct _LnUrlPayCurrency {
+    pub code: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+    pub multiplier: f64,
+}
+
 #[frb(mirror(LnUrlWithdrawRequest))]
 pub struct _LnUrlWithdrawRequest {
     pub data: LnUrlWithdrawRequestData,
@@ -339,6 +380,27 @@ pub fn connect(req: ConnectRequest) -> Result<()> {
     .map_err(anyhow::Error::new::<ConnectError>)
 }
 
+/// See [BreezServices::connect_watch_only]
+pub fn connect_watch_only(req: ConnectWatchOnlyRequest) -> Result<()> {
+    block_on(async move {
+        let mut locked = BREEZ_SERVICES_INSTANCE.lock().await;
+        match *locked {
+            None => {
+                let breez_services =
+                    BreezServices::connect_watch_only(req, Box::new(BindingEventListener::new()))
+                        .await?;
+
+                *locked = Some(breez_services);
+                Ok(())
+            }
+            Some(_) => Err(ConnectError::Generic {
+                err: "Static node services already set, please call disconnect() first".into(),
+            }),
+        }
+    })
+    .map_err(anyhow::Error::new::<ConnectError>)
+}
+
 /// Check whether node service is initialized or not
 pub fn is_initialized() -> bool {
     block_on(async { get_breez_services().await.is_ok() })
@@ -351,12 +413,42 @@ pub fn sync() -> Result<()> {
         .map_err(anyhow::Error::new::<SdkError>)
 }
 
+/// See [BreezServices::take_over_node_lock]
+pub fn take_over_node_lock() -> Result<()> {
+    block_on(async { get_breez_services().await?.take_over_node_lock().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /// See [BreezServices::node_credentials]
 pub fn node_credentials() -> Result<Option<NodeCredentials>> {
     block_on(async { get_breez_services().await?.node_credentials().await })
         .map_err(anyhow::Error::new::<SdkError>)
 }
 
+/// See [BreezServices::watch_only_credentials]
+pub fn watch_only_credentials() -> Result<NodeCredentials> {
+    block_on(async { get_breez_services().await?.watch_only_credentials().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::backup_encryption_key]
+pub fn backup_encryption_key() -> Result<Vec<u8>> {
+    block_on(async { get_breez_services().await?.backup_encryption_key().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::list_devices]
+pub fn list_devices() -> Result<Vec<GreenlightDevice>> {
+    block_on(async { get_breez_services().await?.list_devices().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::register_device]
+pub fn register_device(req: RegisterDeviceRequest) -> Result<RegisterDeviceResponse> {
+    block_on(async { get_breez_services().await?.register_device(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /// See [BreezServices::node_info]
 pub fn node_info() -> Result<NodeState> {
     block_on(async {
@@ -398,6 +490,24 @@ pub fn check_message(req: CheckMessageRequest) -> Result<CheckMessageResponse> {
         .map_err(anyhow::Error::new::<SdkError>)
 }
 
+/// See [BreezServices::app_public_key]
+pub fn app_public_key(key: AppKey) -> Result<AppPublicKey> {
+    block_on(async { get_breez_services().await?.app_public_key(key).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::sign_with_app_key]
+pub fn sign_with_app_key(req: SignWithAppKeyRequest) -> Result<SignWithAppKeyResponse> {
+    block_on(async { get_breez_services().await?.sign_with_app_key(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::app_key_ecdh]
+pub fn app_key_ecdh(req: AppKeyEcdhRequest) -> Result<AppKeyEcdhResponse> {
+    block_on(async { get_breez_services().await?.app_key_ecdh(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /*  Breez Services Helper API's */
 
 /// See [breez_services::mnemonic_to_seed]
@@ -536,12 +646,127 @@ pub fn list_payments(req: ListPaymentsRequest) -> Result<Vec<Payment>> {
         .map_err(anyhow::Error::new::<SdkError>)
 }
 
+/// See [BreezServices::payment_stats]
+pub fn payment_stats(req: StatsRequest) -> Result<StatsResponse> {
+    block_on(async { get_breez_services().await?.payment_stats(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::export_payments]
+pub fn export_payments(req: ExportRequest) -> Result<ExportResponse> {
+    block_on(async { get_breez_services().await?.export_payments(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::add_contact]
+pub fn add_contact(req: AddContactRequest) -> Result<Contact> {
+    block_on(async { get_breez_services().await?.add_contact(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::update_contact]
+pub fn update_contact(req: UpdateContactRequest) -> Result<Contact> {
+    block_on(async { get_breez_services().await?.update_contact(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::delete_contact]
+pub fn delete_contact(id: String) -> Result<()> {
+    block_on(async { get_breez_services().await?.delete_contact(id).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::get_contact]
+pub fn get_contact(id: String) -> Result<Option<Contact>> {
+    block_on(async { get_breez_services().await?.get_contact(id).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::list_contacts]
+pub fn list_contacts() -> Result<Vec<Contact>> {
+    block_on(async { get_breez_services().await?.list_contacts().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::add_recurring_payment]
+pub fn add_recurring_payment(req: AddRecurringPaymentRequest) -> Result<RecurringPayment> {
+    block_on(async { get_breez_services().await?.add_recurring_payment(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::list_recurring_payments]
+pub fn list_recurring_payments() -> Result<Vec<RecurringPayment>> {
+    block_on(async { get_breez_services().await?.list_recurring_payments().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::set_recurring_payment_paused]
+pub fn set_recurring_payment_paused(id: String, paused: bool) -> Result<RecurringPayment> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .set_recurring_payment_paused(id, paused)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::delete_recurring_payment]
+pub fn delete_recurring_payment(id: String) -> Result<()> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .delete_recurring_payment(id)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::list_recurring_payment_runs]
+pub fn list_recurring_payment_runs(id: String) -> Result<Vec<RecurringPaymentRun>> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .list_recurring_payment_runs(id)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::set_spending_policy]
+pub fn set_spending_policy(policy: Option<SpendingPolicy>) -> Result<()> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .set_spending_policy(policy)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::spending_policy]
+pub fn spending_policy() -> Result<Option<SpendingPolicy>> {
+    block_on(async { get_breez_services().await?.spending_policy().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /// See [BreezServices::list_payments]
 pub fn payment_by_hash(hash: String) -> Result<Option<Payment>> {
     block_on(async { get_breez_services().await?.payment_by_hash(hash).await })
         .map_err(anyhow::Error::new::<SdkError>)
 }
 
+/// See [BreezServices::verify_lnurl_payment]
+pub fn verify_lnurl_payment(payment_hash: String) -> Result<Payment> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .verify_lnurl_payment(payment_hash)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /// See [BreezServices::set_payment_metadata]
 pub fn set_payment_metadata(hash: String, metadata: String) -> Result<()> {
     block_on(async {
@@ -578,6 +803,47 @@ pub fn receive_payment(req: ReceivePaymentRequest) -> Result<ReceivePaymentRespo
         .map_err(anyhow::Error::new::<ReceivePaymentError>)
 }
 
+/// See [BreezServices::receive_fiat_payment]
+pub fn receive_fiat_payment(req: ReceiveFiatPaymentRequest) -> Result<ReceivePaymentResponse> {
+    block_on(async { get_breez_services().await?.receive_fiat_payment(req).await })
+        .map_err(anyhow::Error::new::<ReceivePaymentError>)
+}
+
+/// See [BreezServices::list_invoices]
+pub fn list_invoices(req: ListInvoicesRequest) -> Result<Vec<Invoice>> {
+    block_on(async { get_breez_services().await?.list_invoices(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::create_hold_invoice]
+pub fn create_hold_invoice(req: CreateHoldInvoiceRequest) -> Result<HoldInvoice> {
+    block_on(async { get_breez_services().await?.create_hold_invoice(req).await })
+        .map_err(anyhow::Error::new::<ReceivePaymentError>)
+}
+
+/// See [BreezServices::settle_invoice]
+pub fn settle_invoice(preimage: Vec<u8>) -> Result<()> {
+    block_on(async { get_breez_services().await?.settle_invoice(preimage).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::cancel_invoice]
+pub fn cancel_invoice(payment_hash: String) -> Result<()> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .cancel_invoice(payment_hash)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::list_hold_invoices]
+pub fn list_hold_invoices() -> Result<Vec<HoldInvoice>> {
+    block_on(async { get_breez_services().await?.list_hold_invoices().await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /*  LNURL API's */
 
 /// See [BreezServices::lnurl_pay]
@@ -586,6 +852,29 @@ pub fn lnurl_pay(req: LnUrlPayRequest) -> Result<LnUrlPayResult> {
         .map_err(anyhow::Error::new::<LnUrlPayError>)
 }
 
+/// See [BreezServices::lnurl_pay_in_currency]
+pub fn lnurl_pay_in_currency(req: LnUrlPayCurrencyRequest) -> Result<LnUrlPayResult> {
+    block_on(async { get_breez_services().await?.lnurl_pay_in_currency(req).await })
+        .map_err(anyhow::Error::new::<LnUrlPayError>)
+}
+
+/// See [BreezServices::lnurl_zap]
+pub fn lnurl_zap(req: LnUrlZapRequest) -> Result<LnUrlPayResult> {
+    block_on(async { get_breez_services().await?.lnurl_zap(req).await })
+        .map_err(anyhow::Error::new::<LnUrlPayError>)
+}
+
+/// See [BreezServices::link_zap_receipt]
+pub fn link_zap_receipt(payment_hash: String, zap_receipt: String) -> Result<Payment> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .link_zap_receipt(payment_hash, zap_receipt)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /// See [BreezServices::lnurl_withdraw]
 pub fn lnurl_withdraw(req: LnUrlWithdrawRequest) -> Result<LnUrlWithdrawResult> {
     block_on(async { get_breez_services().await?.lnurl_withdraw(req).await })
@@ -614,6 +903,26 @@ pub fn fetch_fiat_rates() -> Result<Vec<Rate>> {
         .map_err(anyhow::Error::new::<SdkError>)
 }
 
+/// See [BreezServices::payment_fiat_value]
+pub fn payment_fiat_value(
+    payment_id: String,
+    currency: String,
+) -> Result<Option<PaymentFiatValue>> {
+    block_on(async {
+        get_breez_services()
+            .await?
+            .payment_fiat_value(payment_id, currency)
+            .await
+    })
+    .map_err(anyhow::Error::new::<SdkError>)
+}
+
+/// See [BreezServices::fiat_valuation]
+pub fn fiat_valuation(req: FiatValuationRequest) -> Result<FiatValuationResponse> {
+    block_on(async { get_breez_services().await?.fiat_valuation(req).await })
+        .map_err(anyhow::Error::new::<SdkError>)
+}
+
 /// See [BreezServices::list_fiat_currencies]
 pub fn list_fiat_currencies() -> Result<Vec<FiatCurrency>> {
     block_on(async { get_breez_services().await?.list_fiat_currencies().await })
@@ -9,7 +9,8 @@ use anyhow::{anyhow, Result};
 use bip39::*;
 use bitcoin::bip32::ChildNumber;
 use bitcoin::hashes::{sha256, Hash};
-use chrono::Local;
+use bitcoin::secp256k1::{PublicKey, SecretKey};
+use chrono::{Local, Utc};
 use futures::{StreamExt, TryFutureExt};
 use log::{LevelFilter, Metadata, Record};
 use sdk_common::grpc;
@@ -17,9 +18,10 @@ use sdk_common::prelude::*;
 use serde::Serialize;
 use serde_json::{json, Value};
 use strum_macros::EnumString;
-use tokio::sync::{mpsc, watch, Mutex};
+use tokio::sync::{mpsc, watch, Mutex, MutexGuard, Notify};
 use tokio::time::{sleep, MissedTickBehavior};
 
+use crate::app_keys;
 use crate::backup::{BackupRequest, BackupTransport, BackupWatcher};
 use crate::buy::{BuyBitcoinApi, BuyBitcoinService};
 use crate::chain::{
@@ -32,22 +34,50 @@ use crate::error::{
 };
 use crate::lnurl::auth::SdkLnurlAuthSigner;
 use crate::lnurl::pay::*;
+use crate::lnurl::pay_server::SdkLnUrlPayInvoiceProvider;
 use crate::lsp::LspInformation;
 use crate::models::{
     sanitize::*, ChannelState, ClosedChannelPaymentDetails, Config, EnvironmentType, LspAPI,
     NodeState, Payment, PaymentDetails, PaymentType, ReverseSwapPairInfo, ReverseSwapServiceAPI,
     SwapInfo, SwapperAPI, INVOICE_PAYMENT_FEE_EXPIRY_SECONDS,
 };
-use crate::node_api::{CreateInvoiceRequest, NodeAPI};
+use crate::node_api::{
+    CreateInvoiceRequest, HeldHtlcs, HoldInvoiceRequest, NodeAPI, NodeLockStatus,
+};
+#[cfg(feature = "nwc")]
+use crate::nwc::{NwcService, NwcServiceConfig, NWC_DERIVATION_INDEX};
 use crate::persist::cache::NodeStateStorage;
 use crate::persist::db::SqliteStorage;
+use crate::persist::recurring::RecurringPaymentClaim;
 use crate::persist::swap::SwapStorage;
 use crate::persist::transactions::PaymentStorage;
+use crate::policy::{self, Payee, PolicyPayment, SpendingPolicyError, SPENDING_POLICY_WINDOW_SEC};
+use crate::recurring::{
+    self, AttemptOutcome, RunAction, CLAIM_TIMEOUT_SEC, IN_FLIGHT_TIMEOUT_SEC,
+    MAX_RECURRING_PAYMENT_ATTEMPTS, RETRY_BACKOFF_SEC,
+};
+use crate::signer::{RemoteSigner, SignerAPI};
 use crate::swap_in::{BTCReceiveSwap, BTCReceiveSwapParameters, TaprootSwapperAPI};
 use crate::swap_out::boltzswap::BoltzApi;
 use crate::swap_out::reverseswap::BTCSendSwap;
 use crate::*;
 
+/// Rates fetched within this interval of the last snapshot are not recorded again
+const FIAT_RATES_SNAPSHOT_INTERVAL_SEC: i64 = 600;
+/// How far from a payment time a recorded rate can be, to be used to value the payment
+const FIAT_RATE_MAX_DISTANCE_SEC: i64 = 86_400;
+/// Default for [LnUrlPayCurrencyRequest::max_slippage_percent]
+const DEFAULT_MAX_SLIPPAGE_PERCENT: f64 = 1.0;
+/// Label of the device of this instance, which is added to the registry without one
+const UNLABELED_DEVICE: &str = "Unlabeled device";
+const WATCH_ONLY_DEVICE: &str = "Watch-only device";
+/// Interval at which an instance records in the device registry that its signer runs
+const DEVICE_HEARTBEAT_INTERVAL_SEC: u64 = 600;
+/// A device seen within this time is considered to run a signer
+const DEVICE_SIGNER_ACTIVE_SEC: i64 = 1_800;
+/// Longest time the recurring payments are left unchecked, when nothing is due earlier
+const RECURRING_PAYMENTS_MAX_WAIT_SEC: i64 = 3_600;
+
 pub type BreezServicesResult<T, E = ConnectError> = Result<T, E>;
 
 /// Trait that can be used to react to various [BreezEvent]s emitted by the SDK.
@@ -82,6 +112,20 @@ pub enum BreezEvent {
     /// Indicates that a swap has been updated which may also
     /// include a status change
     SwapUpdated { details: SwapInfo },
+    /// Indicates that a scheduled payment of a [RecurringPayment] has been attempted
+    RecurringPaymentAttempted {
+        details: RecurringPaymentAttemptedDetails,
+    },
+    /// Indicates that the payment of a [HoldInvoice] is held, waiting to be settled or cancelled.
+    /// Only emitted by LDK nodes, as Greenlight nodes don't support hold invoices.
+    HoldInvoiceAccepted { details: HoldInvoice },
+    /// Indicates that an [Invoice] expired without being paid
+    InvoiceExpired { details: Invoice },
+    /// Indicates that another instance runs the node, so the SDK is read-only: the node
+    /// isn't started, until it's taken over with [BreezServices::take_over_node_lock]
+    NodeLockedByOtherInstance { instance_id: String },
+    /// Indicates that another instance took the node over, so it was stopped here
+    NodeLockLost { instance_id: Option<String> },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -105,6 +149,16 @@ pub struct InvoicePaidDetails {
     pub payment: Option<Payment>,
 }
 
+/// Details of an attempt to make a scheduled payment, included as payload in an emitted [BreezEvent]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecurringPaymentAttemptedDetails {
+    pub run: RecurringPaymentRun,
+    /// The resulting payment, if the attempt succeeded
+    pub payment: Option<Payment>,
+    /// Whether the payment is attempted again later, if the attempt failed
+    pub will_retry: bool,
+}
+
 pub trait LogStream: Send + Sync {
     fn log(&self, l: LogEntry);
 }
@@ -168,6 +222,20 @@ pub struct BreezServices {
     event_listener: Option<Box<dyn EventListener>>,
     backup_watcher: Arc<BackupWatcher>,
     shutdown_sender: watch::Sender<()>,
+    recurring_payments_trigger: Notify,
+    /// Held from checking a payment against the [SpendingPolicy] until it's recorded, so
+    /// concurrent payments can't overrun the budgets
+    spending_policy_lock: Mutex<()>,
+    /// Set on watch-only instances, which can't spend
+    read_only: bool,
+}
+
+/// An outgoing payment allowed by the [SpendingPolicy]
+struct PolicyClearance<'a> {
+    /// To be held until the payment is recorded. Unset when there is no policy.
+    _lock: Option<MutexGuard<'a, ()>>,
+    /// The routing fee limit to send the payment with, when the policy limits fees or spending
+    max_fee_msat: Option<u64>,
 }
 
 impl BreezServices {
@@ -185,6 +253,7 @@ impl BreezServices {
     ///   private key, typically derived from the mnemonic. When using a new `invite_code`,
     ///   the seed should be derived from a new random mnemonic. When re-using an `invite_code`,
     ///   the same mnemonic should be used as when the `invite_code` was first used.
+    ///   With a `remote_signer_url`, the seed stays in the remote signer and must be left empty.
     /// * `event_listener` - Listener to SDK events
     ///
     pub async fn connect(
@@ -194,8 +263,25 @@ impl BreezServices {
         let (sdk_version, sdk_git_hash) = Self::get_sdk_version();
         info!("SDK v{sdk_version} ({sdk_git_hash})");
         let start = Instant::now();
-        let services = BreezServicesBuilder::new(req.config)
-            .seed(req.seed)
+        let mut builder = BreezServicesBuilder::new(req.config);
+        match req.remote_signer_url {
+            Some(url) => {
+                ensure_sdk!(
+                    req.seed.is_empty(),
+                    ConnectError::Generic {
+                        err: "The seed must be empty when using a remote signer".into()
+                    }
+                );
+                let signer = RemoteSigner::connect(url).await.map_err(|e| {
+                    ConnectError::ServiceConnectivity {
+                        err: format!("Failed to connect to the remote signer: {e}"),
+                    }
+                })?;
+                builder.signer(Arc::new(signer))
+            }
+            None => builder.seed(req.seed),
+        };
+        let services = builder
             .build(req.restore_only, Some(event_listener))
             .await?;
         services.start().await?;
@@ -204,6 +290,45 @@ impl BreezServices {
         Ok(services)
     }
 
+    /// Connects a watch-only instance, which shows the balance and the payments of a wallet
+    /// without its seed, e.g. for accounting or support.
+    ///
+    /// It pulls the node state and the payments from the node, and restores the SDK data synced
+    /// by the other instances of the wallet. Spending, like [BreezServices::send_payment],
+    /// [BreezServices::pay_onchain], [BreezServices::refund] or
+    /// [BreezServices::redeem_onchain_funds], fails with a `ReadOnly` error. Receiving, like
+    /// [BreezServices::receive_payment] or [BreezServices::receive_onchain], fails too, and the
+    /// instance neither connects to the LSP nor pushes its own changes to the synced data.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The `config`, and the `node_credentials` and `backup_encryption_key` exported
+    ///   from an instance with the seed by [BreezServices::watch_only_credentials] and
+    ///   [BreezServices::backup_encryption_key]. Only Greenlight nodes are supported.
+    /// * `event_listener` - Listener to SDK events
+    pub async fn connect_watch_only(
+        req: ConnectWatchOnlyRequest,
+        event_listener: Box<dyn EventListener>,
+    ) -> BreezServicesResult<Arc<BreezServices>> {
+        let (sdk_version, sdk_git_hash) = Self::get_sdk_version();
+        info!("SDK v{sdk_version} ({sdk_git_hash}), watch-only");
+        ensure_sdk!(
+            req.backup_encryption_key.len() == 32,
+            ConnectError::Generic {
+                err: "The backup encryption key must be 32 bytes".into()
+            }
+        );
+        let start = Instant::now();
+        let services = BreezServicesBuilder::new(req.config)
+            .watch_only(req.node_credentials, req.backup_encryption_key)
+            .build(None, Some(event_listener))
+            .await?;
+        services.start().await?;
+        let connect_duration = start.elapsed();
+        info!("SDK connected in: {connect_duration:?}");
+        Ok(services)
+    }
+
     fn get_sdk_version() -> (&'static str, &'static str) {
         let sdk_version = option_env!("CARGO_PKG_VERSION").unwrap_or_default();
         let sdk_git_hash = option_env!("SDK_GIT_HASH").unwrap_or_default();
@@ -267,10 +392,22 @@ impl BreezServices {
     ///
     /// Calling `send_payment` ensures that the payment is not already completed; if so, it will result in an error.
     /// If the invoice doesn't specify an amount, the amount is taken from the `amount_msat` arg.
+    /// The payment must also be allowed by the [SpendingPolicy], if one is set.
     pub async fn send_payment(
         &self,
         req: SendPaymentRequest,
     ) -> Result<SendPaymentResponse, SendPaymentError> {
+        self.send_payment_to(req, Payee::default()).await
+    }
+
+    /// Pays a bolt11 invoice. Besides the node of the invoice, `payee` can identify who
+    /// is paid to the [SpendingPolicy], like an LNURL-pay domain.
+    async fn send_payment_to(
+        &self,
+        req: SendPaymentRequest,
+        mut payee: Payee,
+    ) -> Result<SendPaymentResponse, SendPaymentError> {
+        self.ensure_can_spend()?;
         let parsed_invoice = parse_invoice(req.bolt11.as_str())?;
         let invoice_expiration = parsed_invoice.timestamp + parsed_invoice.expiry;
         let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
@@ -308,12 +445,27 @@ impl BreezServices {
             return Err(SendPaymentError::AlreadyPaid);
         }
 
+        payee.ids.push(parsed_invoice.payee_pubkey.clone());
+        let clearance = self
+            .check_spending_policy(PolicyPayment::routed(
+                amount_msat,
+                self.max_routing_fee_msat(amount_msat),
+                payee,
+            ))
+            .await?;
+        let max_fee_msat = clearance.max_fee_msat;
+
         // If there is an lsp, the invoice route hint does not contain the
         // lsp in the hint, and trampoline payments are requested, attempt a
-        // trampoline payment.
-        let maybe_trampoline_id = self.get_trampoline_id(&req, &parsed_invoice)?;
+        // trampoline payment. The trampoline fee is set by the LSP, so it's
+        // skipped when the spending policy limits fees or spending.
+        let maybe_trampoline_id = match max_fee_msat {
+            Some(_) => None,
+            None => self.get_trampoline_id(&req, &parsed_invoice)?,
+        };
 
         self.persist_pending_payment(&parsed_invoice, amount_msat, req.label.clone())?;
+        drop(clearance);
 
         // If trampoline is an option, try trampoline first.
         let trampoline_result = if let Some(trampoline_id) = maybe_trampoline_id {
@@ -356,6 +508,7 @@ impl BreezServices {
                         parsed_invoice.bolt11.clone(),
                         req.amount_msat,
                         req.label.clone(),
+                        max_fee_msat,
                     )
                     .map_err(Into::into)
                     .await
@@ -374,6 +527,71 @@ impl BreezServices {
         Ok(SendPaymentResponse { payment })
     }
 
+    /// Fails with a `ReadOnly` error on instances connected with
+    /// [BreezServices::connect_watch_only]
+    fn ensure_can_spend(&self) -> SdkResult<()> {
+        ensure_sdk!(
+            !self.read_only,
+            SdkError::ReadOnly {
+                err: "Watch-only instances can't spend".into()
+            }
+        );
+        Ok(())
+    }
+
+    /// Fails with a `ReadOnly` error on instances connected with
+    /// [BreezServices::connect_watch_only]
+    fn ensure_can_receive(&self) -> SdkResult<()> {
+        ensure_sdk!(
+            !self.read_only,
+            SdkError::ReadOnly {
+                err: "Watch-only instances can't receive".into()
+            }
+        );
+        Ok(())
+    }
+
+    /// Checks an outgoing payment against the [SpendingPolicy], if one is set.
+    ///
+    /// The returned clearance must be held until the payment is recorded, as pending or sent.
+    async fn check_spending_policy(
+        &self,
+        payment: PolicyPayment,
+    ) -> Result<PolicyClearance<'_>, SpendingPolicyError> {
+        let lock = self.spending_policy_lock.lock().await;
+        let Some(policy) = self.persister.get_spending_policy()? else {
+            return Ok(PolicyClearance {
+                _lock: None,
+                max_fee_msat: None,
+            });
+        };
+        let now = Utc::now().timestamp();
+        let recent = self.persister.list_payments(ListPaymentsRequest {
+            filters: Some(vec![PaymentTypeFilter::Sent]),
+            from_timestamp: Some(now - SPENDING_POLICY_WINDOW_SEC),
+            statuses: Some(vec![PaymentStatus::Pending, PaymentStatus::Complete]),
+            ..Default::default()
+        })?;
+        let max_fee_msat = policy::check_payment(&policy, &payment, &recent, now, |amount_msat| {
+            self.max_routing_fee_msat(amount_msat)
+        })?;
+        // The fee has to be enforced whenever the policy limits it or budgets spending, which
+        // rules out trampoline, whose fee is set by the LSP
+        let limited = policy.max_fee_msat.is_some()
+            || policy.daily_budget_msat.is_some()
+            || policy.weekly_budget_msat.is_some();
+        Ok(PolicyClearance {
+            _lock: Some(lock),
+            max_fee_msat: limited.then_some(max_fee_msat),
+        })
+    }
+
+    /// The most the node may spend on routing fees when paying `amount_msat`
+    fn max_routing_fee_msat(&self, amount_msat: u64) -> u64 {
+        let proportional = amount_msat as f64 * self.config.maxfee_percent / 100.0;
+        (proportional as u64).max(self.config.exemptfee_msat)
+    }
+
     fn get_trampoline_id(
         &self,
         req: &SendPaymentRequest,
@@ -414,6 +632,16 @@ impl BreezServices {
         &self,
         req: SendSpontaneousPaymentRequest,
     ) -> Result<SendPaymentResponse, SendPaymentError> {
+        self.ensure_can_spend()?;
+        // Keysend payments aren't recorded until they complete, so the clearance is held
+        // throughout
+        let clearance = self
+            .check_spending_policy(PolicyPayment::routed(
+                req.amount_msat,
+                self.max_routing_fee_msat(req.amount_msat),
+                Payee::node(&req.node_id),
+            ))
+            .await?;
         let payment_res = self
             .node_api
             .send_spontaneous_payment(
@@ -421,12 +649,14 @@ impl BreezServices {
                 req.amount_msat,
                 req.extra_tlvs,
                 req.label.clone(),
+                clearance.max_fee_msat,
             )
             .map_err(Into::into)
             .await;
         let payment = self
             .on_payment_completed(req.node_id, None, req.label, payment_res)
             .await?;
+        drop(clearance);
         Ok(SendPaymentResponse { payment })
     }
 
@@ -439,7 +669,7 @@ impl BreezServices {
     ///
     /// This method will return an [anyhow::Error] when any validation check fails.
     pub async fn lnurl_pay(&self, req: LnUrlPayRequest) -> Result<LnUrlPayResult, LnUrlPayError> {
-        match validate_lnurl_pay(
+        let validated = validate_lnurl_pay(
             self.rest_client.as_ref(),
             req.amount_msat,
             &req.comment,
@@ -447,8 +677,167 @@ impl BreezServices {
             self.config.network,
             req.validate_success_action_url,
         )
-        .await?
-        {
+        .await?;
+        self.complete_lnurl_pay(req, validated, None, None).await
+    }
+
+    /// Second step of LNURL-pay, with the amount denominated in one of the
+    /// [LnUrlPayRequestData::currencies] advertised by the endpoint, which converts it at its
+    /// own rate.
+    ///
+    /// The invoice amount is checked against the advertised multiplier, within
+    /// [LnUrlPayCurrencyRequest::max_slippage_percent]. The amount and the rate implied by the
+    /// invoice are stored with the payment, see [LnPaymentDetails::fiat_amount].
+    pub async fn lnurl_pay_in_currency(
+        &self,
+        req: LnUrlPayCurrencyRequest,
+    ) -> Result<LnUrlPayResult, LnUrlPayError> {
+        let currency = req
+            .pay_request
+            .data
+            .currencies
+            .iter()
+            .flatten()
+            .find(|c| c.code.eq_ignore_ascii_case(&req.currency))
+            .cloned()
+            .ok_or(LnUrlPayError::Generic {
+                err: format!("Currency {} is not supported by the endpoint", req.currency),
+            })?;
+        let validated = validate_lnurl_pay_in_currency(
+            self.rest_client.as_ref(),
+            req.amount,
+            &currency,
+            req.max_slippage_percent
+                .unwrap_or(DEFAULT_MAX_SLIPPAGE_PERCENT),
+            &req.pay_request.comment,
+            &req.pay_request.data,
+            self.config.network,
+            req.pay_request.validate_success_action_url,
+        )
+        .await?;
+
+        let fiat_amount = match &validated {
+            ValidatedCallbackResponse::EndpointSuccess { data } => {
+                let invoice_amount_msat = parse_invoice(&data.pr)?.amount_msat.unwrap_or_default();
+                let amount = req.amount as f64 / 10f64.powi(currency.decimals as i32);
+                Some(FiatAmount {
+                    currency: currency.code,
+                    amount,
+                    rate: amount / invoice_amount_msat as f64 * 100_000_000_000.0,
+                    spread_percent: 0.0,
+                })
+            }
+            ValidatedCallbackResponse::EndpointError { .. } => None,
+        };
+        self.complete_lnurl_pay(req.pay_request, validated, None, fiat_amount)
+            .await
+    }
+
+    /// Second step of LNURL-pay for a NIP-57 zap. Works like [BreezServices::lnurl_pay], except
+    /// that a signed zap request is attached to the callback, so the recipient's LNURL provider
+    /// publishes a zap receipt once the invoice is paid.
+    ///
+    /// The zap request is signed with [LnUrlZapRequest::nostr_secret_key] or, if not set, with a
+    /// key derived from the wallet seed. It is stored with the payment, see
+    /// [LnPaymentDetails::zap_request], so the receipt can later be linked to it with
+    /// [BreezServices::link_zap_receipt].
+    pub async fn lnurl_zap(&self, req: LnUrlZapRequest) -> Result<LnUrlPayResult, LnUrlPayError> {
+        let secret_key = match req.nostr_secret_key {
+            Some(key) => hex::decode(key).map_err(|_| LnUrlPayError::Generic {
+                err: "Invalid Nostr secret key".into(),
+            })?,
+            None => self
+                .node_api
+                .derive_bip32_key(vec![
+                    ChildNumber::Hardened {
+                        index: ZAP_DERIVATION_INDEX,
+                    },
+                    ChildNumber::Hardened { index: 0 },
+                ])
+                .await
+                .map_err(SdkError::from)?
+                .private_key
+                .secret_bytes()
+                .to_vec(),
+        };
+        let zap_request = build_zap_request(&secret_key, req.pay_request.amount_msat, &req.zap)?;
+        self.lnurl_zap_with_request(req.pay_request, zap_request)
+            .await
+    }
+
+    pub(crate) async fn lnurl_zap_with_request(
+        &self,
+        pay_req: LnUrlPayRequest,
+        zap_request: NostrEvent,
+    ) -> Result<LnUrlPayResult, LnUrlPayError> {
+        let validated = validate_lnurl_zap(
+            self.rest_client.as_ref(),
+            pay_req.amount_msat,
+            &pay_req.comment,
+            &zap_request,
+            &pay_req.data,
+            self.config.network,
+            pay_req.validate_success_action_url,
+        )
+        .await?;
+        let zap_request = serde_json::to_string(&zap_request).map_err(LnUrlError::from)?;
+        self.complete_lnurl_pay(pay_req, validated, Some(zap_request), None)
+            .await
+    }
+
+    /// Links the zap receipt (kind 9735 event) published by the recipient's LNURL provider to a
+    /// zap sent with [BreezServices::lnurl_zap].
+    ///
+    /// The receipt must be signed by the recipient's LNURL provider, with the `nostrPubkey` it
+    /// advertised when the zap was sent, reference the paid invoice and embed the stored zap
+    /// request.
+    pub async fn link_zap_receipt(
+        &self,
+        payment_hash: String,
+        zap_receipt: String,
+    ) -> SdkResult<Payment> {
+        let payment =
+            self.persister
+                .get_payment_by_hash(&payment_hash)?
+                .ok_or(SdkError::Generic {
+                    err: "Payment not found".into(),
+                })?;
+        let PaymentDetails::Ln { ref data } = payment.details else {
+            return Err(SdkError::Generic {
+                err: "Payment is not a Lightning payment".into(),
+            });
+        };
+        let zap_request: NostrEvent =
+            serde_json::from_str(data.zap_request.as_ref().ok_or(SdkError::Generic {
+                err: "Payment is not a zap".into(),
+            })?)?;
+        let nostr_pubkey = self
+            .persister
+            .get_payment_zap_nostr_pubkey(&payment_hash)?
+            .ok_or(SdkError::Generic {
+                err: "The Nostr key of the zap recipient is unknown".into(),
+            })?;
+        let receipt: NostrEvent = serde_json::from_str(&zap_receipt)?;
+        validate_zap_receipt(&receipt, &zap_request, &data.bolt11, Some(&nostr_pubkey))?;
+
+        self.persister
+            .set_payment_zap_receipt(&payment_hash, &zap_receipt)?;
+        Ok(self
+            .persister
+            .get_payment_by_hash(&payment_hash)?
+            .ok_or(SdkError::Generic {
+                err: "Payment not found".into(),
+            })?)
+    }
+
+    async fn complete_lnurl_pay(
+        &self,
+        req: LnUrlPayRequest,
+        validated: ValidatedCallbackResponse,
+        zap_request: Option<String>,
+        fiat_amount: Option<FiatAmount>,
+    ) -> Result<LnUrlPayResult, LnUrlPayError> {
+        match validated {
             ValidatedCallbackResponse::EndpointError { data: e } => {
                 Ok(LnUrlPayResult::EndpointError { data: e })
             }
@@ -460,12 +849,15 @@ impl BreezServices {
                     label: req.payment_label,
                 };
                 let invoice = parse_invoice(cb.pr.as_str())?;
+                let payee = Payee::lnurl(req.data.ln_address.as_deref(), &req.data.domain);
 
-                let payment = match self.send_payment(pay_req).await {
+                let payment = match self.send_payment_to(pay_req, payee).await {
                     Ok(p) => Ok(p),
                     e @ Err(
                         SendPaymentError::InvalidInvoice { .. }
-                        | SendPaymentError::ServiceConnectivity { .. },
+                        | SendPaymentError::ServiceConnectivity { .. }
+                        | SendPaymentError::PolicyViolation { .. }
+                        | SendPaymentError::ReadOnly { .. },
                     ) => e,
                     Err(e) => {
                         return Ok(LnUrlPayResult::PayError {
@@ -515,6 +907,7 @@ impl BreezServices {
                     Some(_) => None,
                     None => Some(req.data.domain),
                 };
+                let zap_nostr_pubkey = zap_request.as_ref().and(req.data.nostr_pubkey);
                 // Store SA (if available) + LN Address in separate table, associated to payment_hash
                 self.persister.insert_payment_external_info(
                     &details.payment_hash,
@@ -522,6 +915,11 @@ impl BreezServices {
                         lnurl_pay_success_action: maybe_sa_processed.clone(),
                         lnurl_pay_domain,
                         lnurl_pay_comment: req.comment,
+                        lnurl_verify_url: cb.verify,
+                        zap_request,
+                        zap_receipt: None,
+                        zap_nostr_pubkey,
+                        fiat_amount,
                         lnurl_metadata: Some(req.data.metadata_str),
                         ln_address: req.data.ln_address,
                         lnurl_withdraw_endpoint: None,
@@ -571,6 +969,11 @@ impl BreezServices {
                     lnurl_pay_success_action: None,
                     lnurl_pay_domain: None,
                     lnurl_pay_comment: None,
+                    lnurl_verify_url: None,
+                    zap_request: None,
+                    zap_receipt: None,
+                    zap_nostr_pubkey: None,
+                    fiat_amount: None,
                     lnurl_metadata: None,
                     ln_address: None,
                     lnurl_withdraw_endpoint: Some(lnurl_w_endpoint),
@@ -600,6 +1003,50 @@ impl BreezServices {
         .await?)
     }
 
+    /// Builds a [LnUrlPayServer] that serves Lightning Addresses backed by this node.
+    ///
+    /// The returned server is transport-agnostic: the embedding HTTP server forwards requests to
+    /// [LnUrlPayServer::handle]. Invoices are created via [BreezServices::receive_payment] with a
+    /// description hash committing to the LNURL metadata.
+    pub fn lnurl_pay_server(
+        &self,
+        config: LnUrlPayServerConfig,
+    ) -> SdkResult<LnUrlPayServer<dyn LnUrlPayInvoiceProvider>> {
+        let provider: Arc<dyn LnUrlPayInvoiceProvider> = Arc::new(SdkLnUrlPayInvoiceProvider::new(
+            self.payment_receiver.clone(),
+            self.persister.clone(),
+        ));
+        Ok(LnUrlPayServer::new(config, provider)?)
+    }
+
+    /// Builds a [NwcService] that lets Nostr Wallet Connect (NIP-47) clients use this node.
+    ///
+    /// The service identity is derived from the node seed, so the connection URIs stay valid
+    /// across restarts. Call [NwcService::start] to start answering requests.
+    #[cfg(feature = "nwc")]
+    pub async fn nwc_service(
+        self: &Arc<Self>,
+        config: NwcServiceConfig,
+    ) -> SdkResult<Arc<NwcService>> {
+        let service_key = self
+            .node_api
+            .derive_bip32_key(vec![
+                ChildNumber::Hardened {
+                    index: NWC_DERIVATION_INDEX,
+                },
+                ChildNumber::Hardened { index: 0 },
+            ])
+            .await?;
+        Ok(Arc::new(NwcService::new(
+            config,
+            &service_key.private_key.secret_bytes(),
+            self.clone(),
+            self.persister.clone(),
+            self.config.network,
+            self.shutdown_sender.subscribe(),
+        )?))
+    }
+
     /// Creates an bolt11 payment request.
     /// This also works when the node doesn't have any channels and need inbound liquidity.
     /// In such case when the invoice is paid a new zero-conf channel will be open by the LSP,
@@ -608,7 +1055,243 @@ impl BreezServices {
         &self,
         req: ReceivePaymentRequest,
     ) -> Result<ReceivePaymentResponse, ReceivePaymentError> {
-        self.payment_receiver.receive_payment(req).await
+        self.ensure_can_receive()?;
+        let description = req.description.clone();
+        let res = self.payment_receiver.receive_payment(req).await?;
+        let ln_invoice = &res.ln_invoice;
+        let payer_amount_msat = ln_invoice.amount_msat.unwrap_or_default();
+        self.persister.insert_invoice(&Invoice {
+            payment_hash: ln_invoice.payment_hash.clone(),
+            bolt11: ln_invoice.bolt11.clone(),
+            description,
+            payer_amount_msat,
+            amount_msat: payer_amount_msat - res.opening_fee_msat.unwrap_or_default(),
+            opening_fee_msat: res.opening_fee_msat,
+            opening_fee_params: res.opening_fee_params.clone(),
+            status: InvoiceStatus::Pending,
+            created_at: ln_invoice.timestamp as i64,
+            expires_at: (ln_invoice.timestamp + ln_invoice.expiry) as i64,
+        })?;
+        Ok(res)
+    }
+
+    /// Creates a bolt11 invoice for an amount denominated in a fiat currency.
+    ///
+    /// The amount is converted at the current exchange rate, see [BreezServices::fetch_fiat_rates],
+    /// and increased by [ReceiveFiatPaymentRequest::spread_percent]. The fiat amount, rate and
+    /// spread are stored with the invoice and exposed on the resulting payment as
+    /// [LnPaymentDetails::fiat_amount].
+    pub async fn receive_fiat_payment(
+        &self,
+        req: ReceiveFiatPaymentRequest,
+    ) -> Result<ReceivePaymentResponse, ReceivePaymentError> {
+        let spread_percent = req.spread_percent.unwrap_or_default();
+        ensure_sdk!(
+            req.amount.is_finite() && req.amount > 0.0,
+            ReceivePaymentError::InvalidAmount {
+                err: "Amount must be positive".into()
+            }
+        );
+        ensure_sdk!(
+            spread_percent.is_finite() && spread_percent >= 0.0,
+            ReceivePaymentError::InvalidAmount {
+                err: "Spread must not be negative".into()
+            }
+        );
+
+        let rate = self
+            .fetch_fiat_rates()
+            .await?
+            .into_iter()
+            .find(|r| r.coin == req.currency)
+            .ok_or(ReceivePaymentError::Generic {
+                err: format!("No exchange rate for {}", req.currency),
+            })?;
+        let fiat_amount = FiatAmount {
+            currency: req.currency,
+            amount: req.amount,
+            rate: rate.value,
+            spread_percent,
+        };
+        let res = self
+            .receive_payment(ReceivePaymentRequest {
+                amount_msat: fiat_amount.to_msat(),
+                description: req.description,
+                preimage: None,
+                opening_fee_params: req.opening_fee_params,
+                use_description_hash: req.use_description_hash,
+                expiry: req.expiry,
+                cltv: req.cltv,
+            })
+            .await?;
+        self.persister.insert_payment_external_info(
+            &res.ln_invoice.payment_hash,
+            PaymentExternalInfo {
+                fiat_amount: Some(fiat_amount),
+                ..Default::default()
+            },
+        )?;
+        Ok(res)
+    }
+
+    /// List the invoices created with [BreezServices::receive_payment], the latest first.
+    pub async fn list_invoices(&self, req: ListInvoicesRequest) -> SdkResult<Vec<Invoice>> {
+        Ok(self.persister.list_invoices(req, Utc::now().timestamp())?)
+    }
+
+    /// Creates a hold invoice for a payment hash chosen by the caller.
+    ///
+    /// When the invoice is paid, the node holds the incoming HTLCs instead of claiming them and
+    /// emits a [BreezEvent::HoldInvoiceAccepted] event. The payment then has to be either settled
+    /// with [BreezServices::settle_invoice], once the preimage is known, or returned to the payer
+    /// with [BreezServices::cancel_invoice], before the `settle_deadline` of the [HoldInvoice].
+    ///
+    /// No channel is opened for a hold invoice, so it can only be paid through the existing inbound
+    /// liquidity of the node.
+    ///
+    /// Hold invoices are only supported on LDK nodes. Greenlight nodes fail with an error, and
+    /// nothing is recorded.
+    pub async fn create_hold_invoice(
+        &self,
+        req: CreateHoldInvoiceRequest,
+    ) -> Result<HoldInvoice, ReceivePaymentError> {
+        self.ensure_can_receive()?;
+        let payment_hash = hex::decode(&req.payment_hash)
+            .ok()
+            .filter(|hash| hash.len() == 32)
+            .ok_or(ReceivePaymentError::Generic {
+                err: "Invalid payment hash".into(),
+            })?;
+        ensure_sdk!(
+            req.amount_msat > 0,
+            ReceivePaymentError::InvalidAmount {
+                err: "Receive amount must be more than 0".into()
+            }
+        );
+        ensure_sdk!(
+            self.persister
+                .get_hold_invoice(&req.payment_hash)?
+                .is_none(),
+            ReceivePaymentError::InvoicePreimageAlreadyExists {
+                err: "A hold invoice already exists for this payment hash".into()
+            }
+        );
+
+        let bolt11 = self
+            .node_api
+            .create_hold_invoice(HoldInvoiceRequest {
+                payment_hash,
+                amount_msat: req.amount_msat,
+                description: req.description,
+                use_description_hash: req.use_description_hash,
+                expiry: req.expiry,
+                cltv: req.cltv,
+            })
+            .await?;
+        let invoice = parse_invoice(&bolt11)?;
+        let now = Utc::now().timestamp();
+        let hold_invoice = HoldInvoice {
+            payment_hash: invoice.payment_hash,
+            bolt11,
+            amount_msat: req.amount_msat,
+            status: HoldInvoiceStatus::Open,
+            accepted_amount_msat: None,
+            settle_deadline: None,
+            created_at: now,
+            expires_at: (invoice.timestamp + invoice.expiry) as i64,
+            updated_at: now,
+        };
+        self.persister.upsert_hold_invoice(&hold_invoice)?;
+        Ok(hold_invoice)
+    }
+
+    /// Settles an accepted hold invoice, claiming its payment with the given preimage.
+    ///
+    /// The received payment is then reported like any other, with a [BreezEvent::InvoicePaid].
+    ///
+    /// Only LDK nodes support hold invoices, see [BreezServices::create_hold_invoice].
+    pub async fn settle_invoice(&self, preimage: Vec<u8>) -> SdkResult<()> {
+        let payment_hash = hex::encode(sha256::Hash::hash(&preimage));
+        let mut hold_invoice = self
+            .persister
+            .get_hold_invoice(&payment_hash)?
+            .ok_or(SdkError::generic("Hold invoice not found"))?;
+        ensure_sdk!(
+            hold_invoice.status == HoldInvoiceStatus::Accepted,
+            SdkError::Generic {
+                err: format!(
+                    "Only accepted hold invoices can be settled, this one is {}",
+                    hold_invoice.status
+                )
+            }
+        );
+        self.node_api.settle_hold_invoice(preimage).await?;
+        hold_invoice.status = HoldInvoiceStatus::Settled;
+        hold_invoice.updated_at = Utc::now().timestamp();
+        Ok(self.persister.upsert_hold_invoice(&hold_invoice)?)
+    }
+
+    /// Cancels an unpaid invoice, so that it can no longer be paid.
+    ///
+    /// On LDK nodes, which support hold invoices, the payment of a hold invoice that is already
+    /// held is returned to the payer.
+    pub async fn cancel_invoice(&self, payment_hash: String) -> SdkResult<()> {
+        if let Some(hold_invoice) = self.persister.get_hold_invoice(&payment_hash)? {
+            return self.cancel_hold_invoice(hold_invoice).await;
+        }
+
+        let invoice = self
+            .persister
+            .get_invoice(&payment_hash, Utc::now().timestamp())?
+            .ok_or(SdkError::generic("Invoice not found"))?;
+        ensure_sdk!(
+            invoice.status == InvoiceStatus::Pending,
+            SdkError::Generic {
+                err: format!(
+                    "Invoice is already {}",
+                    invoice.status.to_string().to_lowercase()
+                )
+            }
+        );
+        let hash =
+            hex::decode(&payment_hash).map_err(|_| SdkError::generic("Invalid payment hash"))?;
+        // The invoice given out may have been re-signed with extra routing hints
+        let bolt11 = match self.node_api.fetch_bolt11(hash).await? {
+            Some(node_invoice) => node_invoice.bolt11,
+            None => invoice.bolt11,
+        };
+        self.node_api.delete_invoice(bolt11).await?;
+        Ok(self.persister.set_invoice_cancelled(&payment_hash)?)
+    }
+
+    async fn cancel_hold_invoice(&self, mut hold_invoice: HoldInvoice) -> SdkResult<()> {
+        ensure_sdk!(
+            matches!(
+                hold_invoice.status,
+                HoldInvoiceStatus::Open | HoldInvoiceStatus::Accepted
+            ),
+            SdkError::Generic {
+                err: format!(
+                    "Hold invoice is already {}",
+                    hold_invoice.status.to_string().to_lowercase()
+                )
+            }
+        );
+        self.node_api
+            .cancel_hold_invoice(
+                hex::decode(&hold_invoice.payment_hash)
+                    .map_err(|_| SdkError::generic("Invalid payment hash"))?,
+            )
+            .await?;
+        hold_invoice.status = HoldInvoiceStatus::Cancelled;
+        hold_invoice.updated_at = Utc::now().timestamp();
+        Ok(self.persister.upsert_hold_invoice(&hold_invoice)?)
+    }
+
+    /// List the hold invoices, the latest first. Always empty on Greenlight nodes, which don't
+    /// support hold invoices.
+    pub async fn list_hold_invoices(&self) -> SdkResult<Vec<HoldInvoice>> {
+        Ok(self.persister.list_hold_invoices()?)
     }
 
     /// Report an issue.
@@ -639,11 +1322,105 @@ impl BreezServices {
         }
     }
 
+    /// Takes the node over from the instance running it, as reported by
+    /// [BreezEvent::NodeLockedByOtherInstance], and starts it here.
+    ///
+    /// The other instance stops its node once it notices, so this should only be called
+    /// once the user confirmed the other device is no longer in use. It returns once the
+    /// lease of the other instance ended, which can take up to about a minute.
+    pub async fn take_over_node_lock(&self) -> SdkResult<()> {
+        Ok(self.node_api.take_over_node_lock().await?)
+    }
+
     /// Retrieve the decrypted credentials from the node.
     pub async fn node_credentials(&self) -> SdkResult<Option<NodeCredentials>> {
         Ok(self.node_api.node_credentials().await?)
     }
 
+    /// Issue credentials which can only read the node. Together with the
+    /// [BreezServices::backup_encryption_key], they let [BreezServices::connect_watch_only] view the
+    /// wallet without the seed.
+    ///
+    /// Unlike the [BreezServices::node_credentials], they can't be used to change the node, e.g. to
+    /// create invoices or to close channels. They belong to a new device, which is added to the
+    /// registry so it shows up in [BreezServices::list_devices].
+    pub async fn watch_only_credentials(&self) -> SdkResult<NodeCredentials> {
+        let issued = self.node_api.issue_watch_only_credentials().await?;
+        self.persister.insert_greenlight_device(
+            &issued.device_id,
+            WATCH_ONLY_DEVICE,
+            Utc::now().timestamp(),
+        )?;
+        Ok(issued.credentials)
+    }
+
+    /// The key encrypting the SDK data synced between the instances of the wallet. Together with
+    /// the [BreezServices::watch_only_credentials], it lets [BreezServices::connect_watch_only]
+    /// view the wallet without the seed.
+    pub async fn backup_encryption_key(&self) -> SdkResult<Vec<u8>> {
+        let key = self
+            .node_api
+            .derive_bip32_key(vec![
+                ChildNumber::from_hardened_idx(139)?,
+                ChildNumber::from(0),
+            ])
+            .await?;
+        Ok(key.to_priv().to_bytes())
+    }
+
+    /// List the devices registered to connect to the Greenlight node.
+    ///
+    /// The registry is synced between the instances of the wallet, each of which adds its device on
+    /// connect and then regularly records that its signer runs. Another device with
+    /// [GreenlightDevice::signer_active] set also signs for the node.
+    ///
+    /// The signers are not queried from the node: only instances of this SDK report theirs, and
+    /// their reports reach the other instances along with their next backup, so an active signer
+    /// may be missed.
+    pub async fn list_devices(&self) -> SdkResult<Vec<GreenlightDevice>> {
+        let current_id = self.node_api.device_id().await?;
+        let active_since = Utc::now().timestamp() - DEVICE_SIGNER_ACTIVE_SEC;
+        Ok(self
+            .persister
+            .list_greenlight_devices()?
+            .into_iter()
+            .map(|device| GreenlightDevice {
+                signer_active: device.last_seen_at.is_some_and(|t| t >= active_since),
+                is_current: device.id == current_id,
+                ..device
+            })
+            .collect())
+    }
+
+    /// Issue credentials for a new device and add it to the registry under the given label.
+    ///
+    /// The returned credentials let the new device connect to the node, they should be
+    /// transferred to it over a secure channel.
+    pub async fn register_device(
+        &self,
+        req: RegisterDeviceRequest,
+    ) -> SdkResult<RegisterDeviceResponse> {
+        let label = req.label.trim().to_string();
+        ensure_sdk!(
+            !label.is_empty(),
+            SdkError::generic("Device label can't be empty")
+        );
+        let issued = self.node_api.issue_device_credentials().await?;
+        self.persister.insert_greenlight_device(
+            &issued.device_id,
+            &label,
+            Utc::now().timestamp(),
+        )?;
+        let device = self
+            .persister
+            .get_greenlight_device(&issued.device_id)?
+            .ok_or_else(|| SdkError::generic("Device not found"))?;
+        Ok(RegisterDeviceResponse {
+            device,
+            credentials: issued.credentials,
+        })
+    }
+
     /// Retrieve the node state from the persistent storage.
     ///
     /// Fail if it could not be retrieved or if `None` was found.
@@ -670,7 +1447,53 @@ impl BreezServices {
         Ok(CheckMessageResponse { is_valid })
     }
 
-    /// Retrieve the node up to date BackupStatus
+    /// The public key of a key derived from the wallet seed for an app.
+    ///
+    /// App keys give the apps built on the SDK deterministic keys, e.g. for a Nostr identity or
+    /// for encrypting their data, without handling the seed. They're derived under a BIP32 branch
+    /// reserved for them, namespaced per app, so the keys of the node and the ones the SDK uses
+    /// itself can't be reached. Their private keys never leave the SDK, use
+    /// [BreezServices::sign_with_app_key] and [BreezServices::app_key_ecdh] instead.
+    pub async fn app_public_key(&self, key: AppKey) -> SdkResult<AppPublicKey> {
+        Ok(app_keys::public_key(&self.derive_app_key(&key).await?))
+    }
+
+    /// Signs the SHA256 digest of a message, or a 32 bytes digest as is when
+    /// [SignWithAppKeyRequest::prehashed], with an app key, see [BreezServices::app_public_key]
+    pub async fn sign_with_app_key(
+        &self,
+        req: SignWithAppKeyRequest,
+    ) -> SdkResult<SignWithAppKeyResponse> {
+        let secret_key = self.derive_app_key(&req.key).await?;
+        Ok(SignWithAppKeyResponse {
+            signature: app_keys::sign(
+                &secret_key,
+                &req.message,
+                req.scheme,
+                req.prehashed.unwrap_or_default(),
+            )?,
+        })
+    }
+
+    /// Computes the ECDH shared secret of an app key with another party's public key, see
+    /// [BreezServices::app_public_key]
+    pub async fn app_key_ecdh(&self, req: AppKeyEcdhRequest) -> SdkResult<AppKeyEcdhResponse> {
+        let secret_key = self.derive_app_key(&req.key).await?;
+        Ok(AppKeyEcdhResponse {
+            shared_secret: app_keys::ecdh(
+                &secret_key,
+                &req.peer_public_key,
+                req.raw.unwrap_or_default(),
+            )?,
+        })
+    }
+
+    async fn derive_app_key(&self, key: &AppKey) -> SdkResult<SecretKey> {
+        let path = app_keys::app_key_path(key)?;
+        Ok(self.node_api.derive_bip32_key(path).await?.private_key)
+    }
+
+    /// Retrieve the node up to date BackupStatus
     pub fn backup_status(&self) -> SdkResult<BackupStatus> {
         let backup_time = self.persister.get_last_backup_time()?;
         let sync_request = self.persister.get_last_sync_request()?;
@@ -701,11 +1524,224 @@ impl BreezServices {
         Ok(self.persister.list_payments(req)?)
     }
 
+    /// Aggregate the payments matching a filter: totals, fees and counts by status, optionally
+    /// as a time series.
+    pub async fn payment_stats(&self, req: StatsRequest) -> SdkResult<StatsResponse> {
+        Ok(self.persister.payment_stats(req)?)
+    }
+
+    /// Export the payment history to a file, for accounting software.
+    ///
+    /// The payments are selected with the same filters as [BreezServices::list_payments] and
+    /// written one at a time, so large histories don't have to fit in memory.
+    pub async fn export_payments(&self, req: ExportRequest) -> SdkResult<ExportResponse> {
+        let node_id = self.node_info()?.id;
+        let persister = self.persister.clone();
+        // The payments are read and the file is written with blocking calls
+        tokio::task::spawn_blocking(move || {
+            crate::export::export_payments(&persister, &node_id, req)
+        })
+        .await
+        .map_err(|e| SdkError::generic(&format!("Export failed: {e}")))?
+    }
+
+    /// Add a contact to the address book.
+    ///
+    /// Payments to its Lightning Address, node id or LNURL-pay domain are attributed to it, see
+    /// [LnPaymentDetails::contact_id]. Contacts are synced with the other instances of the wallet.
+    pub async fn add_contact(&self, req: AddContactRequest) -> SdkResult<Contact> {
+        let now = Utc::now().timestamp();
+        let contact = validate_contact(Contact {
+            id: hex::encode(rand::random::<[u8; 16]>()),
+            name: req.name,
+            ln_address: req.ln_address,
+            node_pubkey: req.node_pubkey,
+            lnurl_pay_domain: req.lnurl_pay_domain,
+            note: req.note,
+            created_at: now,
+            updated_at: now,
+        })?;
+        self.persister.upsert_contact(&contact)?;
+        Ok(contact)
+    }
+
+    /// Replace the details of a contact.
+    pub async fn update_contact(&self, req: UpdateContactRequest) -> SdkResult<Contact> {
+        let existing = self
+            .persister
+            .get_contact(&req.id)?
+            .ok_or_else(|| SdkError::generic("Contact not found"))?;
+        let contact = validate_contact(Contact {
+            id: req.id,
+            name: req.name,
+            ln_address: req.ln_address,
+            node_pubkey: req.node_pubkey,
+            lnurl_pay_domain: req.lnurl_pay_domain,
+            note: req.note,
+            created_at: existing.created_at,
+            updated_at: Utc::now().timestamp(),
+        })?;
+        self.persister.upsert_contact(&contact)?;
+        Ok(contact)
+    }
+
+    /// Remove a contact. Its payments are no longer attributed to it.
+    pub async fn delete_contact(&self, id: String) -> SdkResult<()> {
+        Ok(self.persister.delete_contact(&id, Utc::now().timestamp())?)
+    }
+
+    /// Fetch a contact by its id.
+    pub async fn get_contact(&self, id: String) -> SdkResult<Option<Contact>> {
+        Ok(self.persister.get_contact(&id)?)
+    }
+
+    /// List the contacts, sorted by name.
+    pub async fn list_contacts(&self) -> SdkResult<Vec<Contact>> {
+        Ok(self.persister.list_contacts()?)
+    }
+
+    /// Schedule a payment to be made at regular intervals.
+    ///
+    /// Payments that are due are made in the background while the SDK is connected, when it
+    /// starts and whenever it syncs. Failed payments are retried a few times, with increasing
+    /// delays, before they are skipped until the next one. Every attempt is reported with a
+    /// [BreezEvent::RecurringPaymentAttempted] event.
+    ///
+    /// The schedules are synced with the other instances of the same node.
+    pub async fn add_recurring_payment(
+        &self,
+        req: AddRecurringPaymentRequest,
+    ) -> SdkResult<RecurringPayment> {
+        match &req.destination {
+            RecurringPaymentDestination::Keysend { node_id } => {
+                PublicKey::from_str(node_id).map_err(|_| SdkError::generic("Invalid node id"))?;
+            }
+            RecurringPaymentDestination::LnUrlPay { lnurl } => {
+                ensure_sdk!(
+                    matches!(
+                        parse_with_rest_client(self.rest_client.as_ref(), lnurl, None).await?,
+                        InputType::LnUrlPay { .. }
+                    ),
+                    SdkError::generic("Destination is not an LNURL-pay link or Lightning Address")
+                );
+            }
+        }
+        let valid_amount = match &req.amount {
+            RecurringPaymentAmount::Bitcoin { amount_msat } => *amount_msat > 0,
+            RecurringPaymentAmount::Fiat { amount, .. } => *amount > 0.0,
+        };
+        ensure_sdk!(valid_amount, SdkError::generic("Amount must be positive"));
+
+        let now = Utc::now().timestamp();
+        let start_time = req.start_time.unwrap_or(now);
+        let recurring_payment = RecurringPayment {
+            id: hex::encode(rand::random::<[u8; 16]>()),
+            destination: req.destination,
+            amount: req.amount,
+            interval: req.interval,
+            comment: req.comment,
+            start_time,
+            next_payment_time: start_time,
+            paused: false,
+            created_at: now,
+            updated_at: now,
+        };
+        self.persister
+            .upsert_recurring_payment(&recurring_payment)?;
+        self.recurring_payments_trigger.notify_one();
+        Ok(recurring_payment)
+    }
+
+    /// List the recurring payments, the next one due first.
+    pub async fn list_recurring_payments(&self) -> SdkResult<Vec<RecurringPayment>> {
+        Ok(self.persister.list_recurring_payments()?)
+    }
+
+    /// Pause or resume a recurring payment. When resumed, a payment that became due while
+    /// paused is made right away.
+    pub async fn set_recurring_payment_paused(
+        &self,
+        id: String,
+        paused: bool,
+    ) -> SdkResult<RecurringPayment> {
+        let mut recurring_payment = self
+            .persister
+            .get_recurring_payment(&id)?
+            .ok_or_else(|| SdkError::generic("Recurring payment not found"))?;
+        recurring_payment.paused = paused;
+        recurring_payment.updated_at = Utc::now().timestamp();
+        self.persister
+            .upsert_recurring_payment(&recurring_payment)?;
+        self.recurring_payments_trigger.notify_one();
+        Ok(recurring_payment)
+    }
+
+    /// Cancel a recurring payment. The payments already made are kept.
+    pub async fn delete_recurring_payment(&self, id: String) -> SdkResult<()> {
+        Ok(self
+            .persister
+            .delete_recurring_payment(&id, Utc::now().timestamp())?)
+    }
+
+    /// List the attempted payments of a recurring payment, the latest first.
+    pub async fn list_recurring_payment_runs(
+        &self,
+        id: String,
+    ) -> SdkResult<Vec<RecurringPaymentRun>> {
+        Ok(self.persister.list_recurring_payment_runs(&id)?)
+    }
+
+    /// Sets the [SpendingPolicy] outgoing payments are checked against, or removes it.
+    ///
+    /// Payments that break one of its rules fail with a `PolicyViolation` error. The policy is
+    /// stored with the other local settings, so it is up to the app to control who can change it.
+    pub async fn set_spending_policy(&self, policy: Option<SpendingPolicy>) -> SdkResult<()> {
+        Ok(self.persister.set_spending_policy(policy.as_ref())?)
+    }
+
+    pub async fn spending_policy(&self) -> SdkResult<Option<SpendingPolicy>> {
+        Ok(self.persister.get_spending_policy()?)
+    }
+
     /// Fetch a specific payment by its hash.
     pub async fn payment_by_hash(&self, hash: String) -> SdkResult<Option<Payment>> {
         Ok(self.persister.get_payment_by_hash(&hash)?)
     }
 
+    /// Verify the settlement of a LNURL payment, as per LUD-21.
+    ///
+    /// Queries the verify URL returned by the LNURL-pay endpoint when the payment was made and
+    /// maps the result to the [Payment]: if the endpoint reports it as settled, the payment is
+    /// stored as [PaymentStatus::Complete] along with the reported preimage.
+    pub async fn verify_lnurl_payment(&self, payment_hash: String) -> SdkResult<Payment> {
+        let mut payment =
+            self.persister
+                .get_payment_by_hash(&payment_hash)?
+                .ok_or(SdkError::Generic {
+                    err: "Payment not found".into(),
+                })?;
+        let PaymentDetails::Ln { ref mut data } = payment.details else {
+            return Err(SdkError::Generic {
+                err: "Payment is not a Lightning payment".into(),
+            });
+        };
+        let verify_url = data.lnurl_verify_url.clone().ok_or(SdkError::Generic {
+            err: "Payment has no LNURL verify URL".into(),
+        })?;
+
+        let verify_data =
+            verify_lnurl_pay(self.rest_client.as_ref(), &verify_url, &payment_hash).await?;
+        if verify_data.settled {
+            self.persister
+                .set_payment_settled(&payment_hash, verify_data.preimage.as_deref())?;
+            payment.status = PaymentStatus::Complete;
+            if let Some(preimage) = verify_data.preimage {
+                data.payment_preimage = preimage;
+            }
+        }
+        Ok(payment)
+    }
+
     /// Set the external metadata of a payment as a valid JSON string
     pub async fn set_payment_metadata(&self, hash: String, metadata: String) -> SdkResult<()> {
         Ok(self
@@ -718,6 +1754,7 @@ impl BreezServices {
         &self,
         req: RedeemOnchainFundsRequest,
     ) -> RedeemOnchainResult<RedeemOnchainFundsResponse> {
+        self.ensure_can_spend()?;
         let txid = self
             .node_api
             .redeem_onchain_funds(req.to_address, req.sat_per_vbyte)
@@ -735,8 +1772,116 @@ impl BreezServices {
     }
 
     /// Fetch live rates of fiat currencies, sorted by name
+    ///
+    /// The rates are also recorded in the local rates history, used by [BreezServices::fiat_valuation].
     pub async fn fetch_fiat_rates(&self) -> SdkResult<Vec<Rate>> {
-        self.fiat_api.fetch_fiat_rates().await.map_err(Into::into)
+        let rates = self.fiat_api.fetch_fiat_rates().await?;
+        if let Err(e) = record_fiat_rates(&self.persister, &rates) {
+            warn!("Failed to record fiat rates: {e}");
+        }
+        Ok(rates)
+    }
+
+    /// Value a payment in a fiat currency, at the exchange rate in effect when it settled.
+    ///
+    /// Returns None if the payment is not found.
+    pub async fn payment_fiat_value(
+        &self,
+        payment_id: String,
+        currency: String,
+    ) -> SdkResult<Option<PaymentFiatValue>> {
+        self.persister
+            .get_payment_by_hash(&payment_id)?
+            .map(|payment| self.value_payment(&payment, &currency))
+            .transpose()
+    }
+
+    /// Value the payment history in a fiat currency, each payment at the exchange rate in effect
+    /// when it settled.
+    pub async fn fiat_valuation(
+        &self,
+        req: FiatValuationRequest,
+    ) -> SdkResult<FiatValuationResponse> {
+        let mut res = FiatValuationResponse {
+            currency: req.currency.clone(),
+            payments: vec![],
+            total_received: 0.0,
+            total_sent: 0.0,
+            total_fees: 0.0,
+            unvalued_payments_count: 0,
+        };
+        for payment in self.persister.list_payments(req.filter)? {
+            let value = self.value_payment(&payment, &req.currency)?;
+            match (value.amount, value.fee) {
+                (Some(amount), Some(fee)) => {
+                    match payment.payment_type {
+                        PaymentType::Received => res.total_received += amount,
+                        PaymentType::Sent | PaymentType::ClosedChannel => {
+                            res.total_sent += amount + fee
+                        }
+                    }
+                    res.total_fees += fee;
+                }
+                _ => res.unvalued_payments_count += 1,
+            }
+            res.payments.push(value);
+        }
+        Ok(res)
+    }
+
+    fn value_payment(&self, payment: &Payment, currency: &str) -> SdkResult<PaymentFiatValue> {
+        let rate = self.persister.get_payment_fiat_rate(
+            &payment.id,
+            payment.payment_time,
+            currency,
+            FIAT_RATE_MAX_DISTANCE_SEC,
+        )?;
+        let to_fiat = |msat: u64| {
+            rate.as_ref()
+                .map(|r| msat as f64 / 100_000_000_000.0 * r.value)
+        };
+        Ok(PaymentFiatValue {
+            payment_id: payment.id.clone(),
+            amount: to_fiat(payment.amount_msat),
+            fee: to_fiat(payment.fee_msat),
+            rate,
+        })
+    }
+
+    /// Stores the rates in the rates history, unless a snapshot was taken recently.
+    /// Returns the time of the snapshot the rates belong to.
+    /// Links a settled payment to the current rates, so it can later be valued at its
+    /// settlement time. Failures are logged, as they must not fail the payment itself.
+    ///
+    /// The latest snapshot is used if it is recent enough. Otherwise the rates are fetched in the
+    /// background, so the payment flow doesn't wait on the network.
+    fn attach_fiat_rates(&self, payment_id: &str) {
+        let now = Utc::now().timestamp();
+        match self.persister.last_fiat_rates_timestamp() {
+            Ok(Some(last)) if now - last < FIAT_RATES_SNAPSHOT_INTERVAL_SEC => {
+                if let Err(e) = self.persister.set_payment_fiat_rates(payment_id, last) {
+                    warn!("Failed to attach fiat rates to payment {payment_id}: {e}");
+                }
+            }
+            Ok(_) => {
+                let fiat_api = self.fiat_api.clone();
+                let persister = self.persister.clone();
+                let payment_id = payment_id.to_string();
+                tokio::spawn(async move {
+                    let res: SdkResult<()> = async {
+                        let rates = fiat_api.fetch_fiat_rates().await?;
+                        let timestamp = record_fiat_rates(&persister, &rates)?;
+                        persister.set_payment_fiat_rates(&payment_id, timestamp)?;
+                        Ok(())
+                    }
+                    .await;
+                    if let Err(e) = res {
+                        warn!("Failed to attach fiat rates to payment {payment_id}: {e}");
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to attach fiat rates to payment {payment_id}: {e}"),
+        }
     }
 
     /// List all supported fiat currencies for which there is a known exchange rate.
@@ -755,6 +1900,7 @@ impl BreezServices {
 
     /// Select the LSP to be used and provide inbound liquidity
     pub async fn connect_lsp(&self, lsp_id: String) -> SdkResult<()> {
+        self.ensure_can_receive()?;
         let lsp_pubkey = match self.list_lsps().await?.iter().find(|lsp| lsp.id == lsp_id) {
             Some(lsp) => lsp.pubkey.clone(),
             None => {
@@ -839,6 +1985,7 @@ impl BreezServices {
         &self,
         req: ReceiveOnchainRequest,
     ) -> ReceiveOnchainResult<SwapInfo> {
+        self.ensure_can_receive()?;
         if let Some(in_progress) = self.in_progress_swap().await? {
             return Err(ReceiveOnchainError::SwapInProgress{ err:format!(
                     "A swap was detected for address {}. Use in_progress_swap method to get the current swap state",
@@ -1005,6 +2152,7 @@ impl BreezServices {
     ///
     /// Returns the txid of the refund transaction.
     pub async fn refund(&self, req: RefundRequest) -> SdkResult<RefundResponse> {
+        self.ensure_can_spend()?;
         Ok(self.btc_receive_swapper.refund(req).await?)
     }
 
@@ -1082,6 +2230,7 @@ impl BreezServices {
         &self,
         req: PayOnchainRequest,
     ) -> Result<PayOnchainResponse, SendOnchainError> {
+        self.ensure_can_spend()?;
         ensure_sdk!(
             req.prepare_res.sender_amount_sat > req.prepare_res.recipient_amount_sat,
             SendOnchainError::generic("Send amount must be bigger than receive amount")
@@ -1092,12 +2241,26 @@ impl BreezServices {
             Use the in_progress_onchain_payments method to get an overview of currently ongoing reverse swaps".into(),
         });
 
+        // Held until the sync records the pending swap payment
+        let clearance = self
+            .check_spending_policy(PolicyPayment {
+                amount_msat: req.prepare_res.recipient_amount_sat * 1_000,
+                max_fee_msat: req.prepare_res.total_fees * 1_000,
+                routed: false,
+                payee: Payee {
+                    ids: vec![req.recipient_address.clone()],
+                    domain: None,
+                },
+            })
+            .await?;
+
         let full_rsi = self.btc_send_swapper.create_reverse_swap(req).await?;
         let reverse_swap_info = self
             .btc_send_swapper
             .convert_reverse_swap_info(full_rsi.clone())
             .await?;
         self.do_sync(false).await?;
+        drop(clearance);
 
         if let Some(webhook_url) = self.persister.get_webhook_url()? {
             let address = &full_rsi
@@ -1226,6 +2389,14 @@ impl BreezServices {
 
         // update the cached sync state
         self.persister.set_sync_state(&new_data.sync_state)?;
+
+        for invoice in self
+            .persister
+            .take_newly_expired_invoices(Utc::now().timestamp())?
+        {
+            self.notify_event_listeners(BreezEvent::InvoiceExpired { details: invoice })
+                .await?;
+        }
         self.notify_event_listeners(BreezEvent::Synced).await?;
         Ok(())
     }
@@ -1234,6 +2405,10 @@ impl BreezServices {
     /// This validates if the selected LSP is still in [`list_lsps`].
     /// If not or no LSP is selected, it selects the first LSP in [`list_lsps`].
     async fn connect_lsp_peer(&self, node_pubkey: String) -> SdkResult<()> {
+        // Watch-only instances don't receive, so they have no use for the LSP
+        if self.read_only {
+            return Ok(());
+        }
         let lsps = self.lsp_api.list_lsps(node_pubkey).await?;
         let lsp = match self
             .persister
@@ -1298,6 +2473,11 @@ impl BreezServices {
                         lnurl_success_action: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         ln_address: None,
                         lnurl_metadata: None,
                         lnurl_withdraw_endpoint: None,
@@ -1308,6 +2488,7 @@ impl BreezServices {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             }],
             true,
         )?;
@@ -1318,6 +2499,11 @@ impl BreezServices {
                 lnurl_pay_success_action: None,
                 lnurl_pay_domain: None,
                 lnurl_pay_comment: None,
+                lnurl_verify_url: None,
+                zap_request: None,
+                zap_receipt: None,
+                zap_nostr_pubkey: None,
+                fiat_amount: None,
                 lnurl_metadata: None,
                 ln_address: None,
                 lnurl_withdraw_endpoint: None,
@@ -1338,6 +2524,7 @@ impl BreezServices {
         self.do_sync(false).await?;
         match payment_res {
             Ok(payment) => {
+                self.attach_fiat_rates(&payment.id);
                 self.notify_event_listeners(BreezEvent::PaymentSucceed {
                     details: payment.clone(),
                 })
@@ -1377,6 +2564,9 @@ impl BreezServices {
         if let Err(err) = self.btc_send_swapper.on_event(e.clone()).await {
             debug!("btc_send_swapper failed to process event {e:?}: {err:?}")
         };
+        if matches!(e, BreezEvent::NewBlock { .. } | BreezEvent::Synced) {
+            self.recurring_payments_trigger.notify_one();
+        }
 
         if self.event_listener.is_some() {
             self.event_listener.as_ref().unwrap().on_event(e.clone())
@@ -1414,6 +2604,8 @@ impl BreezServices {
     /// Get the static backup data from the persistent storage.
     /// This data enables the user to recover the node in an external core lightning node.
     /// See here for instructions on how to recover using this data: <https://docs.corelightning.org/docs/backup-and-recovery#backing-up-using-static-channel-backup>
+    ///
+    /// LDK nodes have no static channel backup, so their backup is empty.
     pub fn static_backup(req: StaticBackupRequest) -> SdkResult<StaticBackupResponse> {
         let storage = SqliteStorage::new(req.working_dir);
         Ok(StaticBackupResponse {
@@ -1494,6 +2686,22 @@ impl BreezServices {
         // track new blocks
         self.track_new_blocks().await;
 
+        // make the scheduled payments, which watch-only instances leave to the others
+        if !self.read_only {
+            self.track_recurring_payments().await;
+        }
+
+        // track the payments of hold invoices
+        self.track_held_htlcs().await;
+
+        // track which instance runs the node
+        self.track_node_lock().await;
+
+        // record in the device registry that the signer of this instance runs
+        if !self.read_only {
+            self.track_device().await;
+        }
+
         // track logs
         self.track_logs().await;
 
@@ -1672,6 +2880,7 @@ impl BreezServices {
                             let res = cloned.persister.set_node_state(&node_info);
                             debug!("channel balance was updated {res:?}");
                         }
+                        cloned.attach_fiat_rates(&p.id);
                         payment = cloned
                             .persister
                             .get_payment_by_hash(&p.id)
@@ -1796,105 +3005,529 @@ impl BreezServices {
         });
     }
 
-    async fn init_chainservice_urls(&self) -> Result<()> {
-        let breez_server = Arc::new(BreezServer::new(
-            PRODUCTION_BREEZSERVER_URL.to_string(),
-            None,
-        )?);
-        let persister = &self.persister;
-
-        let cloned_breez_server = breez_server.clone();
-        let cloned_persister = persister.clone();
+    async fn track_held_htlcs(self: &Arc<BreezServices>) {
+        let cloned = self.clone();
         tokio::spawn(async move {
-            match cloned_breez_server.fetch_mempoolspace_urls().await {
-                Ok(fresh_urls) => {
-                    if let Err(e) = cloned_persister.set_mempoolspace_base_urls(fresh_urls) {
-                        error!("Failed to cache mempool.space URLs: {e}");
+            let mut shutdown_receiver = cloned.shutdown_sender.subscribe();
+            loop {
+                if shutdown_receiver.has_changed().unwrap_or(true) {
+                    return;
+                }
+                let mut htlcs_stream = match cloned.node_api.stream_held_htlcs().await {
+                    Ok(htlcs_stream) => htlcs_stream,
+                    Err(e) => {
+                        warn!("stream held htlcs returned error: {e:?}");
+                        tokio::select! {
+                            _ = sleep(Duration::from_secs(1)) => {
+                                continue
+                            }
+                            _ = shutdown_receiver.changed() => {
+                                debug!("Held HTLCs tracking task has completed");
+                                return;
+                            }
+                        };
+                    }
+                };
+
+                loop {
+                    let htlcs = tokio::select! {
+                        htlcs = htlcs_stream.next() => htlcs,
+                        _ = shutdown_receiver.changed() => {
+                            debug!("Held HTLCs tracking task has completed");
+                            return;
+                        }
+                    };
+                    let Some(htlcs) = htlcs else {
+                        debug!("held htlcs stream got None");
+                        break;
+                    };
+                    if let Err(e) = cloned.on_held_htlcs(htlcs).await {
+                        error!("failed to process held htlcs: {e:?}");
                     }
                 }
-                Err(e) => error!("Failed to fetch mempool.space URLs: {e}"),
+
+                tokio::select! {
+                    _ = sleep(Duration::from_secs(1)) => {
+                        continue
+                    }
+                    _ = shutdown_receiver.changed() => {
+                        debug!("Held HTLCs tracking task has completed");
+                        return;
+                    }
+                };
             }
         });
-
-        Ok(())
     }
 
-    /// Configures a global SDK logger that will log to file and will forward log events to
-    /// an optional application-specific logger.
-    ///
-    /// If called, it should be called before any SDK methods (for example, before `connect`).
-    ///
-    /// It must be called only once in the application lifecycle. Alternatively, if the application
-    /// already uses a globally-registered logger, this method shouldn't be called at all.
-    ///
-    /// ### Arguments
-    ///
-    /// - `log_dir`: Location where the the SDK log file will be created. The directory must already exist.
-    ///
-    /// - `app_logger`: Optional application logger.
-    ///
-    /// If the application is to use its own logger, but would also like the SDK to log SDK-specific
-    /// log output to a file in the configured `log_dir`, then do not register the
-    /// app-specific logger as a global logger and instead call this method with the app logger as an arg.
-    ///
-    /// ### Logging Configuration
-    ///
-    /// Setting `breez_sdk_core::input_parser=debug` will include in the logs the raw payloads received
-    /// when interacting with JSON endpoints, for example those used during all LNURL workflows.
-    ///
-    /// ### Errors
-    ///
-    /// An error is thrown if the log file cannot be created in the working directory.
-    ///
-    /// An error is thrown if a global logger is already configured.
-    pub fn init_logging(log_dir: &str, app_logger: Option<Box<dyn log::Log>>) -> Result<()> {
-        let target_log_file = Box::new(
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(format!("{log_dir}/sdk.log"))
-                .map_err(|e| anyhow!("Can't create log file: {e}"))?,
-        );
-        let logger = env_logger::Builder::new()
-            .target(env_logger::Target::Pipe(target_log_file))
-            .parse_filters(
-                r#"
-                info,
-                breez_sdk_core=debug,
-                sdk_common=debug,
-                gl_client=debug,
-                h2=warn,
-                hyper=warn,
-                lightning_signer=warn,
-                reqwest=warn,
-                rustls=warn,
-                rustyline=warn,
-                vls_protocol_signer=warn
-            "#,
-            )
-            .format(|buf, record| {
-                writeln!(
-                    buf,
-                    "[{} {} {}:{}] {}",
-                    Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-                    record.level(),
-                    record.module_path().unwrap_or("unknown"),
-                    record.line().unwrap_or(0),
-                    record.args()
-                )
-            })
-            .build();
-
-        let global_logger = GlobalSdkLogger {
-            logger,
-            log_listener: app_logger,
+    async fn track_device(self: &Arc<BreezServices>) {
+        let device_id = match self.node_api.device_id().await {
+            Ok(device_id) => device_id,
+            Err(e) => {
+                debug!("Not tracking the device: {e}");
+                return;
+            }
         };
+        let cloned = self.clone();
+        tokio::spawn(async move {
+            let mut shutdown_receiver = cloned.shutdown_sender.subscribe();
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(DEVICE_HEARTBEAT_INTERVAL_SEC));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => { }
 
-        log::set_boxed_logger(Box::new(global_logger))
-            .map_err(|e| anyhow!("Failed to set global logger: {e}"))?;
-        log::set_max_level(LevelFilter::Trace);
+                    _ = shutdown_receiver.changed() => {
+                        debug!("Device tracking task has completed");
+                        return;
+                    }
+                }
 
-        Ok(())
+                if let Err(e) = cloned.record_device_seen(&device_id) {
+                    error!("failed to record the device as seen: {e:?}");
+                }
+            }
+        });
+    }
+
+    /// Adds the device of this instance to the registry, if missing, and records that its signer runs
+    fn record_device_seen(&self, device_id: &str) -> Result<()> {
+        let now = Utc::now().timestamp();
+        self.persister
+            .insert_greenlight_device(device_id, UNLABELED_DEVICE, now)?;
+        self.persister
+            .set_greenlight_device_last_seen(device_id, now)?;
+        Ok(())
+    }
+
+    async fn track_node_lock(self: &Arc<BreezServices>) {
+        let cloned = self.clone();
+        tokio::spawn(async move {
+            let mut shutdown_receiver = cloned.shutdown_sender.subscribe();
+            let mut status_stream = match cloned.node_api.stream_node_lock_status().await {
+                Ok(status_stream) => status_stream,
+                Err(e) => {
+                    error!("stream node lock status returned error: {e:?}");
+                    return;
+                }
+            };
+            loop {
+                let status = tokio::select! {
+                    status = status_stream.next() => status,
+                    _ = shutdown_receiver.changed() => {
+                        debug!("Node lock tracking task has completed");
+                        return;
+                    }
+                };
+                let event = match status {
+                    Some(NodeLockStatus::Held) => continue,
+                    Some(NodeLockStatus::HeldByOtherInstance { instance_id }) => {
+                        BreezEvent::NodeLockedByOtherInstance { instance_id }
+                    }
+                    Some(NodeLockStatus::Lost { instance_id }) => {
+                        BreezEvent::NodeLockLost { instance_id }
+                    }
+                    None => {
+                        debug!("node lock status stream got None");
+                        return;
+                    }
+                };
+                if let Err(e) = cloned.notify_event_listeners(event).await {
+                    error!("failed to notify node lock status: {e:?}");
+                }
+            }
+        });
+    }
+
+    /// Marks the hold invoice paid by the held HTLCs as accepted
+    async fn on_held_htlcs(&self, htlcs: HeldHtlcs) -> Result<()> {
+        let payment_hash = hex::encode(&htlcs.payment_hash);
+        let Some(mut hold_invoice) = self.persister.get_hold_invoice(&payment_hash)? else {
+            warn!("Held HTLCs of unknown hold invoice {payment_hash}");
+            return Ok(());
+        };
+        if hold_invoice.status != HoldInvoiceStatus::Open {
+            debug!(
+                "Ignoring held HTLCs of {} hold invoice {payment_hash}",
+                hold_invoice.status
+            );
+            return Ok(());
+        }
+        hold_invoice.status = HoldInvoiceStatus::Accepted;
+        hold_invoice.accepted_amount_msat = Some(htlcs.amount_msat);
+        hold_invoice.settle_deadline = htlcs.claim_deadline;
+        hold_invoice.updated_at = Utc::now().timestamp();
+        self.persister.upsert_hold_invoice(&hold_invoice)?;
+        self.notify_event_listeners(BreezEvent::HoldInvoiceAccepted {
+            details: hold_invoice,
+        })
+        .await
+    }
+
+    async fn track_recurring_payments(self: &Arc<BreezServices>) {
+        let cloned = self.clone();
+        tokio::spawn(async move {
+            let mut shutdown_receiver = cloned.shutdown_sender.subscribe();
+            loop {
+                // Check again when the next payment or retry is due, and at least hourly
+                let mut wait_sec = RECURRING_PAYMENTS_MAX_WAIT_SEC;
+                match cloned.execute_recurring_payments().await {
+                    Ok(Some(wake_time)) => {
+                        wait_sec = (wake_time - Utc::now().timestamp()).clamp(1, wait_sec)
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!("failed to execute recurring payments: {e:?}"),
+                }
+
+                tokio::select! {
+                    _ = cloned.recurring_payments_trigger.notified() => {}
+
+                    _ = sleep(Duration::from_secs(wait_sec as u64)) => {}
+
+                    _ = shutdown_receiver.changed() => {
+                        debug!("Recurring payments task has completed");
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Attempts the recurring payments that are due, unless they are waiting to be retried.
+    /// Returns when the next payment or retry is due, if any is scheduled.
+    async fn execute_recurring_payments(&self) -> Result<Option<i64>> {
+        let now = Utc::now().timestamp();
+        let instance_id = self.persister.get_or_create_instance_id()?;
+        let mut wake_times = Vec::new();
+        let mut due = Vec::new();
+        for mut recurring_payment in self.persister.list_recurring_payments()? {
+            let scheduled_time = recurring_payment.next_payment_time;
+            if recurring_payment.paused {
+                continue;
+            }
+            if scheduled_time > now {
+                wake_times.push(scheduled_time);
+                continue;
+            }
+            let mut run = self
+                .persister
+                .get_recurring_payment_run(&recurring_payment.id, scheduled_time)?;
+
+            // Settle an attempt whose outcome was unknown before making a new one
+            if let Some(run) = run.as_mut() {
+                if let Some(label) = run.attempt_label.clone() {
+                    let payment = self.persister.get_sent_payment_by_label(&label)?;
+                    let unsettled = now < run.updated_at + IN_FLIGHT_TIMEOUT_SEC;
+                    match recurring::attempt_outcome(payment.as_ref(), unsettled) {
+                        AttemptOutcome::InFlight => {
+                            wake_times.push(run.updated_at + IN_FLIGHT_TIMEOUT_SEC);
+                            continue;
+                        }
+                        AttemptOutcome::Paid(payment_id) => {
+                            run.payment_id = Some(payment_id);
+                            run.error = None;
+                        }
+                        AttemptOutcome::Failed => {}
+                    }
+                    run.attempt_label = None;
+                    self.persister.upsert_recurring_payment_run(run)?;
+                }
+            }
+
+            match recurring::run_action(run.as_ref(), now) {
+                RunAction::Wait => wake_times.extend(run.as_ref().map(recurring::retry_time)),
+                RunAction::Advance => {
+                    wake_times.push(self.advance_recurring_payment(&mut recurring_payment, now)?)
+                }
+                RunAction::Pay => due.push((recurring_payment, run)),
+            }
+        }
+
+        if !due.is_empty() {
+            if let Err(e) = self.claim_recurring_payments(&due, now).await {
+                warn!("Failed to claim recurring payments: {e}");
+                wake_times.push(now + RETRY_BACKOFF_SEC);
+                return Ok(wake_times.into_iter().min());
+            }
+        }
+
+        for (mut recurring_payment, run) in due {
+            let scheduled_time = recurring_payment.next_payment_time;
+            let claim = self
+                .persister
+                .get_recurring_payment_claim(&recurring_payment.id, scheduled_time)?
+                .ok_or_else(|| anyhow!("Recurring payment claim not found"))?;
+            let mut run = match claim.instance_id == instance_id {
+                true => run.unwrap_or(RecurringPaymentRun {
+                    recurring_payment_id: recurring_payment.id.clone(),
+                    scheduled_time,
+                    payment_id: None,
+                    error: None,
+                    attempts: 0,
+                    updated_at: now,
+                    attempt_label: None,
+                }),
+                // Another instance pays it, and its run is synced here
+                false if now < claim.claimed_at + CLAIM_TIMEOUT_SEC => {
+                    wake_times.push(claim.claimed_at + CLAIM_TIMEOUT_SEC);
+                    continue;
+                }
+                false => {
+                    wake_times.push(self.advance_recurring_payment(&mut recurring_payment, now)?);
+                    continue;
+                }
+            };
+
+            info!(
+                "Attempting recurring payment {} scheduled at {}",
+                run.recurring_payment_id, run.scheduled_time
+            );
+            // Record the attempt before sending it, so it's not repeated while in flight
+            run.attempts += 1;
+            run.updated_at = Utc::now().timestamp();
+            let label = recurring::attempt_label(&run);
+            run.attempt_label = Some(label.clone());
+            self.persister.upsert_recurring_payment_run(&run)?;
+
+            let payment = match self
+                .pay_recurring_payment(&recurring_payment, label.clone())
+                .await
+            {
+                Ok(payment) => Some(payment),
+                Err(e) => {
+                    warn!("Recurring payment {} failed: {e}", run.recurring_payment_id);
+                    run.error = Some(e.to_string());
+                    let timed_out = e
+                        .downcast_ref::<SendPaymentError>()
+                        .is_some_and(|e| matches!(e, SendPaymentError::PaymentTimeout { .. }));
+                    let recorded = self.persister.get_sent_payment_by_label(&label)?;
+                    match recurring::attempt_outcome(recorded.as_ref(), timed_out) {
+                        AttemptOutcome::InFlight => {
+                            self.persister.upsert_recurring_payment_run(&run)?;
+                            wake_times.push(run.updated_at + IN_FLIGHT_TIMEOUT_SEC);
+                            continue;
+                        }
+                        AttemptOutcome::Paid(_) => recorded,
+                        AttemptOutcome::Failed => None,
+                    }
+                }
+            };
+            run.attempt_label = None;
+            if let Some(payment) = &payment {
+                run.payment_id = Some(payment.id.clone());
+                run.error = None;
+            }
+            self.persister.upsert_recurring_payment_run(&run)?;
+            let will_retry = payment.is_none() && run.attempts < MAX_RECURRING_PAYMENT_ATTEMPTS;
+            self.notify_event_listeners(BreezEvent::RecurringPaymentAttempted {
+                details: RecurringPaymentAttemptedDetails {
+                    run: run.clone(),
+                    payment,
+                    will_retry,
+                },
+            })
+            .await?;
+            match will_retry {
+                true => wake_times.push(recurring::retry_time(&run)),
+                false => {
+                    wake_times.push(self.advance_recurring_payment(&mut recurring_payment, now)?)
+                }
+            }
+        }
+        Ok(wake_times.into_iter().min())
+    }
+
+    /// Moves on to the next scheduled payment, skipping missed ones. Returns when it's due.
+    fn advance_recurring_payment(
+        &self,
+        recurring_payment: &mut RecurringPayment,
+        now: i64,
+    ) -> Result<i64> {
+        let after = now.max(recurring_payment.next_payment_time);
+        recurring_payment.next_payment_time =
+            recurring::next_payment_time(recurring_payment, after);
+        recurring_payment.updated_at = Utc::now().timestamp();
+        self.persister.upsert_recurring_payment(recurring_payment)?;
+        Ok(recurring_payment.next_payment_time)
+    }
+
+    /// Claims the due scheduled payments for this instance and syncs the claims with a single
+    /// backup, so only one of the instances sharing the node pays each. The claims synced first
+    /// win.
+    async fn claim_recurring_payments(
+        &self,
+        due: &[(RecurringPayment, Option<RecurringPaymentRun>)],
+        now: i64,
+    ) -> Result<()> {
+        let instance_id = self.persister.get_or_create_instance_id()?;
+        for (recurring_payment, _) in due {
+            self.persister
+                .insert_recurring_payment_claim(&RecurringPaymentClaim {
+                    recurring_payment_id: recurring_payment.id.clone(),
+                    scheduled_time: recurring_payment.next_payment_time,
+                    instance_id: instance_id.clone(),
+                    claimed_at: now,
+                })?;
+        }
+        Ok(self.backup().await?)
+    }
+
+    /// Pays a scheduled payment, labelled with the label of the attempt
+    async fn pay_recurring_payment(
+        &self,
+        recurring_payment: &RecurringPayment,
+        label: String,
+    ) -> Result<Payment> {
+        let amount_msat = match &recurring_payment.amount {
+            RecurringPaymentAmount::Bitcoin { amount_msat } => *amount_msat,
+            RecurringPaymentAmount::Fiat { currency, amount } => {
+                let rates = self.fetch_fiat_rates().await?;
+                let rate = rates
+                    .iter()
+                    .find(|r| &r.coin == currency)
+                    .ok_or_else(|| anyhow!("No exchange rate for {currency}"))?;
+                recurring::fiat_to_msat(*amount, rate.value)
+            }
+        };
+
+        match &recurring_payment.destination {
+            RecurringPaymentDestination::Keysend { node_id } => Ok(self
+                .send_spontaneous_payment(SendSpontaneousPaymentRequest {
+                    node_id: node_id.clone(),
+                    amount_msat,
+                    extra_tlvs: None,
+                    label: Some(label),
+                })
+                .await?
+                .payment),
+            RecurringPaymentDestination::LnUrlPay { lnurl } => {
+                let InputType::LnUrlPay { data, .. } =
+                    parse_with_rest_client(self.rest_client.as_ref(), lnurl, None).await?
+                else {
+                    return Err(anyhow!("Not an LNURL-pay destination: {lnurl}"));
+                };
+                let comment = recurring_payment
+                    .comment
+                    .clone()
+                    .filter(|_| data.comment_allowed > 0);
+                let res = self
+                    .lnurl_pay(LnUrlPayRequest {
+                        data,
+                        amount_msat,
+                        use_trampoline: false,
+                        comment,
+                        payment_label: Some(label),
+                        validate_success_action_url: None,
+                    })
+                    .await?;
+                match res {
+                    LnUrlPayResult::EndpointSuccess { data } => Ok(data.payment),
+                    LnUrlPayResult::EndpointError { data } => Err(anyhow!(data.reason)),
+                    LnUrlPayResult::PayError { data } => Err(anyhow!(data.reason)),
+                }
+            }
+        }
+    }
+
+    async fn init_chainservice_urls(&self) -> Result<()> {
+        let breez_server = Arc::new(BreezServer::new(
+            PRODUCTION_BREEZSERVER_URL.to_string(),
+            None,
+        )?);
+        let persister = &self.persister;
+
+        let cloned_breez_server = breez_server.clone();
+        let cloned_persister = persister.clone();
+        tokio::spawn(async move {
+            match cloned_breez_server.fetch_mempoolspace_urls().await {
+                Ok(fresh_urls) => {
+                    if let Err(e) = cloned_persister.set_mempoolspace_base_urls(fresh_urls) {
+                        error!("Failed to cache mempool.space URLs: {e}");
+                    }
+                }
+                Err(e) => error!("Failed to fetch mempool.space URLs: {e}"),
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Configures a global SDK logger that will log to file and will forward log events to
+    /// an optional application-specific logger.
+    ///
+    /// If called, it should be called before any SDK methods (for example, before `connect`).
+    ///
+    /// It must be called only once in the application lifecycle. Alternatively, if the application
+    /// already uses a globally-registered logger, this method shouldn't be called at all.
+    ///
+    /// ### Arguments
+    ///
+    /// - `log_dir`: Location where the the SDK log file will be created. The directory must already exist.
+    ///
+    /// - `app_logger`: Optional application logger.
+    ///
+    /// If the application is to use its own logger, but would also like the SDK to log SDK-specific
+    /// log output to a file in the configured `log_dir`, then do not register the
+    /// app-specific logger as a global logger and instead call this method with the app logger as an arg.
+    ///
+    /// ### Logging Configuration
+    ///
+    /// Setting `breez_sdk_core::input_parser=debug` will include in the logs the raw payloads received
+    /// when interacting with JSON endpoints, for example those used during all LNURL workflows.
+    ///
+    /// ### Errors
+    ///
+    /// An error is thrown if the log file cannot be created in the working directory.
+    ///
+    /// An error is thrown if a global logger is already configured.
+    pub fn init_logging(log_dir: &str, app_logger: Option<Box<dyn log::Log>>) -> Result<()> {
+        let target_log_file = Box::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(format!("{log_dir}/sdk.log"))
+                .map_err(|e| anyhow!("Can't create log file: {e}"))?,
+        );
+        let logger = env_logger::Builder::new()
+            .target(env_logger::Target::Pipe(target_log_file))
+            .parse_filters(
+                r#"
+                info,
+                breez_sdk_core=debug,
+                sdk_common=debug,
+                gl_client=debug,
+                h2=warn,
+                hyper=warn,
+                lightning_signer=warn,
+                reqwest=warn,
+                rustls=warn,
+                rustyline=warn,
+                vls_protocol_signer=warn
+            "#,
+            )
+            .format(|buf, record| {
+                writeln!(
+                    buf,
+                    "[{} {} {}:{}] {}",
+                    Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                    record.level(),
+                    record.module_path().unwrap_or("unknown"),
+                    record.line().unwrap_or(0),
+                    record.args()
+                )
+            })
+            .build();
+
+        let global_logger = GlobalSdkLogger {
+            logger,
+            log_listener: app_logger,
+        };
+
+        log::set_boxed_logger(Box::new(global_logger))
+            .map_err(|e| anyhow!("Failed to set global logger: {e}"))?;
+        log::set_max_level(LevelFilter::Trace);
+
+        Ok(())
     }
 
     async fn lookup_chain_service_closing_outspend(
@@ -1992,6 +3625,7 @@ impl BreezServices {
             },
             error: None,
             metadata: None,
+            fiat_rates: Vec::new(),
         })
     }
 
@@ -2006,6 +3640,7 @@ impl BreezServices {
     /// the application was started, then this method should be called to register for callbacks at
     /// the new correct `webhook_url`. To unregister a webhook call [BreezServices::unregister_webhook].
     pub async fn register_webhook(&self, webhook_url: String) -> SdkResult<()> {
+        self.ensure_can_receive()?;
         info!("Registering for webhook notifications");
         let is_new_webhook_url = match self.persister.get_webhook_url()? {
             None => true,
@@ -2261,6 +3896,9 @@ struct BreezServicesBuilder {
     node_api: Option<Arc<dyn NodeAPI>>,
     backup_transport: Option<Arc<dyn BackupTransport>>,
     seed: Option<Vec<u8>>,
+    signer: Option<Arc<dyn SignerAPI>>,
+    /// The node credentials and backup encryption key of a watch-only instance
+    watch_only: Option<(NodeCredentials, Vec<u8>)>,
     lsp_api: Option<Arc<dyn LspAPI>>,
     fiat_api: Option<Arc<dyn FiatAPI>>,
     persister: Option<Arc<SqliteStorage>>,
@@ -2282,6 +3920,8 @@ impl BreezServicesBuilder {
             config,
             node_api: None,
             seed: None,
+            signer: None,
+            watch_only: None,
             lsp_api: None,
             fiat_api: None,
             persister: None,
@@ -2367,14 +4007,35 @@ impl BreezServicesBuilder {
         self
     }
 
+    /// Signs with the given signer instead of the seed
+    pub fn signer(&mut self, signer: Arc<dyn SignerAPI>) -> &mut Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Connects without a seed, so the built services can't spend
+    pub fn watch_only(
+        &mut self,
+        node_credentials: NodeCredentials,
+        backup_encryption_key: Vec<u8>,
+    ) -> &mut Self {
+        self.watch_only = Some((node_credentials, backup_encryption_key));
+        self
+    }
+
     pub async fn build(
         &self,
         restore_only: Option<bool>,
         event_listener: Option<Box<dyn EventListener>>,
     ) -> BreezServicesResult<Arc<BreezServices>> {
-        if self.node_api.is_none() && self.seed.is_none() {
+        if self.node_api.is_none()
+            && self.seed.is_none()
+            && self.signer.is_none()
+            && self.watch_only.is_none()
+        {
             return Err(ConnectError::Generic {
-                err: "Either node_api or both credentials and seed should be provided".into(),
+                err: "Either node_api, seed, signer or watch-only credentials should be provided"
+                    .into(),
             });
         }
 
@@ -2398,13 +4059,26 @@ impl BreezServicesBuilder {
         let mut lsp_api = self.lsp_api.clone();
         let mut receiver = None;
         if node_api.is_none() {
-            let node_impls = node_builder::build_node(
-                self.config.clone(),
-                self.seed.clone().unwrap(),
-                restore_only,
-                persister.clone(),
-            )
-            .await?;
+            let node_impls = match &self.watch_only {
+                Some((node_credentials, _)) => {
+                    node_builder::build_watch_only_node(
+                        self.config.clone(),
+                        node_credentials.clone(),
+                        persister.clone(),
+                    )
+                    .await?
+                }
+                None => {
+                    node_builder::build_node(
+                        self.config.clone(),
+                        self.seed.clone().unwrap_or_default(),
+                        self.signer.clone(),
+                        restore_only,
+                        persister.clone(),
+                    )
+                    .await?
+                }
+            };
             node_api = Some(node_impls.node);
             backup_transport = backup_transport.or(Some(node_impls.backup_transport));
             lsp_api = lsp_api.or(node_impls.lsp);
@@ -2422,27 +4096,40 @@ impl BreezServicesBuilder {
         let unwrapped_backup_transport = backup_transport.unwrap();
 
         // create the backup encryption key and then the backup watcher
-        let backup_encryption_key = unwrapped_node_api
-            .derive_bip32_key(vec![
-                ChildNumber::from_hardened_idx(139)?,
-                ChildNumber::from(0),
-            ])
-            .await?;
+        let (backup_encryption_key, legacy_backup_encryption_key) = match &self.watch_only {
+            // Without the seed there is no legacy key, the given one must be current
+            Some((_, backup_encryption_key)) => {
+                (backup_encryption_key.clone(), backup_encryption_key.clone())
+            }
+            None => {
+                let backup_encryption_key = unwrapped_node_api
+                    .derive_bip32_key(vec![
+                        ChildNumber::from_hardened_idx(139)?,
+                        ChildNumber::from(0),
+                    ])
+                    .await?;
 
-        // We calculate the legacy key as a fallback for the case where the backup is still
-        // encrypted with the old key.
-        let legacy_backup_encryption_key = unwrapped_node_api
-            .legacy_derive_bip32_key(vec![
-                ChildNumber::from_hardened_idx(139)?,
-                ChildNumber::from(0),
-            ])
-            .await?;
+                // We calculate the legacy key as a fallback for the case where the backup is still
+                // encrypted with the old key.
+                let legacy_backup_encryption_key = unwrapped_node_api
+                    .legacy_derive_bip32_key(vec![
+                        ChildNumber::from_hardened_idx(139)?,
+                        ChildNumber::from(0),
+                    ])
+                    .await?;
+                (
+                    backup_encryption_key.to_priv().to_bytes(),
+                    legacy_backup_encryption_key.to_priv().to_bytes(),
+                )
+            }
+        };
         let backup_watcher = BackupWatcher::new(
             self.config.clone(),
             unwrapped_backup_transport.clone(),
             persister.clone(),
-            backup_encryption_key.to_priv().to_bytes(),
-            legacy_backup_encryption_key.to_priv().to_bytes(),
+            backup_encryption_key,
+            legacy_backup_encryption_key,
+            self.watch_only.is_some(),
         );
 
         // Ensure breez server connection is established in the background
@@ -2560,6 +4247,9 @@ impl BreezServicesBuilder {
             event_listener,
             backup_watcher: Arc::new(backup_watcher),
             shutdown_sender,
+            recurring_payments_trigger: Notify::new(),
+            spending_policy_lock: Mutex::new(()),
+            read_only: self.watch_only.is_some(),
         });
 
         Ok(breez_services)
@@ -2933,25 +4623,78 @@ async fn get_notification_lsps(
     Ok(notification_lsps)
 }
 
-#[cfg(test)]
-pub(crate) mod tests {
-    use std::collections::HashMap;
-    use std::sync::Arc;
+/// Records a rates snapshot, unless one was taken recently. Returns the time of the snapshot the
+/// rates belong to.
+fn record_fiat_rates(persister: &SqliteStorage, rates: &[Rate]) -> SdkResult<i64> {
+    let now = Utc::now().timestamp();
+    if let Some(last) = persister.last_fiat_rates_timestamp()? {
+        if now - last < FIAT_RATES_SNAPSHOT_INTERVAL_SEC {
+            return Ok(last);
+        }
+    }
+    persister.insert_fiat_rates(rates, now)?;
+    Ok(now)
+}
 
-    use anyhow::{anyhow, Result};
-    use regex::Regex;
-    use reqwest::Url;
-    use sdk_common::prelude::Rate;
+/// Normalizes the identifiers of a contact, which are matched against those of payments
+fn validate_contact(mut contact: Contact) -> SdkResult<Contact> {
+    let normalize = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+    };
+    contact.name = contact.name.trim().to_string();
+    contact.ln_address = normalize(contact.ln_address);
+    contact.node_pubkey = normalize(contact.node_pubkey);
+    contact.lnurl_pay_domain = normalize(contact.lnurl_pay_domain);
+
+    ensure_sdk!(
+        !contact.name.is_empty(),
+        SdkError::generic("Contact name can't be empty")
+    );
+    if let Some(ln_address) = &contact.ln_address {
+        ensure_sdk!(
+            matches!(ln_address.split_once('@'), Some((user, domain)) if !user.is_empty() && !domain.is_empty()),
+            SdkError::generic("Invalid Lightning Address")
+        );
+    }
+    if let Some(node_pubkey) = &contact.node_pubkey {
+        ensure_sdk!(
+            PublicKey::from_str(node_pubkey).is_ok(),
+            SdkError::generic("Invalid node id")
+        );
+    }
+    Ok(contact)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
 
+    use anyhow::{anyhow, Result};
+    use chrono::Utc;
+    use rand::Rng;
+    use regex::Regex;
+    use reqwest::Url;
+    use sdk_common::prelude::Rate;
+
+    use crate::bitcoin::hashes::{sha256, Hash};
     use crate::breez_services::{BreezServices, BreezServicesBuilder};
+    use crate::error::{
+        ReceiveOnchainError, ReceivePaymentError, RedeemOnchainError, SdkError, SendOnchainError,
+        SendPaymentError,
+    };
     use crate::models::{LnPaymentDetails, NodeState, Payment, PaymentDetails, PaymentTypeFilter};
-    use crate::node_api::NodeAPI;
+    use crate::node_api::{HeldHtlcs, NodeAPI};
     use crate::persist::cache::NodeStateStorage;
+    use crate::persist::recurring::RecurringPaymentClaim;
     use crate::persist::swap::SwapStorage;
+    use crate::recurring::{CLAIM_TIMEOUT_SEC, IN_FLIGHT_TIMEOUT_SEC};
     use crate::test_utils::*;
     use crate::*;
 
-    use super::{PaymentReceiver, Receiver};
+    use super::{PaymentReceiver, Receiver, DEVICE_SIGNER_ACTIVE_SEC};
 
     #[tokio::test]
     async fn test_node_state() -> Result<()> {
@@ -3054,6 +4797,11 @@ pub(crate) mod tests {
                         lnurl_success_action: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: None,
                         ln_address: None,
                         lnurl_withdraw_endpoint: None,
@@ -3064,6 +4812,7 @@ pub(crate) mod tests {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: payment_hash_lnurl_withdraw.to_string(),
@@ -3085,6 +4834,11 @@ pub(crate) mod tests {
                         lnurl_success_action: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: None,
                         ln_address: None,
                         lnurl_withdraw_endpoint: Some(test_lnurl_withdraw_endpoint.to_string()),
@@ -3095,6 +4849,7 @@ pub(crate) mod tests {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: payment_hash_with_lnurl_success_action.to_string(),
@@ -3116,6 +4871,11 @@ pub(crate) mod tests {
                         lnurl_success_action: Some(sa.clone()),
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: Some(lnurl_metadata.to_string()),
                         ln_address: Some(test_ln_address.to_string()),
                         lnurl_withdraw_endpoint: None,
@@ -3126,6 +4886,7 @@ pub(crate) mod tests {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: hex::encode(payment_hash_swap.clone()),
@@ -3147,6 +4908,11 @@ pub(crate) mod tests {
                         lnurl_success_action: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: None,
                         ln_address: None,
                         lnurl_withdraw_endpoint: None,
@@ -3157,6 +4923,7 @@ pub(crate) mod tests {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: hex::encode(payment_hash_rev_swap.clone()),
@@ -3179,6 +4946,11 @@ pub(crate) mod tests {
                         lnurl_metadata: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         ln_address: None,
                         lnurl_withdraw_endpoint: None,
                         swap_info: None,
@@ -3188,6 +4960,7 @@ pub(crate) mod tests {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
         ];
         let node_api = Arc::new(MockNodeAPI::new(dummy_node_state.clone()));
@@ -3202,6 +4975,11 @@ pub(crate) mod tests {
                 lnurl_pay_success_action: Some(sa.clone()),
                 lnurl_pay_domain: None,
                 lnurl_pay_comment: None,
+                lnurl_verify_url: None,
+                zap_request: None,
+                zap_receipt: None,
+                zap_nostr_pubkey: None,
+                fiat_amount: None,
                 lnurl_metadata: Some(lnurl_metadata.to_string()),
                 ln_address: Some(test_ln_address.to_string()),
                 lnurl_withdraw_endpoint: None,
@@ -3215,6 +4993,11 @@ pub(crate) mod tests {
                 lnurl_pay_success_action: None,
                 lnurl_pay_domain: None,
                 lnurl_pay_comment: None,
+                lnurl_verify_url: None,
+                zap_request: None,
+                zap_receipt: None,
+                zap_nostr_pubkey: None,
+                fiat_amount: None,
                 lnurl_metadata: None,
                 ln_address: None,
                 lnurl_withdraw_endpoint: Some(test_lnurl_withdraw_endpoint.to_string()),
@@ -3373,6 +5156,787 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_fiat_valuation() -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let received = Payment {
+            id: "hash1".to_string(),
+            payment_type: PaymentType::Received,
+            payment_time: now,
+            amount_msat: 1_000_000,
+            status: PaymentStatus::Complete,
+            ..Default::default()
+        };
+        let sent = Payment {
+            id: "hash2".to_string(),
+            payment_type: PaymentType::Sent,
+            payment_time: now - 10,
+            amount_msat: 500_000,
+            fee_msat: 5_000,
+            status: PaymentStatus::Complete,
+            ..Default::default()
+        };
+        let old = Payment {
+            id: "hash3".to_string(),
+            payment_type: PaymentType::Received,
+            payment_time: now - 10 * 86_400,
+            amount_msat: 1_000,
+            status: PaymentStatus::Complete,
+            ..Default::default()
+        };
+        let breez_services = breez_services_with(None, None, vec![received, sent, old]).await?;
+
+        // No rates were recorded yet
+        let value = breez_services
+            .payment_fiat_value("hash1".to_string(), "USD".to_string())
+            .await?
+            .unwrap();
+        assert_eq!(value.rate, None);
+        assert_eq!(value.amount, None);
+        assert!(breez_services
+            .payment_fiat_value("unknown".to_string(), "USD".to_string())
+            .await?
+            .is_none());
+
+        // Settling a payment links it to the latest rates, at 20,000 USD per BTC in the mock
+        breez_services.fetch_fiat_rates().await?;
+        breez_services.attach_fiat_rates("hash2");
+        let value = breez_services
+            .payment_fiat_value("hash2".to_string(), "USD".to_string())
+            .await?
+            .unwrap();
+        assert_eq!(value.rate.unwrap().value, 20_000.0);
+        assert!((value.amount.unwrap() - 0.1).abs() < 1e-9);
+        assert!((value.fee.unwrap() - 0.001).abs() < 1e-9);
+        let payment = breez_services
+            .payment_by_hash("hash2".to_string())
+            .await?
+            .unwrap();
+        assert!(payment
+            .fiat_rates
+            .iter()
+            .any(|r| r.coin == "USD" && r.value == 20_000.0));
+
+        // Payments far from any recorded rate can't be valued
+        let res = breez_services
+            .fiat_valuation(FiatValuationRequest {
+                currency: "USD".to_string(),
+                filter: ListPaymentsRequest::default(),
+            })
+            .await?;
+        assert_eq!(res.payments.len(), 3);
+        assert_eq!(res.unvalued_payments_count, 1);
+        assert!((res.total_received - 0.2).abs() < 1e-9);
+        assert!((res.total_sent - 0.101).abs() < 1e-9);
+        assert!((res.total_fees - 0.001).abs() < 1e-9);
+
+        let res = breez_services
+            .fiat_valuation(FiatValuationRequest {
+                currency: "EUR".to_string(),
+                filter: ListPaymentsRequest::default(),
+            })
+            .await?;
+        assert_eq!(res.unvalued_payments_count, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_contacts() -> Result<()> {
+        let breez_services = breez_services().await?;
+
+        let contact = breez_services
+            .add_contact(AddContactRequest {
+                name: " Bob ".to_string(),
+                ln_address: Some("Bob@Example.com".to_string()),
+                lnurl_pay_domain: Some(String::new()),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(contact.name, "Bob");
+        assert_eq!(contact.ln_address, Some("bob@example.com".to_string()));
+        assert_eq!(contact.lnurl_pay_domain, None);
+        assert_eq!(breez_services.list_contacts().await?, vec![contact.clone()]);
+
+        for invalid in [
+            AddContactRequest::default(),
+            AddContactRequest {
+                name: "Bob".to_string(),
+                ln_address: Some("example.com".to_string()),
+                ..Default::default()
+            },
+            AddContactRequest {
+                name: "Bob".to_string(),
+                node_pubkey: Some("02abc".to_string()),
+                ..Default::default()
+            },
+        ] {
+            assert!(breez_services.add_contact(invalid).await.is_err());
+        }
+
+        let updated = breez_services
+            .update_contact(UpdateContactRequest {
+                id: contact.id.clone(),
+                name: "Robert".to_string(),
+                node_pubkey: Some(
+                    "0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798"
+                        .to_string(),
+                ),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(updated.created_at, contact.created_at);
+        assert_eq!(updated.ln_address, None);
+        assert_eq!(
+            updated.node_pubkey.as_deref(),
+            Some("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+        );
+        assert_eq!(
+            breez_services.get_contact(contact.id.clone()).await?,
+            Some(updated)
+        );
+
+        breez_services.delete_contact(contact.id.clone()).await?;
+        assert!(breez_services.list_contacts().await?.is_empty());
+        assert!(breez_services
+            .update_contact(UpdateContactRequest {
+                id: contact.id,
+                name: "Bob".to_string(),
+                ..Default::default()
+            })
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_devices() -> Result<()> {
+        let breez_services = breez_services().await?;
+        // Listing the devices doesn't register the current one, connecting does
+        assert!(breez_services.list_devices().await?.is_empty());
+        breez_services.record_device_seen(MOCK_DEVICE_ID)?;
+
+        let devices = breez_services.list_devices().await?;
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].id, MOCK_DEVICE_ID);
+        assert!(devices[0].is_current);
+        assert!(devices[0].signer_active);
+
+        assert!(breez_services
+            .register_device(RegisterDeviceRequest {
+                label: " ".to_string()
+            })
+            .await
+            .is_err());
+        let registered = breez_services
+            .register_device(RegisterDeviceRequest {
+                label: " Laptop ".to_string(),
+            })
+            .await?;
+        assert_eq!(registered.device.label, "Laptop");
+        assert!(!registered.device.is_current);
+        assert_eq!(breez_services.list_devices().await?.len(), 2);
+
+        // A signer running on the other device is detected
+        let find_registered = |devices: Vec<GreenlightDevice>| {
+            devices
+                .into_iter()
+                .find(|d| d.id == registered.device.id)
+                .unwrap()
+        };
+        assert!(!find_registered(breez_services.list_devices().await?).signer_active);
+        let long_ago = Utc::now().timestamp() - DEVICE_SIGNER_ACTIVE_SEC - 1;
+        breez_services
+            .persister
+            .set_greenlight_device_last_seen(&registered.device.id, long_ago)?;
+        assert!(!find_registered(breez_services.list_devices().await?).signer_active);
+        breez_services
+            .persister
+            .set_greenlight_device_last_seen(&registered.device.id, Utc::now().timestamp())?;
+        assert!(find_registered(breez_services.list_devices().await?).signer_active);
+
+        // Watch-only credentials belong to a device of their own
+        breez_services.watch_only_credentials().await?;
+        let devices = breez_services.list_devices().await?;
+        assert_eq!(devices.len(), 3);
+        let watch_only = devices
+            .iter()
+            .find(|d| d.label == WATCH_ONLY_DEVICE)
+            .unwrap();
+        assert_ne!(watch_only.id, MOCK_DEVICE_ID);
+        assert_ne!(watch_only.id, registered.device.id);
+        assert!(!watch_only.is_current);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watch_only() -> Result<()> {
+        let known_payments = vec![Payment {
+            id: "hash1".to_string(),
+            payment_type: PaymentType::Received,
+            payment_time: 1,
+            amount_msat: 10_000,
+            fee_msat: 0,
+            status: PaymentStatus::Complete,
+            error: None,
+            description: None,
+            details: PaymentDetails::Ln {
+                data: LnPaymentDetails {
+                    payment_hash: "hash1".to_string(),
+                    ..Default::default()
+                },
+            },
+            metadata: None,
+            fiat_rates: Vec::new(),
+        }];
+        let test_config = create_test_config();
+        let persister = Arc::new(create_test_persister(test_config.clone()));
+        persister.init()?;
+        persister.insert_or_update_payments(&known_payments, false)?;
+
+        let breez_services = BreezServicesBuilder::new(test_config)
+            .lsp_api(Arc::new(MockBreezServer {}))
+            .fiat_api(Arc::new(MockBreezServer {}))
+            .persister(persister)
+            .node_api(Arc::new(MockNodeAPI::new(get_dummy_node_state())))
+            .rest_client(Arc::new(MockRestClient::new()))
+            .backup_transport(Arc::new(MockBackupTransport::new()))
+            .watch_only(
+                NodeCredentials::Greenlight {
+                    credentials: GreenlightDeviceCredentials { device: vec![] },
+                },
+                vec![1; 32],
+            )
+            .build(None, None)
+            .await?;
+
+        // Viewing works as usual
+        assert_eq!(
+            breez_services
+                .list_payments(ListPaymentsRequest::default())
+                .await?
+                .len(),
+            1
+        );
+
+        let res = breez_services
+            .send_payment(SendPaymentRequest {
+                bolt11: "lnbc".to_string(),
+                use_trampoline: false,
+                amount_msat: None,
+                label: None,
+            })
+            .await;
+        assert!(matches!(res, Err(SendPaymentError::ReadOnly { .. })));
+        let res = breez_services
+            .send_spontaneous_payment(SendSpontaneousPaymentRequest {
+                node_id: "node".to_string(),
+                amount_msat: 1_000,
+                extra_tlvs: None,
+                label: None,
+            })
+            .await;
+        assert!(matches!(res, Err(SendPaymentError::ReadOnly { .. })));
+        let res = breez_services
+            .redeem_onchain_funds(RedeemOnchainFundsRequest {
+                to_address: "address".to_string(),
+                sat_per_vbyte: 1,
+            })
+            .await;
+        assert!(matches!(res, Err(RedeemOnchainError::ReadOnly { .. })));
+        let res = breez_services
+            .refund(RefundRequest {
+                swap_address: "swap".to_string(),
+                to_address: "address".to_string(),
+                sat_per_vbyte: 1,
+                unilateral: None,
+            })
+            .await;
+        assert!(matches!(res, Err(SdkError::ReadOnly { .. })));
+        let res = breez_services
+            .pay_onchain(PayOnchainRequest {
+                recipient_address: "address".to_string(),
+                prepare_res: PrepareOnchainPaymentResponse {
+                    fees_hash: "hash".to_string(),
+                    fees_percentage: 0.5,
+                    fees_lockup: 1,
+                    fees_claim: 1,
+                    sender_amount_sat: 100_000,
+                    recipient_amount_sat: 99_000,
+                    total_fees: 1_000,
+                },
+            })
+            .await;
+        assert!(matches!(res, Err(SendOnchainError::ReadOnly { .. })));
+
+        // Neither is receiving
+        let res = breez_services
+            .receive_payment(ReceivePaymentRequest {
+                amount_msat: 1_000,
+                description: "receive".to_string(),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(res, Err(ReceivePaymentError::Generic { .. })));
+        let res = breez_services
+            .receive_onchain(ReceiveOnchainRequest {
+                opening_fee_params: None,
+            })
+            .await;
+        assert!(matches!(res, Err(ReceiveOnchainError::Generic { .. })));
+        let res = breez_services.connect_lsp("lsp".to_string()).await;
+        assert!(matches!(res, Err(SdkError::ReadOnly { .. })));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spending_policy() -> Result<()> {
+        let sent = Payment {
+            id: "hash1".to_string(),
+            payment_type: PaymentType::Sent,
+            payment_time: chrono::Utc::now().timestamp() - 60,
+            amount_msat: 15_000,
+            fee_msat: 1_000,
+            status: PaymentStatus::Complete,
+            ..Default::default()
+        };
+        let node_api = Arc::new(MockNodeAPI::new(get_dummy_node_state()));
+        let breez_services = breez_services_with(Some(node_api.clone()), None, vec![sent]).await?;
+        assert_eq!(breez_services.spending_policy().await?, None);
+
+        // A fee limit below the configured exemption
+        let policy = SpendingPolicy {
+            max_payment_msat: Some(10_000),
+            max_fee_msat: Some(1_000),
+            daily_budget_msat: Some(20_000),
+            ..Default::default()
+        };
+        breez_services
+            .set_spending_policy(Some(policy.clone()))
+            .await?;
+        assert_eq!(breez_services.spending_policy().await?, Some(policy));
+
+        // Over the payment limit, then over the daily budget
+        for amount_msat in [20_000, 5_000] {
+            let invoice = create_invoice("".into(), amount_msat, vec![], None);
+            let res = breez_services
+                .send_payment(SendPaymentRequest {
+                    bolt11: invoice.bolt11,
+                    use_trampoline: false,
+                    amount_msat: None,
+                    label: None,
+                })
+                .await;
+            assert!(matches!(res, Err(SendPaymentError::PolicyViolation { .. })));
+            // Rejected payments are not recorded as pending
+            assert!(breez_services
+                .payment_by_hash(invoice.payment_hash)
+                .await?
+                .is_none());
+        }
+
+        let res = breez_services
+            .send_spontaneous_payment(SendSpontaneousPaymentRequest {
+                node_id: "02aa".to_string(),
+                amount_msat: 20_000,
+                extra_tlvs: None,
+                label: None,
+            })
+            .await;
+        assert!(matches!(res, Err(SendPaymentError::PolicyViolation { .. })));
+
+        // Within the budget once its fee is capped at the policy's limit
+        breez_services
+            .send_spontaneous_payment(SendSpontaneousPaymentRequest {
+                node_id: "02aa".to_string(),
+                amount_msat: 3_000,
+                extra_tlvs: None,
+                label: None,
+            })
+            .await?;
+        assert_eq!(*node_api.max_fees_msat.lock().await, vec![Some(1_000)]);
+
+        // Trampoline is skipped even when the fee limit is above the routing fee limit, as the
+        // trampoline fee is set by the LSP
+        breez_services.persister.set_lsp(
+            "lsp".to_string(),
+            Some("02eadbd9e7557375161df8b646776a547c5cbc2e95b3071ec81553f8ec2cea3b8c".to_string()),
+        )?;
+        breez_services
+            .set_spending_policy(Some(SpendingPolicy {
+                max_fee_msat: Some(1_000_000),
+                ..Default::default()
+            }))
+            .await?;
+        let invoice = create_invoice("".into(), 1_000, vec![], None);
+        breez_services
+            .send_payment(SendPaymentRequest {
+                bolt11: invoice.bolt11,
+                use_trampoline: true,
+                amount_msat: None,
+                label: None,
+            })
+            .await?;
+        let max_fees_msat = node_api.max_fees_msat.lock().await.clone();
+        assert_eq!(max_fees_msat.len(), 2);
+        assert!(max_fees_msat[1].is_some());
+
+        breez_services.set_spending_policy(None).await?;
+        assert_eq!(breez_services.spending_policy().await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recurring_payments() -> Result<()> {
+        let breez_services = breez_services().await?;
+        // Claims on scheduled payments are synced before paying
+        breez_services.start_backup_watcher().await?;
+        let now = chrono::Utc::now().timestamp();
+        let add_req = |node_id: &str| AddRecurringPaymentRequest {
+            destination: RecurringPaymentDestination::Keysend {
+                node_id: node_id.to_string(),
+            },
+            amount: RecurringPaymentAmount::Fiat {
+                currency: "USD".to_string(),
+                amount: 2.0,
+            },
+            interval: RecurringPaymentInterval::Weekly,
+            comment: None,
+            start_time: Some(now - 8 * 86_400),
+        };
+        assert!(breez_services
+            .add_recurring_payment(add_req("02aa"))
+            .await
+            .is_err());
+        let recurring_payment = breez_services
+            .add_recurring_payment(add_req(
+                "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ))
+            .await?;
+
+        // Checked again when the next one is due
+        let wake_time = breez_services.execute_recurring_payments().await?;
+        assert_eq!(wake_time, Some(now + 6 * 86_400));
+        let runs = breez_services
+            .list_recurring_payment_runs(recurring_payment.id.clone())
+            .await?;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].scheduled_time, now - 8 * 86_400);
+        assert_eq!(runs[0].attempts, 1);
+        assert_eq!(runs[0].error, None);
+        assert_eq!(runs[0].attempt_label, None);
+        let payment_id = runs[0].payment_id.clone().unwrap();
+        assert!(breez_services.payment_by_hash(payment_id).await?.is_some());
+
+        // The payment missed last week is skipped
+        let recurring_payments = breez_services.list_recurring_payments().await?;
+        assert_eq!(recurring_payments[0].next_payment_time, now + 6 * 86_400);
+        breez_services.execute_recurring_payments().await?;
+        assert_eq!(
+            breez_services
+                .list_recurring_payment_runs(recurring_payment.id.clone())
+                .await?
+                .len(),
+            1
+        );
+
+        // Scheduled payments claimed by another instance are left to it
+        let node_id = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let claimed = breez_services
+            .add_recurring_payment(AddRecurringPaymentRequest {
+                amount: RecurringPaymentAmount::Bitcoin { amount_msat: 5_000 },
+                start_time: Some(now - 60),
+                ..add_req(node_id)
+            })
+            .await?;
+        breez_services
+            .persister
+            .insert_recurring_payment_claim(&RecurringPaymentClaim {
+                recurring_payment_id: claimed.id.clone(),
+                scheduled_time: now - 60,
+                instance_id: "other".to_string(),
+                claimed_at: now - 60,
+            })?;
+        let wake_time = breez_services.execute_recurring_payments().await?;
+        assert_eq!(wake_time, Some(now - 60 + CLAIM_TIMEOUT_SEC));
+        assert!(breez_services
+            .list_recurring_payment_runs(claimed.id.clone())
+            .await?
+            .is_empty());
+        breez_services.delete_recurring_payment(claimed.id).await?;
+
+        // An attempt in flight is not repeated
+        let in_flight = breez_services
+            .add_recurring_payment(AddRecurringPaymentRequest {
+                start_time: Some(now - 60),
+                ..add_req(node_id)
+            })
+            .await?;
+        let run = RecurringPaymentRun {
+            recurring_payment_id: in_flight.id.clone(),
+            scheduled_time: now - 60,
+            payment_id: None,
+            error: Some("Payment timeout".to_string()),
+            attempts: 1,
+            updated_at: now - 30,
+            attempt_label: Some(format!("recurring:{}:{}:1", in_flight.id, now - 60)),
+        };
+        breez_services
+            .persister
+            .upsert_recurring_payment_run(&run)?;
+        let wake_time = breez_services.execute_recurring_payments().await?;
+        assert_eq!(wake_time, Some(now - 30 + IN_FLIGHT_TIMEOUT_SEC));
+        assert_eq!(
+            breez_services
+                .list_recurring_payment_runs(in_flight.id.clone())
+                .await?,
+            vec![run]
+        );
+        breez_services
+            .delete_recurring_payment(in_flight.id)
+            .await?;
+
+        let paused = breez_services
+            .set_recurring_payment_paused(recurring_payment.id.clone(), true)
+            .await?;
+        assert!(paused.paused);
+        breez_services
+            .delete_recurring_payment(recurring_payment.id)
+            .await?;
+        assert!(breez_services.list_recurring_payments().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hold_invoices() -> Result<()> {
+        let breez_services = breez_services().await?;
+        let preimage = rand::thread_rng().gen::<[u8; 32]>().to_vec();
+        let payment_hash = hex::encode(sha256::Hash::hash(&preimage));
+        let req = CreateHoldInvoiceRequest {
+            payment_hash: payment_hash.clone(),
+            amount_msat: 50_000,
+            description: "Escrow".to_string(),
+            use_description_hash: None,
+            expiry: None,
+            cltv: None,
+        };
+        let hold_invoice = breez_services.create_hold_invoice(req.clone()).await?;
+        assert_eq!(hold_invoice.payment_hash, payment_hash);
+        assert_eq!(hold_invoice.status, HoldInvoiceStatus::Open);
+        assert!(breez_services.create_hold_invoice(req).await.is_err());
+
+        // Only held payments can be settled
+        assert!(breez_services
+            .settle_invoice(preimage.clone())
+            .await
+            .is_err());
+        breez_services
+            .on_held_htlcs(HeldHtlcs {
+                payment_hash: hex::decode(&payment_hash)?,
+                amount_msat: 50_000,
+                claim_deadline: Some(800_000),
+            })
+            .await?;
+        let accepted = breez_services.list_hold_invoices().await?;
+        assert_eq!(accepted[0].status, HoldInvoiceStatus::Accepted);
+        assert_eq!(accepted[0].accepted_amount_msat, Some(50_000));
+        assert_eq!(accepted[0].settle_deadline, Some(800_000));
+
+        breez_services.settle_invoice(preimage).await?;
+        assert!(breez_services
+            .cancel_invoice(payment_hash.clone())
+            .await
+            .is_err());
+        assert_eq!(
+            breez_services.list_hold_invoices().await?[0].status,
+            HoldInvoiceStatus::Settled
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_and_cancel_invoices() -> Result<()> {
+        let breez_services = breez_services().await?;
+        breez_services.sync().await?;
+
+        let res = breez_services
+            .receive_payment(ReceivePaymentRequest {
+                amount_msat: 3_000_000,
+                description: "Coffee".to_string(),
+                use_description_hash: Some(false),
+                ..Default::default()
+            })
+            .await?;
+        let payment_hash = res.ln_invoice.payment_hash;
+        let invoices = breez_services
+            .list_invoices(ListInvoicesRequest::default())
+            .await?;
+        assert_eq!(invoices.len(), 1);
+        assert_eq!(invoices[0].payment_hash, payment_hash);
+        assert_eq!(invoices[0].status, InvoiceStatus::Pending);
+        assert_eq!(invoices[0].payer_amount_msat, 3_000_000);
+        assert!(res.opening_fee_msat.is_some());
+        assert_eq!(invoices[0].opening_fee_msat, res.opening_fee_msat);
+
+        breez_services.cancel_invoice(payment_hash.clone()).await?;
+        assert!(breez_services.cancel_invoice(payment_hash).await.is_err());
+        let pending = breez_services
+            .list_invoices(ListInvoicesRequest {
+                statuses: Some(vec![InvoiceStatus::Pending]),
+                ..Default::default()
+            })
+            .await?;
+        assert!(pending.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_receive_fiat_payment() -> Result<()> {
+        let breez_services = breez_services().await?;
+        breez_services.sync().await?;
+
+        let req = ReceiveFiatPaymentRequest {
+            currency: "USD".to_string(),
+            amount: 1.0,
+            spread_percent: Some(2.0),
+            description: "Coffee".to_string(),
+            use_description_hash: Some(false),
+            ..Default::default()
+        };
+        let res = breez_services.receive_fiat_payment(req.clone()).await?;
+        // 1 USD at 20,000 USD/BTC, plus 2%
+        assert_eq!(res.ln_invoice.amount_msat, Some(5_100_000));
+
+        let payment_hash = res.ln_invoice.payment_hash;
+        breez_services.persister.insert_or_update_payments(
+            &[Payment {
+                id: payment_hash.clone(),
+                payment_type: PaymentType::Received,
+                status: PaymentStatus::Complete,
+                amount_msat: 5_100_000,
+                details: PaymentDetails::Ln {
+                    data: LnPaymentDetails {
+                        payment_hash: payment_hash.clone(),
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            }],
+            false,
+        )?;
+        let payment = breez_services.payment_by_hash(payment_hash).await?.unwrap();
+        let PaymentDetails::Ln { data } = payment.details else {
+            panic!("Expected a Lightning payment");
+        };
+        assert_eq!(
+            data.fiat_amount,
+            Some(FiatAmount {
+                currency: "USD".to_string(),
+                amount: 1.0,
+                rate: 20_000.0,
+                spread_percent: 2.0,
+            })
+        );
+
+        let unknown_currency = ReceiveFiatPaymentRequest {
+            currency: "XYZ".to_string(),
+            ..req.clone()
+        };
+        assert!(breez_services
+            .receive_fiat_payment(unknown_currency)
+            .await
+            .is_err());
+        let negative_spread = ReceiveFiatPaymentRequest {
+            spread_percent: Some(-1.0),
+            ..req
+        };
+        assert!(matches!(
+            breez_services.receive_fiat_payment(negative_spread).await,
+            Err(crate::error::ReceivePaymentError::InvalidAmount { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prepare_redeem_onchain_funds() -> Result<()> {
+        let utxo = UnspentTransactionOutput {
+            txid: rand_vec_u8(32),
+            outnum: 0,
+            amount_millisatoshi: 50_000_000,
+            address: "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
+            reserved: false,
+        };
+        let node_api = Arc::new(MockNodeAPI::new(NodeState {
+            onchain_balance_msat: 100_000_000,
+            utxos: vec![utxo.clone(), utxo],
+            ..get_dummy_node_state()
+        }));
+        let breez_services = breez_services_with(Some(node_api), None, vec![]).await?;
+        breez_services.sync().await?;
+
+        let req = PrepareRedeemOnchainFundsRequest {
+            to_address: "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
+            sat_per_vbyte: 10,
+        };
+        let res = breez_services
+            .prepare_redeem_onchain_funds(req.clone())
+            .await?;
+        // Each of the two inputs is counted
+        assert_eq!(res.tx_weight, 710);
+        assert_eq!(res.tx_fee_sat, 1_775);
+
+        let too_high_fee_rate = PrepareRedeemOnchainFundsRequest {
+            sat_per_vbyte: 1_000,
+            ..req
+        };
+        assert!(matches!(
+            breez_services
+                .prepare_redeem_onchain_funds(too_high_fee_rate)
+                .await,
+            Err(RedeemOnchainError::InsufficientFunds { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_close_lsp_channels() -> Result<()> {
+        let node_api = Arc::new(MockNodeAPI::new(get_dummy_node_state()));
+        let breez_services = breez_services_with(Some(node_api.clone()), None, vec![]).await?;
+        breez_services.sync().await?;
+
+        let txids = breez_services.close_lsp_channels().await?;
+        assert_eq!(txids.len(), 1);
+        assert_eq!(
+            *node_api.closed_peers.lock().await,
+            vec![MockBreezServer {}.lsp_pub_key()]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_generate_diagnostic_data() -> Result<()> {
+        let breez_services = breez_services().await?;
+        breez_services.sync().await?;
+
+        let data: serde_json::Value =
+            serde_json::from_str(&breez_services.generate_diagnostic_data().await?)?;
+        assert!(data["timestamp"].is_u64());
+        assert!(data["node"].is_object());
+        assert!(data["sdk"].is_object());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_buy_bitcoin_with_moonpay() -> Result<(), Box<dyn std::error::Error>> {
         let mock_rest_client = MockRestClient::new();
@@ -5,8 +5,8 @@ use sdk_common::prelude::*;
 use thiserror::Error;
 
 use crate::{
-    bitcoin::bip32, node_api::NodeError, persist::error::PersistError, swap_in::ReceiveSwapError,
-    swap_out::error::ReverseSwapError,
+    bitcoin::bip32, node_api::NodeError, persist::error::PersistError, policy::SpendingPolicyError,
+    swap_in::ReceiveSwapError, swap_out::error::ReverseSwapError,
 };
 
 pub type SdkResult<T, E = SdkError> = Result<T, E>;
@@ -68,6 +68,7 @@ impl From<SdkError> for ConnectError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::Generic { err },
         }
     }
 }
@@ -77,6 +78,7 @@ impl From<SdkError> for LnUrlAuthError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::Generic { err },
         }
     }
 }
@@ -94,6 +96,7 @@ impl From<SdkError> for LnUrlPayError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::ReadOnly { err },
         }
     }
 }
@@ -113,6 +116,8 @@ impl From<SendPaymentError> for LnUrlPayError {
             SendPaymentError::RouteTooExpensive { err } => Self::RouteTooExpensive { err },
             SendPaymentError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
             SendPaymentError::InsufficientBalance { err } => Self::InsufficientBalance { err },
+            SendPaymentError::PolicyViolation { err } => Self::PolicyViolation { err },
+            SendPaymentError::ReadOnly { err } => Self::ReadOnly { err },
         }
     }
 }
@@ -147,6 +152,7 @@ impl From<SdkError> for LnUrlWithdrawError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::Generic { err },
         }
     }
 }
@@ -185,6 +191,7 @@ impl From<SdkError> for ReceiveOnchainError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::Generic { err },
         }
     }
 }
@@ -224,6 +231,12 @@ pub enum RedeemOnchainError {
     /// This error is raised when the node does not have enough funds to redeem the onchain balance.
     #[error("{err}")]
     InsufficientFunds { err: String },
+
+    /// This error is raised when spending from an instance connected with
+    /// [crate::breez_services::BreezServices::connect_watch_only], or while another instance
+    /// runs the node, see [crate::breez_services::BreezServices::take_over_node_lock].
+    #[error("Read-only: {err}")]
+    ReadOnly { err: String },
 }
 
 impl From<NodeError> for RedeemOnchainError {
@@ -231,6 +244,7 @@ impl From<NodeError> for RedeemOnchainError {
         match value {
             NodeError::InsufficientFunds(err) => Self::InsufficientFunds { err },
             NodeError::ServiceConnectivity(err) => Self::ServiceConnectivity { err },
+            NodeError::ReadOnly(err) => Self::ReadOnly { err },
             _ => Self::Generic {
                 err: value.to_string(),
             },
@@ -251,6 +265,7 @@ impl From<SdkError> for RedeemOnchainError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::ReadOnly { err },
         }
     }
 }
@@ -343,6 +358,7 @@ impl From<SdkError> for ReceivePaymentError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::Generic { err },
         }
     }
 }
@@ -358,6 +374,12 @@ pub enum SdkError {
     /// This error is raised when a connection to an external service fails.
     #[error("Service connectivity: {err}")]
     ServiceConnectivity { err: String },
+
+    /// This error is raised when spending from an instance connected with
+    /// [crate::breez_services::BreezServices::connect_watch_only], or while another instance
+    /// runs the node, see [crate::breez_services::BreezServices::take_over_node_lock].
+    #[error("Read-only: {err}")]
+    ReadOnly { err: String },
 }
 
 impl SdkError {
@@ -388,6 +410,14 @@ impl From<anyhow::Error> for SdkError {
     }
 }
 
+impl From<bip32::Error> for SdkError {
+    fn from(err: bip32::Error) -> Self {
+        Self::Generic {
+            err: err.to_string(),
+        }
+    }
+}
+
 impl From<crate::bitcoin::hashes::hex::Error> for SdkError {
     fn from(err: crate::bitcoin::hashes::hex::Error) -> Self {
         Self::Generic {
@@ -416,6 +446,7 @@ impl From<NodeError> for SdkError {
     fn from(value: NodeError) -> Self {
         match value {
             NodeError::ServiceConnectivity(err) => Self::ServiceConnectivity { err },
+            NodeError::ReadOnly(err) => Self::ReadOnly { err },
             _ => Self::Generic {
                 err: value.to_string(),
             },
@@ -481,8 +512,10 @@ impl From<SendPaymentError> for SdkError {
             | SendPaymentError::PaymentTimeout { err }
             | SendPaymentError::RouteNotFound { err }
             | SendPaymentError::RouteTooExpensive { err }
-            | SendPaymentError::InsufficientBalance { err } => Self::Generic { err },
+            | SendPaymentError::InsufficientBalance { err }
+            | SendPaymentError::PolicyViolation { err } => Self::Generic { err },
             SendPaymentError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SendPaymentError::ReadOnly { err } => Self::ReadOnly { err },
         }
     }
 }
@@ -527,6 +560,17 @@ pub enum SendOnchainError {
     /// This error is raised when a connection to an external service fails.
     #[error("Service connectivity: {err}")]
     ServiceConnectivity { err: String },
+
+    /// This error is raised when the payment is not allowed by the
+    /// [crate::models::SpendingPolicy].
+    #[error("Policy violation: {err}")]
+    PolicyViolation { err: String },
+
+    /// This error is raised when spending from an instance connected with
+    /// [crate::breez_services::BreezServices::connect_watch_only], or while another instance
+    /// runs the node, see [crate::breez_services::BreezServices::take_over_node_lock].
+    #[error("Read-only: {err}")]
+    ReadOnly { err: String },
 }
 impl SendOnchainError {
     pub(crate) fn generic(err: &str) -> Self {
@@ -550,6 +594,7 @@ impl From<NodeError> for SendOnchainError {
             NodeError::PaymentFailed(err) => Self::PaymentFailed { err },
             NodeError::PaymentTimeout(err) => Self::PaymentTimeout { err },
             NodeError::ServiceConnectivity(err) => Self::ServiceConnectivity { err },
+            NodeError::ReadOnly(err) => Self::ReadOnly { err },
             _ => Self::Generic {
                 err: value.to_string(),
             },
@@ -562,6 +607,7 @@ impl From<SdkError> for SendOnchainError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::ReadOnly { err },
         }
     }
 }
@@ -589,6 +635,15 @@ impl From<PersistError> for SendOnchainError {
     }
 }
 
+impl From<SpendingPolicyError> for SendOnchainError {
+    fn from(value: SpendingPolicyError) -> Self {
+        match value {
+            SpendingPolicyError::Violation(err) => Self::PolicyViolation { err },
+            SpendingPolicyError::Persist(err) => err.into(),
+        }
+    }
+}
+
 /// Error returned by [crate::breez_services::BreezServices::send_payment] and [crate::breez_services::BreezServices::send_spontaneous_payment]
 #[derive(Clone, Debug, Error)]
 pub enum SendPaymentError {
@@ -643,6 +698,17 @@ pub enum SendPaymentError {
     /// This error is raised when the node does not have enough funds to make the payment.
     #[error("Insufficient balance: {err}")]
     InsufficientBalance { err: String },
+
+    /// This error is raised when the payment is not allowed by the
+    /// [crate::models::SpendingPolicy].
+    #[error("Policy violation: {err}")]
+    PolicyViolation { err: String },
+
+    /// This error is raised when spending from an instance connected with
+    /// [crate::breez_services::BreezServices::connect_watch_only], or while another instance
+    /// runs the node, see [crate::breez_services::BreezServices::take_over_node_lock].
+    #[error("Read-only: {err}")]
+    ReadOnly { err: String },
 }
 
 impl From<anyhow::Error> for SendPaymentError {
@@ -672,6 +738,7 @@ impl From<NodeError> for SendPaymentError {
             NodeError::RouteNotFound(err) => Self::RouteNotFound { err },
             NodeError::RouteTooExpensive(err) => Self::RouteTooExpensive { err },
             NodeError::ServiceConnectivity(err) => Self::ServiceConnectivity { err },
+            NodeError::ReadOnly(err) => Self::ReadOnly { err },
             _ => Self::Generic {
                 err: value.to_string(),
             },
@@ -692,6 +759,16 @@ impl From<SdkError> for SendPaymentError {
         match value {
             SdkError::Generic { err } => Self::Generic { err },
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity { err },
+            SdkError::ReadOnly { err } => Self::ReadOnly { err },
+        }
+    }
+}
+
+impl From<SpendingPolicyError> for SendPaymentError {
+    fn from(value: SpendingPolicyError) -> Self {
+        match value {
+            SpendingPolicyError::Violation(err) => Self::PolicyViolation { err },
+            SpendingPolicyError::Persist(err) => err.into(),
         }
     }
 }
@@ -0,0 +1,476 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
+
+use crate::error::{SdkError, SdkResult};
+use crate::models::{
+    ExportFormat, ExportRequest, ExportResponse, Payment, PaymentDetails, PaymentType,
+};
+use crate::persist::db::SqliteStorage;
+use crate::persist::error::PersistError;
+
+const CSV_HEADER: [&str; 20] = [
+    "id",
+    "payment_type",
+    "status",
+    "payment_time",
+    "amount_msat",
+    "fee_msat",
+    "channel_open_fee_msat",
+    "description",
+    "label",
+    "payment_hash",
+    "bolt11",
+    "destination_pubkey",
+    "ln_address",
+    "swap_address",
+    "swap_txids",
+    "swap_refund_txids",
+    "reverse_swap_lockup_txid",
+    "reverse_swap_claim_txid",
+    "channel_funding_txid",
+    "channel_closing_txid",
+];
+
+/// Writes the payments matching the request to a file, one payment at a time.
+pub(crate) fn export_payments(
+    persister: &SqliteStorage,
+    node_id: &str,
+    req: ExportRequest,
+) -> SdkResult<ExportResponse> {
+    let file = File::create(&req.file_path).map_err(io_error)?;
+    let mut writer: Box<dyn PaymentWriter> = match req.format {
+        ExportFormat::Csv => Box::new(CsvWriter(BufWriter::new(file))),
+        ExportFormat::Ofx => Box::new(OfxWriter {
+            out: BufWriter::new(file),
+            node_id: node_id.to_string(),
+            from_timestamp: req.filter.from_timestamp.unwrap_or_default(),
+            to_timestamp: req
+                .filter
+                .to_timestamp
+                .unwrap_or_else(|| Utc::now().timestamp()),
+        }),
+        ExportFormat::Bip329 => Box::new(Bip329Writer(BufWriter::new(file))),
+    };
+
+    writer.begin().map_err(io_error)?;
+    let payments_count = persister.for_each_payment(req.filter, |payment| {
+        writer
+            .write_payment(&payment)
+            .map_err(|e| PersistError::Generic(e.to_string()))
+    })?;
+    writer.end().map_err(io_error)?;
+
+    Ok(ExportResponse {
+        file_path: req.file_path,
+        payments_count,
+    })
+}
+
+fn io_error(err: std::io::Error) -> SdkError {
+    SdkError::Generic {
+        err: format!("Failed to write export file: {err}"),
+    }
+}
+
+trait PaymentWriter {
+    fn begin(&mut self) -> std::io::Result<()>;
+    fn write_payment(&mut self, payment: &Payment) -> std::io::Result<()>;
+    fn end(&mut self) -> std::io::Result<()>;
+}
+
+struct CsvWriter<W: Write>(W);
+
+impl<W: Write> PaymentWriter for CsvWriter<W> {
+    fn begin(&mut self) -> std::io::Result<()> {
+        writeln!(self.0, "{}", CSV_HEADER.join(","))
+    }
+
+    fn write_payment(&mut self, payment: &Payment) -> std::io::Result<()> {
+        let mut fields = vec![
+            payment.id.clone(),
+            payment.payment_type.to_string(),
+            format!("{:?}", payment.status),
+            format_rfc3339(payment.payment_time),
+            payment.amount_msat.to_string(),
+            payment.fee_msat.to_string(),
+            channel_open_fee_msat(payment).to_string(),
+            payment.description.clone().unwrap_or_default(),
+        ];
+        match &payment.details {
+            PaymentDetails::Ln { data } => {
+                let swap = data.swap_info.as_ref();
+                let reverse_swap = data.reverse_swap_info.as_ref();
+                fields.extend([
+                    data.label.clone(),
+                    data.payment_hash.clone(),
+                    data.bolt11.clone(),
+                    data.destination_pubkey.clone(),
+                    data.ln_address.clone().unwrap_or_default(),
+                    swap.map(|s| s.bitcoin_address.clone()).unwrap_or_default(),
+                    swap.map(|s| {
+                        [s.confirmed_tx_ids.clone(), s.unconfirmed_tx_ids.clone()]
+                            .concat()
+                            .join(" ")
+                    })
+                    .unwrap_or_default(),
+                    swap.map(|s| s.refund_tx_ids.join(" ")).unwrap_or_default(),
+                    reverse_swap
+                        .and_then(|s| s.lockup_txid.clone())
+                        .unwrap_or_default(),
+                    reverse_swap
+                        .and_then(|s| s.claim_txid.clone())
+                        .unwrap_or_default(),
+                    String::new(),
+                    String::new(),
+                ]);
+            }
+            PaymentDetails::ClosedChannel { data } => {
+                fields.extend(vec![String::new(); 10]);
+                fields.extend([
+                    data.funding_txid.clone(),
+                    data.closing_txid.clone().unwrap_or_default(),
+                ]);
+            }
+        }
+        let row: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+        writeln!(self.0, "{}", row.join(","))
+    }
+
+    fn end(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+struct OfxWriter<W: Write> {
+    out: W,
+    node_id: String,
+    from_timestamp: i64,
+    to_timestamp: i64,
+}
+
+impl<W: Write> PaymentWriter for OfxWriter<W> {
+    fn begin(&mut self) -> std::io::Result<()> {
+        let now = format_ofx_date(Utc::now().timestamp());
+        write!(
+            self.out,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+             <?OFX OFXHEADER=\"200\" VERSION=\"220\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>\n\
+             <OFX>\n\
+             <SIGNONMSGSRSV1><SONRS><STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\
+             <DTSERVER>{now}</DTSERVER><LANGUAGE>ENG</LANGUAGE></SONRS></SIGNONMSGSRSV1>\n\
+             <BANKMSGSRSV1><STMTTRNRS><TRNUID>0</TRNUID>\
+             <STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\n\
+             <STMTRS><CURDEF>XBT</CURDEF>\n\
+             <BANKACCTFROM><BANKID>BREEZ</BANKID><ACCTID>{}</ACCTID><ACCTTYPE>CHECKING</ACCTTYPE></BANKACCTFROM>\n\
+             <BANKTRANLIST><DTSTART>{}</DTSTART><DTEND>{}</DTEND>\n",
+            xml_escape(&self.node_id),
+            format_ofx_date(self.from_timestamp),
+            format_ofx_date(self.to_timestamp),
+        )
+    }
+
+    fn write_payment(&mut self, payment: &Payment) -> std::io::Result<()> {
+        // Fees are paid on top of sent amounts, and are already deducted from received amounts.
+        // The balance of a closed channel is swept back to the on-chain wallet.
+        let (trn_type, amount_msat) = match payment.payment_type {
+            PaymentType::Received | PaymentType::ClosedChannel => {
+                ("CREDIT", payment.amount_msat as i128)
+            }
+            PaymentType::Sent => ("DEBIT", -((payment.amount_msat + payment.fee_msat) as i128)),
+        };
+        let memo = match payment.fee_msat {
+            0 => payment.description.clone().unwrap_or_default(),
+            fee_msat => format!(
+                "{} (fee: {fee_msat} msat)",
+                payment.description.clone().unwrap_or_default()
+            )
+            .trim_start()
+            .to_string(),
+        };
+        writeln!(
+            self.out,
+            "<STMTTRN><TRNTYPE>{trn_type}</TRNTYPE><DTPOSTED>{}</DTPOSTED>\
+             <TRNAMT>{}</TRNAMT><FITID>{}</FITID><NAME>{}</NAME><MEMO>{}</MEMO></STMTTRN>",
+            format_ofx_date(payment.payment_time),
+            format_btc_amount(amount_msat),
+            xml_escape(&payment.id),
+            xml_escape(&payment.payment_type.to_string()),
+            xml_escape(&memo),
+        )
+    }
+
+    fn end(&mut self) -> std::io::Result<()> {
+        writeln!(
+            self.out,
+            "</BANKTRANLIST>\n</STMTRS>\n</STMTTRNRS>\n</BANKMSGSRSV1>\n</OFX>"
+        )?;
+        self.out.flush()
+    }
+}
+
+struct Bip329Writer<W: Write>(W);
+
+impl<W: Write> Bip329Writer<W> {
+    fn write_label(
+        &mut self,
+        label_type: &str,
+        reference: &str,
+        label: &str,
+    ) -> std::io::Result<()> {
+        writeln!(
+            self.0,
+            "{}",
+            json!({ "type": label_type, "ref": reference, "label": label })
+        )
+    }
+}
+
+impl<W: Write> PaymentWriter for Bip329Writer<W> {
+    fn begin(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn write_payment(&mut self, payment: &Payment) -> std::io::Result<()> {
+        match &payment.details {
+            PaymentDetails::Ln { data } => {
+                if let Some(swap) = &data.swap_info {
+                    let address = &swap.bitcoin_address;
+                    self.write_label("addr", address, "Swap in address")?;
+                    for txid in swap.confirmed_tx_ids.iter().chain(&swap.unconfirmed_tx_ids) {
+                        self.write_label("tx", txid, &format!("Swap in to {address}"))?;
+                    }
+                    for txid in &swap.refund_tx_ids {
+                        self.write_label("tx", txid, &format!("Swap in refund from {address}"))?;
+                    }
+                }
+                if let Some(reverse_swap) = &data.reverse_swap_info {
+                    if let Some(txid) = &reverse_swap.lockup_txid {
+                        let label = format!("Swap out {} lockup", reverse_swap.id);
+                        self.write_label("tx", txid, &label)?;
+                    }
+                    if let Some(txid) = &reverse_swap.claim_txid {
+                        let label = format!("Swap out {} claim", reverse_swap.id);
+                        self.write_label("tx", txid, &label)?;
+                    }
+                }
+            }
+            PaymentDetails::ClosedChannel { data } => {
+                self.write_label("tx", &data.funding_txid, "Channel funding")?;
+                if let Some(txid) = &data.closing_txid {
+                    self.write_label("tx", txid, "Channel close")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// The fee paid to the LSP for opening a channel to receive this payment
+fn channel_open_fee_msat(payment: &Payment) -> u64 {
+    match &payment.details {
+        PaymentDetails::Ln { data }
+            if payment.payment_type == PaymentType::Received
+                && data.open_channel_bolt11.is_some() =>
+        {
+            payment.fee_msat
+        }
+        _ => 0,
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn format_rfc3339(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn format_ofx_date(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|d| d.format("%Y%m%d%H%M%S").to_string())
+        .unwrap_or_default()
+}
+
+/// Formats millisatoshis as BTC, without losing precision
+fn format_btc_amount(amount_msat: i128) -> String {
+    let sign = if amount_msat < 0 { "-" } else { "" };
+    let amount_msat = amount_msat.unsigned_abs();
+    format!(
+        "{sign}{}.{:011}",
+        amount_msat / 100_000_000_000,
+        amount_msat % 100_000_000_000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::*;
+    use crate::models::{
+        ClosedChannelPaymentDetails, LnPaymentDetails, ReverseSwapInfo, ReverseSwapStatus, SwapInfo,
+    };
+    use crate::{ChannelState, PaymentStatus};
+
+    fn test_payments() -> Vec<Payment> {
+        vec![
+            Payment {
+                id: "hash1".to_string(),
+                payment_type: PaymentType::Received,
+                payment_time: 1_700_000_000,
+                amount_msat: 100_000,
+                fee_msat: 2_000,
+                status: PaymentStatus::Complete,
+                description: Some("Coffee, \"large\"".to_string()),
+                details: PaymentDetails::Ln {
+                    data: LnPaymentDetails {
+                        payment_hash: "hash1".to_string(),
+                        open_channel_bolt11: Some("lnbc1...".to_string()),
+                        swap_info: Some(SwapInfo {
+                            bitcoin_address: "bc1qswap".to_string(),
+                            confirmed_tx_ids: vec!["txid1".to_string()],
+                            refund_tx_ids: vec!["txid2".to_string()],
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            },
+            Payment {
+                id: "hash2".to_string(),
+                payment_type: PaymentType::Sent,
+                payment_time: 1_700_000_100,
+                amount_msat: 50_000,
+                fee_msat: 1_000,
+                status: PaymentStatus::Complete,
+                description: None,
+                details: PaymentDetails::Ln {
+                    data: LnPaymentDetails {
+                        payment_hash: "hash2".to_string(),
+                        reverse_swap_info: Some(ReverseSwapInfo {
+                            id: "rs1".to_string(),
+                            claim_pubkey: String::new(),
+                            lockup_txid: Some("txid3".to_string()),
+                            claim_txid: Some("txid4".to_string()),
+                            onchain_amount_sat: 40,
+                            status: ReverseSwapStatus::CompletedConfirmed,
+                        }),
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            },
+            Payment {
+                id: "txid5".to_string(),
+                payment_type: PaymentType::ClosedChannel,
+                payment_time: 1_700_000_200,
+                amount_msat: 10_000,
+                status: PaymentStatus::Complete,
+                details: PaymentDetails::ClosedChannel {
+                    data: ClosedChannelPaymentDetails {
+                        state: ChannelState::Closed,
+                        funding_txid: "txid5".to_string(),
+                        short_channel_id: None,
+                        closing_txid: Some("txid6".to_string()),
+                    },
+                },
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn write_all<P: PaymentWriter>(mut writer: P) -> P {
+        writer.begin().unwrap();
+        for payment in test_payments() {
+            writer.write_payment(&payment).unwrap();
+        }
+        writer.end().unwrap();
+        writer
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let csv = String::from_utf8(write_all(CsvWriter(Vec::new())).0).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "hash1,Received,Complete,2023-11-14T22:13:20Z,100000,2000,2000,\"Coffee, \"\"large\"\"\",,\
+             hash1,,,,bc1qswap,txid1,txid2,,,,"
+        );
+        assert!(lines[2].ends_with(",,,,,txid3,txid4,,"));
+        assert!(lines[3].ends_with(",txid5,txid6"));
+        assert!(lines
+            .iter()
+            .all(|l| l.split(',').count() >= CSV_HEADER.len()));
+    }
+
+    #[test]
+    fn test_export_ofx() {
+        let ofx = String::from_utf8(
+            write_all(OfxWriter {
+                out: Vec::new(),
+                node_id: "node".to_string(),
+                from_timestamp: 0,
+                to_timestamp: 1_700_000_200,
+            })
+            .out,
+        )
+        .unwrap();
+        assert!(ofx.contains("<ACCTID>node</ACCTID>"));
+        assert!(ofx.contains("<DTEND>20231114221640</DTEND>"));
+        assert!(ofx.contains(
+            "<TRNTYPE>CREDIT</TRNTYPE><DTPOSTED>20231114221320</DTPOSTED><TRNAMT>0.00000100000</TRNAMT>"
+        ));
+        assert!(ofx.contains("<TRNTYPE>DEBIT</TRNTYPE><DTPOSTED>20231114221500</DTPOSTED><TRNAMT>-0.00000051000</TRNAMT>"));
+        assert!(ofx.contains("<MEMO>(fee: 1000 msat)</MEMO>"));
+        assert!(ofx.contains(
+            "<TRNTYPE>CREDIT</TRNTYPE><DTPOSTED>20231114221640</DTPOSTED><TRNAMT>0.00000010000</TRNAMT>"
+        ));
+        assert!(ofx.trim_end().ends_with("</OFX>"));
+    }
+
+    #[test]
+    fn test_export_bip329() {
+        let labels = String::from_utf8(write_all(Bip329Writer(Vec::new())).0).unwrap();
+        let labels: Vec<serde_json::Value> = labels
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let refs: Vec<&str> = labels.iter().map(|l| l["ref"].as_str().unwrap()).collect();
+        assert_eq!(
+            refs,
+            vec!["bc1qswap", "txid1", "txid2", "txid3", "txid4", "txid5", "txid6"]
+        );
+        assert_eq!(labels[0]["type"], "addr");
+        assert_eq!(labels[1]["type"], "tx");
+        assert_eq!(labels[3]["label"], "Swap out rs1 lockup");
+    }
+
+    #[test]
+    fn test_format_btc_amount() {
+        assert_eq!(format_btc_amount(0), "0.00000000000");
+        assert_eq!(format_btc_amount(123_456_789_012), "1.23456789012");
+        assert_eq!(format_btc_amount(-1_000), "-0.00000001000");
+    }
+}
@@ -10,7 +10,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use anyhow::{anyhow, Result};
 use ecies::symmetric::{sym_decrypt, sym_encrypt};
 use futures::{Future, Stream};
-use gl_client::credentials::{Device, Nobody};
+use gl_client::credentials::Device;
 use gl_client::node;
 use gl_client::node::ClnClient;
 use gl_client::pb::cln::delinvoice_request::DelinvoiceStatus;
@@ -26,8 +26,7 @@ use gl_client::pb::cln::{
 };
 use gl_client::pb::{incoming_payment, TrampolinePayRequest};
 use gl_client::scheduler::Scheduler;
-use gl_client::signer::model::greenlight::{amount, scheduler};
-use gl_client::signer::Signer;
+use gl_client::signer::model::greenlight::amount;
 use sdk_common::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
@@ -40,21 +39,22 @@ use tokio_stream::StreamExt;
 use crate::bitcoin::bech32::{u5, ToBase32};
 use crate::bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
 use crate::bitcoin::blockdata::constants::WITNESS_SCALE_FACTOR;
-use crate::bitcoin::hashes::Hash;
+use crate::bitcoin::hashes::{sha256, Hash};
 use crate::bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
 use crate::bitcoin::secp256k1::PublicKey;
-use crate::bitcoin::secp256k1::Secp256k1;
 use crate::bitcoin::{
     Address, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
 };
 use crate::lightning::util::message_signing::verify;
 use crate::lightning_invoice::{RawBolt11Invoice, SignedRawBolt11Invoice};
 use crate::node_api::{
-    CreateInvoiceRequest, FetchBolt11Result, IncomingPayment, NodeAPI, NodeError, NodeResult,
+    CreateInvoiceRequest, FetchBolt11Result, HeldHtlcs, HoldInvoiceRequest, IncomingPayment,
+    IssuedDeviceCredentials, NodeAPI, NodeError, NodeLockStatus, NodeResult,
 };
 use crate::persist::cache::NodeStateStorage;
 use crate::persist::db::SqliteStorage;
 use crate::persist::send_pays::{SendPay, SendPayStatus};
+use crate::signer::{SignerAPI, WatchOnlySigner};
 use crate::{models::*, LspInformation};
 use crate::{NodeConfig, PrepareRedeemOnchainFundsRequest, PrepareRedeemOnchainFundsResponse};
 
@@ -66,10 +66,12 @@ const TRAMPOLINE_FEE_PPM: u64 = 5000;
 const PAYMENT_STATE_PENDING: u8 = 1;
 const PAYMENT_STATE_COMPLETE: u8 = 2;
 const PAYMENT_STATE_FAILED: u8 = 4;
+/// Holding HTLCs needs an `htlc_accepted` hook, which Greenlight nodes don't let clients register
+const HOLD_INVOICES_UNSUPPORTED: &str = "Hold invoices are not supported by Greenlight nodes";
 
 pub(crate) struct Greenlight {
     sdk_config: Config,
-    signer: Mutex<Arc<Signer>>,
+    signer: Arc<dyn SignerAPI>,
     device: Device,
     gl_client: Mutex<Option<node::Client>>,
     node_client: Mutex<Option<ClnClient>>,
@@ -98,52 +100,53 @@ impl Greenlight {
     /// or invite code
     /// If the node is already registered and an existing credentials were found, it will try to
     /// connect to the node using these credentials.
+    /// All the operations that need the seed go through the `signer`.
     pub async fn connect(
         config: Config,
-        seed: Vec<u8>,
+        signer: Arc<dyn SignerAPI>,
         restore_only: Option<bool>,
         persister: Arc<SqliteStorage>,
     ) -> NodeResult<Self> {
         // Derive the encryption key from the seed
-        let temp_signer = Arc::new(Signer::new(
-            seed.clone(),
-            config.network.into(),
-            Nobody::new(),
-        )?);
-        let encryption_key = Self::derive_bip32_key(
-            config.network,
-            &temp_signer,
-            vec![ChildNumber::from_hardened_idx(140)?, ChildNumber::from(0)],
-        )?
-        .to_priv()
-        .to_bytes();
+        let encryption_key = signer
+            .derive_bip32_key(vec![
+                ChildNumber::from_hardened_idx(140)?,
+                ChildNumber::from(0),
+            ])
+            .await?
+            .to_priv()
+            .to_bytes();
         let encryption_key_slice = encryption_key.as_slice();
 
         let register_credentials = match config.node_config.clone() {
             NodeConfig::Greenlight { config } => config,
+            NodeConfig::Ldk { .. } => {
+                return Err(NodeError::generic(
+                    "Greenlight requires a Greenlight node config",
+                ))
+            }
         };
 
         // Query for the existing credentials
-        let mut parsed_credentials =
-            Self::get_node_credentials(config.network, &temp_signer, persister.clone())?
-                .ok_or(NodeError::credentials("No credentials found"));
+        let mut parsed_credentials = Self::get_node_credentials(signer.as_ref(), persister.clone())
+            .await?
+            .ok_or(NodeError::credentials("No credentials found"));
         if parsed_credentials.is_err() {
             info!("No credentials found, trying to recover existing node");
-            parsed_credentials = match Self::recover(config.network, seed.clone()).await {
-                Ok(creds) => Ok(creds),
+            parsed_credentials = match signer.recover(None).await {
+                Ok(creds) => Ok(Device::from_bytes(creds)),
                 Err(_) => {
                     match restore_only.unwrap_or(false) {
                         false => {
                             // If we got here it means we failed to recover so we need to register a new node
                             info!("Failed to recover node, registering new one");
-                            let credentials = Self::register(
-                                config.clone().network,
-                                seed.clone(),
-                                register_credentials.partner_credentials,
-                                register_credentials.invite_code,
-                            )
-                            .await?;
-                            Ok(credentials)
+                            let credentials = signer
+                                .register(
+                                    register_credentials.partner_credentials,
+                                    register_credentials.invite_code,
+                                )
+                                .await?;
+                            Ok(Device::from_bytes(credentials))
                         }
                         true => {
                             return Err(NodeError::RestoreOnly("Node does not exist".to_string()));
@@ -156,15 +159,14 @@ impl Greenlight {
         // Persist the connection credentials for future use and return the node instance
         match parsed_credentials {
             Ok(creds) => {
-                let temp_scheduler = Scheduler::new(config.network.into(), creds.clone()).await?;
                 debug!("upgrading credentials");
-                let creds = creds.upgrade(&temp_scheduler, &temp_signer).await?;
+                let creds = Device::from_bytes(signer.upgrade(creds.to_bytes()).await?);
                 debug!("upgrading credentials succeeded");
                 let encrypted_creds = sym_encrypt(encryption_key_slice, &creds.to_bytes());
                 match encrypted_creds {
                     Some(c) => {
                         persister.update_cached_item(KEY_GL_CREDENTIALS, hex::encode(c))?;
-                        Greenlight::new(config, seed, creds.clone(), persister)
+                        Ok(Greenlight::new(config, signer, creds.clone(), persister))
                     }
                     None => Err(NodeError::generic("Failed to encrypt credentials")),
                 }
@@ -173,90 +175,70 @@ impl Greenlight {
         }
     }
 
+    /// Connects to an existing node with the device `credentials` alone, for a watch-only
+    /// instance. The credentials aren't persisted, and nothing can be signed.
+    ///
+    /// The credentials must be issued by [NodeAPI::issue_watch_only_credentials], whose rune only
+    /// lets them read the node.
+    pub async fn connect_watch_only(
+        config: Config,
+        credentials: Vec<u8>,
+        persister: Arc<SqliteStorage>,
+    ) -> NodeResult<Self> {
+        let device = Device::from_bytes(credentials);
+        if device.cert.is_empty() {
+            return Err(NodeError::credentials("Unable to parse credentials"));
+        }
+        if !is_read_only_rune(&device.rune) {
+            return Err(NodeError::credentials(
+                "The credentials aren't restricted to reading the node",
+            ));
+        }
+
+        // Without a seed the node id can only be learnt from the node itself
+        let scheduler = Scheduler::new(config.network.into(), device.clone())
+            .await
+            .map_err(|e| NodeError::ServiceConnectivity(e.to_string()))?;
+        let mut client: node::ClnClient = scheduler.node().await?;
+        let node_id = client
+            .getinfo(cln::GetinfoRequest::default())
+            .await?
+            .into_inner()
+            .id;
+
+        let greenlight = Greenlight::new(
+            config,
+            Arc::new(WatchOnlySigner::new(node_id)),
+            device,
+            persister,
+        );
+        *greenlight.node_client.lock().await = Some(client);
+        Ok(greenlight)
+    }
+
     fn new(
         sdk_config: Config,
-        seed: Vec<u8>,
+        signer: Arc<dyn SignerAPI>,
         device: Device,
         persister: Arc<SqliteStorage>,
-    ) -> NodeResult<Greenlight> {
-        let greenlight_network = sdk_config.network.into();
-        let signer = Signer::new(seed.clone(), greenlight_network, device.clone())?;
-        Ok(Greenlight {
+    ) -> Greenlight {
+        Greenlight {
             sdk_config,
-            signer: Mutex::new(Arc::new(signer)),
+            signer,
             device,
             gl_client: Mutex::new(None),
             node_client: Mutex::new(None),
             persister,
             inprogress_payments: AtomicU16::new(0),
-        })
-    }
-
-    async fn get_signer(&self) -> Arc<Signer> {
-        Arc::clone(&*self.signer.lock().await)
-    }
-
-    fn derive_bip32_key(
-        network: Network,
-        signer: &Arc<Signer>,
-        path: Vec<ChildNumber>,
-    ) -> NodeResult<ExtendedPrivKey> {
-        Ok(
-            ExtendedPrivKey::new_master(network.into(), &signer.bip32_ext_key())?
-                .derive_priv(&Secp256k1::new(), &path)?,
-        )
-    }
-
-    fn legacy_derive_bip32_key(
-        network: Network,
-        signer: &Arc<Signer>,
-        path: Vec<ChildNumber>,
-    ) -> NodeResult<ExtendedPrivKey> {
-        Ok(
-            ExtendedPrivKey::new_master(network.into(), &signer.legacy_bip32_ext_key())?
-                .derive_priv(&Secp256k1::new(), &path)?,
-        )
-    }
-
-    async fn register(
-        network: Network,
-        seed: Vec<u8>,
-        register_credentials: Option<GreenlightCredentials>,
-        invite_code: Option<String>,
-    ) -> Result<Device> {
-        if invite_code.is_some() && register_credentials.is_some() {
-            return Err(anyhow!("Cannot specify both invite code and credentials"));
         }
-        let greenlight_network = network.into();
-        let creds = match register_credentials {
-            Some(creds) => {
-                debug!("registering with credentials");
-                Nobody {
-                    cert: creds.developer_cert,
-                    key: creds.developer_key,
-                    ..Default::default()
-                }
-            }
-            None => Nobody::new(),
-        };
-
-        let signer = Signer::new(seed, greenlight_network, creds.clone())?;
-        let scheduler = Scheduler::new(greenlight_network, creds).await?;
-
-        let register_res: scheduler::RegistrationResponse =
-            scheduler.register(&signer, invite_code).await?;
-
-        Ok(Device::from_bytes(register_res.creds))
     }
 
-    async fn recover(network: Network, seed: Vec<u8>) -> Result<Device> {
-        let greenlight_network = network.into();
-        let credentials = Nobody::new();
-        let signer = Signer::new(seed, greenlight_network, credentials.clone())?;
-        let scheduler = Scheduler::new(greenlight_network, credentials).await?;
-        let recover_res: scheduler::RecoveryResponse = scheduler.recover(&signer).await?;
-
-        Ok(Device::from_bytes(recover_res.creds))
+    /// Registers a new device with the scheduler, with a certificate of its own
+    async fn issue_device(&self) -> NodeResult<Device> {
+        // Recovering the node makes the scheduler issue a new certificate, the ones of the other
+        // devices stay valid
+        let device = self.signer.recover(Some(self.device.to_bytes())).await?;
+        Ok(Device::from_bytes(self.signer.upgrade(device).await?))
     }
 
     async fn get_client(&self) -> NodeResult<node::Client> {
@@ -281,28 +263,29 @@ impl Greenlight {
         Ok(node_client.clone().unwrap())
     }
 
-    fn get_node_credentials(
-        network: Network,
-        signer: &Arc<Signer>,
+    async fn get_node_credentials(
+        signer: &dyn SignerAPI,
         persister: Arc<SqliteStorage>,
     ) -> NodeResult<Option<Device>> {
         // Derive the encryption key from the seed
-        let encryption_key = Self::derive_bip32_key(
-            network,
-            signer,
-            vec![ChildNumber::from_hardened_idx(140)?, ChildNumber::from(0)],
-        )?
-        .to_priv()
-        .to_bytes();
+        let encryption_key = signer
+            .derive_bip32_key(vec![
+                ChildNumber::from_hardened_idx(140)?,
+                ChildNumber::from(0),
+            ])
+            .await?
+            .to_priv()
+            .to_bytes();
         let encryption_key_slice = encryption_key.as_slice();
 
-        let legacy_encryption_key = Self::legacy_derive_bip32_key(
-            network,
-            signer,
-            vec![ChildNumber::from_hardened_idx(140)?, ChildNumber::from(0)],
-        )?
-        .to_priv()
-        .to_bytes();
+        let legacy_encryption_key = signer
+            .legacy_derive_bip32_key(vec![
+                ChildNumber::from_hardened_idx(140)?,
+                ChildNumber::from(0),
+            ])
+            .await?
+            .to_priv()
+            .to_bytes();
         let legacy_encryption_key_slice = legacy_encryption_key.as_slice();
 
         match persister.get_cached_item(KEY_GL_CREDENTIALS)? {
@@ -1011,6 +994,35 @@ struct SyncIndex {
     pub updated: u64,
 }
 
+/// Identifies a device by the hash of its private key. Unlike its certificate, which changes when
+/// renewed, the key is generated once per device.
+fn device_id(device: &Device) -> String {
+    let preimage = [b"breez-sdk/device-id/".as_slice(), &device.key].concat();
+    sha256::Hash::hash(&preimage).to_string()
+}
+
+/// The rune restriction of the watch-only credentials, which only allows the methods listing or
+/// getting data from the node
+const READ_ONLY_RUNE_RESTRICTION: [&str; 2] = ["method^list", "method^get"];
+
+/// Whether the `rune` is limited to the methods of [READ_ONLY_RUNE_RESTRICTION]. All the
+/// restrictions of a rune must hold, so a single one made of those alternatives is enough.
+fn is_read_only_rune(rune: &str) -> bool {
+    let Ok(decoded) = base64::decode_config(rune.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+    else {
+        return false;
+    };
+    // The restrictions follow the 32 bytes authentication code
+    let Some(restrictions) = decoded.get(32..).and_then(|r| std::str::from_utf8(r).ok()) else {
+        return false;
+    };
+    restrictions.split('&').any(|restriction| {
+        restriction
+            .split('|')
+            .all(|alternative| READ_ONLY_RUNE_RESTRICTION.contains(&alternative))
+    })
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct SyncState {
     pub send_pays_index: SyncIndex,
@@ -1020,16 +1032,15 @@ struct SyncState {
 #[tonic::async_trait]
 impl NodeAPI for Greenlight {
     async fn node_credentials(&self) -> NodeResult<Option<NodeCredentials>> {
-        Ok(Self::get_node_credentials(
-            self.sdk_config.network,
-            &self.get_signer().await,
-            self.persister.clone(),
-        )?
-        .map(|credentials| NodeCredentials::Greenlight {
-            credentials: GreenlightDeviceCredentials {
-                device: credentials.to_bytes(),
-            },
-        }))
+        Ok(
+            Self::get_node_credentials(self.signer.as_ref(), self.persister.clone())
+                .await?
+                .map(|credentials| NodeCredentials::Greenlight {
+                    credentials: GreenlightDeviceCredentials {
+                        device: credentials.to_bytes(),
+                    },
+                }),
+        )
     }
 
     async fn configure_node(&self, close_to_address: Option<String>) -> NodeResult<()> {
@@ -1379,6 +1390,7 @@ impl NodeAPI for Greenlight {
         bolt11: String,
         amount_msat: Option<u64>,
         label: Option<String>,
+        max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment> {
         let mut description = None;
         if !bolt11.is_empty() {
@@ -1391,16 +1403,19 @@ impl NodeAPI for Greenlight {
         let request = cln::PayRequest {
             bolt11,
             amount_msat: amount_msat.map(|amt| cln::Amount { msat: amt }),
-            maxfeepercent: Some(self.sdk_config.maxfee_percent),
+            // CLN doesn't accept a fee percentage or exemption alongside an absolute limit
+            maxfeepercent: max_fee_msat
+                .is_none()
+                .then_some(self.sdk_config.maxfee_percent),
             retry_for: Some(self.sdk_config.payment_timeout_sec),
             label,
             maxdelay: None,
             riskfactor: None,
             localinvreqid: None,
             exclude: vec![],
-            maxfee: None,
+            maxfee: max_fee_msat.map(|msat| cln::Amount { msat }),
             description,
-            exemptfee: Some(cln::Amount {
+            exemptfee: max_fee_msat.is_none().then_some(cln::Amount {
                 msat: self.sdk_config.exemptfee_msat,
             }),
             partial_msat: None,
@@ -1469,6 +1484,7 @@ impl NodeAPI for Greenlight {
         amount_msat: u64,
         extra_tlvs: Option<Vec<TlvEntry>>,
         label: Option<String>,
+        max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment> {
         let mut client: node::ClnClient = self.get_node_client().await?;
         let request = cln::KeysendRequest {
@@ -1488,11 +1504,13 @@ impl NodeAPI for Greenlight {
                     .collect(),
             }),
             routehints: None,
-            maxfeepercent: Some(self.sdk_config.maxfee_percent),
+            maxfeepercent: max_fee_msat
+                .is_none()
+                .then_some(self.sdk_config.maxfee_percent),
             exemptfee: None,
             retry_for: Some(self.sdk_config.payment_timeout_sec),
             maxdelay: None,
-            maxfee: None,
+            maxfee: max_fee_msat.map(|msat| cln::Amount { msat }),
         };
 
         // Not wrapped with connection retry, in case it causes to send twice.
@@ -1508,7 +1526,7 @@ impl NodeAPI for Greenlight {
     }
 
     async fn node_id(&self) -> NodeResult<String> {
-        Ok(hex::encode(self.get_signer().await.node_id()))
+        Ok(hex::encode(self.signer.node_id().await?))
     }
 
     async fn redeem_onchain_funds(
@@ -1591,7 +1609,7 @@ impl NodeAPI for Greenlight {
 
     /// Starts the signer that listens in a loop until the shutdown signal is received
     async fn start(&self, shutdown: mpsc::Receiver<()>) {
-        match self.get_signer().await.run_forever(shutdown).await {
+        match self.signer.run(self.device.to_bytes(), shutdown).await {
             Ok(_) => info!("signer exited gracefully"),
             Err(e) => error!("signer exited with error: {e}"),
         }
@@ -1647,9 +1665,9 @@ impl NodeAPI for Greenlight {
 
     async fn sign_message(&self, message: &str) -> NodeResult<String> {
         let (sig, recovery_id) = self
-            .get_signer()
-            .await
-            .sign_message(message.as_bytes().to_vec())?;
+            .signer
+            .sign_message(message.as_bytes().to_vec())
+            .await?;
         let mut complete_signature = vec![31 + recovery_id];
         complete_signature.extend_from_slice(&sig);
         Ok(zbase32::encode_full_bytes(&complete_signature))
@@ -1685,7 +1703,7 @@ impl NodeAPI for Greenlight {
         buf.append(&mut hrp_len_bytes);
         buf.append(&mut hrp_buf);
         // Sign the invoice using the signer
-        let raw_result = self.get_signer().await.sign_invoice(buf)?;
+        let raw_result = self.signer.sign_invoice(buf).await?;
         info!(
             "recover id: {:?} raw = {:?}",
             raw_result, raw_result[64] as i32
@@ -1788,6 +1806,67 @@ impl NodeAPI for Greenlight {
         })))
     }
 
+    async fn create_hold_invoice(&self, _request: HoldInvoiceRequest) -> NodeResult<String> {
+        Err(NodeError::generic(HOLD_INVOICES_UNSUPPORTED))
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: Vec<u8>) -> NodeResult<()> {
+        Err(NodeError::generic(HOLD_INVOICES_UNSUPPORTED))
+    }
+
+    async fn cancel_hold_invoice(&self, _payment_hash: Vec<u8>) -> NodeResult<()> {
+        Err(NodeError::generic(HOLD_INVOICES_UNSUPPORTED))
+    }
+
+    async fn stream_held_htlcs(&self) -> NodeResult<Pin<Box<dyn Stream<Item = HeldHtlcs> + Send>>> {
+        // No hold invoice can be created, so no HTLC is ever held
+        Ok(Box::pin(futures::stream::pending()))
+    }
+
+    async fn stream_node_lock_status(
+        &self,
+    ) -> NodeResult<Pin<Box<dyn Stream<Item = NodeLockStatus> + Send>>> {
+        // Greenlight runs the node itself, each device only connects to it
+        Ok(Box::pin(futures::stream::pending()))
+    }
+
+    async fn take_over_node_lock(&self) -> NodeResult<()> {
+        Err(NodeError::generic(
+            "Greenlight nodes are not locked to an instance",
+        ))
+    }
+
+    async fn device_id(&self) -> NodeResult<String> {
+        Ok(device_id(&self.device))
+    }
+
+    async fn issue_device_credentials(&self) -> NodeResult<IssuedDeviceCredentials> {
+        let device = self.issue_device().await?;
+        Ok(IssuedDeviceCredentials {
+            device_id: device_id(&device),
+            credentials: NodeCredentials::Greenlight {
+                credentials: GreenlightDeviceCredentials {
+                    device: device.to_bytes(),
+                },
+            },
+        })
+    }
+
+    async fn issue_watch_only_credentials(&self) -> NodeResult<IssuedDeviceCredentials> {
+        // The watch-only device gets its own certificate, only its rune is restricted
+        let mut device = self.issue_device().await?;
+        let restriction = READ_ONLY_RUNE_RESTRICTION.map(String::from).to_vec();
+        device.rune = self.signer.create_rune(vec![restriction]).await?;
+        Ok(IssuedDeviceCredentials {
+            device_id: device_id(&device),
+            credentials: NodeCredentials::Greenlight {
+                credentials: GreenlightDeviceCredentials {
+                    device: device.to_bytes(),
+                },
+            },
+        })
+    }
+
     async fn static_backup(&self) -> NodeResult<Vec<String>> {
         let mut client = self.get_node_client().await?;
         let req = cln::StaticbackupRequest {};
@@ -1929,11 +2008,11 @@ impl NodeAPI for Greenlight {
     }
 
     async fn derive_bip32_key(&self, path: Vec<ChildNumber>) -> NodeResult<ExtendedPrivKey> {
-        Self::derive_bip32_key(self.sdk_config.network, &self.get_signer().await, path)
+        Ok(self.signer.derive_bip32_key(path).await?)
     }
 
     async fn legacy_derive_bip32_key(&self, path: Vec<ChildNumber>) -> NodeResult<ExtendedPrivKey> {
-        Self::legacy_derive_bip32_key(self.sdk_config.network, &self.get_signer().await, path)
+        Ok(self.signer.legacy_derive_bip32_key(path).await?)
     }
 
     async fn stream_custom_messages(
@@ -2269,6 +2348,11 @@ impl TryFrom<SendPayAgg> for Payment {
                     lnurl_success_action: None,
                     lnurl_pay_domain: None,
                     lnurl_pay_comment: None,
+                    lnurl_verify_url: None,
+                    zap_request: None,
+                    zap_receipt: None,
+                    fiat_amount: None,
+                    contact_id: None,
                     ln_address: None,
                     lnurl_metadata: None,
                     lnurl_withdraw_endpoint: None,
@@ -2319,7 +2403,12 @@ impl TryFrom<cln::ListinvoicesInvoices> for Payment {
                     lnurl_success_action: None, // For received payments, this is None
                     lnurl_pay_domain: None,     // For received payments, this is None
                     lnurl_pay_comment: None,    // For received payments, this is None
+                    lnurl_verify_url: None,     // For received payments, this is None
+                    zap_request: None,          // For received payments, this is None
+                    zap_receipt: None,          // For received payments, this is None
+                    contact_id: None,           // For received payments, this is None
                     lnurl_metadata: None,       // For received payments, this is None
+                    fiat_amount: None,
                     ln_address: None,
                     lnurl_withdraw_endpoint: None,
                     swap_info: None,
@@ -2329,6 +2418,7 @@ impl TryFrom<cln::ListinvoicesInvoices> for Payment {
                 },
             },
             metadata: None,
+            fiat_rates: Vec::new(),
         })
     }
 }
@@ -2408,6 +2498,11 @@ impl TryFrom<cln::ListpaysPays> for Payment {
                     lnurl_success_action: None,
                     lnurl_pay_domain: None,
                     lnurl_pay_comment: None,
+                    lnurl_verify_url: None,
+                    zap_request: None,
+                    zap_receipt: None,
+                    fiat_amount: None,
+                    contact_id: None,
                     lnurl_metadata: None,
                     ln_address: None,
                     lnurl_withdraw_endpoint: None,
@@ -2418,6 +2513,7 @@ impl TryFrom<cln::ListpaysPays> for Payment {
                 },
             },
             metadata: None,
+            fiat_rates: Vec::new(),
         })
     }
 }
@@ -2580,7 +2676,7 @@ impl TryFrom<ListclosedchannelsClosedchannels> for Channel {
 
 #[cfg(test)]
 mod tests {
-    use crate::greenlight::node_api::convert_to_send_pay_route;
+    use crate::greenlight::node_api::{convert_to_send_pay_route, is_read_only_rune};
     use crate::{models, PaymentPath, PaymentPathEdge};
     use anyhow::Result;
     use gl_client::pb::cln::ChannelState::*;
@@ -2786,4 +2882,20 @@ mod tests {
             their_max_htlc_value_in_flight_msat: None,
         }
     }
+
+    #[test]
+    fn test_is_read_only_rune() {
+        let rune = |restrictions: &str| {
+            let data = [[7; 32].as_slice(), restrictions.as_bytes()].concat();
+            base64::encode_config(data, base64::URL_SAFE)
+        };
+        assert!(is_read_only_rune(&rune("method^list|method^get")));
+        assert!(is_read_only_rune(&rune("method^get|method^list")));
+        assert!(is_read_only_rune(&rune("=0&method^list|method^get&pnum<2")));
+        assert!(!is_read_only_rune(&rune("method^list|method^pay")));
+        assert!(!is_read_only_rune(&rune("=0&pnum<2")));
+        assert!(!is_read_only_rune(&rune("")));
+        assert!(!is_read_only_rune(""));
+        assert!(!is_read_only_rune("not base64!"));
+    }
 }
@@ -1,19 +1,107 @@
+use std::sync::Arc;
+
 use crate::backup::{BackupState, BackupTransport};
 use crate::error::{SdkError, SdkResult};
+use crate::ldk::store::versioned_store::VersionedStore;
+
+const BACKUP_KEY: &str = "breez-sdk/backup";
 
-pub(crate) struct LdkBackupTransport;
+/// Stores the SDK backup under a single key of the remote `VersionedStore`.
+///
+/// The backup generation is the version of the key. The store bumps it by one
+/// on every successful put, and rejects puts made with a stale version, which
+/// makes the `BackupWorker` pull and merge the remote state before retrying.
+pub(crate) struct LdkBackupTransport<S: VersionedStore + Send + Sync> {
+    store: Arc<S>,
+}
+
+impl<S: VersionedStore + Send + Sync> LdkBackupTransport<S> {
+    pub(crate) fn new(store: Arc<S>) -> Self {
+        Self { store }
+    }
+}
 
 #[tonic::async_trait]
-impl BackupTransport for LdkBackupTransport {
+impl<S: VersionedStore + Send + Sync> BackupTransport for LdkBackupTransport<S> {
     async fn pull(&self) -> SdkResult<Option<BackupState>> {
-        Err(SdkError::Generic {
-            err: "LDK BackupTransport not implemented".into(),
-        })
+        let state = self.store.get(BACKUP_KEY.to_string()).await?;
+        state
+            .map(|(data, version)| {
+                Ok(BackupState {
+                    generation: version_to_generation(version)?,
+                    data,
+                })
+            })
+            .transpose()
+    }
+
+    async fn push(&self, version: Option<u64>, data: Vec<u8>) -> SdkResult<u64> {
+        info!("Pushing backup, data length={}", data.len());
+        // Version 0 only matches a key that doesn't exist yet
+        let version = match version {
+            Some(generation) => i64::try_from(generation)
+                .map_err(|_| SdkError::generic("Invalid backup generation"))?,
+            None => 0,
+        };
+        self.store
+            .put(BACKUP_KEY.to_string(), data, version)
+            .await?;
+        version_to_generation(version + 1)
     }
+}
+
+fn version_to_generation(version: i64) -> SdkResult<u64> {
+    u64::try_from(version).map_err(|_| SdkError::generic("Invalid backup version"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::backup::{BackupState, BackupTransport};
+    use crate::ldk::store::mock_versioned_store::MockVersionedStore;
+
+    use super::LdkBackupTransport;
+
+    #[tokio::test]
+    async fn test_backup_transport() {
+        let store = Arc::new(MockVersionedStore::default());
+        let transport = LdkBackupTransport::new(Arc::clone(&store));
+        assert_eq!(transport.pull().await.unwrap(), None);
+
+        let generation = transport.push(None, vec![1]).await.unwrap();
+        assert_eq!(
+            transport.pull().await.unwrap(),
+            Some(BackupState {
+                generation,
+                data: vec![1]
+            })
+        );
+
+        // Another instance pushed in between, the stale push is rejected
+        let other = LdkBackupTransport::new(Arc::clone(&store));
+        let other_generation = other.push(Some(generation), vec![2]).await.unwrap();
+        assert!(other_generation > generation);
+        assert!(transport.push(Some(generation), vec![3]).await.is_err());
+        assert!(transport.push(None, vec![3]).await.is_err());
+
+        // Once the remote state is pulled, the push succeeds
+        let remote = transport.pull().await.unwrap().unwrap();
+        assert_eq!(remote.generation, other_generation);
+        assert_eq!(remote.data, vec![2]);
+        let generation = transport
+            .push(Some(remote.generation), vec![2, 3])
+            .await
+            .unwrap();
+        assert_eq!(
+            transport.pull().await.unwrap().unwrap().generation,
+            generation
+        );
 
-    async fn push(&self, _version: Option<u64>, _data: Vec<u8>) -> SdkResult<u64> {
-        Err(SdkError::Generic {
-            err: "LDK BackupTransport not implemented".into(),
-        })
+        let failing = LdkBackupTransport::new(Arc::new(MockVersionedStore {
+            should_fail_get: true,
+            ..Default::default()
+        }));
+        assert!(failing.pull().await.is_err());
     }
 }
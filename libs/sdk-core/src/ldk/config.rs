@@ -1,19 +1,131 @@
-pub(crate) struct Config {
-    pub esplora_url: &'static str,
-    pub rgs_url: &'static str,
-    pub vss_url: &'static str,
-    pub lsps2_id: &'static str,
-    pub lsps2_address: &'static str,
+use std::str::FromStr;
+
+use reqwest::Url;
+use sdk_common::ensure_sdk;
+use sdk_common::prelude::Network;
+
+use crate::bitcoin::secp256k1::PublicKey;
+use crate::models::{LdkChainSource, LdkGossipSource, LdkNodeConfig};
+use crate::node_api::{NodeError, NodeResult};
+
+/// Checks the endpoints of the config are well formed and usable on `network`.
+///
+/// On mainnet, remote endpoints must be reached over TLS.
+pub(crate) fn validate(config: &LdkNodeConfig, network: Network) -> NodeResult<()> {
+    match &config.chain_source {
+        LdkChainSource::Esplora { url } => validate_url("Esplora", url, network)?,
+        LdkChainSource::Electrum { url } => validate_url("Electrum", url, network)?,
+        LdkChainSource::BitcoindRpc { host, port, .. } => ensure_sdk!(
+            !host.is_empty() && *port != 0,
+            NodeError::generic("Invalid bitcoind RPC address")
+        ),
+    }
+    if let LdkGossipSource::Rgs { url } = &config.gossip_source {
+        validate_url("RGS", url, network)?;
+    }
+    validate_url("VSS", &config.vss_url, network)?;
+
+    PublicKey::from_str(&config.lsp.node_id)
+        .map_err(|e| NodeError::Generic(format!("Invalid LSP node id: {e}")))?;
+    let port = config
+        .lsp
+        .address
+        .rsplit_once(':')
+        .and_then(|(host, port)| (!host.is_empty()).then_some(port))
+        .and_then(|port| port.parse::<u16>().ok());
+    ensure_sdk!(
+        port.is_some(),
+        NodeError::generic("Invalid LSP address, expected host:port")
+    );
+    Ok(())
 }
 
-impl Config {
-    pub fn regtest() -> Self {
-        Self {
-            esplora_url: "http://localhost:30000",
-            rgs_url: "http://localhost:8011/v2",
-            vss_url: "http://localhost:3080/vss",
-            lsps2_id: "02b49b94e068e05c04c2ac98e096a06202d04920daec25d82f7898e21901f15d81",
-            lsps2_address: "localhost:9735",
+fn validate_url(name: &str, url: &str, network: Network) -> NodeResult<()> {
+    let parsed =
+        Url::parse(url).map_err(|e| NodeError::Generic(format!("Invalid {name} URL: {e}")))?;
+
+    // Public services serve the test networks under a path named after them,
+    // e.g. https://mempool.space/signet/api
+    let url_network =
+        parsed
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .find_map(|segment| match segment {
+                "testnet" | "testnet4" => Some(Network::Testnet),
+                "signet" => Some(Network::Signet),
+                "regtest" => Some(Network::Regtest),
+                _ => None,
+            });
+    if let Some(url_network) = url_network {
+        ensure_sdk!(
+            url_network == network,
+            NodeError::Generic(format!(
+                "The {name} URL is for {url_network}, but the node runs on {network}"
+            ))
+        );
+    }
+
+    let is_local = matches!(parsed.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    if network == Network::Bitcoin && !is_local {
+        ensure_sdk!(
+            matches!(parsed.scheme(), "https" | "ssl"),
+            NodeError::Generic(format!("The {name} URL must use TLS on mainnet"))
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sdk_common::prelude::Network;
+
+    use super::validate;
+    use crate::models::{LdkChainSource, LdkGossipSource, LdkNodeConfig};
+
+    fn mainnet() -> LdkNodeConfig {
+        LdkNodeConfig {
+            chain_source: LdkChainSource::Esplora {
+                url: "https://blockstream.info/api".to_string(),
+            },
+            gossip_source: LdkGossipSource::Rgs {
+                url: "https://rapidsync.lightningdevkit.org/snapshot".to_string(),
+            },
+            vss_url: "https://vss.example.com/vss".to_string(),
+            ..LdkNodeConfig::regtest()
         }
     }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&LdkNodeConfig::regtest(), Network::Regtest).is_ok());
+        assert!(validate(&mainnet(), Network::Bitcoin).is_ok());
+        // Local endpoints don't need TLS
+        assert!(validate(&LdkNodeConfig::regtest(), Network::Bitcoin).is_ok());
+
+        let plain_http = LdkNodeConfig {
+            vss_url: "http://vss.example.com/vss".to_string(),
+            ..mainnet()
+        };
+        assert!(validate(&plain_http, Network::Bitcoin).is_err());
+        assert!(validate(&plain_http, Network::Signet).is_ok());
+
+        let signet = LdkNodeConfig {
+            chain_source: LdkChainSource::Esplora {
+                url: "https://mempool.space/signet/api".to_string(),
+            },
+            gossip_source: LdkGossipSource::P2p,
+            ..mainnet()
+        };
+        assert!(validate(&signet, Network::Signet).is_ok());
+        assert!(validate(&signet, Network::Bitcoin).is_err());
+        assert!(validate(&signet, Network::Testnet).is_err());
+
+        let mut bad_lsp = mainnet();
+        bad_lsp.lsp.node_id = "02b4".to_string();
+        assert!(validate(&bad_lsp, Network::Bitcoin).is_err());
+        let mut bad_lsp = mainnet();
+        bad_lsp.lsp.address = "localhost".to_string();
+        assert!(validate(&bad_lsp, Network::Bitcoin).is_err());
+    }
 }
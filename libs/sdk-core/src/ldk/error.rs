@@ -1,5 +1,7 @@
 use sdk_common::prelude::InvoiceError;
 
+use crate::error::SdkError;
+use crate::ldk::store::versioned_store;
 use crate::node_api::NodeError;
 use crate::persist::error::PersistError;
 
@@ -85,3 +87,15 @@ impl From<ldk_node::NodeError> for NodeError {
         }
     }
 }
+
+impl From<versioned_store::Error> for SdkError {
+    fn from(err: versioned_store::Error) -> Self {
+        match err {
+            // A conflict makes the backup worker pull the remote state and retry
+            versioned_store::Error::Conflict(e) => SdkError::Generic {
+                err: format!("Remote store conflict: {e}"),
+            },
+            versioned_store::Error::Internal(e) => SdkError::ServiceConnectivity { err: e },
+        }
+    }
+}
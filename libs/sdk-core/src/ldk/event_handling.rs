@@ -1,16 +1,22 @@
 use std::sync::Arc;
 
+use ldk_node::lightning_types::payment::{PaymentHash, PaymentPreimage};
 use ldk_node::{Event, Node};
 use tokio::sync::{broadcast, mpsc};
 
-use crate::ldk::node_api::PreimageStore;
-use crate::node_api::IncomingPayment;
+use crate::ldk::node_api::HoldInvoiceStore;
+use crate::node_api::{HeldHtlcs, IncomingPayment};
+use crate::persist::db::SqliteStorage;
+use crate::persist::ldk::LdkInvoice;
 
 pub async fn start_event_handling(
     node: Arc<Node>,
-    preimages: PreimageStore,
+    persister: Arc<SqliteStorage>,
+    hold_invoices: HoldInvoiceStore,
     incoming_payments_tx: broadcast::Sender<IncomingPayment>,
-    mut shutdown: mpsc::Receiver<()>,
+    held_htlcs_tx: broadcast::Sender<HeldHtlcs>,
+    closed_channels_tx: broadcast::Sender<String>,
+    shutdown: &mut mpsc::Receiver<()>,
 ) {
     loop {
         let event = tokio::select! {
@@ -29,23 +35,24 @@ pub async fn start_event_handling(
                 amount_msat,
                 ..
             } => {
-                let preimage = preimages.lock().unwrap().remove(&payment_hash);
-                match preimage {
-                    Some(preimage) => {
-                        // TODO: Load bolt11 from the store.
-                        let bolt11 = String::new();
+                // The invoice is kept, the event is replayed if not marked as handled
+                match load_invoice(&persister, &payment_hash) {
+                    Some(LdkInvoice {
+                        preimage: Some(preimage),
+                        bolt11,
+                    }) => {
                         let payment = IncomingPayment {
                             label: String::new(),
                             payment_hash: payment_hash.0.to_vec(),
-                            preimage: preimage.0.to_vec(),
+                            preimage,
                             amount_msat,
-                            bolt11,
+                            bolt11: bolt11.unwrap_or_default(),
                         };
                         if let Err(e) = incoming_payments_tx.send(payment) {
                             warn!("Failed to send payment to incoming_payments_tx: {e}");
                         }
                     }
-                    None => {
+                    _ => {
                         error!("Payment received but preimage not found for payment with id={payment_id:?}");
                     }
                 }
@@ -56,10 +63,39 @@ pub async fn start_event_handling(
                 payment_id,
                 payment_hash,
                 claimable_amount_msat,
+                claim_deadline,
                 ..
             } => {
-                let preimage = preimages.lock().unwrap().get(&payment_hash).cloned();
+                let preimage = load_invoice(&persister, &payment_hash)
+                    .and_then(|invoice| invoice.preimage)
+                    .and_then(|preimage| preimage.try_into().ok())
+                    .map(PaymentPreimage);
+                let is_hold_invoice = hold_invoices
+                    .lock()
+                    .unwrap()
+                    .get_mut(&payment_hash)
+                    .map(|held| *held = Some(claimable_amount_msat))
+                    .is_some();
+                if is_hold_invoice {
+                    if let Err(e) = persister
+                        .set_ldk_hold_invoice_held_amount(&payment_hash.0, claimable_amount_msat)
+                    {
+                        error!(
+                            "Failed to persist held amount of payment with id={payment_id:?}: {e}"
+                        );
+                    }
+                }
                 match preimage {
+                    None if is_hold_invoice => {
+                        let htlcs = HeldHtlcs {
+                            payment_hash: payment_hash.0.to_vec(),
+                            amount_msat: claimable_amount_msat,
+                            claim_deadline,
+                        };
+                        if let Err(e) = held_htlcs_tx.send(htlcs) {
+                            warn!("Failed to send held HTLCs to held_htlcs_tx: {e}");
+                        }
+                    }
                     Some(preimage) => {
                         if let Err(e) = node.bolt11_payment().claim_for_hash(
                             payment_hash,
@@ -80,7 +116,10 @@ pub async fn start_event_handling(
             Event::PaymentForwarded { .. } => (),
             Event::ChannelPending { .. } => (),
             Event::ChannelReady { .. } => (),
-            Event::ChannelClosed { .. } => (),
+            Event::ChannelClosed { channel_id, .. } => {
+                // Only listened to while channels are being closed
+                let _ = closed_channels_tx.send(channel_id.to_string());
+            }
         }
 
         if let Err(e) = node.event_handled() {
@@ -88,3 +127,11 @@ pub async fn start_event_handling(
         }
     }
 }
+
+fn load_invoice(persister: &SqliteStorage, payment_hash: &PaymentHash) -> Option<LdkInvoice> {
+    persister
+        .get_ldk_invoice(&payment_hash.0)
+        .map_err(|e| error!("Failed to load invoice for payment_hash={payment_hash}: {e}"))
+        .ok()
+        .flatten()
+}
@@ -2,25 +2,27 @@ use core::str::FromStr;
 use std::collections::HashSet;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime};
+use std::time::Duration;
 
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use hex::ToHex;
 use ldk_node::bitcoin::hashes::sha256::Hash as Sha256;
 use ldk_node::bitcoin::hashes::Hash;
 use ldk_node::bitcoin::secp256k1::PublicKey;
+use ldk_node::bitcoin::{Address, FeeRate};
 use ldk_node::lightning::ln::msgs::SocketAddress;
 use ldk_node::lightning_invoice::{Bolt11InvoiceDescription, Description};
 use ldk_node::lightning_types::payment::{PaymentHash, PaymentPreimage};
+use ldk_node::payment::{ConfirmationStatus, PaymentDetails, PaymentDirection, PaymentKind};
 use ldk_node::{Builder, Node};
 use rand::Rng;
 use sdk_common::ensure_sdk;
 use sdk_common::invoice::parse_invoice;
-use sdk_common::prelude::Network;
-use serde_json::Value;
+use sdk_common::prelude::{BreezServer, Network};
+use serde_json::{json, Value};
 use tokio::sync::{broadcast, mpsc, watch};
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError::Lagged;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::{BroadcastStream, WatchStream};
 use tokio_stream::{Stream, StreamExt};
 
 use crate::bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
@@ -29,43 +31,67 @@ use crate::breez_services::{OpenChannelParams, Receiver};
 use crate::error::{ReceivePaymentError, SdkError, SdkResult};
 use crate::grpc;
 use crate::ldk::event_handling::start_event_handling;
-use crate::ldk::store_builder::{build_locking_store, build_vss_store};
+use crate::ldk::store_builder::{build_locking_store, build_vss_store, LockingStore};
 use crate::lightning_invoice::RawBolt11Invoice;
 use crate::models::{
-    LspAPI, OpeningFeeParams, OpeningFeeParamsMenu, ReceivePaymentRequest, ReceivePaymentResponse,
-    INVOICE_PAYMENT_FEE_EXPIRY_SECONDS,
+    LdkChainSource, LdkGossipSource, LdkLspConfig, LdkNodeConfig, LspAPI, OpeningFeeParams,
+    ReceivePaymentRequest, ReceivePaymentResponse, INVOICE_PAYMENT_FEE_EXPIRY_SECONDS,
 };
 use crate::node_api::{
-    CreateInvoiceRequest, FetchBolt11Result, IncomingPayment, NodeAPI, NodeError, NodeResult,
+    CreateInvoiceRequest, FetchBolt11Result, HeldHtlcs, HoldInvoiceRequest, IncomingPayment,
+    IssuedDeviceCredentials, NodeAPI, NodeError, NodeLockStatus, NodeResult,
 };
+use crate::persist::db::SqliteStorage;
 use crate::{
     CustomMessage, LspInformation, MaxChannelAmount, NodeCredentials, Payment, PaymentResponse,
     PrepareRedeemOnchainFundsRequest, PrepareRedeemOnchainFundsResponse, RouteHint, RouteHintHop,
     SyncResponse, TlvEntry,
 };
 
-pub(crate) type PreimageStore = Arc<Mutex<std::collections::HashMap<PaymentHash, PaymentPreimage>>>;
+/// How long closing channels are waited on, before their closing transactions are looked up
+const CHANNEL_CLOSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The LDK node runs in the app itself, so there are no device credentials to manage
+const DEVICES_UNSUPPORTED: &str = "Device management is only available on Greenlight nodes";
+
+/// Payment hashes of the open hold invoices, with the claimable amount once their HTLCs are held.
+///
+/// Mirrors the hold invoices persisted in the `ldk_invoices` table, from which it is rebuilt at
+/// startup.
+pub(crate) type HoldInvoiceStore = Arc<Mutex<std::collections::HashMap<PaymentHash, Option<u64>>>>;
 
 pub(crate) struct Ldk {
     network: Network,
     seed: [u8; 64],
     node: Arc<Node>,
     incoming_payments_tx: broadcast::Sender<IncomingPayment>,
-    preimages: PreimageStore,
+    held_htlcs_tx: broadcast::Sender<HeldHtlcs>,
+    /// Ids of the channels reported closed
+    closed_channels_tx: broadcast::Sender<String>,
+    persister: Arc<SqliteStorage>,
+    hold_invoices: HoldInvoiceStore,
+    lsp: LdkLspConfig,
+    /// Serves the fee menu of the LSP
+    breez_server: Arc<BreezServer>,
+    locking_store: Arc<LockingStore>,
     remote_lock_shutdown_tx: mpsc::Sender<()>,
+    lock_status: watch::Sender<NodeLockStatus>,
 }
 
 impl Ldk {
     pub async fn build(
         config: crate::models::Config,
+        ldk_config: LdkNodeConfig,
         seed: &[u8],
         _restore_only: Option<bool>,
+        persister: Arc<SqliteStorage>,
     ) -> NodeResult<Self> {
         debug!("Building LDK Node");
-        ensure_sdk!(
-            config.network == Network::Regtest,
-            NodeError::generic("Only Regtest mode is supported for now")
-        );
+        crate::ldk::config::validate(&ldk_config, config.network)?;
+        let breez_server = BreezServer::new(config.breezserver.clone(), config.api_key.clone())
+            .map_err(|e| {
+                NodeError::Generic(format!("Failed to create Breez server client: {e}"))
+            })?;
 
         let mut builder = Builder::new();
 
@@ -76,15 +102,41 @@ impl Ldk {
         builder.set_log_facade_logger();
         builder.set_network(to_ldk_network(&config.network));
 
-        let ldk_config = crate::ldk::config::Config::regtest();
-        builder.set_chain_source_esplora(ldk_config.esplora_url.to_string(), None);
-        builder.set_gossip_source_rgs(ldk_config.rgs_url.to_string());
+        match &ldk_config.chain_source {
+            LdkChainSource::Esplora { url } => {
+                builder.set_chain_source_esplora(url.clone(), None);
+            }
+            LdkChainSource::Electrum { url } => {
+                builder.set_chain_source_electrum(url.clone(), None);
+            }
+            LdkChainSource::BitcoindRpc {
+                host,
+                port,
+                user,
+                password,
+            } => {
+                builder.set_chain_source_bitcoind_rpc(
+                    host.clone(),
+                    *port,
+                    user.clone(),
+                    password.clone(),
+                );
+            }
+        }
+        match &ldk_config.gossip_source {
+            LdkGossipSource::P2p => {
+                builder.set_gossip_source_p2p();
+            }
+            LdkGossipSource::Rgs { url } => {
+                builder.set_gossip_source_rgs(url.clone());
+            }
+        }
 
-        let lsps2 = PublicKey::from_str(ldk_config.lsps2_id)
+        let lsps2 = PublicKey::from_str(&ldk_config.lsp.node_id)
             .map_err(|e| NodeError::Generic(format!("Invalid LSP public key: {e}")))?;
-        let address = SocketAddress::from_str(ldk_config.lsps2_address)
+        let address = SocketAddress::from_str(&ldk_config.lsp.address)
             .map_err(|e| NodeError::Generic(format!("Invalid LSP address: {e}")))?;
-        builder.set_liquidity_source_lsps2(lsps2, address, None);
+        builder.set_liquidity_source_lsps2(lsps2, address, ldk_config.lsp.token.clone());
 
         let store_id = match config.network {
             Network::Regtest => {
@@ -95,13 +147,19 @@ impl Ldk {
             }
             _ => "ldk_node".to_string(),
         };
-        let vss_store = build_vss_store(ldk_config.vss_url.to_string(), store_id);
+        let vss_store = build_vss_store(ldk_config.vss_url.clone(), store_id);
 
         // It is not possible to use oneshot here, because `oneshot::Sender::send()`
         // consumes itself, not allowing to call `closed()` method after.
         let (remote_lock_shutdown_tx, remote_lock_shutdown_rx) = mpsc::channel(1);
-        let _locking_store =
-            build_locking_store(&config.working_dir, vss_store, remote_lock_shutdown_rx).await?;
+        let (lock_status, _) = watch::channel(NodeLockStatus::Held);
+        let locking_store = build_locking_store(
+            &config.working_dir,
+            vss_store,
+            remote_lock_shutdown_rx,
+            lock_status.clone(),
+        )
+        .await?;
 
         // TODO: Use remote/local storage.
         builder.set_storage_dir_path(config.working_dir);
@@ -113,17 +171,93 @@ impl Ldk {
         debug!("LDK Node was built");
 
         let (incoming_payments_tx, _) = broadcast::channel(10);
+        let (held_htlcs_tx, _) = broadcast::channel(10);
+        let (closed_channels_tx, _) = broadcast::channel(10);
+        let hold_invoices = persister
+            .list_open_ldk_hold_invoices()?
+            .into_iter()
+            .filter_map(|(payment_hash, held_amount_msat)| {
+                Some((PaymentHash(payment_hash.try_into().ok()?), held_amount_msat))
+            })
+            .collect();
 
         Ok(Self {
             network: config.network,
             seed,
             node,
             incoming_payments_tx,
-            preimages: PreimageStore::default(),
+            held_htlcs_tx,
+            closed_channels_tx,
+            persister,
+            hold_invoices: Arc::new(Mutex::new(hold_invoices)),
+            lsp: ldk_config.lsp,
+            breez_server: Arc::new(breez_server),
+            locking_store,
             remote_lock_shutdown_tx,
+            lock_status,
         })
     }
 
+    /// The remote store, only usable while this instance holds its lock
+    pub(crate) fn locking_store(&self) -> Arc<LockingStore> {
+        Arc::clone(&self.locking_store)
+    }
+
+    /// Fails with [NodeError::ReadOnly] while another instance runs the node
+    fn ensure_lock_held(&self) -> NodeResult<()> {
+        match &*self.lock_status.borrow() {
+            NodeLockStatus::Held => Ok(()),
+            NodeLockStatus::HeldByOtherInstance { instance_id } => Err(NodeError::ReadOnly(
+                format!("The node is run by instance `{instance_id}`"),
+            )),
+            NodeLockStatus::Lost { instance_id } => Err(NodeError::ReadOnly(format!(
+                "The node was taken over by instance `{}`",
+                instance_id.as_deref().unwrap_or("unknown")
+            ))),
+        }
+    }
+
+    fn parse_address(&self, address: &str) -> NodeResult<Address> {
+        Address::from_str(address)
+            .map_err(|e| NodeError::Generic(format!("Invalid address: {e}")))?
+            .require_network(to_ldk_network(&self.network))
+            .map_err(|e| NodeError::Generic(format!("Invalid address: {e}")))
+    }
+
+    /// Estimates the number of outputs the on-chain wallet can spend.
+    ///
+    /// LDK Node doesn't expose the wallet UTXOs, so they are estimated from its on-chain payments:
+    /// the outputs received since the last outgoing payment, which is assumed to have spent the
+    /// earlier ones, and its change output unless the spendable balance is covered without it.
+    fn count_spendable_outputs(&self) -> u64 {
+        let payments = self
+            .node
+            .list_payments_with_filter(|p| matches!(p.kind, PaymentKind::Onchain { .. }));
+        let height = |p: &PaymentDetails| match p.kind {
+            PaymentKind::Onchain {
+                status: ConfirmationStatus::Confirmed { height, .. },
+                ..
+            } => Some(height),
+            _ => None,
+        };
+        // Unconfirmed outgoing payments are the latest ones
+        let last_spent_at = payments
+            .iter()
+            .filter(|p| p.direction == PaymentDirection::Outbound)
+            .map(|p| height(p).unwrap_or(u32::MAX))
+            .max();
+        // Unconfirmed incoming payments are not spendable
+        let received: Vec<u64> = payments
+            .iter()
+            .filter(|p| p.direction == PaymentDirection::Inbound)
+            .filter(|p| height(p).is_some_and(|h| !last_spent_at.is_some_and(|spent| h <= spent)))
+            .map(|p| p.amount_msat.unwrap_or_default() / 1_000)
+            .collect();
+        let spendable_sat = self.node.list_balances().spendable_onchain_balance_sats;
+        let change = last_spent_at.is_some() && spendable_sat > received.iter().sum::<u64>();
+        received.len() as u64 + u64::from(change)
+    }
+
     async fn load_default_opening_fee_params(&self, expiry: u32) -> SdkResult<OpeningFeeParams> {
         self.list_lsps(self.node.node_id().to_string())
             .await?
@@ -143,18 +277,23 @@ impl Ldk {
         preimage: Option<PaymentPreimage>,
         expiry: u32,
     ) -> NodeResult<String> {
+        self.ensure_lock_held()?;
         let preimage =
             preimage.unwrap_or_else(|| PaymentPreimage(rand::thread_rng().gen::<[u8; 32]>()));
         let payment_hash: PaymentHash = preimage.into();
-        // TODO: Store preimage in the mirroring store.
-        match self.preimages.lock().unwrap().entry(payment_hash) {
-            std::collections::hash_map::Entry::Occupied(_) => {
-                return Err(NodeError::InvoicePreimageAlreadyExists(
-                    "Failed to create invoice, preimage already exists".to_string(),
-                ));
-            }
-            std::collections::hash_map::Entry::Vacant(entry) => entry.insert(preimage),
-        };
+        // The preimage is stored before the invoice exists, so a payment to it is
+        // always claimable, even after a restart
+        let inserted = self.persister.insert_ldk_invoice(
+            &payment_hash.0,
+            Some(&preimage.0),
+            Utc::now().timestamp(),
+        )?;
+        ensure_sdk!(
+            inserted,
+            NodeError::InvoicePreimageAlreadyExists(
+                "Failed to create invoice, preimage already exists".to_string()
+            )
+        );
 
         let payments = self.node.bolt11_payment();
         let invoice = match opening_fee_msat {
@@ -166,8 +305,17 @@ impl Ldk {
                 payment_hash,
             ),
             None => payments.receive_for_hash(amount_msat, &description, expiry, payment_hash),
-        }?;
-        Ok(invoice.to_string())
+        };
+        let invoice = match invoice {
+            Ok(invoice) => invoice.to_string(),
+            Err(e) => {
+                self.persister.delete_ldk_invoice(&payment_hash.0)?;
+                return Err(e.into());
+            }
+        };
+        self.persister
+            .set_ldk_invoice_bolt11(&payment_hash.0, &invoice)?;
+        Ok(invoice)
     }
 }
 
@@ -191,8 +339,15 @@ impl NodeAPI for Ldk {
         Err(NodeError::generic("LDK implementation not yet available"))
     }
 
-    async fn fetch_bolt11(&self, _payment_hash: Vec<u8>) -> NodeResult<Option<FetchBolt11Result>> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+    async fn fetch_bolt11(&self, payment_hash: Vec<u8>) -> NodeResult<Option<FetchBolt11Result>> {
+        let bolt11 = self
+            .persister
+            .get_ldk_invoice(&payment_hash)?
+            .and_then(|invoice| invoice.bolt11);
+        Ok(bolt11.map(|bolt11| FetchBolt11Result {
+            bolt11,
+            payer_amount_msat: None,
+        }))
     }
 
     async fn pull_changed(
@@ -215,6 +370,7 @@ impl NodeAPI for Ldk {
         _bolt11: String,
         _amount_msat: Option<u64>,
         _label: Option<String>,
+        _max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment> {
         Err(NodeError::generic("LDK implementation not yet available"))
     }
@@ -225,6 +381,7 @@ impl NodeAPI for Ldk {
         _amount_msat: u64,
         _extra_tlvs: Option<Vec<TlvEntry>>,
         _label: Option<String>,
+        _max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment> {
         Err(NodeError::generic("LDK implementation not yet available"))
     }
@@ -258,42 +415,99 @@ impl NodeAPI for Ldk {
 
     async fn redeem_onchain_funds(
         &self,
-        _to_address: String,
-        _sat_per_vbyte: u32,
+        to_address: String,
+        sat_per_vbyte: u32,
     ) -> NodeResult<Vec<u8>> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+        self.ensure_lock_held()?;
+        let address = self.parse_address(&to_address)?;
+        let fee_rate = FeeRate::from_sat_per_vb(sat_per_vbyte.into())
+            .ok_or(NodeError::generic("Invalid fee rate"))?;
+        // Anchor channels need on-chain funds to bump their closing transactions
+        let retain_reserves = true;
+        let txid = self.node.onchain_payment().send_all_to_address(
+            &address,
+            retain_reserves,
+            Some(fee_rate),
+        )?;
+        // Txids are reported in their displayed byte order, as by Greenlight
+        let mut txid = txid.to_byte_array();
+        txid.reverse();
+        Ok(txid.to_vec())
     }
 
     async fn prepare_redeem_onchain_funds(
         &self,
-        _req: PrepareRedeemOnchainFundsRequest,
+        req: PrepareRedeemOnchainFundsRequest,
     ) -> NodeResult<PrepareRedeemOnchainFundsResponse> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+        let address = self.parse_address(&req.to_address)?;
+        // As in `redeem_onchain_funds`, the reserves are kept in a change output, and only the
+        // spendable balance, which excludes them, is swept
+        let balances = self.node.list_balances();
+        let amount_sat = balances.spendable_onchain_balance_sats;
+        let inputs = self.count_spendable_outputs().max(1);
+        let mut script_lens = vec![address.script_pubkey().len() as u64];
+        if balances.total_anchor_channels_reserve_sats > 0 {
+            script_lens.push(P2WPKH_SCRIPT_LEN);
+        }
+        let tx_weight = estimate_sweep_weight(inputs, &script_lens);
+        let tx_fee_sat = (tx_weight * u64::from(req.sat_per_vbyte)).div_ceil(4);
+        ensure_sdk!(
+            tx_fee_sat < amount_sat,
+            NodeError::InsufficientFunds("Insufficient funds to pay fees".to_string())
+        );
+        Ok(PrepareRedeemOnchainFundsResponse {
+            tx_weight,
+            tx_fee_sat,
+        })
     }
 
-    async fn start(&self, shutdown: mpsc::Receiver<()>) {
-        debug!("Starting LDK Node");
-        if let Err(e) = self.node.start() {
-            error!("Failed to start LDK Node: {e}");
-            return;
-        }
-        debug!("LDK Node started");
-
-        debug!("Starting event handling");
-        start_event_handling(
-            Arc::clone(&self.node),
-            Arc::clone(&self.preimages),
-            self.incoming_payments_tx.clone(),
-            shutdown,
-        )
-        .await;
-        info!("Event handling stopped");
+    async fn start(&self, mut shutdown: mpsc::Receiver<()>) {
+        let mut lock_status = self.lock_status.subscribe();
+        loop {
+            // Only the instance holding the remote lock may run the node
+            tokio::select! {
+                held = lock_status.wait_for(|s| *s == NodeLockStatus::Held) => {
+                    if held.is_err() {
+                        break;
+                    }
+                }
+                _ = shutdown.recv() => break,
+            }
 
-        debug!("Stopping LDK Node");
-        if let Err(e) = self.node.stop() {
-            error!("Error on stopping LDK Node: {e}");
+            debug!("Starting LDK Node");
+            if let Err(e) = self.node.start() {
+                error!("Failed to start LDK Node: {e}");
+                break;
+            }
+            debug!("LDK Node started");
+
+            debug!("Starting event handling");
+            let lock_lost = tokio::select! {
+                _ = start_event_handling(
+                    Arc::clone(&self.node),
+                    Arc::clone(&self.persister),
+                    Arc::clone(&self.hold_invoices),
+                    self.incoming_payments_tx.clone(),
+                    self.held_htlcs_tx.clone(),
+                    self.closed_channels_tx.clone(),
+                    &mut shutdown,
+                ) => false,
+                _ = lock_status.wait_for(|s| matches!(s, NodeLockStatus::Lost { .. })) => {
+                    warn!("Remote lock was lost, stopping LDK Node");
+                    true
+                }
+            };
+            info!("Event handling stopped");
+
+            debug!("Stopping LDK Node");
+            if let Err(e) = self.node.stop() {
+                error!("Error on stopping LDK Node: {e}");
+            }
+            debug!("LDK Node stopped");
+            if !lock_lost {
+                break;
+            }
         }
-        debug!("LDK Node stopped");
 
         debug!("Stopping remote lock refreshing");
         let _ = self.remote_lock_shutdown_tx.send(()).await;
@@ -308,6 +522,7 @@ impl NodeAPI for Ldk {
     }
 
     async fn connect_peer(&self, node_id: String, addr: String) -> NodeResult<()> {
+        self.ensure_lock_held()?;
         let node_id = PublicKey::from_str(&node_id)
             .map_err(|e| NodeError::Generic(format!("Invalid LSP public key: {e}")))?;
         let address = SocketAddress::from_str(&addr)
@@ -321,8 +536,71 @@ impl NodeAPI for Ldk {
         Err(NodeError::generic("LDK implementation not yet available"))
     }
 
-    async fn close_peer_channels(&self, _node_id: String) -> NodeResult<Vec<String>> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+    /// Closes the channels with the peer cooperatively.
+    ///
+    /// LDK negotiates and broadcasts the closing transactions in the background, and reports the
+    /// channels closed without their closing transactions. These pay to the on-chain wallet, so
+    /// once the channels are closed, they are the new transactions the wallet received.
+    async fn close_peer_channels(&self, node_id: String) -> NodeResult<Vec<String>> {
+        self.ensure_lock_held()?;
+        let node_id = PublicKey::from_str(&node_id)
+            .map_err(|e| NodeError::Generic(format!("Invalid peer public key: {e}")))?;
+        let received_txids = |node: &Node| -> HashSet<String> {
+            node.list_payments_with_filter(|p| p.direction == PaymentDirection::Inbound)
+                .into_iter()
+                .filter_map(|p| match p.kind {
+                    PaymentKind::Onchain { txid, .. } => Some(txid.to_string()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let known_txids = received_txids(&self.node);
+
+        let mut closed_channels = BroadcastStream::new(self.closed_channels_tx.subscribe());
+        let mut closing = HashSet::new();
+        for channel in self.node.list_channels() {
+            if channel.counterparty_node_id != node_id {
+                continue;
+            }
+            match self
+                .node
+                .close_channel(&channel.user_channel_id, channel.counterparty_node_id)
+            {
+                Ok(()) => {
+                    closing.insert(channel.channel_id.to_string());
+                }
+                Err(e) => error!("Failed to close channel {}: {e}", channel.channel_id),
+            }
+        }
+        if closing.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let all_closed = async {
+            while !closing.is_empty() {
+                match closed_channels.next().await {
+                    Some(Ok(channel_id)) => {
+                        closing.remove(&channel_id);
+                    }
+                    Some(Err(Lagged(n))) => warn!("Closed channels stream missed {n} events"),
+                    None => break,
+                }
+            }
+        };
+        if tokio::time::timeout(CHANNEL_CLOSE_TIMEOUT, all_closed)
+            .await
+            .is_err()
+        {
+            warn!("Timed out waiting for the channels with {node_id} to close");
+        }
+        let node = Arc::clone(&self.node);
+        tokio::task::spawn_blocking(move || node.sync_wallets())
+            .await
+            .map_err(|e| NodeError::Generic(format!("Failed to sync wallets: {e}")))??;
+        Ok(received_txids(&self.node)
+            .difference(&known_txids)
+            .cloned()
+            .collect())
     }
 
     async fn stream_incoming_payments(
@@ -340,8 +618,148 @@ impl NodeAPI for Ldk {
         Ok(Box::pin(futures::stream::empty()))
     }
 
+    async fn create_hold_invoice(&self, request: HoldInvoiceRequest) -> NodeResult<String> {
+        self.ensure_lock_held()?;
+        ensure_sdk!(
+            request.cltv.is_none(),
+            NodeError::generic(
+                "LDK Node sets the min_final_cltv_expiry of invoices, cltv is not supported"
+            )
+        );
+        let payment_hash = PaymentHash(
+            request
+                .payment_hash
+                .try_into()
+                .map_err(|_| NodeError::generic("Invalid payment hash"))?,
+        );
+        let description = invoice_description(
+            request.description,
+            request.use_description_hash.unwrap_or(false),
+        )?;
+        let expiry = request.expiry.unwrap_or(INVOICE_PAYMENT_FEE_EXPIRY_SECONDS);
+        let inserted =
+            self.persister
+                .insert_ldk_invoice(&payment_hash.0, None, Utc::now().timestamp())?;
+        ensure_sdk!(
+            inserted,
+            NodeError::InvoicePreimageAlreadyExists(
+                "Failed to create invoice, preimage already exists".to_string()
+            )
+        );
+        // The invoice is persisted without a preimage, which marks it as a hold invoice
+        match self.hold_invoices.lock().unwrap().entry(payment_hash) {
+            std::collections::hash_map::Entry::Occupied(_) => {
+                return Err(NodeError::InvoicePreimageAlreadyExists(
+                    "Failed to create invoice, hold invoice already exists".to_string(),
+                ));
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => entry.insert(None),
+        };
+        let invoice = self.node.bolt11_payment().receive_for_hash(
+            request.amount_msat,
+            &description,
+            expiry,
+            payment_hash,
+        );
+        match invoice {
+            Ok(invoice) => {
+                let invoice = invoice.to_string();
+                self.persister
+                    .set_ldk_invoice_bolt11(&payment_hash.0, &invoice)?;
+                Ok(invoice)
+            }
+            Err(e) => {
+                self.hold_invoices.lock().unwrap().remove(&payment_hash);
+                self.persister.delete_ldk_invoice(&payment_hash.0)?;
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn settle_hold_invoice(&self, preimage: Vec<u8>) -> NodeResult<()> {
+        self.ensure_lock_held()?;
+        let preimage = PaymentPreimage(
+            preimage
+                .try_into()
+                .map_err(|_| NodeError::generic("Invalid preimage"))?,
+        );
+        let payment_hash: PaymentHash = preimage.into();
+        let claimable_amount_msat = self
+            .hold_invoices
+            .lock()
+            .unwrap()
+            .get(&payment_hash)
+            .copied()
+            .ok_or(NodeError::generic("Hold invoice not found"))?
+            .ok_or(NodeError::generic("No payment is held for this invoice"))?;
+        // The preimage is needed once the payment is received
+        self.persister
+            .set_ldk_invoice_preimage(&payment_hash.0, &preimage.0)?;
+        self.node
+            .bolt11_payment()
+            .claim_for_hash(payment_hash, claimable_amount_msat, preimage)?;
+        self.hold_invoices.lock().unwrap().remove(&payment_hash);
+        Ok(())
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: Vec<u8>) -> NodeResult<()> {
+        self.ensure_lock_held()?;
+        let payment_hash = PaymentHash(
+            payment_hash
+                .try_into()
+                .map_err(|_| NodeError::generic("Invalid payment hash"))?,
+        );
+        // Without an entry any later HTLCs are failed back by the event handling
+        self.persister.cancel_ldk_hold_invoice(&payment_hash.0)?;
+        let held = self.hold_invoices.lock().unwrap().remove(&payment_hash);
+        if let Some(Some(_)) = held {
+            self.node.bolt11_payment().fail_for_hash(payment_hash)?;
+        }
+        Ok(())
+    }
+
+    async fn stream_held_htlcs(&self) -> NodeResult<Pin<Box<dyn Stream<Item = HeldHtlcs> + Send>>> {
+        let stream = BroadcastStream::new(self.held_htlcs_tx.subscribe()).filter_map(|r| {
+            r.map_err(|Lagged(n)| warn!("Held HTLCs stream missed {n} events"))
+                .ok()
+        });
+        Ok(Box::pin(stream))
+    }
+
+    async fn stream_node_lock_status(
+        &self,
+    ) -> NodeResult<Pin<Box<dyn Stream<Item = NodeLockStatus> + Send>>> {
+        Ok(Box::pin(WatchStream::new(self.lock_status.subscribe())))
+    }
+
+    async fn take_over_node_lock(&self) -> NodeResult<()> {
+        // Returns once the lease of the previous holder ended
+        self.locking_store
+            .take_over()
+            .await
+            .map_err(|e| NodeError::Generic(format!("Failed to take over the remote lock: {e}")))?;
+        info!("Remote lock was taken over");
+        self.lock_status.send_replace(NodeLockStatus::Held);
+        Ok(())
+    }
+
+    async fn device_id(&self) -> NodeResult<String> {
+        Err(NodeError::generic(DEVICES_UNSUPPORTED))
+    }
+
+    async fn issue_device_credentials(&self) -> NodeResult<IssuedDeviceCredentials> {
+        Err(NodeError::generic(DEVICES_UNSUPPORTED))
+    }
+
+    async fn issue_watch_only_credentials(&self) -> NodeResult<IssuedDeviceCredentials> {
+        Err(NodeError::generic(
+            "LDK nodes can't be connected watch-only",
+        ))
+    }
+
+    /// LDK has no static channel backup, the channel states are backed up to VSS instead
     async fn static_backup(&self) -> NodeResult<Vec<String>> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+        Ok(Vec::new())
     }
 
     async fn execute_command(&self, _command: String) -> NodeResult<Value> {
@@ -349,20 +767,66 @@ impl NodeAPI for Ldk {
     }
 
     async fn generate_diagnostic_data(&self) -> NodeResult<Value> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+        let balances = self.node.list_balances();
+        let channels: Vec<Value> = self
+            .node
+            .list_channels()
+            .into_iter()
+            .map(|c| {
+                json!({
+                    "channel_id": c.channel_id.to_string(),
+                    "counterparty_node_id": c.counterparty_node_id.to_string(),
+                    "funding_txo": c.funding_txo.map(|txo| txo.to_string()),
+                    "channel_value_sats": c.channel_value_sats,
+                    "outbound_capacity_msat": c.outbound_capacity_msat,
+                    "inbound_capacity_msat": c.inbound_capacity_msat,
+                    "is_channel_ready": c.is_channel_ready,
+                    "is_usable": c.is_usable,
+                })
+            })
+            .collect();
+        let peers: Vec<Value> = self
+            .node
+            .list_peers()
+            .into_iter()
+            .map(|p| {
+                json!({
+                    "node_id": p.node_id.to_string(),
+                    "address": p.address.to_string(),
+                    "is_connected": p.is_connected,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "node_id": self.node.node_id().to_string(),
+            "status": format!("{:?}", self.node.status()),
+            "balances": {
+                "total_onchain_balance_sats": balances.total_onchain_balance_sats,
+                "spendable_onchain_balance_sats": balances.spendable_onchain_balance_sats,
+                "total_anchor_channels_reserve_sats": balances.total_anchor_channels_reserve_sats,
+                "total_lightning_balance_sats": balances.total_lightning_balance_sats,
+            },
+            "channels": channels,
+            "peers": peers,
+        }))
     }
 
-    async fn sign_message(&self, _message: &str) -> NodeResult<String> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+    async fn sign_message(&self, message: &str) -> NodeResult<String> {
+        // Same zbase32 format as Core Lightning's `signmessage`
+        Ok(self.node.sign_message(message.as_bytes()))
     }
 
     async fn check_message(
         &self,
-        _message: &str,
-        _pubkey: &str,
-        _signature: &str,
+        message: &str,
+        pubkey: &str,
+        signature: &str,
     ) -> NodeResult<bool> {
-        Err(NodeError::generic("LDK implementation not yet available"))
+        let pubkey = PublicKey::from_str(pubkey)
+            .map_err(|e| NodeError::Generic(format!("Invalid public key: {e}")))?;
+        Ok(self
+            .node
+            .verify_signature(message.as_bytes(), signature, &pubkey))
     }
 
     async fn send_custom_message(&self, _message: CustomMessage) -> NodeResult<()> {
@@ -401,41 +865,12 @@ impl NodeAPI for Ldk {
 
 #[tonic::async_trait]
 impl LspAPI for Ldk {
-    async fn list_lsps(&self, _node_pubkey: String) -> SdkResult<Vec<LspInformation>> {
-        // TODO: Load data dynamically from LSP.
-        // TODO: For now hard-code values for other environments.
-        // Regtest
-        let year = Duration::from_secs(60 * 60 * 24 * 365);
-        let in_one_year = SystemTime::now() + year;
-        let in_one_year: DateTime<Utc> = in_one_year.into();
-        let opening_fee_params = OpeningFeeParams {
-            min_msat: 1_000_000,
-            proportional: 100_000,
-            valid_until: in_one_year.to_rfc3339(),
-            max_idle_time: 0,
-            max_client_to_self_delay: 10_000,
-            promise: "I promise".to_string(),
-        };
-        let config = crate::ldk::config::Config::regtest();
-        let pubkey = PublicKey::from_str(config.lsps2_id).map_err(|e| SdkError::Generic {
-            err: format!("Invalid LSP public key: {e}"),
-        })?;
-        let lsp = LspInformation {
-            id: pubkey.to_string(),
-            name: "Breez SDK Regtest LSPS2".to_string(),
-            widget_url: "http://widget.example.com".to_string(),
-            pubkey: pubkey.to_string(),
-            host: config.lsps2_address.to_string(),
-            base_fee_msat: 1_000,
-            fee_rate: 0.0,
-            time_lock_delta: 72,
-            min_htlc_msat: 1,
-            lsp_pubkey: pubkey.serialize().to_vec(),
-            opening_fee_params_list: OpeningFeeParamsMenu {
-                values: vec![opening_fee_params],
-            },
-        };
-        Ok(vec![lsp])
+    async fn list_lsps(&self, node_pubkey: String) -> SdkResult<Vec<LspInformation>> {
+        // LDK Node fetches the LSPS2 fee menu itself when the LSP opens a channel, but doesn't
+        // expose it. The Breez LSP API serves the same menu, and LDK Node refuses a channel costing
+        // more than the fee quoted from it.
+        let lsps = self.breez_server.list_lsps(node_pubkey).await?;
+        Ok(vec![configured_lsp(lsps, &self.lsp)?])
     }
 
     async fn list_used_lsps(&self, node_pubkey: String) -> SdkResult<Vec<LspInformation>> {
@@ -488,6 +923,7 @@ impl Receiver for Ldk {
         &self,
         req: ReceivePaymentRequest,
     ) -> Result<ReceivePaymentResponse, ReceivePaymentError> {
+        self.ensure_lock_held()?;
         ensure_sdk!(
             req.amount_msat > 0,
             ReceivePaymentError::InvalidAmount {
@@ -516,16 +952,8 @@ impl Receiver for Ldk {
             );
         }
 
-        let description = if req.use_description_hash.unwrap_or(false) {
-            let hash = Sha256::hash(req.description.as_bytes());
-            Bolt11InvoiceDescription::Hash(ldk_node::lightning_invoice::Sha256(hash))
-        } else {
-            let description =
-                Description::new(req.description).map_err(|e| ReceivePaymentError::Generic {
-                    err: format!("Failed to create invoice description: {e}"),
-                })?;
-            Bolt11InvoiceDescription::Direct(description)
-        };
+        let description =
+            invoice_description(req.description, req.use_description_hash.unwrap_or(false))?;
 
         let preimage = match req.preimage.map(|p| p.as_slice().try_into()) {
             Some(Ok(preimage)) => Some(PaymentPreimage(preimage)),
@@ -559,6 +987,35 @@ impl Receiver for Ldk {
     }
 }
 
+fn invoice_description(
+    description: String,
+    use_description_hash: bool,
+) -> NodeResult<Bolt11InvoiceDescription> {
+    if use_description_hash {
+        let hash = Sha256::hash(description.as_bytes());
+        return Ok(Bolt11InvoiceDescription::Hash(
+            ldk_node::lightning_invoice::Sha256(hash),
+        ));
+    }
+    let description = Description::new(description)
+        .map_err(|e| NodeError::Generic(format!("Failed to create invoice description: {e}")))?;
+    Ok(Bolt11InvoiceDescription::Direct(description))
+}
+
+/// Length of the P2WPKH scripts of the on-chain wallet
+const P2WPKH_SCRIPT_LEN: u64 = 22;
+
+/// Estimates the weight of a transaction spending `inputs` P2WPKH outputs
+/// into outputs with scripts of `script_lens` bytes
+fn estimate_sweep_weight(inputs: u64, script_lens: &[u64]) -> u64 {
+    // Version, locktime, input and output counts, then the segwit marker and flag
+    const TX_OVERHEAD_WEIGHT: u64 = (4 + 4 + 1 + 1) * 4 + 2;
+    // Outpoint, empty script_sig and sequence, then the signature and public key witness
+    const P2WPKH_INPUT_WEIGHT: u64 = (36 + 1 + 4) * 4 + 108;
+    let outputs_weight: u64 = script_lens.iter().map(|len| (8 + 1 + len) * 4).sum();
+    TX_OVERHEAD_WEIGHT + inputs * P2WPKH_INPUT_WEIGHT + outputs_weight
+}
+
 fn to_ldk_network(network: &Network) -> ldk_node::bitcoin::network::Network {
     match network {
         Network::Bitcoin => ldk_node::bitcoin::network::Network::Bitcoin,
@@ -567,3 +1024,70 @@ fn to_ldk_network(network: &Network) -> ldk_node::bitcoin::network::Network {
         Network::Regtest => ldk_node::bitcoin::network::Network::Regtest,
     }
 }
+
+/// The configured LSP among those of the Breez LSP API, reached at the configured address
+fn configured_lsp(lsps: Vec<LspInformation>, config: &LdkLspConfig) -> SdkResult<LspInformation> {
+    let mut lsp = lsps
+        .into_iter()
+        .find(|lsp| lsp.pubkey.eq_ignore_ascii_case(&config.node_id))
+        .ok_or_else(|| SdkError::Generic {
+            err: format!("No fee menu is available for LSP {}", config.node_id),
+        })?;
+    lsp.host = config.address.clone();
+    Ok(lsp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{configured_lsp, estimate_sweep_weight, P2WPKH_SCRIPT_LEN};
+    use crate::models::{LdkLspConfig, OpeningFeeParamsMenu};
+    use crate::LspInformation;
+
+    #[test]
+    fn test_estimate_sweep_weight() {
+        // A 1-input 1-output P2WPKH transaction is 110 vbytes
+        assert_eq!(
+            estimate_sweep_weight(1, &[P2WPKH_SCRIPT_LEN]).div_ceil(4),
+            110
+        );
+        // Each extra input adds 68 vbytes
+        assert_eq!(
+            estimate_sweep_weight(2, &[P2WPKH_SCRIPT_LEN])
+                - estimate_sweep_weight(1, &[P2WPKH_SCRIPT_LEN]),
+            272
+        );
+        // A P2WPKH change output adds 31 vbytes
+        assert_eq!(
+            estimate_sweep_weight(1, &[P2WPKH_SCRIPT_LEN, P2WPKH_SCRIPT_LEN])
+                - estimate_sweep_weight(1, &[P2WPKH_SCRIPT_LEN]),
+            124
+        );
+    }
+
+    #[test]
+    fn test_configured_lsp() {
+        let lsp = |pubkey: &str| LspInformation {
+            id: pubkey.to_string(),
+            name: pubkey.to_string(),
+            widget_url: String::new(),
+            pubkey: pubkey.to_string(),
+            host: "lsp.example.com:9735".to_string(),
+            base_fee_msat: 1_000,
+            fee_rate: 0.0,
+            time_lock_delta: 72,
+            min_htlc_msat: 1,
+            lsp_pubkey: vec![],
+            opening_fee_params_list: OpeningFeeParamsMenu { values: vec![] },
+        };
+        let config = LdkLspConfig {
+            node_id: "02AA".to_string(),
+            address: "127.0.0.1:9735".to_string(),
+            token: None,
+        };
+
+        let found = configured_lsp(vec![lsp("02bb"), lsp("02aa")], &config).unwrap();
+        assert_eq!(found.pubkey, "02aa");
+        assert_eq!(found.host, "127.0.0.1:9735");
+        assert!(configured_lsp(vec![lsp("02bb")], &config).is_err());
+    }
+}
@@ -7,6 +7,9 @@ use tonic::async_trait;
 struct VersionedTimeLock {
     tl: TimeLock,
     version: i64,
+    /// Whether the last write of the lock succeeded. Once another instance
+    /// wrote it, only [LockingStore::take_over] writes it again.
+    acquired: bool,
 }
 
 /// A wrapper around a `VersionedStore` that provides distributed locking capabilities.
@@ -16,8 +19,9 @@ struct VersionedTimeLock {
 /// processes or instances might try to access the same storage concurrently.
 /// It provides *exclusive* access to the store, meaning no one else can acquire/release
 /// the lock while the `LockingStore` is managed.
-/// *Warning:* If the lock is somehow lost, `LockingStore` cannot recover,
-/// and all operations (except `unlock()`) will result in a `Conflict` error.
+/// *Warning:* If the lock is somehow lost, `LockingStore` cannot recover unless it is
+/// explicitly taken over, and all operations (except `unlock()` and `take_over()`)
+/// will result in a `Conflict` error.
 ///
 /// The locking mechanism works by:
 /// 1. Acquiring a lock when the store is created
@@ -45,22 +49,48 @@ impl<S: VersionedStore + Send + Sync> LockingStore<S> {
     /// 3. If a lock exists, verify it's not held by another instance
     /// 4. If the lock is held by another instance, return a `Conflict` error
     pub async fn new(instance_id: String, store: S) -> Result<(Self, PreviousHolder), Error> {
+        let (locking_store, acquired) = Self::new_or_unlocked(instance_id, store).await?;
+        let previous_holder = acquired.map_err(|LockedBy(instance_id)| {
+            Error::Conflict(format!("Remote lock aquired by `{instance_id}`"))
+        })?;
+        Ok((locking_store, previous_holder))
+    }
+
+    /// Creates a new `LockingStore` like [LockingStore::new], except that if the lock is
+    /// held by another instance, the store is still returned, without the lock.
+    ///
+    /// All its operations then fail with a `Conflict` error, until the lock is
+    /// taken over with [LockingStore::take_over].
+    pub async fn new_or_unlocked(
+        instance_id: String,
+        store: S,
+    ) -> Result<(Self, Result<PreviousHolder, LockedBy>), Error> {
         let (lock_data, version) = store.get(Self::KEY.to_string()).await?.unwrap_or_default();
         let lock_data = LockData::decode(&lock_data)
             .map_err(|e| Error::Internal(format!("Failed to decode lock_data: {e:?}")))?;
-        let (tl, previous_holder) = TimeLock::new(Self::LOCK_DURATION, instance_id, lock_data)
-            .map_err(|LockedBy(instance_id)| {
-                Error::Conflict(format!("Remote lock aquired by `{instance_id}`"))
-            })?;
-        let versioned_tl = Mutex::new(VersionedTimeLock { tl, version });
+        let acquired = TimeLock::new(Self::LOCK_DURATION, instance_id.clone(), lock_data);
+        let (tl, acquired) = match acquired {
+            Ok((tl, previous_holder)) => (tl, Ok(previous_holder)),
+            Err(locked_by) => (
+                TimeLock::unlocked(Self::LOCK_DURATION, instance_id),
+                Err(locked_by),
+            ),
+        };
+        let versioned_tl = Mutex::new(VersionedTimeLock {
+            tl,
+            version,
+            acquired: false,
+        });
 
         let locking_store = Self {
             inner: store,
             versioned_tl,
         };
-        locking_store.lock().await?;
+        if acquired.is_ok() {
+            locking_store.lock().await?;
+        }
 
-        Ok((locking_store, previous_holder))
+        Ok((locking_store, acquired))
     }
 
     /// Refreshes the distributed lock to extend its duration.
@@ -76,22 +106,83 @@ impl<S: VersionedStore + Send + Sync> LockingStore<S> {
         // If we failed to lock here, we can still try to lock again,
         // asserting that the previous lock was acquired by us
         // (i.e., no one acquired/released the lock in between).
-        let locked_until = self.lock().await?;
+        let mut versioned_tl = self.versioned_tl.lock().await;
+        if !versioned_tl.acquired {
+            return Err(Error::Conflict("Remote lock was not aquired".to_string()));
+        }
+        let locked_until = self.put_lock(&mut versioned_tl).await?;
         Ok(locked_until - Self::REFRESH_WINDOW)
     }
 
+    /// Acquires the distributed lock, even if it is held by another instance.
+    ///
+    /// The other instance notices it lost the lock on its next refresh, since
+    /// the version of the lock it knows is then outdated. Until then it may still
+    /// use the store, so this only returns once its lease ended, and the store
+    /// operations wait for it.
+    ///
+    /// # Returns
+    /// * `Ok(SystemTime)` - The time until which the lock should be refreshed
+    /// * `Err(Error::Conflict)` - If the lock was modified concurrently
+    /// * `Err(Error::Internal)` - If there was an internal error
+    pub async fn take_over(&self) -> Result<SystemTime, Error> {
+        let mut versioned_tl = self.versioned_tl.lock().await;
+        let (lock_data, version) = self
+            .inner
+            .get(Self::KEY.to_string())
+            .await?
+            .unwrap_or_default();
+        let lock_data = LockData::decode(&lock_data)
+            .map_err(|e| Error::Internal(format!("Failed to decode lock_data: {e:?}")))?;
+        versioned_tl.version = version;
+        // Keeps the other instance from refreshing its lease
+        let mut locked_until = self.put_lock(&mut versioned_tl).await?;
+        if let Some(lease_end) = versioned_tl.tl.lease_end(&lock_data) {
+            let wait = lease_end
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            // The lease ran while waiting
+            locked_until = self.put_lock(&mut versioned_tl).await?;
+        }
+        Ok(locked_until - Self::REFRESH_WINDOW)
+    }
+
+    /// Reads the instance id of the current lock holder, if the lock is held by another instance
+    pub async fn holder(&self) -> Result<Option<String>, Error> {
+        let (lock_data, _) = self
+            .inner
+            .get(Self::KEY.to_string())
+            .await?
+            .unwrap_or_default();
+        let lock_data = LockData::decode(&lock_data)
+            .map_err(|e| Error::Internal(format!("Failed to decode lock_data: {e:?}")))?;
+        let versioned_tl = self.versioned_tl.lock().await;
+        Ok(versioned_tl.tl.held_by(&lock_data).map(|LockedBy(id)| id))
+    }
+
     async fn lock(&self) -> Result<SystemTime, Error> {
         // Hold the lock on `self.versioned_tl` during the whole call to avoid
         // race conditions with `unlock()` method.
         let mut versioned_tl = self.versioned_tl.lock().await;
+        self.put_lock(&mut versioned_tl).await
+    }
+
+    async fn put_lock(&self, versioned_tl: &mut VersionedTimeLock) -> Result<SystemTime, Error> {
         let lock_data = versioned_tl.tl.next_lock();
         let value = lock_data
             .encode()
             .map_err(|e| Error::Internal(format!("Failed to encode lock_data: {e}")))?;
-        self.inner
+        let result = self
+            .inner
             .put(Self::KEY.to_string(), value, versioned_tl.version)
-            .await?;
+            .await;
+        if let Err(Error::Conflict(_)) = result {
+            versioned_tl.acquired = false;
+        }
+        result?;
         versioned_tl.version += 1;
+        versioned_tl.acquired = true;
         Ok(versioned_tl.tl.update_lock(&lock_data))
     }
 
@@ -108,6 +199,9 @@ impl<S: VersionedStore + Send + Sync> LockingStore<S> {
         // Hold the lock on `self.versioned_tl` during the whole call to avoid
         // race conditions with `lock()` method.
         let mut versioned_tl = self.versioned_tl.lock().await;
+        if !versioned_tl.acquired {
+            return Ok(());
+        }
         if let Some(lock_data) = versioned_tl.tl.unlock() {
             let value = lock_data
                 .encode()
@@ -120,7 +214,8 @@ impl<S: VersionedStore + Send + Sync> LockingStore<S> {
     }
 
     async fn ensure_locked(&self) -> Result<(), Error> {
-        if !self.versioned_tl.lock().await.tl.is_locked() {
+        let versioned_tl = self.versioned_tl.lock().await;
+        if !versioned_tl.acquired || !versioned_tl.tl.is_locked() {
             return Err(Error::Conflict("Remote lock was not aquired".to_string()));
         }
         Ok(())
@@ -210,4 +305,55 @@ mod tests {
         // but it can instantly reaquire the lock.
         let _locking_store2 = LockingStore::new(instance_id_2, store).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_locking_store_take_over() {
+        let store = MockVersionedStore::default();
+        let (locking_store1, _) = LockingStore::new("instance_1".to_string(), store.clone())
+            .await
+            .unwrap();
+
+        // The second instance gets the store, without the lock.
+        let (locking_store2, acquired) =
+            LockingStore::new_or_unlocked("instance_2".to_string(), store.clone())
+                .await
+                .unwrap();
+        assert_eq!(acquired, Err(LockedBy("instance_1".to_string())));
+        assert_eq!(
+            locking_store2.holder().await.unwrap(),
+            Some("instance_1".to_string())
+        );
+        let result = locking_store2.get("key".to_string()).await;
+        assert!(matches!(result, Err(Error::Conflict(_))));
+        let result = locking_store2.refresh_lock().await;
+        assert!(matches!(result, Err(Error::Conflict(_))));
+
+        // The lease of the first instance ends in a second.
+        let lease_end = SystemTime::now() + Duration::from_secs(1);
+        let lock_data = serde_json::json!({
+            "locked_until": lease_end,
+            "instance_id": "instance_1",
+        });
+        store.data.lock().unwrap().get_mut("lock").unwrap().0 =
+            serde_json::to_vec(&lock_data).unwrap();
+
+        // It takes the lock over once that lease ended, the first instance loses
+        // it on refresh.
+        locking_store2.take_over().await.unwrap();
+        assert!(SystemTime::now() >= lease_end + Duration::from_secs(3));
+        locking_store2
+            .put("key".to_string(), "value".as_bytes().to_vec(), 0)
+            .await
+            .unwrap();
+        let result = locking_store1.refresh_lock().await;
+        assert!(matches!(result, Err(Error::Conflict(_))));
+        assert_eq!(
+            locking_store1.holder().await.unwrap(),
+            Some("instance_2".to_string())
+        );
+        // Later refreshes don't take the lock back.
+        let result = locking_store1.refresh_lock().await;
+        assert!(matches!(result, Err(Error::Conflict(_))));
+        locking_store2.refresh_lock().await.unwrap();
+    }
 }
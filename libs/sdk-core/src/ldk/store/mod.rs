@@ -1,6 +1,6 @@
 pub(crate) mod locking_store;
 #[cfg(test)]
-mod mock_versioned_store;
-mod time_lock;
-mod versioned_store;
+pub(crate) mod mock_versioned_store;
+pub(crate) mod time_lock;
+pub(crate) mod versioned_store;
 pub(crate) mod vss_store;
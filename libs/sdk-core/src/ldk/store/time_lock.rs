@@ -75,6 +75,34 @@ impl TimeLock {
         }
     }
 
+    /// Creates a `TimeLock` which doesn't hold the lock, for an instance that
+    /// found it held by another one.
+    pub fn unlocked(lock_duration: Duration, instance_id: String) -> Self {
+        debug_assert!(lock_duration > Self::CLOCK_SKEW_LEEWAY);
+        debug_assert!(!instance_id.is_empty());
+        Self {
+            lock_duration,
+            instance_id,
+            locked_until: UNIX_EPOCH,
+        }
+    }
+
+    /// Returns the other instance holding the lock described by `lock_data`, if
+    /// the lock hasn't expired.
+    pub fn held_by(&self, lock_data: &LockData) -> Option<LockedBy> {
+        let held = !lock_data.instance_id.is_empty()
+            && lock_data.instance_id != self.instance_id
+            && SystemTime::now() < lock_data.locked_until + Self::CLOCK_SKEW_LEEWAY;
+        held.then(|| LockedBy(lock_data.instance_id.clone()))
+    }
+
+    /// Returns when the lease of the other instance holding the lock described by
+    /// `lock_data` ends, accounting for clock skew, if it hasn't ended yet.
+    pub fn lease_end(&self, lock_data: &LockData) -> Option<SystemTime> {
+        self.held_by(lock_data)
+            .map(|_| lock_data.locked_until + Self::CLOCK_SKEW_LEEWAY)
+    }
+
     /// Checks if the current instance holds an active lock.
     pub fn is_locked(&self) -> bool {
         SystemTime::now() < self.locked_until - Self::CLOCK_SKEW_LEEWAY
@@ -319,4 +347,29 @@ mod tests {
         let result = TimeLock::new(lock_duration, instance_id_2, lock_data_1).unwrap_err();
         assert_eq!(result, LockedBy("instance-1".to_string()));
     }
+
+    #[test]
+    fn test_time_lock_lease_end() {
+        let lock_duration = Duration::from_secs(10);
+        let time_lock = TimeLock::unlocked(lock_duration, "instance-2".to_string());
+
+        let locked_until = SystemTime::now() + Duration::from_secs(30);
+        let lease_end = time_lock.lease_end(&LockData {
+            locked_until,
+            instance_id: "instance-1".to_string(),
+        });
+        assert_eq!(lease_end, Some(locked_until + TimeLock::CLOCK_SKEW_LEEWAY));
+
+        // An expired lease and our own lease don't need to be waited for
+        let expired = time_lock.lease_end(&LockData {
+            locked_until: SystemTime::now() - Duration::from_secs(30),
+            instance_id: "instance-1".to_string(),
+        });
+        assert_eq!(expired, None);
+        let own = time_lock.lease_end(&LockData {
+            locked_until,
+            instance_id: "instance-2".to_string(),
+        });
+        assert_eq!(own, None);
+    }
 }
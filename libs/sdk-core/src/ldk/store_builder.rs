@@ -6,13 +6,15 @@ use std::time::{Duration, SystemTime};
 
 use rand::distributions::Alphanumeric;
 use rand::Rng;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use vss_client::client::VssClient;
 use vss_client::error::VssError;
 use vss_client::util::retry::{ExponentialBackoffRetryPolicy, MaxAttemptsRetryPolicy, RetryPolicy};
 
+use crate::ldk::store::time_lock::LockedBy;
+use crate::ldk::store::versioned_store::Error;
 use crate::ldk::store::vss_store::VssStore;
-use crate::node_api::NodeResult;
+use crate::node_api::{NodeLockStatus, NodeResult};
 use crate::persist::error::PersistError;
 
 pub(crate) type CustomRetryPolicy = MaxAttemptsRetryPolicy<ExponentialBackoffRetryPolicy<VssError>>;
@@ -28,19 +30,32 @@ pub(crate) fn build_vss_store(url: String, store_id: String) -> VssStore<CustomR
     VssStore::new(vss_client, store_id)
 }
 
+/// Builds the store and tries to lock it.
+///
+/// If another instance holds the lock, the store is still returned, without the
+/// lock, and `lock_status` tells which instance holds it.
 pub(crate) async fn build_locking_store(
     working_dir: &str,
     vss_store: VssStore<CustomRetryPolicy>,
     remote_lock_shutdown_rx: mpsc::Receiver<()>,
+    lock_status: watch::Sender<NodeLockStatus>,
 ) -> NodeResult<Arc<LockingStore>> {
     let instance_id = read_or_generate_instance_id(working_dir)?;
-    let (locking_store, _previous_holder) = LockingStore::new(instance_id, vss_store)
+    let (locking_store, acquired) = LockingStore::new_or_unlocked(instance_id, vss_store)
         .await
         .map_err(|e| PersistError::Generic(format!("Failed to build locking store: {e}")))?;
+    lock_status.send_replace(match acquired {
+        Ok(_previous_holder) => NodeLockStatus::Held,
+        Err(LockedBy(instance_id)) => {
+            warn!("Remote lock is held by `{instance_id}`");
+            NodeLockStatus::HeldByOtherInstance { instance_id }
+        }
+    });
     let locking_store = Arc::new(locking_store);
     tokio::task::spawn(start_refreshing(
         Arc::clone(&locking_store),
         remote_lock_shutdown_rx,
+        lock_status,
     ));
     Ok(locking_store)
 }
@@ -74,13 +89,25 @@ fn generate_instance_id() -> String {
         .collect()
 }
 
-async fn start_refreshing(locking_store: Arc<LockingStore>, mut shutdown_rx: mpsc::Receiver<()>) {
+async fn start_refreshing(
+    locking_store: Arc<LockingStore>,
+    mut shutdown_rx: mpsc::Receiver<()>,
+    lock_status: watch::Sender<NodeLockStatus>,
+) {
     loop {
         let duration = match locking_store.refresh_lock().await {
             Ok(until) => {
                 trace!("Remote lock was refreshed");
                 until.duration_since(SystemTime::now()).unwrap_or_default()
             }
+            Err(Error::Conflict(e)) if *lock_status.borrow() == NodeLockStatus::Held => {
+                let instance_id = locking_store.holder().await.unwrap_or_default();
+                warn!("Remote lock was lost to {instance_id:?}: {e}");
+                lock_status.send_replace(NodeLockStatus::Lost { instance_id });
+                Duration::from_secs(5)
+            }
+            // Not held, until taken over
+            Err(Error::Conflict(_)) => Duration::from_secs(5),
             Err(e) => {
                 warn!("Failed to refresh remote lock: {e:?}");
                 Duration::from_secs(5)
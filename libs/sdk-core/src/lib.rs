@@ -120,6 +120,7 @@
 //!
 //! * [BreezServices::list_fiat_currencies] to get the supported fiat currencies
 //! * [BreezServices::fetch_fiat_rates] to get the current exchange rates
+//! * [BreezServices::receive_fiat_payment] to create an invoice for an amount in a fiat currency
 //! * [BreezServices::recommended_fees] for the recommended mempool fees
 //!
 //! ### G. Connecting to an LSP
@@ -162,12 +163,14 @@ mod frb_generated; /* AUTO INJECTED BY flutter_rust_bridge. This line may not be
 #[macro_use]
 extern crate log;
 
+mod app_keys;
 mod backup;
 pub mod binding;
 mod breez_services;
 mod chain;
 mod crypt;
 pub mod error;
+mod export;
 #[rustfmt::skip]
 mod node_api; // flutter_rust_bridge_codegen: has to be defined before greenlight; greenlight::node_api
 #[cfg(feature = "greenlight")]
@@ -183,8 +186,13 @@ mod lsps0;
 mod lsps2;
 mod models;
 mod node_builder;
+#[cfg(feature = "nwc")]
+pub mod nwc;
 mod persist;
+mod policy;
+mod recurring;
 mod serializer;
+pub mod signer;
 mod support;
 mod swap_in;
 mod swap_out;
@@ -196,7 +204,7 @@ mod test_utils;
 pub use breez_services::{
     mnemonic_to_seed, BackupFailedData, BreezEvent, BreezServices, CheckMessageRequest,
     CheckMessageResponse, EventListener, InvoicePaidDetails, LogStream, PaymentFailedData,
-    SignMessageRequest, SignMessageResponse,
+    RecurringPaymentAttemptedDetails, SignMessageRequest, SignMessageResponse,
 };
 pub use chain::RecommendedFees;
 pub use lsp::LspInformation;
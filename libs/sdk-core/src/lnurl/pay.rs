@@ -3,6 +3,9 @@ use serde::Serialize;
 
 use crate::Payment;
 
+/// Hardened BIP32 index under which the key signing zap requests is derived
+pub(crate) const ZAP_DERIVATION_INDEX: u32 = 142;
+
 /// Contains the result of the entire LNURL-pay interaction, as reported by the LNURL endpoint.
 ///
 /// * `EndpointSuccess` indicates the payment is complete. The endpoint may return a `SuccessActionProcessed`,
@@ -40,7 +43,7 @@ pub(crate) mod tests {
     use crate::bitcoin::hashes::{sha256, Hash};
     use crate::breez_services::tests::{breez_services_with, get_dummy_node_state};
     use crate::lnurl::pay::*;
-    use crate::{test_utils::*, LnUrlPayRequest};
+    use crate::{test_utils::*, LnUrlPayRequest, PaymentDetails};
 
     struct LnurlPayCallbackParams {
         error: Option<String>,
@@ -214,6 +217,7 @@ pub(crate) mod tests {
             domain: "localhost".into(),
             allows_nostr: false,
             nostr_pubkey: None,
+            currencies: None,
             ln_address: None,
         }
     }
@@ -329,6 +333,298 @@ pub(crate) mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_lnurl_pay_verify() -> Result<()> {
+        let mock_rest_client = Arc::new(MockRestClient::new());
+        let pay_req = get_test_pay_req_data(0, 100_000, COMMENT_LENGTH);
+        let preimage = sha256::Hash::hash(&rand_vec_u8(10));
+        let inv = rand_invoice_with_description_hash_and_preimage(
+            pay_req.metadata_str.clone(),
+            preimage,
+        )?;
+        let payment_hash = hex::encode(inv.payment_hash());
+        let verify_url = format!("https://localhost/verify/{payment_hash}");
+
+        mock_rest_client.add_response(MockResponse::new(
+            200,
+            json!({
+                "pr": inv.to_string(),
+                "routes": [],
+                "verify": verify_url,
+            })
+            .to_string(),
+        ));
+
+        let rest_client: Arc<dyn RestClient> = mock_rest_client.clone();
+        let mock_breez_services = breez_services_with(None, Some(rest_client), vec![]).await?;
+        let res = mock_breez_services
+            .lnurl_pay(LnUrlPayRequest {
+                data: pay_req,
+                amount_msat: inv.amount_milli_satoshis().unwrap(),
+                use_trampoline: false,
+                comment: None,
+                payment_label: None,
+                validate_success_action_url: None,
+            })
+            .await?;
+        let LnUrlPayResult::EndpointSuccess { data } = res else {
+            return Err(anyhow!("Unexpected LNURL-pay result"));
+        };
+        assert!(matches!(
+            &data.payment.details,
+            PaymentDetails::Ln { data } if data.lnurl_verify_url == Some(verify_url.clone())
+        ));
+
+        // A settled response maps the preimage into the payment
+        mock_rest_client.add_response(MockResponse::new(
+            200,
+            json!({
+                "status": "OK",
+                "settled": true,
+                "preimage": hex::encode(preimage),
+                "pr": inv.to_string(),
+            })
+            .to_string(),
+        ));
+        let payment = mock_breez_services
+            .verify_lnurl_payment(payment_hash.clone())
+            .await?;
+        assert_eq!(payment.status, PaymentStatus::Complete);
+        assert!(matches!(
+            payment.details,
+            PaymentDetails::Ln { data } if data.payment_preimage == hex::encode(preimage)
+        ));
+        let stored = mock_breez_services
+            .payment_by_hash(payment_hash.clone())
+            .await?
+            .unwrap();
+        assert_eq!(stored.status, PaymentStatus::Complete);
+        assert!(matches!(
+            stored.details,
+            PaymentDetails::Ln { data } if data.payment_preimage == hex::encode(preimage)
+        ));
+
+        // A preimage not matching the payment hash is rejected
+        mock_rest_client.add_response(MockResponse::new(
+            200,
+            json!({
+                "status": "OK",
+                "settled": true,
+                "preimage": hex::encode(rand_vec_u8(32)),
+                "pr": inv.to_string(),
+            })
+            .to_string(),
+        ));
+        assert!(mock_breez_services
+            .verify_lnurl_payment(payment_hash)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lnurl_zap() -> Result<()> {
+        let mock_rest_client = Arc::new(MockRestClient::new());
+        let provider_key = [2u8; 32];
+        let mut pay_req = get_test_pay_req_data(0, 100_000, COMMENT_LENGTH);
+        pay_req.allows_nostr = true;
+        pay_req.nostr_pubkey = Some(
+            NostrEvent::sign(&provider_key, 0, 1, vec![], String::new())?
+                .pubkey,
+        );
+        let zap = ZapRequestData {
+            recipient_pubkey: "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d"
+                .to_string(),
+            relays: vec!["wss://relay.example.com".to_string()],
+            event_id: None,
+            lnurl: None,
+            content: "Zap!".to_string(),
+        };
+        let zap_request = build_zap_request(&[1u8; 32], 50_000, &zap)?;
+        let zap_request_json = serde_json::to_string(&zap_request)?;
+
+        // The provider must commit to the zap request in the invoice description hash
+        let rest_client: Arc<dyn RestClient> = mock_rest_client.clone();
+        let mock_breez_services = breez_services_with(None, Some(rest_client), vec![]).await?;
+        let wrong_inv = rand_invoice_with_description_hash(pay_req.metadata_str.clone())?;
+        mock_lnurl_pay_callback_endpoint_no_success_action(
+            &mock_rest_client,
+            LnurlPayCallbackParams {
+                pr: Some(wrong_inv.to_string()),
+                error: None,
+            },
+        );
+        let pay_request = LnUrlPayRequest {
+            data: pay_req.clone(),
+            amount_msat: 50_000,
+            use_trampoline: false,
+            comment: None,
+            payment_label: None,
+            validate_success_action_url: None,
+        };
+        assert!(mock_breez_services
+            .lnurl_zap_with_request(pay_request.clone(), zap_request.clone())
+            .await
+            .is_err());
+
+        let inv = rand_invoice_with_description_hash(zap_request_json.clone())?;
+        mock_lnurl_pay_callback_endpoint_no_success_action(
+            &mock_rest_client,
+            LnurlPayCallbackParams {
+                pr: Some(inv.to_string()),
+                error: None,
+            },
+        );
+        let res = mock_breez_services
+            .lnurl_zap_with_request(pay_request, zap_request.clone())
+            .await?;
+        let LnUrlPayResult::EndpointSuccess { data } = res else {
+            return Err(anyhow!("Unexpected LNURL-pay result"));
+        };
+        assert!(matches!(
+            &data.payment.details,
+            PaymentDetails::Ln { data } if data.zap_request == Some(zap_request_json.clone())
+        ));
+
+        // A receipt signed by anyone but the provider is rejected
+        let payment_hash = hex::encode(inv.payment_hash());
+        let receipt_tags = vec![
+            vec!["bolt11".to_string(), inv.to_string()],
+            vec!["description".to_string(), zap_request_json],
+        ];
+        let forged_receipt = NostrEvent::sign(
+            &[4u8; 32],
+            zap_request.created_at,
+            ZAP_RECEIPT_KIND,
+            receipt_tags.clone(),
+            String::new(),
+        )?;
+        assert!(mock_breez_services
+            .link_zap_receipt(
+                payment_hash.clone(),
+                serde_json::to_string(&forged_receipt)?
+            )
+            .await
+            .is_err());
+
+        // The zap receipt published by the provider can then be linked to the payment
+        let receipt = NostrEvent::sign(
+            &provider_key,
+            zap_request.created_at,
+            ZAP_RECEIPT_KIND,
+            receipt_tags,
+            String::new(),
+        )?;
+        let receipt_json = serde_json::to_string(&receipt)?;
+        let payment = mock_breez_services
+            .link_zap_receipt(payment_hash.clone(), receipt_json.clone())
+            .await?;
+        assert!(matches!(
+            payment.details,
+            PaymentDetails::Ln { data } if data.zap_receipt == Some(receipt_json)
+        ));
+
+        let other_request = build_zap_request(&[3u8; 32], 50_000, &zap)?;
+        let other_receipt = NostrEvent::sign(
+            &provider_key,
+            zap_request.created_at,
+            ZAP_RECEIPT_KIND,
+            vec![
+                vec!["bolt11".to_string(), inv.to_string()],
+                vec![
+                    "description".to_string(),
+                    serde_json::to_string(&other_request)?,
+                ],
+            ],
+            String::new(),
+        )?;
+        assert!(mock_breez_services
+            .link_zap_receipt(payment_hash, serde_json::to_string(&other_receipt)?)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lnurl_pay_in_currency() -> Result<()> {
+        let mock_rest_client = Arc::new(MockRestClient::new());
+        let rest_client: Arc<dyn RestClient> = mock_rest_client.clone();
+        let mock_breez_services = breez_services_with(None, Some(rest_client), vec![]).await?;
+
+        let usd = |multiplier| LnUrlPayCurrency {
+            code: "USD".to_string(),
+            name: "US Dollar".to_string(),
+            symbol: "$".to_string(),
+            decimals: 2,
+            multiplier,
+        };
+        let mut pay_req = get_test_pay_req_data(0, 100_000, COMMENT_LENGTH);
+        let req = |data: LnUrlPayRequestData, currency: &str| LnUrlPayCurrencyRequest {
+            pay_request: LnUrlPayRequest {
+                data,
+                amount_msat: 0,
+                use_trampoline: false,
+                comment: None,
+                payment_label: None,
+                validate_success_action_url: None,
+            },
+            currency: currency.to_string(),
+            amount: 10,
+            max_slippage_percent: None,
+        };
+
+        // The endpoint doesn't advertise the currency
+        pay_req.currencies = Some(vec![usd(4_975.0)]);
+        assert!(mock_breez_services
+            .lnurl_pay_in_currency(req(pay_req.clone(), "EUR"))
+            .await
+            .is_err());
+
+        // The 50 sat invoice is more than 1% off the advertised 49 sat for 10 cents
+        let inv = rand_invoice_with_description_hash(pay_req.metadata_str.clone())?;
+        mock_lnurl_pay_callback_endpoint_no_success_action(
+            &mock_rest_client,
+            LnurlPayCallbackParams {
+                pr: Some(inv.to_string()),
+                error: None,
+            },
+        );
+        let mut expensive_req = pay_req.clone();
+        expensive_req.currencies = Some(vec![usd(4_900.0)]);
+        assert!(mock_breez_services
+            .lnurl_pay_in_currency(req(expensive_req, "USD"))
+            .await
+            .is_err());
+
+        // but within 1% of 49.75 sat
+        let inv = rand_invoice_with_description_hash(pay_req.metadata_str.clone())?;
+        mock_lnurl_pay_callback_endpoint_no_success_action(
+            &mock_rest_client,
+            LnurlPayCallbackParams {
+                pr: Some(inv.to_string()),
+                error: None,
+            },
+        );
+        let LnUrlPayResult::EndpointSuccess { data } = mock_breez_services
+            .lnurl_pay_in_currency(req(pay_req, "usd"))
+            .await?
+        else {
+            return Err(anyhow!("Unexpected LNURL-pay result"));
+        };
+        let PaymentDetails::Ln { data } = data.payment.details else {
+            return Err(anyhow!("Unexpected payment type"));
+        };
+        let fiat_amount = data.fiat_amount.unwrap();
+        assert_eq!(fiat_amount.currency, "USD");
+        assert_eq!(fiat_amount.amount, 0.1);
+        // 10 cents for 50 sat
+        assert!((fiat_amount.rate - 200_000.0).abs() < 1e-6);
+
+        Ok(())
+    }
+
     static COMMENT_LENGTH: u16 = 10;
 
     #[tokio::test]
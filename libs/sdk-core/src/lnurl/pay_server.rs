@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use sdk_common::prelude::*;
+
+use crate::breez_services::Receiver;
+use crate::models::{PaymentDetails, PaymentStatus, PaymentType, ReceivePaymentRequest};
+use crate::persist::db::SqliteStorage;
+use crate::persist::lnurl_pay_server::LnUrlPayServerInvoiceRecord;
+
+/// Mints the invoices of a [LnUrlPayServer] through the SDK [Receiver], so channel opening
+/// fees and LSP route hints are handled like for any other `receive_payment` call.
+///
+/// The issued invoices are recorded, so the verify endpoint only reports on them and not on any
+/// other payment of the node.
+pub(crate) struct SdkLnUrlPayInvoiceProvider {
+    receiver: Arc<dyn Receiver>,
+    persister: Arc<SqliteStorage>,
+}
+
+impl SdkLnUrlPayInvoiceProvider {
+    pub fn new(receiver: Arc<dyn Receiver>, persister: Arc<SqliteStorage>) -> Self {
+        Self {
+            receiver,
+            persister,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl LnUrlPayInvoiceProvider for SdkLnUrlPayInvoiceProvider {
+    async fn create_invoice(
+        &self,
+        amount_msat: u64,
+        metadata: String,
+        comment: Option<String>,
+    ) -> LnUrlResult<LnUrlPayServerInvoice> {
+        let res = self
+            .receiver
+            .receive_payment(ReceivePaymentRequest {
+                amount_msat,
+                description: metadata,
+                use_description_hash: Some(true),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| LnUrlError::Generic(e.to_string()))?;
+        self.persister
+            .insert_lnurl_pay_server_invoice(&LnUrlPayServerInvoiceRecord {
+                payment_hash: res.ln_invoice.payment_hash.clone(),
+                bolt11: res.ln_invoice.bolt11.clone(),
+                comment,
+                created_at: Utc::now().timestamp(),
+            })
+            .map_err(|e| LnUrlError::Generic(e.to_string()))?;
+        Ok(LnUrlPayServerInvoice {
+            bolt11: res.ln_invoice.bolt11,
+            payment_hash: res.ln_invoice.payment_hash,
+        })
+    }
+
+    async fn invoice_status(
+        &self,
+        payment_hash: &str,
+    ) -> LnUrlResult<Option<LnUrlPayServerInvoiceStatus>> {
+        let Some(invoice) = self
+            .persister
+            .get_lnurl_pay_server_invoice(payment_hash)
+            .map_err(|e| LnUrlError::Generic(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        let payment = self
+            .persister
+            .get_payment_by_hash(payment_hash)
+            .map_err(|e| LnUrlError::Generic(e.to_string()))?;
+        let Some(payment) = payment else {
+            return Ok(Some(LnUrlPayServerInvoiceStatus {
+                bolt11: invoice.bolt11,
+                settled: false,
+                preimage: None,
+            }));
+        };
+        let PaymentDetails::Ln { data } = payment.details else {
+            return Ok(None);
+        };
+        if payment.payment_type != PaymentType::Received {
+            return Ok(None);
+        }
+        let settled = payment.status == PaymentStatus::Complete;
+        Ok(Some(LnUrlPayServerInvoiceStatus {
+            bolt11: invoice.bolt11,
+            settled,
+            preimage: settled.then_some(data.payment_preimage),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use sdk_common::prelude::*;
+
+    use super::SdkLnUrlPayInvoiceProvider;
+    use crate::breez_services::tests::{breez_services_with, get_dummy_node_state};
+    use crate::models::{LnPaymentDetails, Payment, PaymentDetails, PaymentType};
+    use crate::persist::db::SqliteStorage;
+    use crate::test_utils::*;
+    use crate::PaymentStatus;
+
+    #[tokio::test]
+    async fn test_lnurl_pay_server_receive() -> Result<()> {
+        let node_api = Arc::new(MockNodeAPI::new(get_dummy_node_state()));
+        let breez_services = breez_services_with(Some(node_api.clone()), None, vec![]).await?;
+        let server = breez_services.lnurl_pay_server(LnUrlPayServerConfig {
+            domain: "example.com".to_string(),
+            usernames: vec!["alice".to_string()],
+            min_sendable: 1_000,
+            max_sendable: 100_000_000,
+            comment_allowed: 0,
+            description: None,
+            enable_verify: true,
+        })?;
+
+        let res = server
+            .handle(LnUrlServerRequest::from_url(
+                "/lnurlp/alice/callback?amount=2000",
+            )?)
+            .await;
+        let cb: LnUrlPayServerCallbackResponse = serde_json::from_str(&res.body)?;
+        let invoice = parse_invoice(&cb.pr)?;
+        assert_eq!(invoice.amount_msat, Some(2_000));
+        let verify_url = format!(
+            "https://example.com/lnurlp/alice/verify/{}",
+            invoice.payment_hash
+        );
+        assert_eq!(cb.verify, Some(verify_url.clone()));
+
+        let res = server
+            .handle(LnUrlServerRequest::from_url(&verify_url)?)
+            .await;
+        let verify: LnUrlPayServerVerifyResponse = serde_json::from_str(&res.body)?;
+        assert!(!verify.settled);
+        assert_eq!(verify.pr, cb.pr);
+        assert!(verify.preimage.is_none());
+
+        // Payments not issued by the server are not disclosed, even when their hash is guessed
+        let sent = node_api.add_dummy_payment_rand().await?;
+        breez_services.sync().await?;
+        let res = server
+            .handle(LnUrlServerRequest::from_url(&format!(
+                "https://example.com/lnurlp/alice/verify/{}",
+                sent.id
+            ))?)
+            .await;
+        assert_eq!(res.status_code, 404);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lnurl_pay_server_invoice_status() -> Result<()> {
+        let persister = Arc::new(SqliteStorage::new(get_test_working_dir()));
+        persister.init()?;
+        let provider =
+            SdkLnUrlPayInvoiceProvider::new(Arc::new(MockReceiver::default()), persister.clone());
+
+        let invoice = provider
+            .create_invoice(2_000, "metadata".to_string(), Some("thanks".to_string()))
+            .await?;
+        let record = persister
+            .get_lnurl_pay_server_invoice(&invoice.payment_hash)?
+            .unwrap();
+        assert_eq!(record.comment, Some("thanks".to_string()));
+        assert_eq!(record.bolt11, invoice.bolt11);
+
+        let status = provider
+            .invoice_status(&invoice.payment_hash)
+            .await?
+            .unwrap();
+        assert!(!status.settled);
+        assert_eq!(status.bolt11, invoice.bolt11);
+
+        let payment = |id: &str, payment_type| Payment {
+            id: id.to_string(),
+            payment_type,
+            status: PaymentStatus::Complete,
+            amount_msat: 2_000,
+            details: PaymentDetails::Ln {
+                data: LnPaymentDetails {
+                    payment_hash: id.to_string(),
+                    payment_preimage: "11".repeat(32),
+                    bolt11: "lnbc1other".to_string(),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        persister.insert_or_update_payments(
+            &[
+                payment(&invoice.payment_hash, PaymentType::Received),
+                payment(&"ff".repeat(32), PaymentType::Sent),
+            ],
+            false,
+        )?;
+
+        let status = provider
+            .invoice_status(&invoice.payment_hash)
+            .await?
+            .unwrap();
+        assert!(status.settled);
+        assert_eq!(status.bolt11, invoice.bolt11);
+        assert_eq!(status.preimage, Some("11".repeat(32)));
+
+        // Known payments that were not issued by the server are reported as unknown
+        assert!(provider.invoice_status(&"ff".repeat(32)).await?.is_none());
+        assert!(provider.invoice_status(&"00".repeat(32)).await?.is_none());
+
+        Ok(())
+    }
+}
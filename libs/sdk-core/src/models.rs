@@ -522,7 +522,13 @@ impl Config {
 
 #[derive(Clone)]
 pub enum NodeConfig {
-    Greenlight { config: GreenlightNodeConfig },
+    Greenlight {
+        config: GreenlightNodeConfig,
+    },
+    /// A self-hosted LDK node, only available with the `ldk` feature
+    Ldk {
+        config: LdkNodeConfig,
+    },
 }
 
 #[derive(Clone, Serialize)]
@@ -538,6 +544,75 @@ pub struct GreenlightNodeConfig {
     pub invite_code: Option<String>,
 }
 
+/// Configuration of an LDK node. The endpoints must match the [Config::network], which is checked
+/// when the node is built. Only [Network::Regtest] is supported for now.
+#[derive(Clone, Debug)]
+pub struct LdkNodeConfig {
+    pub chain_source: LdkChainSource,
+    pub gossip_source: LdkGossipSource,
+    /// URL of the VSS (Versioned Storage Service) server the node state is stored in
+    pub vss_url: String,
+    /// The LSPS2 service used to open channels just in time
+    pub lsp: LdkLspConfig,
+}
+
+impl LdkNodeConfig {
+    /// Endpoints of the local regtest environment
+    pub fn regtest() -> Self {
+        Self {
+            chain_source: LdkChainSource::Esplora {
+                url: "http://localhost:30000".to_string(),
+            },
+            gossip_source: LdkGossipSource::Rgs {
+                url: "http://localhost:8011/v2".to_string(),
+            },
+            vss_url: "http://localhost:3080/vss".to_string(),
+            lsp: LdkLspConfig {
+                node_id: "02b49b94e068e05c04c2ac98e096a06202d04920daec25d82f7898e21901f15d81"
+                    .to_string(),
+                address: "localhost:9735".to_string(),
+                token: None,
+            },
+        }
+    }
+}
+
+/// Where an LDK node gets chain data from
+#[derive(Clone, Debug)]
+pub enum LdkChainSource {
+    Esplora {
+        url: String,
+    },
+    Electrum {
+        url: String,
+    },
+    BitcoindRpc {
+        host: String,
+        port: u16,
+        user: String,
+        password: String,
+    },
+}
+
+/// Where an LDK node gets the network graph from
+#[derive(Clone, Debug)]
+pub enum LdkGossipSource {
+    /// Gossip received from peers
+    P2p,
+    /// Snapshots downloaded from a Rapid Gossip Sync server
+    Rgs { url: String },
+}
+
+#[derive(Clone, Debug)]
+pub struct LdkLspConfig {
+    /// The LSP node id, hex encoded
+    pub node_id: String,
+    /// The LSP address, as `host:port`
+    pub address: String,
+    /// Token authenticating the node with the LSP, if it requires one
+    pub token: Option<String>,
+}
+
 /// Indicates the different kinds of supported environments for [crate::BreezServices].
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, EnumString)]
 pub enum EnvironmentType {
@@ -562,6 +637,101 @@ pub struct GreenlightDeviceCredentials {
     pub device: Vec<u8>,
 }
 
+/// A device authorized to connect to the Greenlight node, as recorded in the device registry that
+/// is synced between the instances of the wallet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GreenlightDevice {
+    /// Hex encoded hash of the device key, which is kept when its certificate is renewed
+    pub id: String,
+    pub label: String,
+    /// Epoch time, in seconds
+    pub registered_at: i64,
+    /// Epoch time, in seconds. When an instance of the wallet last reported running a signer on
+    /// the device, as last synced from it
+    pub last_seen_at: Option<i64>,
+    /// Whether the device reported running a signer recently. Another device doing so also signs
+    /// for the node. Signers not run by this SDK, or whose reports weren't synced yet, are missed.
+    pub signer_active: bool,
+    /// Whether these are the credentials this instance is connected with
+    pub is_current: bool,
+}
+
+/// Represents a register device request.
+#[derive(Clone, Debug)]
+pub struct RegisterDeviceRequest {
+    pub label: String,
+}
+
+/// Represents a register device response.
+pub struct RegisterDeviceResponse {
+    pub device: GreenlightDevice,
+    /// The credentials to hand over to the new device
+    pub credentials: NodeCredentials,
+}
+
+/// Identifies a key derived from the wallet seed for an app, see
+/// [crate::BreezServices::app_public_key]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppKey {
+    /// Separates the keys of different apps or uses, e.g. `nostr` or `com.example.chat`. Up to
+    /// 64 bytes.
+    pub namespace: String,
+    /// Index of the key within the namespace, below 2^31
+    pub index: u32,
+}
+
+/// The public key of an [AppKey], hex encoded in its two usual forms
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppPublicKey {
+    /// The 33 bytes compressed key, for ECDSA signatures and ECDH
+    pub public_key: String,
+    /// The 32 bytes x-only key, for BIP340 Schnorr signatures like Nostr's
+    pub x_only_public_key: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppSignatureScheme {
+    Ecdsa,
+    Schnorr,
+}
+
+/// Represents a sign with app key request.
+#[derive(Clone, Debug)]
+pub struct SignWithAppKeyRequest {
+    pub key: AppKey,
+    /// The message, whose SHA256 digest is signed
+    pub message: Vec<u8>,
+    pub scheme: AppSignatureScheme,
+    /// If true, the `message` is a 32 bytes digest signed as is, e.g. the id of a Nostr event
+    pub prehashed: Option<bool>,
+}
+
+/// Represents a sign with app key response.
+#[derive(Clone, Debug)]
+pub struct SignWithAppKeyResponse {
+    /// The hex encoded 64 bytes signature, compact for ECDSA
+    pub signature: String,
+}
+
+/// Represents an app key ECDH request.
+#[derive(Clone, Debug)]
+pub struct AppKeyEcdhRequest {
+    pub key: AppKey,
+    /// The hex encoded compressed public key of the other party
+    pub peer_public_key: String,
+    /// If true, the shared secret is the bare x-coordinate of the shared point instead of its
+    /// SHA256, as NIP-44 expects
+    pub raw: Option<bool>,
+}
+
+/// Represents an app key ECDH response.
+#[derive(Clone, Debug)]
+pub struct AppKeyEcdhResponse {
+    /// The hex encoded SHA256 of the shared point, or its x-coordinate when
+    /// [AppKeyEcdhRequest::raw], the same for both parties
+    pub shared_secret: String,
+}
+
 /// Represents a configure node request.
 #[derive(Default)]
 pub struct ConfigureNodeRequest {
@@ -574,6 +744,18 @@ pub struct ConnectRequest {
     pub seed: Vec<u8>,
     /// If true, only restores an existing node and otherwise result in an error
     pub restore_only: Option<bool>,
+    /// `unix://<path>` socket of a signer holding the seed in another process, see
+    /// `signer::serve`. Only supported by Greenlight nodes on Unix.
+    pub remote_signer_url: Option<String>,
+}
+
+/// Represents a watch-only connect request, see [crate::BreezServices::connect_watch_only]
+pub struct ConnectWatchOnlyRequest {
+    pub config: Config,
+    /// The credentials returned by [crate::BreezServices::watch_only_credentials]
+    pub node_credentials: NodeCredentials,
+    /// The key returned by [crate::BreezServices::backup_encryption_key]
+    pub backup_encryption_key: Vec<u8>,
 }
 
 /// Different types of supported filters which can be applied when retrieving the transaction list
@@ -680,6 +862,9 @@ pub struct Payment {
     pub description: Option<String>,
     pub details: PaymentDetails,
     pub metadata: Option<String>,
+    /// The rates snapshot taken when the payment settled, see
+    /// [crate::BreezServices::payment_fiat_value]. Empty if none was recorded.
+    pub fiat_rates: Vec<HistoricalRate>,
 }
 
 /// Represents a payments external information.
@@ -688,11 +873,17 @@ pub struct PaymentExternalInfo {
     pub lnurl_pay_success_action: Option<SuccessActionProcessed>,
     pub lnurl_pay_domain: Option<String>,
     pub lnurl_pay_comment: Option<String>,
+    pub lnurl_verify_url: Option<String>,
+    pub zap_request: Option<String>,
+    pub zap_receipt: Option<String>,
+    /// The key the recipient's LNURL provider signs zap receipts with
+    pub zap_nostr_pubkey: Option<String>,
     pub lnurl_metadata: Option<String>,
     pub ln_address: Option<String>,
     pub lnurl_withdraw_endpoint: Option<String>,
     pub attempted_amount_msat: Option<u64>,
     pub attempted_error: Option<String>,
+    pub fiat_amount: Option<FiatAmount>,
 }
 
 /// Represents a list payments request.
@@ -707,6 +898,314 @@ pub struct ListPaymentsRequest {
     pub include_failures: Option<bool>,
     pub offset: Option<u32>,
     pub limit: Option<u32>,
+    /// Full-text search over the description, label, LN address, LNURL-pay domain and comment,
+    /// and metadata of the payments. Each word of the query must match the start of a word.
+    pub search: Option<String>,
+    pub min_amount_msat: Option<u64>,
+    pub max_amount_msat: Option<u64>,
+    /// If set, only payments with one of these statuses are listed and `include_failures`
+    /// is ignored
+    pub statuses: Option<Vec<PaymentStatus>>,
+    /// Hex prefix of the payment hash, or of the funding txid for closed channels
+    pub payment_hash_prefix: Option<String>,
+    /// Only lists payments sent to this node
+    pub counterparty_pubkey: Option<String>,
+    /// Only lists payments attributed to this [Contact]
+    pub contact_id: Option<String>,
+    /// Only lists the payments that come after this position, in the order payments are listed.
+    ///
+    /// To page through a large history, set it from the last payment of the previous page
+    /// rather than increasing `offset`.
+    pub cursor: Option<PaymentCursor>,
+}
+
+/// A position in the list of payments, see [ListPaymentsRequest::cursor]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentCursor {
+    pub payment_time: i64,
+    pub payment_id: String,
+}
+
+impl From<&Payment> for PaymentCursor {
+    fn from(payment: &Payment) -> Self {
+        PaymentCursor {
+            payment_time: payment.payment_time,
+            payment_id: payment.id.clone(),
+        }
+    }
+}
+
+/// An address book entry, automatically attributed to the payments made to its Lightning Address,
+/// node id or LNURL-pay domain. See [LnPaymentDetails::contact_id]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub id: String,
+    pub name: String,
+    pub ln_address: Option<String>,
+    pub node_pubkey: Option<String>,
+    pub lnurl_pay_domain: Option<String>,
+    pub note: Option<String>,
+    /// Epoch time, in seconds
+    pub created_at: i64,
+    /// Epoch time, in seconds
+    pub updated_at: i64,
+}
+
+/// Represents an add contact request.
+#[derive(Clone, Debug, Default)]
+pub struct AddContactRequest {
+    pub name: String,
+    pub ln_address: Option<String>,
+    pub node_pubkey: Option<String>,
+    pub lnurl_pay_domain: Option<String>,
+    pub note: Option<String>,
+}
+
+/// Represents an update contact request. All the fields of the contact are replaced.
+#[derive(Clone, Debug, Default)]
+pub struct UpdateContactRequest {
+    pub id: String,
+    pub name: String,
+    pub ln_address: Option<String>,
+    pub node_pubkey: Option<String>,
+    pub lnurl_pay_domain: Option<String>,
+    pub note: Option<String>,
+}
+
+/// Rules every outgoing payment is checked against, see [crate::BreezServices::set_spending_policy].
+///
+/// Payees are matched by node id, Lightning Address or on-chain address, and domains by
+/// LNURL-pay domain or Lightning Address domain, ignoring case.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpendingPolicy {
+    /// The largest amount a single payment can send, fees excluded
+    pub max_payment_msat: Option<u64>,
+    /// The most that can be sent in the last 24 hours, including the fees already paid and the
+    /// most the new payment may cost
+    pub daily_budget_msat: Option<u64>,
+    /// The most that can be sent in the last 7 days, counted as `daily_budget_msat`
+    pub weekly_budget_msat: Option<u64>,
+    /// If set, along with `allowed_domains`, only these payees can be paid
+    pub allowed_payees: Vec<String>,
+    pub denied_payees: Vec<String>,
+    /// If set, along with `allowed_payees`, only payees of these domains can be paid
+    pub allowed_domains: Vec<String>,
+    pub denied_domains: Vec<String>,
+    /// The most a single payment may cost in fees.
+    ///
+    /// Lightning fees are only known once the payment is done, so Lightning payments are sent
+    /// with their routing fees capped at this limit, overriding [Config::maxfee_percent] and
+    /// [Config::exemptfee_msat] when lower. On-chain payments are checked against their total fees.
+    pub max_fee_msat: Option<u64>,
+}
+
+/// Where a [RecurringPayment] is sent
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecurringPaymentDestination {
+    /// An LNURL-pay link or Lightning Address, resolved again before every payment
+    LnUrlPay { lnurl: String },
+    /// A node paid with keysend
+    Keysend { node_id: String },
+}
+
+/// How much a [RecurringPayment] sends every time
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecurringPaymentAmount {
+    Bitcoin {
+        amount_msat: u64,
+    },
+    /// An amount of a fiat currency, converted at the exchange rate of the time of each payment
+    Fiat {
+        currency: String,
+        amount: f64,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString, Display, Serialize, Deserialize)]
+pub enum RecurringPaymentInterval {
+    Daily,
+    Weekly,
+    /// Monthly on the day of the month of the start time, or the last day of shorter months
+    Monthly,
+}
+
+/// A payment made by the SDK on a schedule, see [crate::BreezServices::add_recurring_payment]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecurringPayment {
+    pub id: String,
+    pub destination: RecurringPaymentDestination,
+    pub amount: RecurringPaymentAmount,
+    pub interval: RecurringPaymentInterval,
+    /// Sent along LNURL payments, if the endpoint accepts comments
+    pub comment: Option<String>,
+    /// Epoch time, in seconds, of the first payment. The next ones are scheduled from it.
+    pub start_time: i64,
+    /// Epoch time, in seconds, from which the next payment is due
+    pub next_payment_time: i64,
+    pub paused: bool,
+    /// Epoch time, in seconds
+    pub created_at: i64,
+    /// Epoch time, in seconds
+    pub updated_at: i64,
+}
+
+/// Represents an add recurring payment request.
+#[derive(Clone, Debug)]
+pub struct AddRecurringPaymentRequest {
+    pub destination: RecurringPaymentDestination,
+    pub amount: RecurringPaymentAmount,
+    pub interval: RecurringPaymentInterval,
+    pub comment: Option<String>,
+    /// Epoch time, in seconds, of the first payment. Defaults to now.
+    pub start_time: Option<i64>,
+}
+
+/// The attempts to make one of the scheduled payments of a [RecurringPayment]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecurringPaymentRun {
+    pub recurring_payment_id: String,
+    /// Epoch time, in seconds, the payment was scheduled at
+    pub scheduled_time: i64,
+    /// The id of the resulting [Payment], once an attempt succeeded
+    pub payment_id: Option<String>,
+    /// The error of the last failed attempt
+    pub error: Option<String>,
+    pub attempts: u32,
+    /// Epoch time, in seconds, of the last attempt
+    pub updated_at: i64,
+    /// The [LnPaymentDetails::label] of the last attempt, recorded before it's sent and kept
+    /// while its outcome is unknown, like after a timeout. No new attempt is made until then.
+    pub attempt_label: Option<String>,
+}
+
+/// The length of the time buckets of [StatsResponse::series]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatsInterval {
+    Day,
+    /// Weeks start on Monday
+    Week,
+    Month,
+}
+
+/// Represents a payment stats request.
+pub struct StatsRequest {
+    /// Selects the payments to aggregate, like for [crate::BreezServices::list_payments]
+    pub filter: ListPaymentsRequest,
+    /// If set, the stats are also returned as a series of time buckets of this length
+    pub interval: Option<StatsInterval>,
+}
+
+/// Aggregates of the payments of a time bucket, see [StatsResponse]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentStatsBucket {
+    /// Epoch time, in seconds, at which the bucket starts (UTC)
+    pub start_timestamp: i64,
+    pub sent_msat: u64,
+    pub received_msat: u64,
+    pub routing_fees_msat: u64,
+    pub channel_opening_fees_msat: u64,
+    pub swap_fees_msat: u64,
+    pub pending_count: u32,
+    pub complete_count: u32,
+    pub failed_count: u32,
+}
+
+/// Represents a payment stats response.
+///
+/// Amounts and fees only account for completed payments, while the counts cover all the
+/// payments matching the filter.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsResponse {
+    /// Sent payments and closed channels, excluding fees
+    pub total_sent_msat: u64,
+    pub total_received_msat: u64,
+    /// Lightning routing fees of sent payments
+    pub routing_fees_msat: u64,
+    /// Fees paid to the LSP to open channels when receiving payments
+    pub channel_opening_fees_msat: u64,
+    /// Fees of swaps in and out, including their on-chain fees
+    pub swap_fees_msat: u64,
+    pub pending_count: u32,
+    pub complete_count: u32,
+    pub failed_count: u32,
+    /// Empty unless [StatsRequest::interval] is set
+    pub series: Vec<PaymentStatsBucket>,
+}
+
+/// The file formats supported by [crate::BreezServices::export_payments]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    /// One row per payment, including fees and swap transaction ids
+    Csv,
+    /// An OFX 2 bank statement, as imported by accounting software
+    Ofx,
+    /// BIP329 wallet labels for the on-chain transactions of swaps and reverse swaps
+    ///
+    /// See <https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki>
+    Bip329,
+}
+
+/// Represents an export payments request.
+pub struct ExportRequest {
+    pub format: ExportFormat,
+    /// Selects the payments to export, like for [crate::BreezServices::list_payments]
+    pub filter: ListPaymentsRequest,
+    /// The file to write the export to. An existing file is overwritten.
+    pub file_path: String,
+}
+
+/// Represents an export payments response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportResponse {
+    pub file_path: String,
+    /// The number of payments exported
+    pub payments_count: u64,
+}
+
+/// A fiat exchange rate, as it was at a given time
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoricalRate {
+    pub coin: String,
+    /// The price of one bitcoin in this currency
+    pub value: f64,
+    /// Epoch time, in seconds, at which the rate was recorded
+    pub timestamp: i64,
+}
+
+// Rates are recorded as returned by the rates API, never NaN
+impl Eq for HistoricalRate {}
+
+/// The fiat value of a payment, at the exchange rate in effect when it settled
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaymentFiatValue {
+    pub payment_id: String,
+    /// The rate used for the valuation, or None if no rate was recorded around the payment time
+    pub rate: Option<HistoricalRate>,
+    pub amount: Option<f64>,
+    pub fee: Option<f64>,
+}
+
+/// Represents a fiat valuation request.
+pub struct FiatValuationRequest {
+    /// The fiat currency id, as in [Rate::coin]
+    pub currency: String,
+    /// Selects the payments to value, like for [crate::BreezServices::list_payments]
+    pub filter: ListPaymentsRequest,
+}
+
+/// Represents a fiat valuation response.
+///
+/// Payments without a known rate are listed, but left out of the totals.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FiatValuationResponse {
+    pub currency: String,
+    pub payments: Vec<PaymentFiatValue>,
+    pub total_received: f64,
+    /// The value of sent payments and closed channels, including fees
+    pub total_sent: f64,
+    pub total_fees: f64,
+    pub unvalued_payments_count: u32,
 }
 
 /// Represents a payment response.
@@ -775,6 +1274,27 @@ pub struct LnPaymentDetails {
     /// Only set for [PaymentType::Sent] payments if the user sent the comment using LNURL-pay
     pub lnurl_pay_comment: Option<String>,
 
+    /// Only set for [PaymentType::Sent] payments where the LNURL-pay endpoint returned a LUD-21
+    /// verify URL. See [crate::BreezServices::verify_lnurl_payment]
+    pub lnurl_verify_url: Option<String>,
+
+    /// Only set for [PaymentType::Sent] zaps, the JSON encoded NIP-57 zap request (kind 9734 event).
+    /// See [crate::BreezServices::lnurl_zap]
+    pub zap_request: Option<String>,
+
+    /// Only set for [PaymentType::Sent] zaps, the JSON encoded zap receipt (kind 9735 event) once
+    /// linked with [crate::BreezServices::link_zap_receipt]
+    pub zap_receipt: Option<String>,
+
+    /// Set for payments denominated in a fiat currency, either received with
+    /// [crate::BreezServices::receive_fiat_payment] or sent with
+    /// [crate::BreezServices::lnurl_pay_in_currency]
+    pub fiat_amount: Option<FiatAmount>,
+
+    /// The [Contact] this payment is attributed to, matched by Lightning Address, node id (for
+    /// sent payments) or LNURL-pay domain
+    pub contact_id: Option<String>,
+
     /// Only set for [PaymentType::Sent] payments that are sent to a Lightning Address
     pub ln_address: Option<String>,
 
@@ -843,6 +1363,48 @@ pub struct ReceivePaymentRequest {
     pub cltv: Option<u32>,
 }
 
+/// Represents a request to receive a payment denominated in a fiat currency, see
+/// [crate::BreezServices::receive_fiat_payment]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReceiveFiatPaymentRequest {
+    /// The fiat currency id, as in [Rate::coin]
+    pub currency: String,
+    /// The amount, in units of the currency
+    pub amount: f64,
+    /// Percentage added on top of the converted amount, to cover the exchange rate moving
+    /// before the invoice is paid
+    pub spread_percent: Option<f64>,
+    pub description: String,
+    pub opening_fee_params: Option<OpeningFeeParams>,
+    pub use_description_hash: Option<bool>,
+    pub expiry: Option<u32>,
+    pub cltv: Option<u32>,
+}
+
+/// The fiat amount a payment was denominated in, and the exchange rate used to convert it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FiatAmount {
+    /// The fiat currency id, as in [Rate::coin]
+    pub currency: String,
+    /// The amount, in units of the currency
+    pub amount: f64,
+    /// The price of one bitcoin in the currency
+    pub rate: f64,
+    /// The spread, in percent, applied on top of the converted amount
+    pub spread_percent: f64,
+}
+
+// The fields are validated to be finite before a FiatAmount is created, so equality is total
+impl Eq for FiatAmount {}
+
+impl FiatAmount {
+    /// The amount in millisatoshis, after applying the spread
+    pub(crate) fn to_msat(&self) -> u64 {
+        (self.amount / self.rate * (1.0 + self.spread_percent / 100.0) * 100_000_000_000.0).round()
+            as u64
+    }
+}
+
 /// Represents a receive payment response.
 ///
 /// Breez SDK may have to open a new channel to receive this payment. In that case, the channel will
@@ -858,6 +1420,87 @@ pub struct ReceivePaymentResponse {
     pub opening_fee_msat: Option<u64>,
 }
 
+/// Request to create a hold invoice, see [crate::BreezServices::create_hold_invoice]. Only
+/// supported on LDK nodes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateHoldInvoiceRequest {
+    /// Hex-encoded hash of the preimage that settles the invoice, which the node doesn't know
+    pub payment_hash: String,
+    pub amount_msat: u64,
+    pub description: String,
+    /// If set to true, then the bolt11 invoice returned includes the description hash.
+    pub use_description_hash: Option<bool>,
+    /// If specified, set the time the invoice is valid for, in seconds.
+    pub expiry: Option<u32>,
+    /// If specified, sets the min_final_cltv_expiry for the invoice. Not supported by LDK
+    /// nodes, which reject it.
+    pub cltv: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString, Display, Serialize, Deserialize)]
+pub enum HoldInvoiceStatus {
+    /// Waiting for the payment
+    Open,
+    /// The payment's HTLCs are held by the node until the invoice is settled or cancelled
+    Accepted,
+    Settled,
+    Cancelled,
+}
+
+/// An invoice whose incoming payment is held until it is explicitly settled or cancelled. Only
+/// LDK nodes support hold invoices.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HoldInvoice {
+    pub payment_hash: String,
+    pub bolt11: String,
+    pub amount_msat: u64,
+    pub status: HoldInvoiceStatus,
+    /// The amount of the held HTLCs, once accepted
+    pub accepted_amount_msat: Option<u64>,
+    /// The block height at which the node fails back the held HTLCs if the invoice isn't settled
+    pub settle_deadline: Option<u32>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString, Display, Serialize, Deserialize)]
+pub enum InvoiceStatus {
+    Pending,
+    Paid,
+    Expired,
+    Cancelled,
+}
+
+/// An invoice created with [crate::BreezServices::receive_payment]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Invoice {
+    pub payment_hash: String,
+    pub bolt11: String,
+    pub description: String,
+    /// The amount the payer is asked to pay
+    pub payer_amount_msat: u64,
+    /// The amount received, once the channel opening fee, if any, is deducted
+    pub amount_msat: u64,
+    /// Set if a channel is opened when the invoice is paid
+    pub opening_fee_msat: Option<u64>,
+    pub opening_fee_params: Option<OpeningFeeParams>,
+    pub status: InvoiceStatus,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ListInvoicesRequest {
+    pub statuses: Option<Vec<InvoiceStatus>>,
+    /// Epoch time, in seconds. If set, acts as filter for minimum invoice creation time, inclusive.
+    pub from_timestamp: Option<i64>,
+    /// Epoch time, in seconds. If set, acts as filter for maximum invoice creation time, exclusive.
+    pub to_timestamp: Option<i64>,
+    pub offset: Option<u32>,
+    pub limit: Option<u32>,
+}
+
 /// Represents a send payment request.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SendPaymentRequest {
@@ -1159,6 +1802,48 @@ impl ToSql for OpeningFeeParams {
     }
 }
 
+impl FromSql for FiatAmount {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        serde_json::from_str(value.as_str()?).map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+impl ToSql for FiatAmount {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(
+            serde_json::to_string(&self).map_err(|_| FromSqlError::InvalidType)?,
+        ))
+    }
+}
+
+impl FromSql for RecurringPaymentDestination {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        serde_json::from_str(value.as_str()?).map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+impl ToSql for RecurringPaymentDestination {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(
+            serde_json::to_string(&self).map_err(|_| FromSqlError::InvalidType)?,
+        ))
+    }
+}
+
+impl FromSql for RecurringPaymentAmount {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        serde_json::from_str(value.as_str()?).map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+impl ToSql for RecurringPaymentAmount {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(
+            serde_json::to_string(&self).map_err(|_| FromSqlError::InvalidType)?,
+        ))
+    }
+}
+
 pub enum DynamicFeeType {
     Cheapest,
     Longest,
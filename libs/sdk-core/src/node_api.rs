@@ -64,6 +64,10 @@ pub enum NodeError {
     #[error("{0}")]
     InsufficientFunds(String),
 
+    /// The node is run by another instance, which must be taken over first
+    #[error("{0}")]
+    ReadOnly(String),
+
     #[error("invoice already paid")]
     InvoiceAlreadyPaid,
 }
@@ -115,6 +119,43 @@ pub struct CreateInvoiceRequest {
     pub cltv: Option<u32>,
 }
 
+/// An invoice for a payment hash whose preimage the node doesn't know, see
+/// [NodeAPI::create_hold_invoice]
+pub struct HoldInvoiceRequest {
+    pub payment_hash: Vec<u8>,
+    pub amount_msat: u64,
+    pub description: String,
+    pub use_description_hash: Option<bool>,
+    pub expiry: Option<u32>,
+    pub cltv: Option<u32>,
+}
+
+/// The HTLCs of a hold invoice payment, held by the node until the invoice is settled or cancelled
+#[derive(Debug, Clone)]
+pub struct HeldHtlcs {
+    pub payment_hash: Vec<u8>,
+    pub amount_msat: u64,
+    /// The block height by which the HTLCs must be settled, if the node enforces one
+    pub claim_deadline: Option<u32>,
+}
+
+/// The state of the lease that lets a single instance run the node at a time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeLockStatus {
+    /// This instance holds the lease and runs the node
+    Held,
+    /// Another instance held the lease on connect, the node isn't run until it's taken over
+    HeldByOtherInstance { instance_id: String },
+    /// Another instance took the lease over, the node was stopped
+    Lost { instance_id: Option<String> },
+}
+
+/// Credentials issued for another device, see [NodeAPI::issue_device_credentials]
+pub struct IssuedDeviceCredentials {
+    pub device_id: String,
+    pub credentials: NodeCredentials,
+}
+
 pub struct FetchBolt11Result {
     pub bolt11: String,
     pub payer_amount_msat: Option<u64>,
@@ -155,7 +196,12 @@ impl TryFrom<IncomingPayment> for Payment {
                     lnurl_success_action: None, // For received payments, this is None
                     lnurl_pay_domain: None,     // For received payments, this is None
                     lnurl_pay_comment: None,    // For received payments, this is None
+                    lnurl_verify_url: None,     // For received payments, this is None
+                    zap_request: None,          // For received payments, this is None
+                    zap_receipt: None,          // For received payments, this is None
+                    contact_id: None,           // For received payments, this is None
                     lnurl_metadata: None,       // For received payments, this is None
+                    fiat_amount: None,
                     ln_address: None,
                     lnurl_withdraw_endpoint: None,
                     swap_info: None,
@@ -165,6 +211,7 @@ impl TryFrom<IncomingPayment> for Payment {
                 },
             },
             metadata: None,
+            fiat_rates: Vec::new(),
         })
     }
 }
@@ -184,12 +231,15 @@ pub trait NodeAPI: Send + Sync {
         sync_state: Option<Value>,
         match_local_balance: bool,
     ) -> NodeResult<SyncResponse>;
-    /// As per the `pb::PayRequest` docs, `amount_msat` is only needed when the invoice doesn't specify an amount
+    /// As per the `pb::PayRequest` docs, `amount_msat` is only needed when the invoice doesn't specify an amount.
+    ///
+    /// `max_fee_msat` overrides the configured routing fee limits, see [crate::Config::maxfee_percent].
     async fn send_payment(
         &self,
         bolt11: String,
         amount_msat: Option<u64>,
         label: Option<String>,
+        max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment>;
     async fn send_spontaneous_payment(
         &self,
@@ -197,6 +247,7 @@ pub trait NodeAPI: Send + Sync {
         amount_msat: u64,
         extra_tlvs: Option<Vec<TlvEntry>>,
         label: Option<String>,
+        max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment>;
     async fn send_trampoline_payment(
         &self,
@@ -239,13 +290,42 @@ pub trait NodeAPI: Send + Sync {
     async fn stream_log_messages(
         &self,
     ) -> NodeResult<Pin<Box<dyn Stream<Item = String> + Send>>>;
+
+    /// Creates an invoice whose incoming HTLCs are held, rather than claimed, until
+    /// [NodeAPI::settle_hold_invoice] or [NodeAPI::cancel_hold_invoice] is called.
+    ///
+    /// Only LDK nodes support hold invoices, Greenlight nodes return an error.
+    async fn create_hold_invoice(&self, request: HoldInvoiceRequest) -> NodeResult<String>;
+    /// Claims the held HTLCs of the hold invoice paying to the hash of `preimage`
+    async fn settle_hold_invoice(&self, preimage: Vec<u8>) -> NodeResult<()>;
+    /// Fails back the held HTLCs of a hold invoice, if any, and rejects any later ones
+    async fn cancel_hold_invoice(&self, payment_hash: Vec<u8>) -> NodeResult<()>;
+    /// Streams the hold invoice payments as soon as their HTLCs are held
+    async fn stream_held_htlcs(&self) -> NodeResult<Pin<Box<dyn Stream<Item = HeldHtlcs> + Send>>>;
+    /// Streams the changes of the node lease, starting with its current status
+    async fn stream_node_lock_status(
+        &self,
+    ) -> NodeResult<Pin<Box<dyn Stream<Item = NodeLockStatus> + Send>>>;
+    /// Takes the node lease over from the instance holding it
+    async fn take_over_node_lock(&self) -> NodeResult<()>;
+    /// Identifier of the device credentials this instance connects with
+    async fn device_id(&self) -> NodeResult<String>;
+    /// Issues new credentials to connect another device to the same node
+    async fn issue_device_credentials(&self) -> NodeResult<IssuedDeviceCredentials>;
+    /// Issues credentials which can only read the node, for a watch-only instance
+    async fn issue_watch_only_credentials(&self) -> NodeResult<IssuedDeviceCredentials>;
     async fn static_backup(&self) -> NodeResult<Vec<String>>;
     async fn execute_command(&self, command: String) -> NodeResult<Value>;
     async fn generate_diagnostic_data(&self) -> NodeResult<Value>;
     async fn sign_message(&self, message: &str) -> NodeResult<String>;
     async fn check_message(&self, message: &str, pubkey: &str, signature: &str)
         -> NodeResult<bool>;
+    /// Sends a BOLT1 custom message to a peer.
+    ///
+    /// Only Greenlight nodes support custom messages, LDK nodes return an error: LDK Node doesn't
+    /// expose a custom message handler, and talks LSPS with its LSP itself.
     async fn send_custom_message(&self, message: CustomMessage) -> NodeResult<()>;
+    /// Streams the BOLT1 custom messages received from peers, only on Greenlight nodes
     async fn stream_custom_messages(
         &self,
     ) -> NodeResult<Pin<Box<dyn Stream<Item = Result<CustomMessage>> + Send>>>;
@@ -9,7 +9,8 @@ use crate::ldk::{Ldk, LdkBackupTransport};
 use crate::models::{Config, LspAPI};
 use crate::node_api::{NodeAPI, NodeResult};
 use crate::persist::db::SqliteStorage;
-use crate::NodeConfig;
+use crate::signer::SignerAPI;
+use crate::{NodeConfig, NodeCredentials};
 
 pub struct NodeImpls {
     pub node: Arc<dyn NodeAPI>,
@@ -22,30 +23,20 @@ pub struct NodeImpls {
 pub async fn build_node(
     config: Config,
     seed: Vec<u8>,
+    signer: Option<Arc<dyn SignerAPI>>,
     restore_only: Option<bool>,
     persister: Arc<SqliteStorage>,
 ) -> NodeResult<NodeImpls> {
-    // TODO: Add NodeConfig::Ldk variant once the feature is developed.
-    #[cfg(feature = "ldk")]
-    {
-        let ldk = Ldk::build(config, &seed, restore_only).await?;
-        let ldk = Arc::new(ldk);
-        let backup_transport = Arc::new(LdkBackupTransport {});
-        let lsp: Option<Arc<dyn LspAPI>> = Some(ldk.clone());
-        let receiver: Option<Arc<dyn Receiver>> = Some(ldk.clone());
-        return Ok(NodeImpls {
-            node: ldk,
-            backup_transport,
-            lsp,
-            receiver,
-        });
-    }
-    #[allow(unreachable_code)]
-    match config.node_config {
+    match config.node_config.clone() {
         NodeConfig::Greenlight { .. } => {
             #[cfg(feature = "greenlight")]
             {
-                let greenlight = Greenlight::connect(config, seed, restore_only, persister).await?;
+                let signer = match signer {
+                    Some(signer) => signer,
+                    None => Arc::new(crate::signer::LocalSigner::new(seed, config.network)?),
+                };
+                let greenlight =
+                    Greenlight::connect(config, signer, restore_only, persister).await?;
                 let greenlight = Arc::new(greenlight);
                 let backup_transport = GLBackupTransport {
                     inner: greenlight.clone(),
@@ -63,5 +54,67 @@ pub async fn build_node(
                 "Misconfigration: `greenlight` feature must be enabled.",
             ))
         }
+        NodeConfig::Ldk { config: ldk_config } => {
+            #[cfg(feature = "ldk")]
+            {
+                if signer.is_some() {
+                    // LDK Node signs with the keys it derives from the seed it's built with
+                    return Err(crate::node_api::NodeError::generic(
+                        "LDK nodes can't use a remote signer",
+                    ));
+                }
+                let ldk = Ldk::build(config, ldk_config, &seed, restore_only, persister).await?;
+                let ldk = Arc::new(ldk);
+                let backup_transport = Arc::new(LdkBackupTransport::new(ldk.locking_store()));
+                let lsp: Option<Arc<dyn LspAPI>> = Some(ldk.clone());
+                let receiver: Option<Arc<dyn Receiver>> = Some(ldk.clone());
+                Ok(NodeImpls {
+                    node: ldk,
+                    backup_transport,
+                    lsp,
+                    receiver,
+                })
+            }
+            #[cfg(not(feature = "ldk"))]
+            Err(crate::node_api::NodeError::generic(
+                "Misconfigration: `ldk` feature must be enabled.",
+            ))
+        }
+    }
+}
+
+/// Builds a node from its credentials alone, which can't sign. Only Greenlight nodes can be
+/// watched, LDK nodes run in the SDK and need the seed.
+#[allow(unused_variables)]
+pub async fn build_watch_only_node(
+    config: Config,
+    credentials: NodeCredentials,
+    persister: Arc<SqliteStorage>,
+) -> NodeResult<NodeImpls> {
+    match (config.node_config.clone(), credentials) {
+        (NodeConfig::Greenlight { .. }, NodeCredentials::Greenlight { credentials }) => {
+            #[cfg(feature = "greenlight")]
+            {
+                let greenlight =
+                    Greenlight::connect_watch_only(config, credentials.device, persister).await?;
+                let greenlight = Arc::new(greenlight);
+                let backup_transport = Arc::new(GLBackupTransport {
+                    inner: greenlight.clone(),
+                });
+                Ok(NodeImpls {
+                    node: greenlight,
+                    backup_transport,
+                    lsp: None,
+                    receiver: None,
+                })
+            }
+            #[cfg(not(feature = "greenlight"))]
+            Err(crate::node_api::NodeError::generic(
+                "Misconfigration: `greenlight` feature must be enabled.",
+            ))
+        }
+        (NodeConfig::Ldk { .. }, _) => Err(crate::node_api::NodeError::generic(
+            "LDK nodes can't be connected watch-only",
+        )),
     }
 }
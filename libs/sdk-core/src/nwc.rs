@@ -0,0 +1,1044 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{debug, error, info, warn};
+use sdk_common::nostr_sdk::{
+    Client, Event, EventBuilder, EventId, Filter, Keys, Kind, PublicKey, RelayPoolNotification,
+    SecretKey, Tag, TagKind, Timestamp,
+};
+use sdk_common::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast::error::RecvError, watch, Mutex};
+
+use crate::breez_services::BreezServices;
+use crate::error::{SdkError, SdkResult, SendPaymentError};
+use crate::models::{
+    ListPaymentsRequest, Payment, PaymentDetails, PaymentStatus, PaymentType, PaymentTypeFilter,
+    ReceivePaymentRequest, SendPaymentRequest,
+};
+use crate::persist::db::SqliteStorage;
+
+/// Hardened BIP32 index under which the wallet service key is derived
+pub(crate) const NWC_DERIVATION_INDEX: u32 = 141;
+
+/// How long the ids of processed requests are kept to drop their duplicates, in seconds. Older
+/// requests are ignored.
+const NWC_PROCESSED_EVENTS_TTL_SECS: i64 = 60 * 60;
+
+/// Methods a NWC connection can be granted. `get_info` is always allowed.
+const NWC_SUPPORTED_METHODS: [NwcMethod; 5] = [
+    NwcMethod::PayInvoice,
+    NwcMethod::MakeInvoice,
+    NwcMethod::LookupInvoice,
+    NwcMethod::ListTransactions,
+    NwcMethod::GetBalance,
+];
+
+/// Configuration of the [NwcService]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NwcServiceConfig {
+    /// The relays the service listens on, and which are advertised in the connection URIs
+    pub relays: Vec<String>,
+}
+
+/// How often the budget of a [NwcConnection] is reset
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NwcBudgetRenewal {
+    Daily,
+    Weekly,
+    Monthly,
+    Never,
+}
+
+impl NwcBudgetRenewal {
+    /// Length of the budget window, in seconds
+    fn window_secs(&self) -> Option<i64> {
+        match self {
+            NwcBudgetRenewal::Daily => Some(24 * 60 * 60),
+            NwcBudgetRenewal::Weekly => Some(7 * 24 * 60 * 60),
+            NwcBudgetRenewal::Monthly => Some(30 * 24 * 60 * 60),
+            NwcBudgetRenewal::Never => None,
+        }
+    }
+}
+
+/// The maximum amount a [NwcConnection] can spend over a rolling window
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NwcBudget {
+    pub max_amount_msat: u64,
+    pub renewal: NwcBudgetRenewal,
+}
+
+/// A Nostr Wallet Connect client authorized to use this wallet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NwcConnection {
+    /// The hex encoded public key derived from the connection secret
+    pub client_pubkey: String,
+    pub name: String,
+    pub permissions: Vec<NwcMethod>,
+    /// If set, payments are refused once this budget is exhausted
+    pub budget: Option<NwcBudget>,
+    /// Epoch time, in seconds
+    pub created_at: i64,
+}
+
+/// Represents a request to authorize a new NWC client
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateNwcConnectionRequest {
+    pub name: String,
+    pub permissions: Vec<NwcMethod>,
+    pub budget: Option<NwcBudget>,
+}
+
+/// Represents a create NWC connection response
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateNwcConnectionResponse {
+    pub connection: NwcConnection,
+    /// The `nostr+walletconnect://` URI to hand to the client. It contains the connection secret,
+    /// which is not stored by the SDK.
+    pub uri: String,
+}
+
+/// A NIP-47 wallet service, answering the requests of authorized clients through the
+/// configured relays by delegating them to [BreezServices].
+///
+/// See <https://github.com/nostr-protocol/nips/blob/master/47.md>
+pub struct NwcService {
+    config: NwcServiceConfig,
+    keys: Keys,
+    client: Client,
+    handler: NwcRequestHandler,
+    /// The requests answered recently, with their creation time
+    processed_events: Mutex<HashMap<EventId, i64>>,
+    sdk_shutdown_receiver: watch::Receiver<()>,
+    shutdown_sender: watch::Sender<()>,
+}
+
+impl NwcService {
+    pub(crate) fn new(
+        config: NwcServiceConfig,
+        service_secret: &[u8],
+        breez_services: Arc<BreezServices>,
+        persister: Arc<SqliteStorage>,
+        network: Network,
+        sdk_shutdown_receiver: watch::Receiver<()>,
+    ) -> SdkResult<Self> {
+        let secret_key = SecretKey::from_slice(service_secret).map_err(|e| SdkError::Generic {
+            err: format!("Invalid NWC service key: {e}"),
+        })?;
+        let keys = Keys::new(secret_key);
+        let (shutdown_sender, _) = watch::channel(());
+        Ok(Self {
+            config,
+            client: Client::new(keys.clone()),
+            handler: NwcRequestHandler {
+                breez_services,
+                persister,
+                network,
+                budget_lock: Mutex::new(()),
+            },
+            keys,
+            processed_events: Mutex::new(HashMap::new()),
+            sdk_shutdown_receiver,
+            shutdown_sender,
+        })
+    }
+
+    /// The hex encoded public key of the wallet service
+    pub fn service_pubkey(&self) -> String {
+        self.keys.public_key().to_hex()
+    }
+
+    /// Connects to the relays, publishes the NIP-47 info event and starts answering requests.
+    ///
+    /// The service stops when [NwcService::stop] is called or when the SDK disconnects.
+    pub async fn start(self: &Arc<Self>) -> SdkResult<()> {
+        for relay in &self.config.relays {
+            self.client
+                .add_relay(relay.as_str())
+                .await
+                .map_err(|e| SdkError::Generic {
+                    err: format!("Failed to add NWC relay {relay}: {e}"),
+                })?;
+        }
+        self.client.connect().await;
+
+        let methods: Vec<&str> = NWC_SUPPORTED_METHODS
+            .iter()
+            .chain([NwcMethod::GetInfo].iter())
+            .map(|m| m.as_str())
+            .collect();
+        let info = EventBuilder::new(Kind::WalletConnectInfo, methods.join(" "))
+            .tag(Tag::custom(
+                TagKind::custom("encryption"),
+                ["nip44_v2 nip04"],
+            ))
+            .sign_with_keys(&self.keys)
+            .map_err(|e| SdkError::Generic { err: e.to_string() })?;
+        if let Err(e) = self.client.send_event(&info).await {
+            warn!("Failed to publish NWC info event: {e}");
+        }
+
+        let filter = Filter::new()
+            .kind(Kind::WalletConnectRequest)
+            .pubkey(self.keys.public_key())
+            .since(Timestamp::now());
+        let mut notifications = self.client.notifications();
+        self.client
+            .subscribe(filter, None)
+            .await
+            .map_err(|e| SdkError::Generic {
+                err: format!("Failed to subscribe to NWC requests: {e}"),
+            })?;
+
+        let cloned = self.clone();
+        let mut shutdown_receiver = self.shutdown_sender.subscribe();
+        let mut sdk_shutdown_receiver = self.sdk_shutdown_receiver.clone();
+        tokio::spawn(async move {
+            info!("NWC service started: {}", cloned.service_pubkey());
+            loop {
+                tokio::select! {
+                    notification = notifications.recv() => match notification {
+                        Ok(RelayPoolNotification::Event { event, .. }) => {
+                            if event.kind == Kind::WalletConnectRequest {
+                                cloned.process_event(&event).await;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("NWC service skipped {skipped} relay notifications");
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = shutdown_receiver.changed() => break,
+                    _ = sdk_shutdown_receiver.changed() => break,
+                }
+            }
+            cloned.client.disconnect().await;
+            info!("NWC service stopped");
+        });
+        Ok(())
+    }
+
+    /// Stops answering requests and disconnects from the relays.
+    pub fn stop(&self) {
+        let _ = self.shutdown_sender.send(());
+    }
+
+    /// Authorizes a new client, returning the connection URI to hand to it.
+    pub async fn create_connection(
+        &self,
+        req: CreateNwcConnectionRequest,
+    ) -> SdkResult<CreateNwcConnectionResponse> {
+        ensure_sdk!(
+            !req.permissions.is_empty(),
+            SdkError::Generic {
+                err: "At least one permission is required".into()
+            }
+        );
+        ensure_sdk!(
+            req.permissions
+                .iter()
+                .all(|p| NWC_SUPPORTED_METHODS.contains(p)),
+            SdkError::Generic {
+                err: "Unsupported NWC permission".into()
+            }
+        );
+        ensure_sdk!(
+            !self.config.relays.is_empty(),
+            SdkError::Generic {
+                err: "At least one relay is required".into()
+            }
+        );
+
+        let client_keys = Keys::generate();
+        let connection = NwcConnection {
+            client_pubkey: client_keys.public_key().to_hex(),
+            name: req.name,
+            permissions: req.permissions,
+            budget: req.budget,
+            created_at: now(),
+        };
+        self.handler.persister.insert_nwc_connection(&connection)?;
+
+        let mut uri =
+            reqwest::Url::parse(&format!("nostr+walletconnect://{}", self.service_pubkey()))
+                .map_err(|e| SdkError::Generic { err: e.to_string() })?;
+        for relay in &self.config.relays {
+            uri.query_pairs_mut().append_pair("relay", relay);
+        }
+        uri.query_pairs_mut()
+            .append_pair("secret", &client_keys.secret_key().to_secret_hex());
+
+        Ok(CreateNwcConnectionResponse {
+            connection,
+            uri: uri.to_string(),
+        })
+    }
+
+    /// Lists the authorized clients
+    pub fn list_connections(&self) -> SdkResult<Vec<NwcConnection>> {
+        Ok(self.handler.persister.list_nwc_connections()?)
+    }
+
+    /// Revokes a client. Its further requests are answered with `UNAUTHORIZED`.
+    pub fn remove_connection(&self, client_pubkey: String) -> SdkResult<()> {
+        Ok(self
+            .handler
+            .persister
+            .delete_nwc_connection(&client_pubkey)?)
+    }
+
+    async fn process_event(&self, event: &Event) {
+        // The same request is delivered by every relay it was published to
+        let created_at = event.created_at.as_u64() as i64;
+        let cutoff = now() - NWC_PROCESSED_EVENTS_TTL_SECS;
+        if created_at < cutoff {
+            debug!("Ignoring stale NWC request {}", event.id);
+            return;
+        }
+        {
+            let mut processed_events = self.processed_events.lock().await;
+            processed_events.retain(|_, created_at| *created_at >= cutoff);
+            if processed_events.insert(event.id, created_at).is_some() {
+                return;
+            }
+        }
+
+        let (content, encryption) =
+            match nwc_decrypt(self.keys.secret_key(), &event.pubkey, &event.content) {
+                Ok(res) => res,
+                Err(e) => {
+                    warn!("Failed to decrypt NWC request {}: {e}", event.id);
+                    return;
+                }
+            };
+        let request: NwcRequest = match serde_json::from_str(&content) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Failed to parse NWC request {}: {e}", event.id);
+                return;
+            }
+        };
+
+        debug!("Handling NWC {} request", request.method().as_str());
+        let response = self.handler.handle(&event.pubkey.to_hex(), request).await;
+        if let Err(e) = self
+            .send_response(&event.pubkey, event.id, &response, encryption)
+            .await
+        {
+            error!("Failed to send NWC response to {}: {e}", event.id);
+        }
+    }
+
+    async fn send_response(
+        &self,
+        client_pubkey: &PublicKey,
+        request_id: EventId,
+        response: &NwcResponse,
+        encryption: NwcEncryption,
+    ) -> anyhow::Result<()> {
+        let content = nwc_encrypt(
+            self.keys.secret_key(),
+            client_pubkey,
+            &serde_json::to_string(response)?,
+            encryption,
+        )?;
+        let event = EventBuilder::new(Kind::WalletConnectResponse, content)
+            .tag(Tag::public_key(*client_pubkey))
+            .tag(Tag::event(request_id))
+            .sign_with_keys(&self.keys)?;
+        self.client.send_event(&event).await?;
+        Ok(())
+    }
+}
+
+/// Answers decrypted NIP-47 requests, enforcing the permissions and budget of the connection.
+pub(crate) struct NwcRequestHandler {
+    breez_services: Arc<BreezServices>,
+    persister: Arc<SqliteStorage>,
+    network: Network,
+    /// Held while checking the budget and reserving a payment against it
+    budget_lock: Mutex<()>,
+}
+
+impl NwcRequestHandler {
+    pub(crate) async fn handle(&self, client_pubkey: &str, request: NwcRequest) -> NwcResponse {
+        let method = request.method();
+        match self.handle_inner(client_pubkey, request).await {
+            Ok(response) => response,
+            Err(e) => NwcResponse::error(method, NwcErrorCode::Internal, e.to_string()),
+        }
+    }
+
+    async fn handle_inner(
+        &self,
+        client_pubkey: &str,
+        request: NwcRequest,
+    ) -> SdkResult<NwcResponse> {
+        let method = request.method();
+        let Some(connection) = self.persister.get_nwc_connection(client_pubkey)? else {
+            return Ok(NwcResponse::error(
+                method,
+                NwcErrorCode::Unauthorized,
+                "Unknown connection".into(),
+            ));
+        };
+        if method != NwcMethod::GetInfo && !connection.permissions.contains(&method) {
+            return Ok(NwcResponse::error(
+                method,
+                NwcErrorCode::Restricted,
+                format!("{} is not allowed for this connection", method.as_str()),
+            ));
+        }
+
+        match request {
+            NwcRequest::PayInvoice { invoice, amount } => {
+                self.pay_invoice(&connection, invoice, amount).await
+            }
+            NwcRequest::MakeInvoice {
+                amount,
+                description,
+                expiry,
+                ..
+            } => {
+                let res = self
+                    .breez_services
+                    .receive_payment(ReceivePaymentRequest {
+                        amount_msat: amount,
+                        description: description.unwrap_or_default(),
+                        expiry: expiry.map(|e| e as u32),
+                        ..Default::default()
+                    })
+                    .await;
+                Ok(match res {
+                    Ok(res) => NwcResponse::success(
+                        method,
+                        &invoice_to_nwc_transaction(&res.ln_invoice, NwcTransactionType::Incoming),
+                    ),
+                    Err(e) => NwcResponse::error(method, NwcErrorCode::Other, e.to_string()),
+                })
+            }
+            NwcRequest::LookupInvoice {
+                payment_hash,
+                invoice,
+            } => {
+                let parsed_invoice = invoice.as_deref().map(parse_invoice).transpose()?;
+                let Some(payment_hash) =
+                    payment_hash.or(parsed_invoice.as_ref().map(|i| i.payment_hash.clone()))
+                else {
+                    return Ok(NwcResponse::error(
+                        method,
+                        NwcErrorCode::Other,
+                        "Either payment_hash or invoice is required".into(),
+                    ));
+                };
+                // Only the payments and invoices of the wallet are reported, the invoice of the
+                // request is not echoed back
+                let payment = self
+                    .breez_services
+                    .payment_by_hash(payment_hash.clone())
+                    .await?;
+                if let Some(tx) = payment.as_ref().and_then(payment_to_nwc_transaction) {
+                    return Ok(NwcResponse::success(method, &tx));
+                }
+                Ok(match self.persister.get_invoice(&payment_hash, now())? {
+                    Some(invoice) => NwcResponse::success(
+                        method,
+                        &invoice_to_nwc_transaction(
+                            &parse_invoice(&invoice.bolt11)?,
+                            NwcTransactionType::Incoming,
+                        ),
+                    ),
+                    None => NwcResponse::error(
+                        method,
+                        NwcErrorCode::NotFound,
+                        "Invoice not found".into(),
+                    ),
+                })
+            }
+            NwcRequest::ListTransactions {
+                from,
+                until,
+                limit,
+                offset,
+                transaction_type,
+                ..
+            } => {
+                let filters = transaction_type.map(|t| {
+                    vec![match t {
+                        NwcTransactionType::Incoming => PaymentTypeFilter::Received,
+                        NwcTransactionType::Outgoing => PaymentTypeFilter::Sent,
+                    }]
+                });
+                let payments = self
+                    .breez_services
+                    .list_payments(ListPaymentsRequest {
+                        filters: filters.or(Some(vec![
+                            PaymentTypeFilter::Sent,
+                            PaymentTypeFilter::Received,
+                        ])),
+                        from_timestamp: from.map(|t| t as i64),
+                        to_timestamp: until.map(|t| t as i64),
+                        offset: offset.map(|o| o as u32),
+                        limit: limit.map(|l| l as u32),
+                        ..Default::default()
+                    })
+                    .await?;
+                Ok(NwcResponse::success(
+                    method,
+                    &NwcListTransactionsResult {
+                        transactions: payments
+                            .iter()
+                            .filter_map(payment_to_nwc_transaction)
+                            .collect(),
+                    },
+                ))
+            }
+            NwcRequest::GetBalance {} => {
+                let node_state = self.breez_services.node_info()?;
+                Ok(NwcResponse::success(
+                    method,
+                    &NwcGetBalanceResult {
+                        balance: node_state.channels_balance_msat,
+                    },
+                ))
+            }
+            NwcRequest::GetInfo {} => {
+                let node_state = self.breez_services.node_info()?;
+                Ok(NwcResponse::success(
+                    method,
+                    &NwcGetInfoResult {
+                        alias: String::new(),
+                        pubkey: node_state.id,
+                        network: nwc_network(self.network).to_string(),
+                        block_height: node_state.block_height,
+                        methods: connection
+                            .permissions
+                            .iter()
+                            .map(|m| m.as_str().to_string())
+                            .collect(),
+                    },
+                ))
+            }
+        }
+    }
+
+    async fn pay_invoice(
+        &self,
+        connection: &NwcConnection,
+        invoice: String,
+        amount_msat: Option<u64>,
+    ) -> SdkResult<NwcResponse> {
+        let method = NwcMethod::PayInvoice;
+        let parsed_invoice = parse_invoice(&invoice)?;
+        let Some(payment_amount_msat) = amount_msat.or(parsed_invoice.amount_msat) else {
+            return Ok(NwcResponse::error(
+                method,
+                NwcErrorCode::Other,
+                "Amount must be provided when paying a zero invoice".into(),
+            ));
+        };
+
+        // Reserve the amount against the budget before paying, so concurrent requests can't
+        // overspend it
+        let budget_guard = self.budget_lock.lock().await;
+        if let Some(budget) = &connection.budget {
+            let since = budget.renewal.window_secs().map_or(0, |w| now() - w);
+            let spent_msat = self
+                .persister
+                .get_nwc_spent_msat(&connection.client_pubkey, since)?;
+            if spent_msat + payment_amount_msat > budget.max_amount_msat {
+                return Ok(NwcResponse::error(
+                    method,
+                    NwcErrorCode::QuotaExceeded,
+                    "Payment exceeds the connection budget".into(),
+                ));
+            }
+        }
+        let Some(reservation_id) = self.persister.insert_nwc_payment(
+            &connection.client_pubkey,
+            &parsed_invoice.payment_hash,
+            payment_amount_msat,
+            now(),
+        )?
+        else {
+            return Ok(NwcResponse::error(
+                method,
+                NwcErrorCode::PaymentFailed,
+                "Invoice already paid".into(),
+            ));
+        };
+        drop(budget_guard);
+
+        let res = self
+            .breez_services
+            .send_payment(SendPaymentRequest {
+                bolt11: invoice,
+                use_trampoline: false,
+                amount_msat,
+                label: None,
+            })
+            .await;
+        match res {
+            Ok(res) => {
+                let PaymentDetails::Ln { data } = &res.payment.details else {
+                    return Err(SdkError::Generic {
+                        err: "Unexpected payment type".into(),
+                    });
+                };
+                self.persister.complete_nwc_payment(
+                    reservation_id,
+                    payment_amount_msat + res.payment.fee_msat,
+                )?;
+                Ok(NwcResponse::success(
+                    method,
+                    &NwcPayInvoiceResult {
+                        preimage: data.payment_preimage.clone(),
+                        fees_paid: Some(res.payment.fee_msat),
+                    },
+                ))
+            }
+            Err(e) => {
+                // A timed out payment may still succeed, so it keeps counting against the budget
+                if !matches!(e, SendPaymentError::PaymentTimeout { .. }) {
+                    self.persister.delete_pending_nwc_payment(reservation_id)?;
+                }
+                let code = match e {
+                    SendPaymentError::InsufficientBalance { .. } => {
+                        NwcErrorCode::InsufficientBalance
+                    }
+                    SendPaymentError::PolicyViolation { .. } => NwcErrorCode::Restricted,
+                    _ => NwcErrorCode::PaymentFailed,
+                };
+                Ok(NwcResponse::error(method, code, e.to_string()))
+            }
+        }
+    }
+}
+
+fn payment_to_nwc_transaction(payment: &Payment) -> Option<NwcTransaction> {
+    let PaymentDetails::Ln { data } = &payment.details else {
+        return None;
+    };
+    let invoice = parse_invoice(&data.bolt11).ok();
+    let settled = payment.status == PaymentStatus::Complete;
+    Some(NwcTransaction {
+        transaction_type: Some(match payment.payment_type {
+            PaymentType::Received => NwcTransactionType::Incoming,
+            _ => NwcTransactionType::Outgoing,
+        }),
+        invoice: (!data.bolt11.is_empty()).then(|| data.bolt11.clone()),
+        description: payment.description.clone(),
+        description_hash: invoice.as_ref().and_then(|i| i.description_hash.clone()),
+        preimage: settled.then(|| data.payment_preimage.clone()),
+        payment_hash: data.payment_hash.clone(),
+        amount: payment.amount_msat,
+        fees_paid: payment.fee_msat,
+        created_at: payment.payment_time as u64,
+        expires_at: invoice.map(|i| i.timestamp + i.expiry),
+        settled_at: settled.then_some(payment.payment_time as u64),
+    })
+}
+
+fn invoice_to_nwc_transaction(
+    invoice: &LNInvoice,
+    transaction_type: NwcTransactionType,
+) -> NwcTransaction {
+    NwcTransaction {
+        transaction_type: Some(transaction_type),
+        invoice: Some(invoice.bolt11.clone()),
+        description: invoice.description.clone(),
+        description_hash: invoice.description_hash.clone(),
+        preimage: None,
+        payment_hash: invoice.payment_hash.clone(),
+        amount: invoice.amount_msat.unwrap_or_default(),
+        fees_paid: 0,
+        created_at: invoice.timestamp,
+        expires_at: Some(invoice.timestamp + invoice.expiry),
+        settled_at: None,
+    }
+}
+
+/// The network names used by NIP-47 `get_info`
+fn nwc_network(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "mainnet",
+        Network::Testnet => "testnet",
+        Network::Signet => "signet",
+        Network::Regtest => "regtest",
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use anyhow::{anyhow, Result};
+    use nostr_relay_builder::MockRelay;
+    use sdk_common::prelude::*;
+
+    use crate::breez_services::tests::{breez_services_with, get_dummy_node_state};
+    use crate::nwc::*;
+    use crate::test_utils::*;
+
+    async fn test_service() -> Result<(Arc<NwcService>, Arc<MockNodeAPI>)> {
+        let node_api = Arc::new(MockNodeAPI::new(get_dummy_node_state()));
+        let breez_services = breez_services_with(Some(node_api.clone()), None, vec![]).await?;
+        breez_services.sync().await?;
+        let service = breez_services
+            .nwc_service(NwcServiceConfig {
+                relays: vec!["wss://relay.example.com".to_string()],
+            })
+            .await?;
+        Ok((service, node_api))
+    }
+
+    #[tokio::test]
+    async fn test_nwc_create_connection() -> Result<()> {
+        let (service, _) = test_service().await?;
+        let res = service
+            .create_connection(CreateNwcConnectionRequest {
+                name: "test".to_string(),
+                permissions: vec![NwcMethod::GetBalance],
+                budget: None,
+            })
+            .await?;
+
+        let InputType::NostrWalletConnectUri { data } = parse(&res.uri, None).await? else {
+            panic!("Expected a NWC URI");
+        };
+        assert_eq!(data.public_key, service.service_pubkey());
+        assert_eq!(data.relays, vec!["wss://relay.example.com"]);
+        let client_keys = Keys::parse(&data.secret)?;
+        assert_eq!(
+            client_keys.public_key().to_hex(),
+            res.connection.client_pubkey
+        );
+
+        assert_eq!(service.list_connections()?, vec![res.connection.clone()]);
+        service.remove_connection(res.connection.client_pubkey.clone())?;
+        assert!(service.list_connections()?.is_empty());
+
+        assert!(service
+            .create_connection(CreateNwcConnectionRequest {
+                name: "test".to_string(),
+                permissions: vec![NwcMethod::GetInfo],
+                budget: None,
+            })
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nwc_permissions() -> Result<()> {
+        let (service, _) = test_service().await?;
+        let connection = service
+            .create_connection(CreateNwcConnectionRequest {
+                name: "test".to_string(),
+                permissions: vec![NwcMethod::GetBalance, NwcMethod::MakeInvoice],
+                budget: None,
+            })
+            .await?
+            .connection;
+
+        let res = service
+            .handler
+            .handle(&connection.client_pubkey, NwcRequest::GetBalance {})
+            .await;
+        assert_eq!(
+            res.into_result::<NwcGetBalanceResult>()?.balance,
+            get_dummy_node_state().channels_balance_msat
+        );
+
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::MakeInvoice {
+                    amount: 10_000,
+                    description: Some("test".to_string()),
+                    description_hash: None,
+                    expiry: None,
+                },
+            )
+            .await;
+        let tx = res.into_result::<NwcTransaction>()?;
+        assert_eq!(tx.amount, 10_000);
+        assert_eq!(tx.transaction_type, Some(NwcTransactionType::Incoming));
+
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::PayInvoice {
+                    invoice: tx.invoice.unwrap(),
+                    amount: None,
+                },
+            )
+            .await;
+        assert_eq!(
+            res.into_result::<NwcPayInvoiceResult>().unwrap_err().code,
+            NwcErrorCode::Restricted
+        );
+
+        let res = service
+            .handler
+            .handle("unknown", NwcRequest::GetBalance {})
+            .await;
+        assert_eq!(
+            res.into_result::<NwcGetBalanceResult>().unwrap_err().code,
+            NwcErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nwc_pay_invoice_budget() -> Result<()> {
+        let (service, _) = test_service().await?;
+        let connection = service
+            .create_connection(CreateNwcConnectionRequest {
+                name: "test".to_string(),
+                permissions: vec![NwcMethod::PayInvoice, NwcMethod::ListTransactions],
+                budget: Some(NwcBudget {
+                    max_amount_msat: 75_000,
+                    renewal: NwcBudgetRenewal::Daily,
+                }),
+            })
+            .await?
+            .connection;
+
+        // Test invoices are for 50 sats, so only the first one fits in the budget
+        let invoice = rand_invoice_with_description_hash("test".to_string())?;
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::PayInvoice {
+                    invoice: invoice.to_string(),
+                    amount: None,
+                },
+            )
+            .await;
+        assert!(!res
+            .into_result::<NwcPayInvoiceResult>()?
+            .preimage
+            .is_empty());
+
+        let invoice = rand_invoice_with_description_hash("test".to_string())?;
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::PayInvoice {
+                    invoice: invoice.to_string(),
+                    amount: None,
+                },
+            )
+            .await;
+        assert_eq!(
+            res.into_result::<NwcPayInvoiceResult>().unwrap_err().code,
+            NwcErrorCode::QuotaExceeded
+        );
+
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::ListTransactions {
+                    from: None,
+                    until: None,
+                    limit: None,
+                    offset: None,
+                    unpaid: None,
+                    transaction_type: Some(NwcTransactionType::Outgoing),
+                },
+            )
+            .await;
+        let txs = res.into_result::<NwcListTransactionsResult>()?.transactions;
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].amount, 50_000);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nwc_lookup_invoice() -> Result<()> {
+        let (service, _) = test_service().await?;
+        let connection = service
+            .create_connection(CreateNwcConnectionRequest {
+                name: "test".to_string(),
+                permissions: vec![NwcMethod::MakeInvoice, NwcMethod::LookupInvoice],
+                budget: None,
+            })
+            .await?
+            .connection;
+
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::MakeInvoice {
+                    amount: 10_000,
+                    description: Some("test".to_string()),
+                    description_hash: None,
+                    expiry: None,
+                },
+            )
+            .await;
+        let invoice = res.into_result::<NwcTransaction>()?.invoice.unwrap();
+
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::LookupInvoice {
+                    payment_hash: None,
+                    invoice: Some(invoice.clone()),
+                },
+            )
+            .await;
+        assert_eq!(res.into_result::<NwcTransaction>()?.invoice, Some(invoice));
+
+        // Invoices unknown to the wallet are not echoed back
+        let unknown = rand_invoice_with_description_hash("test".to_string())?;
+        let res = service
+            .handler
+            .handle(
+                &connection.client_pubkey,
+                NwcRequest::LookupInvoice {
+                    payment_hash: None,
+                    invoice: Some(unknown.to_string()),
+                },
+            )
+            .await;
+        assert_eq!(
+            res.into_result::<NwcTransaction>().unwrap_err().code,
+            NwcErrorCode::NotFound
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nwc_relay_budget() -> Result<()> {
+        let relay = MockRelay::run().await?;
+        let relay_url = relay.url().await.to_string();
+        let node_api = Arc::new(MockNodeAPI::new(get_dummy_node_state()));
+        let breez_services = breez_services_with(Some(node_api), None, vec![]).await?;
+        breez_services.sync().await?;
+        let service = breez_services
+            .nwc_service(NwcServiceConfig {
+                relays: vec![relay_url.clone()],
+            })
+            .await?;
+        service.start().await?;
+        let res = service
+            .create_connection(CreateNwcConnectionRequest {
+                name: "test".to_string(),
+                permissions: vec![NwcMethod::PayInvoice],
+                budget: Some(NwcBudget {
+                    max_amount_msat: 125_000,
+                    renewal: NwcBudgetRenewal::Daily,
+                }),
+            })
+            .await?;
+
+        let InputType::NostrWalletConnectUri { data } = parse(&res.uri, None).await? else {
+            return Err(anyhow!("Expected a NWC URI"));
+        };
+        let client_keys = Keys::parse(&data.secret)?;
+        let client = Client::new(client_keys.clone());
+        client.add_relay(relay_url.as_str()).await?;
+        client.connect().await;
+        let service_pubkey = PublicKey::from_hex(&service.service_pubkey())?;
+        let pay = |invoice: String| NwcRequest::PayInvoice {
+            invoice,
+            amount: None,
+        };
+
+        // Test invoices are for 50 sats, so two of them fit in the budget
+        let invoice = rand_invoice_with_description_hash("test".to_string())?.to_string();
+        let res = relay_request(
+            &client,
+            &client_keys,
+            &service_pubkey,
+            &pay(invoice.clone()),
+        )
+        .await?;
+        assert!(res.into_result::<NwcPayInvoiceResult>().is_ok());
+
+        // Replaying a paid invoice neither pays it again nor releases its budget
+        let res = relay_request(&client, &client_keys, &service_pubkey, &pay(invoice)).await?;
+        assert_eq!(
+            res.into_result::<NwcPayInvoiceResult>().unwrap_err().code,
+            NwcErrorCode::PaymentFailed
+        );
+
+        let invoice = rand_invoice_with_description_hash("test".to_string())?.to_string();
+        let res = relay_request(&client, &client_keys, &service_pubkey, &pay(invoice)).await?;
+        assert!(res.into_result::<NwcPayInvoiceResult>().is_ok());
+
+        let invoice = rand_invoice_with_description_hash("test".to_string())?.to_string();
+        let res = relay_request(&client, &client_keys, &service_pubkey, &pay(invoice)).await?;
+        assert_eq!(
+            res.into_result::<NwcPayInvoiceResult>().unwrap_err().code,
+            NwcErrorCode::QuotaExceeded
+        );
+
+        client.disconnect().await;
+        service.stop();
+        Ok(())
+    }
+
+    /// Sends a request to the service through the relay, as a NIP-47 client would
+    async fn relay_request(
+        client: &Client,
+        client_keys: &Keys,
+        service_pubkey: &PublicKey,
+        request: &NwcRequest,
+    ) -> Result<NwcResponse> {
+        let content = nwc_encrypt(
+            client_keys.secret_key(),
+            service_pubkey,
+            &serde_json::to_string(request)?,
+            NwcEncryption::Nip44V2,
+        )?;
+        let event = EventBuilder::new(Kind::WalletConnectRequest, content)
+            .tag(Tag::public_key(*service_pubkey))
+            .sign_with_keys(client_keys)?;
+
+        let mut notifications = client.notifications();
+        let filter = Filter::new()
+            .kind(Kind::WalletConnectResponse)
+            .author(*service_pubkey)
+            .pubkey(client_keys.public_key())
+            .event(event.id);
+        client.subscribe(filter, None).await?;
+        client.send_event(&event).await?;
+
+        let response = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Ok(RelayPoolNotification::Event { event: res, .. }) =
+                    notifications.recv().await
+                {
+                    if res.tags.event_ids().any(|id| *id == event.id) {
+                        return res;
+                    }
+                }
+            }
+        })
+        .await?;
+        let (content, _) =
+            nwc_decrypt(client_keys.secret_key(), service_pubkey, &response.content)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
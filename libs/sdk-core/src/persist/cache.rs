@@ -10,6 +10,7 @@ const KEY_NODE_STATE: &str = "node_state";
 const KEY_STATIC_BACKUP: &str = "static_backup";
 const KEY_WEBHOOK_URL: &str = "webhook_url";
 const KEY_MEMPOOLSPACE_BASE_URLS: &str = "mempoolspace_base_urls";
+const KEY_INSTANCE_ID: &str = "instance_id";
 
 #[cfg_attr(test, mockall::automock)]
 pub(crate) trait NodeStateStorage: Send + Sync {
@@ -116,6 +117,17 @@ impl SqliteStorage {
         self.update_cached_item(KEY_MEMPOOLSPACE_BASE_URLS, serialized)
     }
 
+    /// A random id for this SDK instance, generated on first use, telling it apart from the
+    /// other instances sharing the node and synced state
+    pub(crate) fn get_or_create_instance_id(&self) -> PersistResult<String> {
+        if let Some(instance_id) = self.get_cached_item(KEY_INSTANCE_ID)? {
+            return Ok(instance_id);
+        }
+        let instance_id = hex::encode(rand::random::<[u8; 16]>());
+        self.update_cached_item(KEY_INSTANCE_ID, instance_id.clone())?;
+        Ok(instance_id)
+    }
+
     pub fn get_mempoolspace_base_urls(&self) -> PersistResult<Vec<String>> {
         let res = match self.get_cached_item(KEY_MEMPOOLSPACE_BASE_URLS)? {
             Some(str) => serde_json::from_str(str.as_str())?,
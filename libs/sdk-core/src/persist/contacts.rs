@@ -0,0 +1,235 @@
+use rusqlite::{named_params, OptionalExtension, Row};
+use sdk_common::ensure_sdk;
+
+use super::{
+    db::SqliteStorage,
+    error::{PersistError, PersistResult},
+};
+use crate::models::Contact;
+
+impl SqliteStorage {
+    /// Inserts a new contact, or replaces all the fields of an existing one
+    pub(crate) fn upsert_contact(&self, contact: &Contact) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT INTO sync.contacts (
+              id,
+              name,
+              ln_address,
+              node_pubkey,
+              lnurl_pay_domain,
+              note,
+              created_at,
+              updated_at
+            )
+            VALUES (:id, :name, :ln_address, :node_pubkey, :lnurl_pay_domain, :note, :created_at, :updated_at)
+            ON CONFLICT(id) DO UPDATE SET
+              name = excluded.name,
+              ln_address = excluded.ln_address,
+              node_pubkey = excluded.node_pubkey,
+              lnurl_pay_domain = excluded.lnurl_pay_domain,
+              note = excluded.note,
+              updated_at = excluded.updated_at
+            ",
+            named_params! {
+                ":id": contact.id,
+                ":name": contact.name,
+                ":ln_address": contact.ln_address,
+                ":node_pubkey": contact.node_pubkey,
+                ":lnurl_pay_domain": contact.lnurl_pay_domain,
+                ":note": contact.note,
+                ":created_at": contact.created_at,
+                ":updated_at": contact.updated_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_contact(&self, id: &str) -> PersistResult<Option<Contact>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "SELECT * FROM sync.contacts WHERE id = ?1 AND deleted = 0",
+                [id],
+                |row| self.sql_row_to_contact(row),
+            )
+            .optional()?)
+    }
+
+    pub(crate) fn list_contacts(&self) -> PersistResult<Vec<Contact>> {
+        let con = self.get_connection()?;
+        let mut stmt = con.prepare(
+            "SELECT * FROM sync.contacts WHERE deleted = 0 ORDER BY name COLLATE NOCASE, id",
+        )?;
+        let vec = stmt
+            .query_map([], |row| self.sql_row_to_contact(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec)
+    }
+
+    /// Marks a contact as deleted, so the deletion is synced to the other instances
+    pub(crate) fn delete_contact(&self, id: &str, updated_at: i64) -> PersistResult<()> {
+        let updated = self.get_connection()?.execute(
+            "UPDATE sync.contacts SET deleted = 1, updated_at = ?2 WHERE id = ?1 AND deleted = 0",
+            (id, updated_at),
+        )?;
+        ensure_sdk!(updated == 1, PersistError::generic("Contact not found"));
+        Ok(())
+    }
+
+    fn sql_row_to_contact(&self, row: &Row) -> rusqlite::Result<Contact> {
+        Ok(Contact {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            ln_address: row.get("ln_address")?,
+            node_pubkey: row.get("node_pubkey")?,
+            lnurl_pay_domain: row.get("lnurl_pay_domain")?,
+            note: row.get("note")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Contact, LnPaymentDetails, Payment, PaymentDetails, PaymentType};
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::test_utils;
+    use crate::{ListPaymentsRequest, PaymentExternalInfo};
+
+    fn contact(id: &str, name: &str) -> Contact {
+        Contact {
+            id: id.to_string(),
+            name: name.to_string(),
+            ln_address: None,
+            node_pubkey: None,
+            lnurl_pay_domain: None,
+            note: None,
+            created_at: 100,
+            updated_at: 100,
+        }
+    }
+
+    fn sent_payment(id: &str, destination_pubkey: &str) -> Payment {
+        Payment {
+            id: id.to_string(),
+            payment_type: PaymentType::Sent,
+            details: PaymentDetails::Ln {
+                data: LnPaymentDetails {
+                    payment_hash: id.to_string(),
+                    destination_pubkey: destination_pubkey.to_string(),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_contacts() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        let mut bob = contact("1", "bob");
+        storage.upsert_contact(&bob)?;
+        storage.upsert_contact(&contact("2", "Alice"))?;
+        assert_eq!(storage.get_contact("1")?, Some(bob.clone()));
+        let names: Vec<String> = storage
+            .list_contacts()?
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["Alice", "bob"]);
+
+        bob.name = "Bob".to_string();
+        bob.note = Some("Coffee".to_string());
+        bob.updated_at = 200;
+        storage.upsert_contact(&bob)?;
+        assert_eq!(storage.get_contact("1")?, Some(bob));
+
+        storage.delete_contact("2", 300)?;
+        assert!(storage.delete_contact("2", 300).is_err());
+        assert_eq!(storage.get_contact("2")?, None);
+        assert_eq!(storage.list_contacts()?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_contact_attribution() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        let payments = vec![
+            sent_payment("p1", "node_a"),
+            sent_payment("p2", "node_b"),
+            sent_payment("p3", "node_c"),
+            sent_payment("p4", "node_d"),
+        ];
+        storage.insert_or_update_payments(&payments, false)?;
+        storage.insert_payment_external_info(
+            "p2",
+            PaymentExternalInfo {
+                ln_address: Some("Bob@Wallet.com".to_string()),
+                ..Default::default()
+            },
+        )?;
+        storage.insert_payment_external_info(
+            "p3",
+            PaymentExternalInfo {
+                lnurl_pay_domain: Some("shop.com".to_string()),
+                ..Default::default()
+            },
+        )?;
+
+        storage.upsert_contact(&Contact {
+            node_pubkey: Some("node_a".to_string()),
+            ..contact("a", "Node A")
+        })?;
+        storage.upsert_contact(&Contact {
+            ln_address: Some("bob@wallet.com".to_string()),
+            ..contact("b", "Bob")
+        })?;
+        // Less specific than Bob's Lightning Address
+        storage.upsert_contact(&Contact {
+            lnurl_pay_domain: Some("wallet.com".to_string()),
+            ..contact("w", "Wallet")
+        })?;
+        storage.upsert_contact(&Contact {
+            lnurl_pay_domain: Some("shop.com".to_string()),
+            ..contact("s", "Shop")
+        })?;
+
+        let contact_of = |id: &str| -> PersistResult<Option<String>> {
+            match storage.get_payment_by_hash(id)?.unwrap().details {
+                PaymentDetails::Ln { data } => Ok(data.contact_id),
+                _ => Ok(None),
+            }
+        };
+        assert_eq!(contact_of("p1")?, Some("a".to_string()));
+        assert_eq!(contact_of("p2")?, Some("b".to_string()));
+        assert_eq!(contact_of("p3")?, Some("s".to_string()));
+        assert_eq!(contact_of("p4")?, None);
+
+        let by_contact = |contact_id: &str| -> PersistResult<Vec<String>> {
+            Ok(storage
+                .list_payments(ListPaymentsRequest {
+                    contact_id: Some(contact_id.to_string()),
+                    ..Default::default()
+                })?
+                .into_iter()
+                .map(|p| p.id)
+                .collect())
+        };
+        assert_eq!(by_contact("b")?, vec!["p2"]);
+        assert!(by_contact("w")?.is_empty());
+
+        // Payments of deleted contacts are no longer attributed
+        storage.delete_contact("b", 200)?;
+        assert_eq!(contact_of("p2")?, Some("w".to_string()));
+
+        Ok(())
+    }
+}
@@ -48,6 +48,7 @@ impl SqliteStorage {
     pub(crate) fn init(&self) -> PersistResult<()> {
         self.migrate_main_db()?;
         Self::migrate_sync_db(self.sync_db_file.clone())?;
+        self.ensure_payments_search_index()?;
         Ok(())
     }
 
@@ -73,7 +74,8 @@ impl SqliteStorage {
         // We want to notify any subscribers with hook events.
         let events_publisher = self.events_publisher.clone();
         con.update_hook(Some(move |action, db: &str, t: &str, _| {
-            if action == Action::SQLITE_INSERT && db == "sync" {
+            // The search index is derived data, and can change a lot at once
+            if action == Action::SQLITE_INSERT && db == "sync" && !t.starts_with("payments_fts") {
                 _ = events_publisher.send(HookEvent::Insert { table: t.into() });
             }
         }));
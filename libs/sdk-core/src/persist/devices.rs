@@ -0,0 +1,151 @@
+use rusqlite::{named_params, OptionalExtension, Row};
+
+use super::{db::SqliteStorage, error::PersistResult};
+use crate::models::GreenlightDevice;
+
+impl SqliteStorage {
+    /// Adds a device to the registry. A device that is already registered keeps its label.
+    ///
+    /// Returns false if the device was already registered.
+    pub(crate) fn insert_greenlight_device(
+        &self,
+        id: &str,
+        label: &str,
+        registered_at: i64,
+    ) -> PersistResult<bool> {
+        let inserted = self.get_connection()?.execute(
+            "
+            INSERT OR IGNORE INTO sync.greenlight_devices (id, label, registered_at)
+            VALUES (:id, :label, :registered_at)
+            ",
+            named_params! {
+                ":id": id,
+                ":label": label,
+                ":registered_at": registered_at,
+            },
+        )?;
+        Ok(inserted == 1)
+    }
+
+    pub(crate) fn get_greenlight_device(
+        &self,
+        id: &str,
+    ) -> PersistResult<Option<GreenlightDevice>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "SELECT * FROM sync.greenlight_devices WHERE id = ?1",
+                [id],
+                |row| self.sql_row_to_greenlight_device(row),
+            )
+            .optional()?)
+    }
+
+    /// Lists the registered devices, oldest first
+    pub(crate) fn list_greenlight_devices(&self) -> PersistResult<Vec<GreenlightDevice>> {
+        let con = self.get_connection()?;
+        let mut stmt =
+            con.prepare("SELECT * FROM sync.greenlight_devices ORDER BY registered_at, id")?;
+        let vec = stmt
+            .query_map([], |row| self.sql_row_to_greenlight_device(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec)
+    }
+
+    /// Records that a signer ran on the device.
+    ///
+    /// This doesn't request a sync, the time is only pushed to the other instances along with the
+    /// next change to back up.
+    pub(crate) fn set_greenlight_device_last_seen(
+        &self,
+        id: &str,
+        last_seen_at: i64,
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            UPDATE sync.greenlight_devices
+            SET last_seen_at = :last_seen_at
+            WHERE id = :id
+            ",
+            named_params! {
+                ":id": id,
+                ":last_seen_at": last_seen_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    fn sql_row_to_greenlight_device(&self, row: &Row) -> rusqlite::Result<GreenlightDevice> {
+        Ok(GreenlightDevice {
+            id: row.get("id")?,
+            label: row.get("label")?,
+            registered_at: row.get("registered_at")?,
+            last_seen_at: row.get("last_seen_at")?,
+            signer_active: false,
+            is_current: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::test_utils;
+
+    #[test]
+    fn test_greenlight_devices() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        assert!(storage.insert_greenlight_device("b", "Laptop", 200)?);
+        assert!(storage.insert_greenlight_device("a", "Phone", 100)?);
+        assert!(!storage.insert_greenlight_device("a", "Other", 300)?);
+        let labels: Vec<String> = storage
+            .list_greenlight_devices()?
+            .into_iter()
+            .map(|d| d.label)
+            .collect();
+        assert_eq!(labels, vec!["Phone", "Laptop"]);
+
+        // Heartbeats don't request a sync
+        let sync_requests = storage.get_last_sync_request()?;
+        storage.set_greenlight_device_last_seen("b", 600)?;
+        assert_eq!(storage.get_last_sync_request()?, sync_requests);
+        assert_eq!(
+            storage.get_greenlight_device("b")?.unwrap().last_seen_at,
+            Some(600)
+        );
+        assert!(storage.get_greenlight_device("c")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_greenlight_devices() -> PersistResult<()> {
+        let local_storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        local_storage.init()?;
+        local_storage.insert_greenlight_device("a", "Phone", 100)?;
+
+        let remote_storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        remote_storage.init()?;
+        remote_storage.insert_greenlight_device("a", "Phone", 100)?;
+        remote_storage.insert_greenlight_device("b", "Laptop", 200)?;
+        remote_storage.set_greenlight_device_last_seen("a", 350)?;
+        // The device is seen later here, which is kept
+        local_storage.set_greenlight_device_last_seen("a", 400)?;
+
+        local_storage.import_remote_changes(&remote_storage, true)?;
+        assert_eq!(local_storage.list_greenlight_devices()?.len(), 2);
+        let device = local_storage.get_greenlight_device("a")?.unwrap();
+        assert_eq!(device.last_seen_at, Some(400));
+
+        // Seeing the device later on the other instance is synced too
+        remote_storage.set_greenlight_device_last_seen("a", 500)?;
+        local_storage.import_remote_changes(&remote_storage, true)?;
+        let device = local_storage.get_greenlight_device("a")?.unwrap();
+        assert_eq!(device.last_seen_at, Some(500));
+
+        Ok(())
+    }
+}
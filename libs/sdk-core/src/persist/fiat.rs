@@ -0,0 +1,159 @@
+use rusqlite::{named_params, OptionalExtension, Row};
+use sdk_common::prelude::Rate;
+
+use super::{db::SqliteStorage, error::PersistResult};
+use crate::models::HistoricalRate;
+
+impl SqliteStorage {
+    pub(crate) fn insert_fiat_rates(&self, rates: &[Rate], timestamp: i64) -> PersistResult<()> {
+        let mut con = self.get_connection()?;
+        let tx = con.transaction()?;
+        for rate in rates {
+            tx.execute(
+                "INSERT OR REPLACE INTO fiat_rates_history (coin, value, timestamp) VALUES (?1, ?2, ?3)",
+                (&rate.coin, rate.value, timestamp),
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The time of the most recent rates snapshot, if any
+    pub(crate) fn last_fiat_rates_timestamp(&self) -> PersistResult<Option<i64>> {
+        Ok(self.get_connection()?.query_row(
+            "SELECT MAX(timestamp) FROM fiat_rates_history",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Links a payment to the rates snapshot taken when it settled
+    pub(crate) fn set_payment_fiat_rates(
+        &self,
+        payment_id: &str,
+        rates_timestamp: i64,
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "INSERT OR IGNORE INTO payment_fiat_rates (payment_id, rates_timestamp) VALUES (?1, ?2)",
+            (payment_id, rates_timestamp),
+        )?;
+        Ok(())
+    }
+
+    /// Finds the rate of a currency for a payment.
+    ///
+    /// The rate from the snapshot linked to the payment is preferred. Otherwise the recorded rate
+    /// closest to the payment time is used, as long as it is at most `max_distance_sec` away.
+    pub(crate) fn get_payment_fiat_rate(
+        &self,
+        payment_id: &str,
+        payment_time: i64,
+        coin: &str,
+        max_distance_sec: i64,
+    ) -> PersistResult<Option<HistoricalRate>> {
+        let con = self.get_connection()?;
+        let linked_rate = con
+            .query_row(
+                "
+                SELECT h.coin, h.value, h.timestamp
+                FROM payment_fiat_rates p
+                JOIN fiat_rates_history h ON h.timestamp = p.rates_timestamp
+                WHERE p.payment_id = :payment_id AND h.coin = :coin
+                ",
+                named_params! {
+                    ":payment_id": payment_id,
+                    ":coin": coin,
+                },
+                |row| self.sql_row_to_historical_rate(row),
+            )
+            .optional()?;
+        if linked_rate.is_some() {
+            return Ok(linked_rate);
+        }
+
+        Ok(con
+            .query_row(
+                "
+                SELECT coin, value, timestamp
+                FROM fiat_rates_history
+                WHERE coin = :coin AND ABS(timestamp - :time) <= :max_distance
+                ORDER BY ABS(timestamp - :time)
+                LIMIT 1
+                ",
+                named_params! {
+                    ":coin": coin,
+                    ":time": payment_time,
+                    ":max_distance": max_distance_sec,
+                },
+                |row| self.sql_row_to_historical_rate(row),
+            )
+            .optional()?)
+    }
+
+    fn sql_row_to_historical_rate(&self, row: &Row) -> rusqlite::Result<HistoricalRate> {
+        Ok(HistoricalRate {
+            coin: row.get(0)?,
+            value: row.get(1)?,
+            timestamp: row.get(2)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdk_common::prelude::Rate;
+
+    use crate::models::HistoricalRate;
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::test_utils;
+
+    fn usd(value: f64) -> Rate {
+        Rate {
+            coin: "USD".to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_payment_fiat_rate() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+        assert_eq!(storage.last_fiat_rates_timestamp()?, None);
+
+        storage.insert_fiat_rates(&[usd(20_000.0)], 1_000)?;
+        storage.insert_fiat_rates(&[usd(30_000.0)], 5_000)?;
+        assert_eq!(storage.last_fiat_rates_timestamp()?, Some(5_000));
+
+        // Without a linked snapshot, the closest rate within range is used
+        let rate = storage.get_payment_fiat_rate("hash1", 4_000, "USD", 3_600)?;
+        assert_eq!(
+            rate,
+            Some(HistoricalRate {
+                coin: "USD".to_string(),
+                value: 30_000.0,
+                timestamp: 5_000,
+            })
+        );
+        assert_eq!(
+            storage.get_payment_fiat_rate("hash1", 10_000, "USD", 3_600)?,
+            None
+        );
+        assert_eq!(
+            storage.get_payment_fiat_rate("hash1", 4_000, "EUR", 3_600)?,
+            None
+        );
+
+        // A linked snapshot wins over a closer one
+        storage.set_payment_fiat_rates("hash1", 1_000)?;
+        let rate = storage.get_payment_fiat_rate("hash1", 4_000, "USD", 3_600)?;
+        assert_eq!(rate.map(|r| r.value), Some(20_000.0));
+
+        // The snapshot taken at settlement is never replaced
+        storage.set_payment_fiat_rates("hash1", 5_000)?;
+        let rate = storage.get_payment_fiat_rate("hash1", 4_000, "USD", 3_600)?;
+        assert_eq!(rate.map(|r| r.value), Some(20_000.0));
+
+        Ok(())
+    }
+}
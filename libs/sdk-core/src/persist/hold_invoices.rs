@@ -0,0 +1,138 @@
+use std::str::FromStr;
+
+use rusqlite::{named_params, OptionalExtension, Row};
+
+use super::{db::SqliteStorage, error::PersistResult};
+use crate::models::{HoldInvoice, HoldInvoiceStatus};
+
+impl SqliteStorage {
+    pub(crate) fn upsert_hold_invoice(&self, invoice: &HoldInvoice) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT INTO hold_invoices (
+              payment_hash,
+              bolt11,
+              amount_msat,
+              status,
+              accepted_amount_msat,
+              settle_deadline,
+              created_at,
+              expires_at,
+              updated_at
+            )
+            VALUES (:payment_hash, :bolt11, :amount_msat, :status, :accepted_amount_msat, :settle_deadline, :created_at, :expires_at, :updated_at)
+            ON CONFLICT(payment_hash) DO UPDATE SET
+              status = excluded.status,
+              accepted_amount_msat = excluded.accepted_amount_msat,
+              settle_deadline = excluded.settle_deadline,
+              updated_at = excluded.updated_at
+            ",
+            named_params! {
+                ":payment_hash": invoice.payment_hash,
+                ":bolt11": invoice.bolt11,
+                ":amount_msat": invoice.amount_msat,
+                ":status": invoice.status.to_string(),
+                ":accepted_amount_msat": invoice.accepted_amount_msat,
+                ":settle_deadline": invoice.settle_deadline,
+                ":created_at": invoice.created_at,
+                ":expires_at": invoice.expires_at,
+                ":updated_at": invoice.updated_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_hold_invoice(
+        &self,
+        payment_hash: &str,
+    ) -> PersistResult<Option<HoldInvoice>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "SELECT * FROM hold_invoices WHERE payment_hash = ?1",
+                [payment_hash],
+                |row| self.sql_row_to_hold_invoice(row),
+            )
+            .optional()?)
+    }
+
+    /// Lists the hold invoices, the latest first
+    pub(crate) fn list_hold_invoices(&self) -> PersistResult<Vec<HoldInvoice>> {
+        let con = self.get_connection()?;
+        let mut stmt =
+            con.prepare("SELECT * FROM hold_invoices ORDER BY created_at DESC, payment_hash")?;
+        let vec = stmt
+            .query_map([], |row| self.sql_row_to_hold_invoice(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec)
+    }
+
+    fn sql_row_to_hold_invoice(&self, row: &Row) -> rusqlite::Result<HoldInvoice> {
+        let status: String = row.get("status")?;
+        Ok(HoldInvoice {
+            payment_hash: row.get("payment_hash")?,
+            bolt11: row.get("bolt11")?,
+            amount_msat: row.get("amount_msat")?,
+            status: HoldInvoiceStatus::from_str(&status).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    3,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+            accepted_amount_msat: row.get("accepted_amount_msat")?,
+            settle_deadline: row.get("settle_deadline")?,
+            created_at: row.get("created_at")?,
+            expires_at: row.get("expires_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{HoldInvoice, HoldInvoiceStatus};
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::test_utils;
+
+    #[test]
+    fn test_hold_invoices() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        let mut escrow = HoldInvoice {
+            payment_hash: "aa".to_string(),
+            bolt11: "lnbc1".to_string(),
+            amount_msat: 10_000,
+            status: HoldInvoiceStatus::Open,
+            accepted_amount_msat: None,
+            settle_deadline: None,
+            created_at: 100,
+            expires_at: 3_700,
+            updated_at: 100,
+        };
+        let order = HoldInvoice {
+            payment_hash: "bb".to_string(),
+            bolt11: "lnbc2".to_string(),
+            created_at: 200,
+            ..escrow.clone()
+        };
+        storage.upsert_hold_invoice(&escrow)?;
+        storage.upsert_hold_invoice(&order)?;
+        assert_eq!(
+            storage.list_hold_invoices()?,
+            vec![order.clone(), escrow.clone()]
+        );
+
+        escrow.status = HoldInvoiceStatus::Accepted;
+        escrow.accepted_amount_msat = Some(10_000);
+        escrow.settle_deadline = Some(800_000);
+        escrow.updated_at = 300;
+        storage.upsert_hold_invoice(&escrow)?;
+        assert_eq!(storage.get_hold_invoice("aa")?, Some(escrow));
+        assert_eq!(storage.get_hold_invoice("cc")?, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use std::str::FromStr;
+
+use rusqlite::{named_params, OptionalExtension, Row};
+
+use super::{db::SqliteStorage, error::PersistResult};
+use crate::models::{Invoice, InvoiceStatus, ListInvoicesRequest, PaymentStatus};
+
+impl SqliteStorage {
+    /// Records an invoice created by the SDK. Its status and channel opening fee are derived
+    /// when it is listed.
+    pub(crate) fn insert_invoice(&self, invoice: &Invoice) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT OR IGNORE INTO invoices (
+              payment_hash,
+              bolt11,
+              description,
+              amount_msat,
+              opening_fee_params,
+              created_at,
+              expires_at
+            )
+            VALUES (:payment_hash, :bolt11, :description, :amount_msat, :opening_fee_params, :created_at, :expires_at)
+            ",
+            named_params! {
+                ":payment_hash": invoice.payment_hash,
+                ":bolt11": invoice.bolt11,
+                ":description": invoice.description,
+                ":amount_msat": invoice.amount_msat,
+                ":opening_fee_params": invoice.opening_fee_params,
+                ":created_at": invoice.created_at,
+                ":expires_at": invoice.expires_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_invoice(
+        &self,
+        payment_hash: &str,
+        now: i64,
+    ) -> PersistResult<Option<Invoice>> {
+        let query = self.select_invoice_query("i.payment_hash = :payment_hash");
+        Ok(self
+            .get_connection()?
+            .query_row(
+                &query,
+                named_params! {
+                    ":payment_hash": payment_hash,
+                    ":now": now,
+                },
+                |row| self.sql_row_to_invoice(row),
+            )
+            .optional()?)
+    }
+
+    /// Lists the invoices matching the request, the latest first
+    pub(crate) fn list_invoices(
+        &self,
+        req: ListInvoicesRequest,
+        now: i64,
+    ) -> PersistResult<Vec<Invoice>> {
+        let statuses = match req.statuses {
+            Some(statuses) if statuses.is_empty() => return Ok(Vec::new()),
+            Some(statuses) => Some(serde_json::to_string(
+                &statuses.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            )?),
+            None => None,
+        };
+
+        let mut query = self.select_invoice_query(
+            "
+            (:statuses IS NULL OR status IN (SELECT value FROM json_each(:statuses)))
+            AND (:from_timestamp IS NULL OR i.created_at >= :from_timestamp)
+            AND (:to_timestamp IS NULL OR i.created_at < :to_timestamp)
+            ",
+        );
+        query.push_str(
+            "
+            ORDER BY i.created_at DESC, i.payment_hash
+            LIMIT :limit
+            OFFSET :offset
+            ",
+        );
+
+        let con = self.get_connection()?;
+        let mut stmt = con.prepare(&query)?;
+        let vec = stmt
+            .query_map(
+                named_params! {
+                    ":now": now,
+                    ":statuses": statuses,
+                    ":from_timestamp": req.from_timestamp,
+                    ":to_timestamp": req.to_timestamp,
+                    ":limit": req.limit.map_or(-1, i64::from),
+                    ":offset": req.offset.unwrap_or_default(),
+                },
+                |row| self.sql_row_to_invoice(row),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec)
+    }
+
+    pub(crate) fn set_invoice_cancelled(&self, payment_hash: &str) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "UPDATE invoices SET cancelled = 1 WHERE payment_hash = ?1",
+            [payment_hash],
+        )?;
+        Ok(())
+    }
+
+    /// Lists the invoices that expired unpaid since the last call, marking them as notified
+    pub(crate) fn take_newly_expired_invoices(&self, now: i64) -> PersistResult<Vec<Invoice>> {
+        let expired = self.list_invoices(
+            ListInvoicesRequest {
+                statuses: Some(vec![InvoiceStatus::Expired]),
+                ..Default::default()
+            },
+            now,
+        )?;
+        let con = self.get_connection()?;
+        let mut newly_expired = Vec::new();
+        for invoice in expired {
+            let updated = con.execute(
+                "UPDATE invoices SET expiry_notified = 1 WHERE payment_hash = ?1 AND expiry_notified = 0",
+                [&invoice.payment_hash],
+            )?;
+            if updated == 1 {
+                newly_expired.push(invoice);
+            }
+        }
+        Ok(newly_expired)
+    }
+
+    fn select_invoice_query(&self, where_clause: &str) -> String {
+        format!(
+            "
+            SELECT * FROM (
+              SELECT
+               i.*,
+               o.payer_amount_msat,
+               CASE
+                WHEN p.id IS NOT NULL THEN '{paid}'
+                WHEN i.cancelled = 1 THEN '{cancelled}'
+                WHEN i.expires_at <= :now THEN '{expired}'
+                ELSE '{pending}'
+               END AS status
+              FROM invoices i
+              LEFT JOIN sync.open_channel_payment_info o ON o.payment_hash = i.payment_hash
+              LEFT JOIN payments p
+               ON p.id = i.payment_hash
+               AND p.payment_type = 'Received'
+               AND p.status = {complete}
+            ) i
+            WHERE {where_clause}
+            ",
+            paid = InvoiceStatus::Paid,
+            cancelled = InvoiceStatus::Cancelled,
+            expired = InvoiceStatus::Expired,
+            pending = InvoiceStatus::Pending,
+            complete = PaymentStatus::Complete as i64,
+        )
+    }
+
+    fn sql_row_to_invoice(&self, row: &Row) -> rusqlite::Result<Invoice> {
+        let amount_msat: u64 = row.get("amount_msat")?;
+        let payer_amount_msat: Option<u64> = row.get("payer_amount_msat")?;
+        let status: String = row.get("status")?;
+        Ok(Invoice {
+            payment_hash: row.get("payment_hash")?,
+            bolt11: row.get("bolt11")?,
+            description: row.get("description")?,
+            payer_amount_msat: payer_amount_msat.unwrap_or(amount_msat),
+            amount_msat,
+            opening_fee_msat: payer_amount_msat.map(|payer| payer.saturating_sub(amount_msat)),
+            opening_fee_params: row.get("opening_fee_params")?,
+            status: InvoiceStatus::from_str(&status).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+            created_at: row.get("created_at")?,
+            expires_at: row.get("expires_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Invoice, InvoiceStatus, ListInvoicesRequest, Payment, PaymentStatus};
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::test_utils;
+    use crate::PaymentType;
+
+    fn invoice(payment_hash: &str, created_at: i64) -> Invoice {
+        Invoice {
+            payment_hash: payment_hash.to_string(),
+            bolt11: format!("lnbc{payment_hash}"),
+            description: "Coffee".to_string(),
+            payer_amount_msat: 10_000,
+            amount_msat: 10_000,
+            opening_fee_msat: None,
+            opening_fee_params: None,
+            status: InvoiceStatus::Pending,
+            created_at,
+            expires_at: created_at + 3_600,
+        }
+    }
+
+    #[test]
+    fn test_invoices() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        let now = 10_000;
+        let paid = invoice("aa", 7_000);
+        let expired = invoice("bb", 5_000);
+        let cancelled = invoice("cc", 8_000);
+        let pending = Invoice {
+            amount_msat: 8_000,
+            ..invoice("dd", 9_000)
+        };
+        for i in [&paid, &expired, &cancelled, &pending] {
+            storage.insert_invoice(i)?;
+        }
+        storage.insert_or_update_payments(
+            &[Payment {
+                id: "aa".to_string(),
+                payment_type: PaymentType::Received,
+                status: PaymentStatus::Complete,
+                amount_msat: 10_000,
+                payment_time: 7_100,
+                ..Default::default()
+            }],
+            false,
+        )?;
+        storage.insert_open_channel_payment_info("dd", 10_000, "lnbcdd")?;
+        storage.set_invoice_cancelled("cc")?;
+
+        let statuses: Vec<_> = storage
+            .list_invoices(ListInvoicesRequest::default(), now)?
+            .into_iter()
+            .map(|i| (i.payment_hash, i.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("dd".to_string(), InvoiceStatus::Pending),
+                ("cc".to_string(), InvoiceStatus::Cancelled),
+                ("aa".to_string(), InvoiceStatus::Paid),
+                ("bb".to_string(), InvoiceStatus::Expired),
+            ]
+        );
+
+        let pending = storage.get_invoice("dd", now)?.unwrap();
+        assert_eq!(pending.payer_amount_msat, 10_000);
+        assert_eq!(pending.opening_fee_msat, Some(2_000));
+
+        let req = ListInvoicesRequest {
+            statuses: Some(vec![InvoiceStatus::Pending, InvoiceStatus::Paid]),
+            from_timestamp: Some(7_000),
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        };
+        let listed = storage.list_invoices(req, now)?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].payment_hash, "aa");
+
+        // Invoices are reported once, after they expire
+        let expired = storage.take_newly_expired_invoices(now)?;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].payment_hash, "bb");
+        assert!(storage.take_newly_expired_invoices(now)?.is_empty());
+        let expired = storage.take_newly_expired_invoices(now + 3_600)?;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].payment_hash, "dd");
+
+        Ok(())
+    }
+}
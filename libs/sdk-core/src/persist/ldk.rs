@@ -0,0 +1,183 @@
+use rusqlite::{named_params, params, OptionalExtension};
+
+use super::{db::SqliteStorage, error::PersistResult};
+
+/// An invoice issued by the LDK node. The preimage of a hold invoice is only
+/// known once it is settled.
+pub(crate) struct LdkInvoice {
+    pub preimage: Option<Vec<u8>>,
+    pub bolt11: Option<String>,
+}
+
+impl SqliteStorage {
+    /// Reserves the payment hash of an invoice about to be created.
+    ///
+    /// Returns false if an invoice with this payment hash already exists.
+    pub(crate) fn insert_ldk_invoice(
+        &self,
+        payment_hash: &[u8],
+        preimage: Option<&[u8]>,
+        created_at: i64,
+    ) -> PersistResult<bool> {
+        let inserted = self.get_connection()?.execute(
+            "
+            INSERT OR IGNORE INTO ldk_invoices (payment_hash, preimage, created_at)
+            VALUES (:payment_hash, :preimage, :created_at)
+            ",
+            named_params! {
+                ":payment_hash": hex::encode(payment_hash),
+                ":preimage": preimage.map(hex::encode),
+                ":created_at": created_at,
+            },
+        )?;
+        Ok(inserted == 1)
+    }
+
+    pub(crate) fn set_ldk_invoice_bolt11(
+        &self,
+        payment_hash: &[u8],
+        bolt11: &str,
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "UPDATE ldk_invoices SET bolt11 = ?1 WHERE payment_hash = ?2",
+            params![bolt11, hex::encode(payment_hash)],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn set_ldk_invoice_preimage(
+        &self,
+        payment_hash: &[u8],
+        preimage: &[u8],
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "UPDATE ldk_invoices SET preimage = ?1 WHERE payment_hash = ?2",
+            [hex::encode(preimage), hex::encode(payment_hash)],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn delete_ldk_invoice(&self, payment_hash: &[u8]) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "DELETE FROM ldk_invoices WHERE payment_hash = ?1",
+            [hex::encode(payment_hash)],
+        )?;
+        Ok(())
+    }
+
+    /// Records the claimable amount of the HTLCs held for a hold invoice
+    pub(crate) fn set_ldk_hold_invoice_held_amount(
+        &self,
+        payment_hash: &[u8],
+        amount_msat: u64,
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "UPDATE ldk_invoices SET held_amount_msat = ?1 WHERE payment_hash = ?2",
+            params![amount_msat, hex::encode(payment_hash)],
+        )?;
+        Ok(())
+    }
+
+    /// Marks a hold invoice as cancelled, so any later HTLCs paying it are failed back
+    pub(crate) fn cancel_ldk_hold_invoice(&self, payment_hash: &[u8]) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "UPDATE ldk_invoices SET cancelled = 1 WHERE payment_hash = ?1",
+            [hex::encode(payment_hash)],
+        )?;
+        Ok(())
+    }
+
+    /// Lists the payment hashes of the hold invoices neither settled nor cancelled, with the
+    /// claimable amount of their HTLCs once held
+    pub(crate) fn list_open_ldk_hold_invoices(&self) -> PersistResult<Vec<(Vec<u8>, Option<u64>)>> {
+        let con = self.get_connection()?;
+        let mut stmt = con.prepare(
+            "
+            SELECT payment_hash, held_amount_msat FROM ldk_invoices
+            WHERE preimage IS NULL AND cancelled = 0
+            ",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<u64>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(|(hash, held)| Ok((hex::decode(hash)?, held)))
+            .collect()
+    }
+
+    pub(crate) fn get_ldk_invoice(&self, payment_hash: &[u8]) -> PersistResult<Option<LdkInvoice>> {
+        let row: Option<(Option<String>, Option<String>)> = self
+            .get_connection()?
+            .query_row(
+                "SELECT preimage, bolt11 FROM ldk_invoices WHERE payment_hash = ?1",
+                [hex::encode(payment_hash)],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((preimage, bolt11)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(LdkInvoice {
+            preimage: preimage.map(hex::decode).transpose()?,
+            bolt11,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::test_utils;
+
+    #[test]
+    fn test_ldk_invoices() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        let (hash, preimage) = ([1u8; 32], [2u8; 32]);
+        assert!(storage.get_ldk_invoice(&hash)?.is_none());
+        assert!(storage.insert_ldk_invoice(&hash, Some(&preimage), 1_000)?);
+        assert!(!storage.insert_ldk_invoice(&hash, Some(&[3u8; 32]), 2_000)?);
+        storage.set_ldk_invoice_bolt11(&hash, "lnbc1")?;
+        let invoice = storage.get_ldk_invoice(&hash)?.unwrap();
+        assert_eq!(invoice.preimage, Some(preimage.to_vec()));
+        assert_eq!(invoice.bolt11.as_deref(), Some("lnbc1"));
+
+        // Hold invoices learn their preimage on settlement
+        let hold_hash = [4u8; 32];
+        assert!(storage.insert_ldk_invoice(&hold_hash, None, 1_000)?);
+        assert!(storage
+            .get_ldk_invoice(&hold_hash)?
+            .unwrap()
+            .preimage
+            .is_none());
+        assert_eq!(
+            storage.list_open_ldk_hold_invoices()?,
+            vec![(hold_hash.to_vec(), None)]
+        );
+        storage.set_ldk_hold_invoice_held_amount(&hold_hash, 50_000)?;
+        assert_eq!(
+            storage.list_open_ldk_hold_invoices()?,
+            vec![(hold_hash.to_vec(), Some(50_000))]
+        );
+        storage.set_ldk_invoice_preimage(&hold_hash, &[5u8; 32])?;
+        assert_eq!(
+            storage.get_ldk_invoice(&hold_hash)?.unwrap().preimage,
+            Some([5u8; 32].to_vec())
+        );
+        assert!(storage.list_open_ldk_hold_invoices()?.is_empty());
+
+        // Cancelled hold invoices are not open either
+        let cancelled_hash = [6u8; 32];
+        assert!(storage.insert_ldk_invoice(&cancelled_hash, None, 1_000)?);
+        storage.cancel_ldk_hold_invoice(&cancelled_hash)?;
+        assert!(storage.list_open_ldk_hold_invoices()?.is_empty());
+
+        storage.delete_ldk_invoice(&hash)?;
+        assert!(storage.get_ldk_invoice(&hash)?.is_none());
+        Ok(())
+    }
+}
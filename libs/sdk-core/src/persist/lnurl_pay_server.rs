@@ -0,0 +1,59 @@
+use rusqlite::{named_params, OptionalExtension, Row};
+
+use super::{db::SqliteStorage, error::PersistResult};
+
+/// An invoice issued through the LNURL-pay server, with the LUD-12 comment of the payer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LnUrlPayServerInvoiceRecord {
+    pub payment_hash: String,
+    pub bolt11: String,
+    pub comment: Option<String>,
+    pub created_at: i64,
+}
+
+impl SqliteStorage {
+    pub(crate) fn insert_lnurl_pay_server_invoice(
+        &self,
+        invoice: &LnUrlPayServerInvoiceRecord,
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT INTO lnurl_pay_server_invoices (payment_hash, bolt11, comment, created_at)
+            VALUES (:payment_hash, :bolt11, :comment, :created_at)
+            ",
+            named_params! {
+                ":payment_hash": invoice.payment_hash,
+                ":bolt11": invoice.bolt11,
+                ":comment": invoice.comment,
+                ":created_at": invoice.created_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_lnurl_pay_server_invoice(
+        &self,
+        payment_hash: &str,
+    ) -> PersistResult<Option<LnUrlPayServerInvoiceRecord>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "SELECT * FROM lnurl_pay_server_invoices WHERE payment_hash = ?1",
+                [payment_hash],
+                |row| self.sql_row_to_lnurl_pay_server_invoice(row),
+            )
+            .optional()?)
+    }
+
+    fn sql_row_to_lnurl_pay_server_invoice(
+        &self,
+        row: &Row,
+    ) -> rusqlite::Result<LnUrlPayServerInvoiceRecord> {
+        Ok(LnUrlPayServerInvoiceRecord {
+            payment_hash: row.get("payment_hash")?,
+            bolt11: row.get("bolt11")?,
+            comment: row.get("comment")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
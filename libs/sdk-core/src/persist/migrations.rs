@@ -486,7 +486,85 @@ pub(crate) fn current_migrations() -> Vec<&'static str> {
         DELETE FROM payments;
         DELETE FROM cached_items WHERE key = 'sync_state';
        ",
-       "ALTER TABLE swaps_info ADD COLUMN chain_data TEXT;"
+       "ALTER TABLE swaps_info ADD COLUMN chain_data TEXT;",
+       "
+       CREATE TABLE IF NOT EXISTS nwc_connections (
+        client_pubkey TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        permissions TEXT NOT NULL,
+        budget_msat INTEGER,
+        budget_renewal TEXT,
+        created_at INTEGER NOT NULL
+       ) STRICT;
+
+       CREATE TABLE IF NOT EXISTS nwc_payments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        client_pubkey TEXT NOT NULL,
+        payment_hash TEXT NOT NULL,
+        amount_msat INTEGER NOT NULL,
+        status TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        UNIQUE (client_pubkey, payment_hash)
+       ) STRICT;
+       ",
+       "
+       CREATE TABLE IF NOT EXISTS fiat_rates_history (
+        coin TEXT NOT NULL,
+        value REAL NOT NULL,
+        timestamp INTEGER NOT NULL,
+        PRIMARY KEY (coin, timestamp)
+       ) STRICT;
+
+       CREATE TABLE IF NOT EXISTS payment_fiat_rates (
+        payment_id TEXT PRIMARY KEY NOT NULL,
+        rates_timestamp INTEGER NOT NULL
+       ) STRICT;
+       ",
+       "CREATE INDEX IF NOT EXISTS payments_time_id ON payments(payment_time DESC, id);",
+       "
+       CREATE TABLE IF NOT EXISTS hold_invoices (
+        payment_hash TEXT PRIMARY KEY NOT NULL,
+        bolt11 TEXT NOT NULL,
+        amount_msat INTEGER NOT NULL,
+        status TEXT NOT NULL,
+        accepted_amount_msat INTEGER,
+        settle_deadline INTEGER,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+       ) STRICT;
+       ",
+       "
+       CREATE TABLE IF NOT EXISTS invoices (
+        payment_hash TEXT PRIMARY KEY NOT NULL,
+        bolt11 TEXT NOT NULL,
+        description TEXT NOT NULL,
+        amount_msat INTEGER NOT NULL,
+        opening_fee_params TEXT,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL,
+        cancelled INTEGER NOT NULL DEFAULT 0,
+        expiry_notified INTEGER NOT NULL DEFAULT 0
+       ) STRICT;
+       ",
+       "
+       CREATE TABLE IF NOT EXISTS ldk_invoices (
+        payment_hash TEXT PRIMARY KEY NOT NULL,
+        preimage TEXT,
+        bolt11 TEXT,
+        created_at INTEGER NOT NULL,
+        held_amount_msat INTEGER,
+        cancelled INTEGER NOT NULL DEFAULT 0
+       ) STRICT;
+       ",
+       "
+       CREATE TABLE IF NOT EXISTS lnurl_pay_server_invoices (
+        payment_hash TEXT PRIMARY KEY NOT NULL,
+        bolt11 TEXT NOT NULL,
+        comment TEXT,
+        created_at INTEGER NOT NULL
+       ) STRICT;
+       ",
     ]
 }
 
@@ -673,5 +751,127 @@ pub(crate) fn current_sync_migrations() -> Vec<&'static str> {
         ",
         "ALTER TABLE payments_external_info ADD COLUMN lnurl_pay_comment TEXT;",
         "DELETE FROM open_channel_payment_info;",
+        "ALTER TABLE payments_external_info ADD COLUMN lnurl_verify_url TEXT;",
+        "
+        ALTER TABLE payments_external_info ADD COLUMN zap_request TEXT;
+        ALTER TABLE payments_external_info ADD COLUMN zap_receipt TEXT;
+        ALTER TABLE payments_external_info ADD COLUMN zap_nostr_pubkey TEXT;
+        ",
+        // Populated from the main DB payments, see SqliteStorage::index_payments_for_search
+        "
+        CREATE VIRTUAL TABLE IF NOT EXISTS payments_fts USING fts5(
+         payment_id UNINDEXED,
+         description,
+         label,
+         ln_address,
+         lnurl_pay_domain,
+         lnurl_pay_comment,
+         metadata
+        );
+        ",
+        "
+        CREATE TABLE IF NOT EXISTS contacts (
+         id TEXT PRIMARY KEY NOT NULL,
+         name TEXT NOT NULL,
+         ln_address TEXT,
+         node_pubkey TEXT,
+         lnurl_pay_domain TEXT,
+         note TEXT,
+         created_at INTEGER NOT NULL,
+         updated_at INTEGER NOT NULL,
+         deleted INTEGER NOT NULL DEFAULT 0
+        ) STRICT;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_contacts
+         AFTER INSERT ON contacts
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('contacts');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_contacts_update
+         AFTER UPDATE ON contacts
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('contacts');
+        END;
+        ",
+        "
+        CREATE TABLE IF NOT EXISTS recurring_payments (
+         id TEXT PRIMARY KEY NOT NULL,
+         destination TEXT NOT NULL,
+         amount TEXT NOT NULL,
+         interval TEXT NOT NULL,
+         comment TEXT,
+         start_time INTEGER NOT NULL,
+         next_payment_time INTEGER NOT NULL,
+         paused INTEGER NOT NULL DEFAULT 0,
+         created_at INTEGER NOT NULL,
+         updated_at INTEGER NOT NULL,
+         deleted INTEGER NOT NULL DEFAULT 0
+        ) STRICT;
+
+        CREATE TABLE IF NOT EXISTS recurring_payment_runs (
+         recurring_payment_id TEXT NOT NULL,
+         scheduled_time INTEGER NOT NULL,
+         payment_id TEXT,
+         error TEXT,
+         attempts INTEGER NOT NULL,
+         updated_at INTEGER NOT NULL,
+         attempt_label TEXT,
+         PRIMARY KEY (recurring_payment_id, scheduled_time)
+        ) STRICT;
+
+        CREATE TABLE IF NOT EXISTS recurring_payment_claims (
+         recurring_payment_id TEXT NOT NULL,
+         scheduled_time INTEGER NOT NULL,
+         instance_id TEXT NOT NULL,
+         claimed_at INTEGER NOT NULL,
+         PRIMARY KEY (recurring_payment_id, scheduled_time)
+        ) STRICT;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_recurring_payments
+         AFTER INSERT ON recurring_payments
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('recurring_payments');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_recurring_payments_update
+         AFTER UPDATE ON recurring_payments
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('recurring_payments');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_recurring_payment_runs
+         AFTER INSERT ON recurring_payment_runs
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('recurring_payment_runs');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_recurring_payment_runs_update
+         AFTER UPDATE ON recurring_payment_runs
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('recurring_payment_runs');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_recurring_payment_claims
+         AFTER INSERT ON recurring_payment_claims
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('recurring_payment_claims');
+        END;
+        ",
+        "ALTER TABLE payments_external_info ADD COLUMN fiat_amount TEXT;",
+        "
+        CREATE TABLE IF NOT EXISTS greenlight_devices (
+         id TEXT PRIMARY KEY NOT NULL,
+         label TEXT NOT NULL,
+         registered_at INTEGER NOT NULL,
+         last_seen_at INTEGER
+        ) STRICT;
+
+        CREATE TRIGGER IF NOT EXISTS sync_requests_greenlight_devices
+         AFTER INSERT ON greenlight_devices
+        BEGIN
+         INSERT INTO sync_requests(changed_table) VALUES('greenlight_devices');
+        END;
+        ",
 	]
 }
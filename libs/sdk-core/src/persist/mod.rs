@@ -1,8 +1,19 @@
 pub(crate) mod cache;
 pub(crate) mod channels;
+pub(crate) mod contacts;
 pub(crate) mod db;
+pub(crate) mod devices;
 pub(crate) mod error;
+pub(crate) mod fiat;
+pub(crate) mod hold_invoices;
+pub(crate) mod invoices;
+#[cfg(feature = "ldk")]
+pub(crate) mod ldk;
+pub(crate) mod lnurl_pay_server;
 pub(crate) mod migrations;
+#[cfg(feature = "nwc")]
+pub(crate) mod nwc;
+pub(crate) mod recurring;
 pub(crate) mod reverseswap;
 pub(crate) mod send_pays;
 pub(crate) mod settings;
@@ -0,0 +1,235 @@
+use rusqlite::{named_params, OptionalExtension, Row};
+use sdk_common::prelude::NwcMethod;
+
+use super::{db::SqliteStorage, error::PersistResult};
+use crate::nwc::{NwcBudget, NwcBudgetRenewal, NwcConnection};
+
+impl SqliteStorage {
+    pub(crate) fn insert_nwc_connection(&self, connection: &NwcConnection) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT INTO nwc_connections (
+              client_pubkey,
+              name,
+              permissions,
+              budget_msat,
+              budget_renewal,
+              created_at
+            )
+            VALUES (:client_pubkey, :name, :permissions, :budget_msat, :budget_renewal, :created_at)
+            ",
+            named_params! {
+                ":client_pubkey": connection.client_pubkey,
+                ":name": connection.name,
+                ":permissions": serde_json::to_string(&connection.permissions)?,
+                ":budget_msat": connection.budget.as_ref().map(|b| b.max_amount_msat),
+                ":budget_renewal": connection
+                    .budget
+                    .as_ref()
+                    .map(|b| serde_json::to_string(&b.renewal))
+                    .transpose()?,
+                ":created_at": connection.created_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_nwc_connection(
+        &self,
+        client_pubkey: &str,
+    ) -> PersistResult<Option<NwcConnection>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "SELECT * FROM nwc_connections WHERE client_pubkey = ?1",
+                [client_pubkey],
+                |row| self.sql_row_to_nwc_connection(row),
+            )
+            .optional()?)
+    }
+
+    pub(crate) fn list_nwc_connections(&self) -> PersistResult<Vec<NwcConnection>> {
+        let con = self.get_connection()?;
+        let mut stmt = con.prepare("SELECT * FROM nwc_connections ORDER BY created_at DESC")?;
+        let vec = stmt
+            .query_map([], |row| self.sql_row_to_nwc_connection(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec)
+    }
+
+    pub(crate) fn delete_nwc_connection(&self, client_pubkey: &str) -> PersistResult<()> {
+        let con = self.get_connection()?;
+        con.execute(
+            "DELETE FROM nwc_connections WHERE client_pubkey = ?1",
+            [client_pubkey],
+        )?;
+        con.execute(
+            "DELETE FROM nwc_payments WHERE client_pubkey = ?1",
+            [client_pubkey],
+        )?;
+        Ok(())
+    }
+
+    /// Reserves an amount against the budget of a connection, before paying `payment_hash`.
+    ///
+    /// Returns the id of the reserved spend, or `None` if the connection already paid or is
+    /// paying this hash.
+    pub(crate) fn insert_nwc_payment(
+        &self,
+        client_pubkey: &str,
+        payment_hash: &str,
+        amount_msat: u64,
+        created_at: i64,
+    ) -> PersistResult<Option<i64>> {
+        let con = self.get_connection()?;
+        let inserted = con.execute(
+            "
+            INSERT OR IGNORE INTO nwc_payments (
+              client_pubkey,
+              payment_hash,
+              amount_msat,
+              status,
+              created_at
+            )
+            VALUES (:client_pubkey, :payment_hash, :amount_msat, 'pending', :created_at)
+            ",
+            named_params! {
+                ":client_pubkey": client_pubkey,
+                ":payment_hash": payment_hash,
+                ":amount_msat": amount_msat,
+                ":created_at": created_at,
+            },
+        )?;
+        Ok((inserted == 1).then(|| con.last_insert_rowid()))
+    }
+
+    /// Records the final amount of a succeeded payment, fees included. The spend is kept for
+    /// good from then on.
+    pub(crate) fn complete_nwc_payment(&self, id: i64, amount_msat: u64) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "UPDATE nwc_payments SET amount_msat = :amount_msat, status = 'complete' WHERE id = :id",
+            named_params! {
+                ":id": id,
+                ":amount_msat": amount_msat,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Releases the reservation of a failed payment. Completed payments are never removed.
+    pub(crate) fn delete_pending_nwc_payment(&self, id: i64) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "DELETE FROM nwc_payments WHERE id = ?1 AND status = 'pending'",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// The total amount spent by a connection since the given epoch time, in seconds.
+    pub(crate) fn get_nwc_spent_msat(&self, client_pubkey: &str, since: i64) -> PersistResult<u64> {
+        Ok(self.get_connection()?.query_row(
+            "
+            SELECT COALESCE(SUM(amount_msat), 0)
+            FROM nwc_payments
+            WHERE client_pubkey = ?1 AND created_at >= ?2
+            ",
+            (client_pubkey, since),
+            |row| row.get(0),
+        )?)
+    }
+
+    fn sql_row_to_nwc_connection(&self, row: &Row) -> Result<NwcConnection, rusqlite::Error> {
+        let permissions: String = row.get(2)?;
+        let budget_msat: Option<u64> = row.get(3)?;
+        let budget_renewal: Option<String> = row.get(4)?;
+        let budget = match (budget_msat, budget_renewal) {
+            (Some(max_amount_msat), Some(renewal)) => Some(NwcBudget {
+                max_amount_msat,
+                renewal: serde_json::from_str::<NwcBudgetRenewal>(&renewal).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        4,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?,
+            }),
+            _ => None,
+        };
+        Ok(NwcConnection {
+            client_pubkey: row.get(0)?,
+            name: row.get(1)?,
+            permissions: serde_json::from_str::<Vec<NwcMethod>>(&permissions).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+            budget,
+            created_at: row.get(5)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdk_common::prelude::NwcMethod;
+
+    use crate::nwc::{NwcBudget, NwcBudgetRenewal, NwcConnection};
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::test_utils;
+
+    #[test]
+    fn test_nwc_connections() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        let connection = NwcConnection {
+            client_pubkey: "client".to_string(),
+            name: "test".to_string(),
+            permissions: vec![NwcMethod::PayInvoice, NwcMethod::GetBalance],
+            budget: Some(NwcBudget {
+                max_amount_msat: 10_000,
+                renewal: NwcBudgetRenewal::Daily,
+            }),
+            created_at: 100,
+        };
+        storage.insert_nwc_connection(&connection)?;
+        assert_eq!(
+            storage.get_nwc_connection("client")?,
+            Some(connection.clone())
+        );
+        assert_eq!(storage.list_nwc_connections()?, vec![connection]);
+
+        let id1 = storage
+            .insert_nwc_payment("client", "hash1", 1_000, 100)?
+            .unwrap();
+        let id2 = storage
+            .insert_nwc_payment("client", "hash2", 2_000, 200)?
+            .unwrap();
+        storage.insert_nwc_payment("other", "hash3", 4_000, 200)?;
+        assert_eq!(storage.get_nwc_spent_msat("client", 0)?, 3_000);
+        assert_eq!(storage.get_nwc_spent_msat("client", 150)?, 2_000);
+
+        // The same hash can't be reserved twice by a connection
+        assert!(storage
+            .insert_nwc_payment("client", "hash1", 1_000, 300)?
+            .is_none());
+        assert!(storage
+            .insert_nwc_payment("other", "hash1", 1_000, 300)?
+            .is_some());
+
+        // Completed payments are not released
+        storage.complete_nwc_payment(id1, 1_100)?;
+        storage.delete_pending_nwc_payment(id1)?;
+        storage.delete_pending_nwc_payment(id2)?;
+        assert_eq!(storage.get_nwc_spent_msat("client", 0)?, 1_100);
+
+        storage.delete_nwc_connection("client")?;
+        assert!(storage.get_nwc_connection("client")?.is_none());
+        assert_eq!(storage.get_nwc_spent_msat("client", 0)?, 0);
+        assert_eq!(storage.get_nwc_spent_msat("other", 0)?, 5_000);
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use std::str::FromStr;
+
+use rusqlite::{named_params, OptionalExtension, Row};
+use sdk_common::ensure_sdk;
+
+use super::{
+    db::SqliteStorage,
+    error::{PersistError, PersistResult},
+};
+use crate::models::{RecurringPayment, RecurringPaymentInterval, RecurringPaymentRun};
+
+/// The instance that pays one of the scheduled payments of a [RecurringPayment], so the
+/// instances sharing a node don't all pay it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RecurringPaymentClaim {
+    pub recurring_payment_id: String,
+    pub scheduled_time: i64,
+    pub instance_id: String,
+    pub claimed_at: i64,
+}
+
+impl SqliteStorage {
+    pub(crate) fn upsert_recurring_payment(&self, payment: &RecurringPayment) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT INTO sync.recurring_payments (
+              id,
+              destination,
+              amount,
+              interval,
+              comment,
+              start_time,
+              next_payment_time,
+              paused,
+              created_at,
+              updated_at
+            )
+            VALUES (:id, :destination, :amount, :interval, :comment, :start_time, :next_payment_time, :paused, :created_at, :updated_at)
+            ON CONFLICT(id) DO UPDATE SET
+              destination = excluded.destination,
+              amount = excluded.amount,
+              interval = excluded.interval,
+              comment = excluded.comment,
+              start_time = excluded.start_time,
+              next_payment_time = excluded.next_payment_time,
+              paused = excluded.paused,
+              updated_at = excluded.updated_at
+            ",
+            named_params! {
+                ":id": payment.id,
+                ":destination": payment.destination,
+                ":amount": payment.amount,
+                ":interval": payment.interval.to_string(),
+                ":comment": payment.comment,
+                ":start_time": payment.start_time,
+                ":next_payment_time": payment.next_payment_time,
+                ":paused": payment.paused,
+                ":created_at": payment.created_at,
+                ":updated_at": payment.updated_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_recurring_payment(
+        &self,
+        id: &str,
+    ) -> PersistResult<Option<RecurringPayment>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "SELECT * FROM sync.recurring_payments WHERE id = ?1 AND deleted = 0",
+                [id],
+                |row| self.sql_row_to_recurring_payment(row),
+            )
+            .optional()?)
+    }
+
+    /// Lists the recurring payments, the next one due first
+    pub(crate) fn list_recurring_payments(&self) -> PersistResult<Vec<RecurringPayment>> {
+        let con = self.get_connection()?;
+        let mut stmt = con.prepare(
+            "SELECT * FROM sync.recurring_payments WHERE deleted = 0 ORDER BY next_payment_time, id",
+        )?;
+        let vec = stmt
+            .query_map([], |row| self.sql_row_to_recurring_payment(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec)
+    }
+
+    /// Marks a recurring payment as deleted, so the deletion is synced to the other instances
+    pub(crate) fn delete_recurring_payment(&self, id: &str, updated_at: i64) -> PersistResult<()> {
+        let updated = self.get_connection()?.execute(
+            "UPDATE sync.recurring_payments SET deleted = 1, updated_at = ?2 WHERE id = ?1 AND deleted = 0",
+            (id, updated_at),
+        )?;
+        ensure_sdk!(
+            updated == 1,
+            PersistError::generic("Recurring payment not found")
+        );
+        Ok(())
+    }
+
+    pub(crate) fn upsert_recurring_payment_run(
+        &self,
+        run: &RecurringPaymentRun,
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT OR REPLACE INTO sync.recurring_payment_runs (
+              recurring_payment_id,
+              scheduled_time,
+              payment_id,
+              error,
+              attempts,
+              updated_at,
+              attempt_label
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ",
+            (
+                &run.recurring_payment_id,
+                run.scheduled_time,
+                &run.payment_id,
+                &run.error,
+                run.attempts,
+                run.updated_at,
+                &run.attempt_label,
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Claims a scheduled payment for an instance, unless it's already claimed
+    pub(crate) fn insert_recurring_payment_claim(
+        &self,
+        claim: &RecurringPaymentClaim,
+    ) -> PersistResult<()> {
+        self.get_connection()?.execute(
+            "
+            INSERT OR IGNORE INTO sync.recurring_payment_claims (
+              recurring_payment_id,
+              scheduled_time,
+              instance_id,
+              claimed_at
+            )
+            VALUES (:recurring_payment_id, :scheduled_time, :instance_id, :claimed_at)
+            ",
+            named_params! {
+                ":recurring_payment_id": claim.recurring_payment_id,
+                ":scheduled_time": claim.scheduled_time,
+                ":instance_id": claim.instance_id,
+                ":claimed_at": claim.claimed_at,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_recurring_payment_claim(
+        &self,
+        recurring_payment_id: &str,
+        scheduled_time: i64,
+    ) -> PersistResult<Option<RecurringPaymentClaim>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "
+                SELECT * FROM sync.recurring_payment_claims
+                WHERE recurring_payment_id = ?1 AND scheduled_time = ?2
+                ",
+                (recurring_payment_id, scheduled_time),
+                |row| {
+                    Ok(RecurringPaymentClaim {
+                        recurring_payment_id: row.get("recurring_payment_id")?,
+                        scheduled_time: row.get("scheduled_time")?,
+                        instance_id: row.get("instance_id")?,
+                        claimed_at: row.get("claimed_at")?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    pub(crate) fn get_recurring_payment_run(
+        &self,
+        recurring_payment_id: &str,
+        scheduled_time: i64,
+    ) -> PersistResult<Option<RecurringPaymentRun>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "
+                SELECT * FROM sync.recurring_payment_runs
+                WHERE recurring_payment_id = ?1 AND scheduled_time = ?2
+                ",
+                (recurring_payment_id, scheduled_time),
+                |row| self.sql_row_to_recurring_payment_run(row),
+            )
+            .optional()?)
+    }
+
+    /// Lists the runs of a recurring payment, the latest first
+    pub(crate) fn list_recurring_payment_runs(
+        &self,
+        recurring_payment_id: &str,
+    ) -> PersistResult<Vec<RecurringPaymentRun>> {
+        let con = self.get_connection()?;
+        let mut stmt = con.prepare(
+            "
+            SELECT * FROM sync.recurring_payment_runs
+            WHERE recurring_payment_id = ?1
+            ORDER BY scheduled_time DESC
+            ",
+        )?;
+        let vec = stmt
+            .query_map([recurring_payment_id], |row| {
+                self.sql_row_to_recurring_payment_run(row)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec)
+    }
+
+    fn sql_row_to_recurring_payment(&self, row: &Row) -> rusqlite::Result<RecurringPayment> {
+        let interval: String = row.get("interval")?;
+        Ok(RecurringPayment {
+            id: row.get("id")?,
+            destination: row.get("destination")?,
+            amount: row.get("amount")?,
+            interval: RecurringPaymentInterval::from_str(&interval).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    3,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+            comment: row.get("comment")?,
+            start_time: row.get("start_time")?,
+            next_payment_time: row.get("next_payment_time")?,
+            paused: row.get("paused")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+
+    fn sql_row_to_recurring_payment_run(&self, row: &Row) -> rusqlite::Result<RecurringPaymentRun> {
+        Ok(RecurringPaymentRun {
+            recurring_payment_id: row.get("recurring_payment_id")?,
+            scheduled_time: row.get("scheduled_time")?,
+            payment_id: row.get("payment_id")?,
+            error: row.get("error")?,
+            attempts: row.get("attempts")?,
+            updated_at: row.get("updated_at")?,
+            attempt_label: row.get("attempt_label")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{
+        RecurringPayment, RecurringPaymentAmount, RecurringPaymentDestination,
+        RecurringPaymentInterval, RecurringPaymentRun,
+    };
+    use crate::persist::db::SqliteStorage;
+    use crate::persist::error::PersistResult;
+    use crate::persist::recurring::RecurringPaymentClaim;
+    use crate::persist::test_utils;
+
+    #[test]
+    fn test_recurring_payments() -> PersistResult<()> {
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+
+        let mut donation = RecurringPayment {
+            id: "1".to_string(),
+            destination: RecurringPaymentDestination::LnUrlPay {
+                lnurl: "alice@example.com".to_string(),
+            },
+            amount: RecurringPaymentAmount::Fiat {
+                currency: "USD".to_string(),
+                amount: 5.0,
+            },
+            interval: RecurringPaymentInterval::Monthly,
+            comment: Some("Thanks".to_string()),
+            start_time: 1_000,
+            next_payment_time: 1_000,
+            paused: false,
+            created_at: 100,
+            updated_at: 100,
+        };
+        let payout = RecurringPayment {
+            id: "2".to_string(),
+            destination: RecurringPaymentDestination::Keysend {
+                node_id: "02aa".to_string(),
+            },
+            amount: RecurringPaymentAmount::Bitcoin {
+                amount_msat: 10_000,
+            },
+            interval: RecurringPaymentInterval::Weekly,
+            comment: None,
+            start_time: 500,
+            next_payment_time: 500,
+            ..donation.clone()
+        };
+        storage.upsert_recurring_payment(&donation)?;
+        storage.upsert_recurring_payment(&payout)?;
+        assert_eq!(
+            storage.list_recurring_payments()?,
+            vec![payout.clone(), donation.clone()]
+        );
+
+        donation.next_payment_time = 2_000;
+        donation.paused = true;
+        donation.updated_at = 200;
+        storage.upsert_recurring_payment(&donation)?;
+        assert_eq!(storage.get_recurring_payment("1")?, Some(donation));
+
+        let mut run = RecurringPaymentRun {
+            recurring_payment_id: "2".to_string(),
+            scheduled_time: 500,
+            payment_id: None,
+            error: Some("No route".to_string()),
+            attempts: 1,
+            updated_at: 600,
+            attempt_label: Some("recurring:2:500:1".to_string()),
+        };
+        storage.upsert_recurring_payment_run(&run)?;
+        run.payment_id = Some("hash".to_string());
+        run.attempts = 2;
+        run.attempt_label = None;
+        storage.upsert_recurring_payment_run(&run)?;
+        assert_eq!(
+            storage.get_recurring_payment_run("2", 500)?,
+            Some(run.clone())
+        );
+        assert_eq!(storage.get_recurring_payment_run("2", 1_000)?, None);
+        assert_eq!(storage.list_recurring_payment_runs("2")?, vec![run]);
+
+        // The first claim is kept
+        let claim = RecurringPaymentClaim {
+            recurring_payment_id: "2".to_string(),
+            scheduled_time: 500,
+            instance_id: "a".to_string(),
+            claimed_at: 500,
+        };
+        storage.insert_recurring_payment_claim(&claim)?;
+        storage.insert_recurring_payment_claim(&RecurringPaymentClaim {
+            instance_id: "b".to_string(),
+            ..claim.clone()
+        })?;
+        assert_eq!(storage.get_recurring_payment_claim("2", 500)?, Some(claim));
+        assert_eq!(storage.get_recurring_payment_claim("2", 1_000)?, None);
+
+        storage.delete_recurring_payment("2", 300)?;
+        assert!(storage.delete_recurring_payment("2", 300).is_err());
+        assert_eq!(storage.get_recurring_payment("2")?, None);
+        assert_eq!(storage.list_recurring_payments()?.len(), 1);
+
+        Ok(())
+    }
+}
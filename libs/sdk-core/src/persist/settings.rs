@@ -1,6 +1,9 @@
 use serde::Serialize;
 
 use super::{db::SqliteStorage, error::PersistResult};
+use crate::models::SpendingPolicy;
+
+const KEY_SPENDING_POLICY: &str = "spending-policy";
 
 #[allow(dead_code)]
 #[derive(Serialize)]
@@ -81,6 +84,23 @@ impl SqliteStorage {
     pub fn get_lsp_pubkey(&self) -> PersistResult<Option<String>> {
         self.get_setting("lsp-pubkey".to_string())
     }
+
+    pub fn set_spending_policy(&self, policy: Option<&SpendingPolicy>) -> PersistResult<()> {
+        match policy {
+            Some(policy) => self.update_setting(
+                KEY_SPENDING_POLICY.to_string(),
+                serde_json::to_string(policy)?,
+            ),
+            None => self.delete_setting(KEY_SPENDING_POLICY.to_string()),
+        }
+    }
+
+    pub fn get_spending_policy(&self) -> PersistResult<Option<SpendingPolicy>> {
+        Ok(match self.get_setting(KEY_SPENDING_POLICY.to_string())? {
+            Some(str) => Some(serde_json::from_str(&str)?),
+            None => None,
+        })
+    }
 }
 
 #[test]
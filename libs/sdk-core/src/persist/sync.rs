@@ -159,6 +159,25 @@ impl SqliteStorage {
             [],
         )?;
 
+        // The payments whose external info or metadata is imported below, to refresh their search
+        // entries
+        let imported_payment_ids: Vec<String> = tx
+            .prepare(
+                "
+             SELECT payment_id
+             FROM remote_sync.payments_external_info
+             WHERE payment_id NOT IN (SELECT payment_id FROM sync.payments_external_info)
+             UNION
+             SELECT remote_sync.payments_metadata.payment_id
+             FROM remote_sync.payments_metadata
+             LEFT JOIN sync.payments_metadata
+             ON sync.payments_metadata.payment_id = remote_sync.payments_metadata.payment_id
+             WHERE
+              remote_sync.payments_metadata.updated_at > sync.payments_metadata.updated_at;",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
         // sync remote payments_external_info table
         tx.execute(
             "
@@ -172,7 +191,12 @@ impl SqliteStorage {
               attempted_amount_msat,
               attempted_error,
               lnurl_pay_domain,
-              lnurl_pay_comment
+              lnurl_pay_comment,
+              lnurl_verify_url,
+              zap_request,
+              zap_receipt,
+              zap_nostr_pubkey,
+              fiat_amount
              FROM remote_sync.payments_external_info
              WHERE payment_id NOT IN (SELECT payment_id FROM sync.payments_external_info);",
             [],
@@ -194,6 +218,111 @@ impl SqliteStorage {
             [],
         )?;
 
+        // sync remote contacts table, the latest update wins
+        tx.execute(
+            "
+             INSERT OR REPLACE INTO sync.contacts
+             SELECT
+              remote_sync.contacts.id,
+              remote_sync.contacts.name,
+              remote_sync.contacts.ln_address,
+              remote_sync.contacts.node_pubkey,
+              remote_sync.contacts.lnurl_pay_domain,
+              remote_sync.contacts.note,
+              remote_sync.contacts.created_at,
+              remote_sync.contacts.updated_at,
+              remote_sync.contacts.deleted
+             FROM remote_sync.contacts
+             LEFT JOIN sync.contacts
+             ON sync.contacts.id = remote_sync.contacts.id
+             WHERE
+              sync.contacts.id IS NULL
+              OR remote_sync.contacts.updated_at > sync.contacts.updated_at;",
+            [],
+        )?;
+
+        // sync remote greenlight_devices table, devices keep their first label and
+        // last_seen_at is only ever moved forward
+        tx.execute(
+            "
+             INSERT OR REPLACE INTO sync.greenlight_devices
+             SELECT
+              r.id,
+              IFNULL(l.label, r.label),
+              IFNULL(l.registered_at, r.registered_at),
+              NULLIF(MAX(IFNULL(r.last_seen_at, 0), IFNULL(l.last_seen_at, 0)), 0)
+             FROM remote_sync.greenlight_devices r
+             LEFT JOIN sync.greenlight_devices l
+             ON l.id = r.id
+             WHERE
+              l.id IS NULL
+              OR IFNULL(r.last_seen_at, 0) > IFNULL(l.last_seen_at, 0);",
+            [],
+        )?;
+
+        // sync remote recurring_payments table, the latest update wins
+        tx.execute(
+            "
+             INSERT OR REPLACE INTO sync.recurring_payments
+             SELECT
+              remote_sync.recurring_payments.id,
+              remote_sync.recurring_payments.destination,
+              remote_sync.recurring_payments.amount,
+              remote_sync.recurring_payments.interval,
+              remote_sync.recurring_payments.comment,
+              remote_sync.recurring_payments.start_time,
+              remote_sync.recurring_payments.next_payment_time,
+              remote_sync.recurring_payments.paused,
+              remote_sync.recurring_payments.created_at,
+              remote_sync.recurring_payments.updated_at,
+              remote_sync.recurring_payments.deleted
+             FROM remote_sync.recurring_payments
+             LEFT JOIN sync.recurring_payments
+             ON sync.recurring_payments.id = remote_sync.recurring_payments.id
+             WHERE
+              sync.recurring_payments.id IS NULL
+              OR remote_sync.recurring_payments.updated_at > sync.recurring_payments.updated_at;",
+            [],
+        )?;
+
+        // sync remote recurring_payment_runs table, a successful run is never replaced
+        tx.execute(
+            "
+             INSERT OR REPLACE INTO sync.recurring_payment_runs
+             SELECT
+              r.recurring_payment_id,
+              r.scheduled_time,
+              r.payment_id,
+              r.error,
+              r.attempts,
+              r.updated_at,
+              r.attempt_label
+             FROM remote_sync.recurring_payment_runs r
+             LEFT JOIN sync.recurring_payment_runs l
+             ON l.recurring_payment_id = r.recurring_payment_id
+              AND l.scheduled_time = r.scheduled_time
+             WHERE
+              l.recurring_payment_id IS NULL
+              OR (l.payment_id IS NULL AND (r.payment_id IS NOT NULL OR r.updated_at > l.updated_at));",
+            [],
+        )?;
+
+        // sync remote recurring_payment_claims table, the claim pushed first wins: remote claims
+        // replace local ones, and local claims are only added to the remote state when new
+        let claims_query = match to_local {
+            true => {
+                "
+             INSERT OR REPLACE INTO sync.recurring_payment_claims
+             SELECT * FROM remote_sync.recurring_payment_claims;"
+            }
+            false => {
+                "
+             INSERT OR IGNORE INTO sync.recurring_payment_claims
+             SELECT * FROM remote_sync.recurring_payment_claims;"
+            }
+        };
+        tx.execute(claims_query, [])?;
+
         // sync remote reverse_swaps table
         tx.execute(
             "
@@ -232,6 +361,12 @@ impl SqliteStorage {
         // created_at is used to settle conflicts, since we assume small variations in the client local times
         Self::sync_swaps_fees_local(&tx)?;
 
+        // Only the local storage has the payments the search entries are built from
+        if to_local {
+            let ids: Vec<&str> = imported_payment_ids.iter().map(String::as_str).collect();
+            self.index_payments_for_search(&tx, &ids)?;
+        }
+
         tx.commit()?;
         con.execute("DETACH DATABASE remote_sync", [])?;
 
@@ -270,12 +405,60 @@ mod tests {
     use rand::random;
     use std::time::Duration;
 
+    use crate::models::{
+        LnPaymentDetails, Payment, PaymentDetails, PaymentExternalInfo, PaymentStatus, PaymentType,
+    };
     use crate::persist::db::SqliteStorage;
     use crate::persist::error::PersistResult;
     use crate::persist::swap::SwapStorage;
     use crate::persist::test_utils;
     use crate::test_utils::{get_test_ofp_48h, rand_string, rand_vec_u8};
-    use crate::{ListSwapsRequest, SwapInfo};
+    use crate::{ListPaymentsRequest, ListSwapsRequest, SwapInfo};
+
+    #[test]
+    fn test_sync_indexes_imported_payments() -> PersistResult<()> {
+        let local_storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        local_storage.init()?;
+        let payment = |id: &str| Payment {
+            id: id.to_string(),
+            payment_type: PaymentType::Sent,
+            status: PaymentStatus::Complete,
+            description: Some("Rent".to_string()),
+            details: PaymentDetails::Ln {
+                data: LnPaymentDetails {
+                    payment_hash: id.to_string(),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        local_storage.insert_or_update_payments(&[payment("ab01"), payment("cd02")], false)?;
+
+        let remote_storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        remote_storage.init()?;
+        remote_storage.insert_payment_external_info(
+            "ab01",
+            PaymentExternalInfo {
+                ln_address: Some("landlord@getalby.com".to_string()),
+                ..Default::default()
+            },
+        )?;
+
+        local_storage.import_remote_changes(&remote_storage, true)?;
+        let search = |query: &str| -> PersistResult<Vec<String>> {
+            Ok(local_storage
+                .list_payments(ListPaymentsRequest {
+                    search: Some(query.to_string()),
+                    ..Default::default()
+                })?
+                .into_iter()
+                .map(|p| p.id)
+                .collect())
+        };
+        assert_eq!(search("landlord")?, vec!["ab01"]);
+        assert_eq!(search("rent")?.len(), 2);
+        Ok(())
+    }
 
     #[test]
     fn test_sync() -> PersistResult<()> {
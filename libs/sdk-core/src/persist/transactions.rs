@@ -2,8 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Type, ValueRef};
-use rusqlite::Row;
-use rusqlite::{named_params, params, OptionalExtension};
+use rusqlite::{named_params, params, Connection, OptionalExtension, Row};
 use sdk_common::prelude::*;
 use serde_json::{Map, Value};
 
@@ -13,6 +12,29 @@ use crate::models::*;
 
 const METADATA_MAX_LEN: usize = 1000;
 
+const INDEX_PAYMENTS_FOR_SEARCH: &str = "
+    INSERT INTO sync.payments_fts (
+      payment_id,
+      description,
+      label,
+      ln_address,
+      lnurl_pay_domain,
+      lnurl_pay_comment,
+      metadata
+    )
+    SELECT
+      p.id,
+      p.description,
+      json_extract(p.details, '$.label'),
+      e.ln_address,
+      e.lnurl_pay_domain,
+      e.lnurl_pay_comment,
+      m.metadata
+    FROM payments p
+    LEFT JOIN sync.payments_external_info e ON p.id = e.payment_id
+    LEFT JOIN sync.payments_metadata m ON p.id = m.payment_id
+";
+
 #[cfg_attr(test, mockall::automock)]
 pub(crate) trait PaymentStorage: Send + Sync {
     fn get_completed_payment_by_hash(&self, hash: &str) -> PersistResult<Option<Payment>>;
@@ -90,6 +112,52 @@ impl SqliteStorage {
                 &is_pseudo,
             ))?;
         }
+        let ids: Vec<&str> = transactions.iter().map(|p| p.id.as_str()).collect();
+        self.index_payments_for_search(&con, &ids)?;
+        Ok(())
+    }
+
+    /// Refreshes the full-text search entries of the given payments.
+    ///
+    /// The search index lives in the sync DB, but it also covers the description and label of
+    /// the main DB payments, so it can't be maintained by triggers.
+    pub(crate) fn index_payments_for_search(
+        &self,
+        con: &Connection,
+        payment_ids: &[&str],
+    ) -> PersistResult<()> {
+        let ids = serde_json::to_string(payment_ids)?;
+        con.execute(
+            "DELETE FROM sync.payments_fts WHERE payment_id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )?;
+        con.execute(
+            &format!("{INDEX_PAYMENTS_FOR_SEARCH} WHERE p.id IN (SELECT value FROM json_each(?1))"),
+            [&ids],
+        )?;
+        Ok(())
+    }
+
+    /// Rebuilds the full-text search index of all payments
+    pub(crate) fn rebuild_payments_search_index(&self) -> PersistResult<()> {
+        let mut con = self.get_connection()?;
+        let tx = con.transaction()?;
+        tx.execute("DELETE FROM sync.payments_fts", [])?;
+        tx.execute(INDEX_PAYMENTS_FOR_SEARCH, [])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Builds the full-text search index if it was never built, like after upgrading from a
+    /// version without it
+    pub(crate) fn ensure_payments_search_index(&self) -> PersistResult<()> {
+        let indexed = self
+            .get_connection()?
+            .prepare("SELECT 1 FROM sync.payments_fts LIMIT 1")?
+            .exists([])?;
+        if !indexed {
+            self.rebuild_payments_search_index()?;
+        }
         Ok(())
     }
 
@@ -122,9 +190,14 @@ impl SqliteStorage {
            ln_address,
            lnurl_withdraw_endpoint,
            attempted_amount_msat,
-           attempted_error
+           attempted_error,
+           lnurl_verify_url,
+           zap_request,
+           zap_receipt,
+           fiat_amount,
+           zap_nostr_pubkey
          )
-         VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9)
+         VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14)
         ",
         )?;
 
@@ -138,11 +211,76 @@ impl SqliteStorage {
             payment_external_info.lnurl_withdraw_endpoint,
             payment_external_info.attempted_amount_msat,
             payment_external_info.attempted_error,
+            payment_external_info.lnurl_verify_url,
+            payment_external_info.zap_request,
+            payment_external_info.zap_receipt,
+            payment_external_info.fiat_amount,
+            payment_external_info.zap_nostr_pubkey,
         ))?;
+        self.index_payments_for_search(&con, &[payment_hash])?;
+
+        Ok(())
+    }
+
+    /// Links the zap receipt to a zap previously stored with [Self::insert_payment_external_info]
+    pub(crate) fn set_payment_zap_receipt(
+        &self,
+        payment_hash: &str,
+        zap_receipt: &str,
+    ) -> PersistResult<()> {
+        let updated = self.get_connection()?.execute(
+            "UPDATE sync.payments_external_info SET zap_receipt = ?2 WHERE payment_id = ?1",
+            params![payment_hash, zap_receipt],
+        )?;
+        ensure_sdk!(updated == 1, PersistError::generic("Payment not found"));
+        Ok(())
+    }
 
+    /// Marks a payment as complete, recording its preimage if known. Used when the settlement is
+    /// learned from a LUD-21 verify URL before the node reports it.
+    pub(crate) fn set_payment_settled(
+        &self,
+        payment_hash: &str,
+        preimage: Option<&str>,
+    ) -> PersistResult<()> {
+        let updated = self.get_connection()?.execute(
+            "
+            UPDATE payments
+            SET
+             status = :status,
+             details = CASE
+              WHEN :preimage IS NULL THEN details
+              ELSE json_set(details, '$.payment_preimage', :preimage)
+             END
+            WHERE id = :payment_hash
+            ",
+            named_params! {
+                ":payment_hash": payment_hash,
+                ":status": PaymentStatus::Complete,
+                ":preimage": preimage,
+            },
+        )?;
+        ensure_sdk!(updated == 1, PersistError::generic("Payment not found"));
         Ok(())
     }
 
+    /// The key expected to sign the zap receipt of a zap stored with
+    /// [Self::insert_payment_external_info]
+    pub(crate) fn get_payment_zap_nostr_pubkey(
+        &self,
+        payment_hash: &str,
+    ) -> PersistResult<Option<String>> {
+        Ok(self
+            .get_connection()?
+            .query_row(
+                "SELECT zap_nostr_pubkey FROM sync.payments_external_info WHERE payment_id = ?1",
+                [payment_hash],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten())
+    }
+
     /// Updates the metadata object associated to a payment
     pub fn set_payment_external_metadata(
         &self,
@@ -168,7 +306,8 @@ impl SqliteStorage {
             return Err(PersistError::generic("Payment not found"));
         }
 
-        self.get_connection()?.execute(
+        let con = self.get_connection()?;
+        con.execute(
             "
              INSERT OR REPLACE INTO sync.payments_metadata(
                 payment_id,
@@ -182,6 +321,7 @@ impl SqliteStorage {
              );",
             params![payment_hash, new_metadata],
         )?;
+        self.index_payments_for_search(&con, &[payment_hash.as_str()])?;
 
         Ok(())
     }
@@ -232,48 +372,138 @@ impl SqliteStorage {
     /// This queries all payments. To query a single payment, see [Self::get_payment_by_hash]
     /// or [Self::get_completed_payment_by_hash]
     pub fn list_payments(&self, req: ListPaymentsRequest) -> PersistResult<Vec<Payment>> {
-        let where_clause = filter_to_where_clause(
-            req.filters,
-            &req.metadata_filters,
-            req.from_timestamp,
-            req.to_timestamp,
-            req.include_failures,
-        );
+        let mut payments = Vec::new();
+        self.for_each_payment(req, |payment| {
+            payments.push(payment);
+            Ok(())
+        })?;
+        Ok(payments)
+    }
+
+    /// Calls `f` with every payment matching the request, in the [Self::list_payments] order,
+    /// without loading them all in memory. Returns the number of payments visited.
+    pub(crate) fn for_each_payment<F>(
+        &self,
+        req: ListPaymentsRequest,
+        mut f: F,
+    ) -> PersistResult<u64>
+    where
+        F: FnMut(Payment) -> PersistResult<()>,
+    {
+        let mut params: HashMap<String, String> = HashMap::new();
+        let where_clause = filter_to_where_clause(&req, &mut params);
         let offset = req.offset.unwrap_or(0u32);
         let limit = req.limit.unwrap_or(u32::MAX);
         let con = self.get_connection()?;
         let query = self.select_payments_query(where_clause.as_str(), offset, limit)?;
         let mut stmt = con.prepare(query.as_str())?;
 
-        let mut params: HashMap<String, String> = HashMap::new();
-
-        if let Some(metadata_filters) = &req.metadata_filters {
-            metadata_filters.iter().enumerate().for_each(
-                |(
-                    i,
-                    MetadataFilter {
-                        json_path,
-                        json_value,
-                    },
-                )| {
-                    params.insert(format!(":json_path_{i}"), format!("$.{json_path}"));
-                    params.insert(format!(":json_value_{i}"), json_value.clone());
-                },
-            )
+        let rows = stmt.query_map(
+            params
+                .iter()
+                .map(|(k, v)| (k.as_str(), v as &dyn ToSql))
+                .collect::<Vec<(&str, &dyn ToSql)>>()
+                .as_slice(),
+            |row| self.sql_row_to_payment(row),
+        )?;
+        let mut count = 0;
+        for payment in rows {
+            f(payment?)?;
+            count += 1;
         }
+        Ok(count)
+    }
+
+    /// Aggregates the payments matching the request filter, grouped in time buckets if an
+    /// interval is requested.
+    ///
+    /// Amounts and fees only account for completed payments, while the counts cover all statuses.
+    pub(crate) fn payment_stats(&self, req: StatsRequest) -> PersistResult<StatsResponse> {
+        let mut params: HashMap<String, String> = HashMap::new();
+        let where_clause = filter_to_where_clause(&req.filter, &mut params);
+        let offset = req.filter.offset.unwrap_or(0u32);
+        let limit = req.filter.limit.unwrap_or(u32::MAX);
+        let payments_query = self.select_payments_query(where_clause.as_str(), offset, limit)?;
+        let bucket = match req.interval {
+            None => "0".to_string(),
+            Some(interval) => {
+                let modifiers = match interval {
+                    StatsInterval::Day => "",
+                    StatsInterval::Week => ", 'weekday 0', '-6 days'",
+                    StatsInterval::Month => ", 'start of month'",
+                };
+                format!(
+                    "CAST(strftime('%s', date(payment_time, 'unixepoch'{modifiers})) AS INTEGER)"
+                )
+            }
+        };
+        let complete = PaymentStatus::Complete as i64;
+        let query = format!(
+            "
+          SELECT
+           {bucket} AS bucket,
+           SUM(CASE WHEN status = {complete} AND payment_type != 'Received' THEN amount_msat ELSE 0 END),
+           SUM(CASE WHEN status = {complete} AND payment_type = 'Received' THEN amount_msat ELSE 0 END),
+           SUM(CASE WHEN status = {complete} AND payment_type = 'Sent' THEN fee_msat ELSE 0 END),
+           SUM(CASE WHEN status = {complete} AND payment_type = 'Received' AND payer_amount_msat IS NOT NULL
+             THEN payer_amount_msat - amount_msat ELSE 0 END),
+           SUM(CASE
+             WHEN status = {complete} AND payment_type = 'Received' AND swaps_payment_hash IS NOT NULL
+               THEN MAX(swaps_confirmed_sats * 1000 - COALESCE(payer_amount_msat, amount_msat), 0)
+             WHEN status = {complete} AND payment_type = 'Sent' AND revswaps_id IS NOT NULL
+               THEN MAX(amount_msat - revswaps_onchain_amount_sat * 1000, 0)
+             ELSE 0 END),
+           SUM(CASE WHEN status = {} THEN 1 ELSE 0 END),
+           SUM(CASE WHEN status = {complete} THEN 1 ELSE 0 END),
+           SUM(CASE WHEN status = {} THEN 1 ELSE 0 END)
+          FROM ({payments_query})
+          GROUP BY bucket
+          ORDER BY bucket
+        ",
+            PaymentStatus::Pending as i64,
+            PaymentStatus::Failed as i64,
+        );
 
-        let vec: Vec<Payment> = stmt
+        let con = self.get_connection()?;
+        let mut stmt = con.prepare(query.as_str())?;
+        let buckets = stmt
             .query_map(
                 params
                     .iter()
                     .map(|(k, v)| (k.as_str(), v as &dyn ToSql))
                     .collect::<Vec<(&str, &dyn ToSql)>>()
                     .as_slice(),
-                |row| self.sql_row_to_payment(row),
+                |row| {
+                    Ok(PaymentStatsBucket {
+                        start_timestamp: row.get(0)?,
+                        sent_msat: row.get(1)?,
+                        received_msat: row.get(2)?,
+                        routing_fees_msat: row.get(3)?,
+                        channel_opening_fees_msat: row.get(4)?,
+                        swap_fees_msat: row.get(5)?,
+                        pending_count: row.get(6)?,
+                        complete_count: row.get(7)?,
+                        failed_count: row.get(8)?,
+                    })
+                },
             )?
-            .map(|i| i.unwrap())
-            .collect();
-        Ok(vec)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut res = StatsResponse::default();
+        for b in &buckets {
+            res.total_sent_msat += b.sent_msat;
+            res.total_received_msat += b.received_msat;
+            res.routing_fees_msat += b.routing_fees_msat;
+            res.channel_opening_fees_msat += b.channel_opening_fees_msat;
+            res.swap_fees_msat += b.swap_fees_msat;
+            res.pending_count += b.pending_count;
+            res.complete_count += b.complete_count;
+            res.failed_count += b.failed_count;
+        }
+        if req.interval.is_some() {
+            res.series = buckets;
+        }
+        Ok(res)
     }
 
     pub fn select_payments_query(
@@ -308,6 +538,37 @@ impl SqliteStorage {
            m.metadata,
            e.lnurl_pay_domain,
            e.lnurl_pay_comment,
+           e.lnurl_verify_url,
+           e.zap_request,
+           e.zap_receipt,
+           e.fiat_amount,
+           (
+            SELECT c.id
+            FROM sync.contacts c
+            WHERE c.deleted = 0 AND (
+             c.ln_address = e.ln_address COLLATE NOCASE
+             OR (p.payment_type = 'Sent' AND c.node_pubkey = json_extract(p.details, '$.destination_pubkey'))
+             OR c.lnurl_pay_domain = COALESCE(
+              e.lnurl_pay_domain,
+              substr(e.ln_address, instr(e.ln_address, '@') + 1)
+             ) COLLATE NOCASE
+            )
+            ORDER BY
+             c.ln_address = e.ln_address COLLATE NOCASE DESC,
+             c.node_pubkey = json_extract(p.details, '$.destination_pubkey') DESC,
+             c.created_at
+            LIMIT 1
+           ) AS contact_id,
+           (
+            SELECT json_group_array(json_object('coin', coin, 'value', value, 'timestamp', timestamp))
+            FROM (
+             SELECT h.coin, h.value, h.timestamp
+             FROM payment_fiat_rates r
+             JOIN fiat_rates_history h ON h.timestamp = r.rates_timestamp
+             WHERE r.payment_id = p.id
+             ORDER BY h.coin
+            )
+           ) AS fiat_rates,
            {swap_fields},
            {rev_swap_fields}
           FROM payments p
@@ -327,7 +588,7 @@ impl SqliteStorage {
            ON
             json_extract(p.details, '$.payment_preimage') = hex(revswaps_preimage) COLLATE NOCASE
           {where_clause}
-          ORDER BY payment_time DESC
+          ORDER BY payment_time DESC, p.id
           LIMIT {limit}
           OFFSET {offset}
         "
@@ -349,6 +610,20 @@ impl SqliteStorage {
             .optional()?)
     }
 
+    /// Looks up the latest outgoing payment sent with the given label, which may be pending,
+    /// completed or failed
+    pub(crate) fn get_sent_payment_by_label(&self, label: &str) -> PersistResult<Option<Payment>> {
+        let query = self.select_payments_query(
+            "where payment_type = 'Sent' AND json_extract(p.details, '$.label') = ?1",
+            0,
+            1,
+        )?;
+        Ok(self
+            .get_connection()?
+            .query_row(query.as_str(), [label], |row| self.sql_row_to_payment(row))
+            .optional()?)
+    }
+
     /// Look up a modified open channel bolt11 by hash.
     #[allow(unused)]
     pub(crate) fn get_open_channel_bolt11_by_hash(
@@ -389,7 +664,11 @@ impl SqliteStorage {
             details: row.get(7)?,
             error: row.get(13)?,
             metadata: row.get(16)?,
+            fiat_rates: Vec::new(),
         };
+        let fiat_rates: String = row.get(24)?;
+        payment.fiat_rates = serde_json::from_str(&fiat_rates)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(24, Type::Text, Box::new(e)))?;
 
         if let PaymentDetails::Ln { ref mut data } = payment.details {
             let lnurl_success_action_str: Option<String> = row.get(8)?;
@@ -402,6 +681,11 @@ impl SqliteStorage {
 
             data.lnurl_pay_domain = row.get(17)?;
             data.lnurl_pay_comment = row.get(18)?;
+            data.lnurl_verify_url = row.get(19)?;
+            data.zap_request = row.get(20)?;
+            data.zap_receipt = row.get(21)?;
+            data.fiat_amount = row.get(22)?;
+            data.contact_id = row.get(23)?;
             data.lnurl_metadata = row.get(9)?;
             data.ln_address = row.get(10)?;
             data.lnurl_withdraw_endpoint = row.get(11)?;
@@ -429,26 +713,42 @@ impl SqliteStorage {
 }
 
 fn filter_to_where_clause(
-    type_filters: Option<Vec<PaymentTypeFilter>>,
-    metadata_filters: &Option<Vec<MetadataFilter>>,
-    from_timestamp: Option<i64>,
-    to_timestamp: Option<i64>,
-    include_failures: Option<bool>,
+    req: &ListPaymentsRequest,
+    params: &mut HashMap<String, String>,
 ) -> String {
     let mut where_clause: Vec<String> = Vec::new();
-    let with_failures = include_failures.unwrap_or(false);
+    let with_failures = req.include_failures.unwrap_or(false);
 
-    if let Some(t) = from_timestamp {
+    if let Some(t) = req.from_timestamp {
         where_clause.push(format!("payment_time >= {t}"));
     };
-    if let Some(t) = to_timestamp {
+    if let Some(t) = req.to_timestamp {
         where_clause.push(format!("payment_time <= {t}"));
     };
-    if !with_failures {
-        where_clause.push(format!("status != {}", PaymentStatus::Failed as i64));
+    match &req.statuses {
+        Some(statuses) => {
+            where_clause.push(format!(
+                "status in ({})",
+                statuses
+                    .iter()
+                    .map(|s| (*s as i64).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        None if !with_failures => {
+            where_clause.push(format!("status != {}", PaymentStatus::Failed as i64));
+        }
+        None => {}
+    };
+    if let Some(amount_msat) = req.min_amount_msat {
+        where_clause.push(format!("p.amount_msat >= {amount_msat}"));
+    };
+    if let Some(amount_msat) = req.max_amount_msat {
+        where_clause.push(format!("p.amount_msat <= {amount_msat}"));
     };
 
-    if let Some(filters) = type_filters {
+    if let Some(filters) = &req.filters {
         if !filters.is_empty() {
             let mut type_filter_clause: HashSet<PaymentType> = HashSet::new();
             for type_filter in filters {
@@ -476,10 +776,52 @@ fn filter_to_where_clause(
         }
     }
 
-    if let Some(filters) = metadata_filters {
-        filters.iter().enumerate().for_each(|(i, _)| {
-            where_clause.push(format!("metadata->:json_path_{i} = :json_value_{i}"));
-        });
+    if let Some(filters) = &req.metadata_filters {
+        filters.iter().enumerate().for_each(
+            |(
+                i,
+                MetadataFilter {
+                    json_path,
+                    json_value,
+                },
+            )| {
+                where_clause.push(format!("metadata->:json_path_{i} = :json_value_{i}"));
+                params.insert(format!(":json_path_{i}"), format!("$.{json_path}"));
+                params.insert(format!(":json_value_{i}"), json_value.clone());
+            },
+        );
+    }
+
+    if let Some(search) = req.search.as_deref().and_then(to_fts_query) {
+        where_clause.push(
+            "p.id in (SELECT payment_id FROM sync.payments_fts WHERE payments_fts MATCH :search)"
+                .to_string(),
+        );
+        params.insert(":search".to_string(), search);
+    }
+    if let Some(prefix) = &req.payment_hash_prefix {
+        where_clause.push(
+            "substr(p.id, 1, length(:payment_hash_prefix)) = :payment_hash_prefix".to_string(),
+        );
+        params.insert(":payment_hash_prefix".to_string(), prefix.to_lowercase());
+    }
+    if let Some(pubkey) = &req.counterparty_pubkey {
+        where_clause.push(
+            "json_extract(p.details, '$.destination_pubkey') = :counterparty_pubkey".to_string(),
+        );
+        params.insert(":counterparty_pubkey".to_string(), pubkey.clone());
+    }
+    if let Some(contact_id) = &req.contact_id {
+        where_clause.push("contact_id = :contact_id".to_string());
+        params.insert(":contact_id".to_string(), contact_id.clone());
+    }
+    // Follows the ORDER BY of the payments query
+    if let Some(cursor) = &req.cursor {
+        let t = cursor.payment_time;
+        where_clause.push(format!(
+            "(payment_time < {t} or (payment_time = {t} and p.id > :cursor_id))"
+        ));
+        params.insert(":cursor_id".to_string(), cursor.payment_id.clone());
     }
 
     let mut where_clause_str = String::new();
@@ -490,6 +832,19 @@ fn filter_to_where_clause(
     where_clause_str
 }
 
+/// Turns free text into an FTS5 query matching every word as a prefix, so that user input
+/// can't be interpreted as FTS5 query syntax
+fn to_fts_query(search: &str) -> Option<String> {
+    let terms: Vec<String> = search
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    match terms.is_empty() {
+        true => None,
+        false => Some(terms.join(" ")),
+    }
+}
+
 impl FromSql for PaymentDetails {
     fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
         serde_json::from_str(value.as_str()?).map_err(|_| FromSqlError::InvalidType)
@@ -544,6 +899,7 @@ mod test {
         let test_ln_address = "test@ln.adddress.com";
         let test_lnurl_pay_domain = "example.com";
         let test_lnurl_pay_comment = "Thank you Satoshi!";
+        let test_lnurl_verify_url = "https://example.com/lnurlp/verify/126";
         let sa = SuccessActionProcessed::Message {
             data: MessageSuccessActionData {
                 message: "test message".into(),
@@ -637,6 +993,11 @@ mod test {
                         lnurl_success_action: Some(sa.clone()),
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: Some(lnurl_metadata.to_string()),
                         ln_address: Some(test_ln_address.to_string()),
                         lnurl_withdraw_endpoint: None,
@@ -647,6 +1008,7 @@ mod test {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: payment_hash_with_lnurl_withdraw.to_string(),
@@ -668,6 +1030,11 @@ mod test {
                         lnurl_success_action: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: None,
                         ln_address: None,
                         lnurl_withdraw_endpoint: Some(lnurl_withdraw_url.to_string()),
@@ -678,6 +1045,7 @@ mod test {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: hex::encode(payment_hash_with_swap_info.clone()),
@@ -699,6 +1067,11 @@ mod test {
                         lnurl_success_action: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: None,
                         ln_address: None,
                         lnurl_withdraw_endpoint: None,
@@ -709,6 +1082,7 @@ mod test {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: hex::encode(payment_hash_with_rev_swap_info.clone()),
@@ -731,6 +1105,11 @@ mod test {
                         lnurl_metadata: None,
                         lnurl_pay_domain: None,
                         lnurl_pay_comment: None,
+                        lnurl_verify_url: None,
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         ln_address: None,
                         lnurl_withdraw_endpoint: None,
                         swap_info: None,
@@ -740,6 +1119,7 @@ mod test {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
             Payment {
                 id: payment_hash_with_lnurl_domain.to_string(),
@@ -761,6 +1141,11 @@ mod test {
                         lnurl_success_action: None,
                         lnurl_pay_domain: Some(test_lnurl_pay_domain.to_string()),
                         lnurl_pay_comment: Some(test_lnurl_pay_comment.to_string()),
+                        lnurl_verify_url: Some(test_lnurl_verify_url.to_string()),
+                        zap_request: None,
+                        zap_receipt: None,
+                        fiat_amount: None,
+                        contact_id: None,
                         lnurl_metadata: Some(lnurl_metadata.to_string()),
                         ln_address: None,
                         lnurl_withdraw_endpoint: None,
@@ -771,6 +1156,7 @@ mod test {
                     },
                 },
                 metadata: None,
+                fiat_rates: Vec::new(),
             },
         ];
         let failed_txs = [Payment {
@@ -793,6 +1179,11 @@ mod test {
                     lnurl_success_action: None,
                     lnurl_pay_domain: None,
                     lnurl_pay_comment: None,
+                    lnurl_verify_url: None,
+                    zap_request: None,
+                    zap_receipt: None,
+                    fiat_amount: None,
+                    contact_id: None,
                     lnurl_metadata: None,
                     ln_address: None,
                     lnurl_withdraw_endpoint: None,
@@ -803,6 +1194,7 @@ mod test {
                 },
             },
             metadata: None,
+            fiat_rates: Vec::new(),
         }];
         let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
         storage.init()?;
@@ -814,6 +1206,11 @@ mod test {
                 lnurl_pay_success_action: Some(sa.clone()),
                 lnurl_pay_domain: None,
                 lnurl_pay_comment: None,
+                lnurl_verify_url: None,
+                zap_request: None,
+                zap_receipt: None,
+                zap_nostr_pubkey: None,
+                fiat_amount: None,
                 lnurl_metadata: Some(lnurl_metadata.to_string()),
                 ln_address: Some(test_ln_address.to_string()),
                 lnurl_withdraw_endpoint: None,
@@ -827,6 +1224,11 @@ mod test {
                 lnurl_pay_success_action: None,
                 lnurl_pay_domain: None,
                 lnurl_pay_comment: None,
+                lnurl_verify_url: None,
+                zap_request: None,
+                zap_receipt: None,
+                zap_nostr_pubkey: None,
+                fiat_amount: None,
                 lnurl_metadata: None,
                 ln_address: None,
                 lnurl_withdraw_endpoint: Some(lnurl_withdraw_url.to_string()),
@@ -845,6 +1247,11 @@ mod test {
                 lnurl_pay_success_action: None,
                 lnurl_pay_domain: Some(test_lnurl_pay_domain.to_string()),
                 lnurl_pay_comment: Some(test_lnurl_pay_comment.to_string()),
+                lnurl_verify_url: Some(test_lnurl_verify_url.to_string()),
+                zap_request: None,
+                zap_receipt: None,
+                zap_nostr_pubkey: None,
+                fiat_amount: None,
                 lnurl_metadata: Some(lnurl_metadata.to_string()),
                 ln_address: None,
                 lnurl_withdraw_endpoint: None,
@@ -1028,4 +1435,257 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_list_payments_search_and_filters() -> PersistResult<()> {
+        use crate::models::{LnPaymentDetails, Payment, PaymentCursor, PaymentDetails};
+        use crate::persist::test_utils;
+
+        let payment = |id: &str, time: i64, amount_msat: u64, description: &str| Payment {
+            id: id.to_string(),
+            payment_type: PaymentType::Sent,
+            payment_time: time,
+            amount_msat,
+            status: PaymentStatus::Complete,
+            description: Some(description.to_string()),
+            details: PaymentDetails::Ln {
+                data: LnPaymentDetails {
+                    payment_hash: id.to_string(),
+                    label: "groceries".to_string(),
+                    destination_pubkey: format!("pubkey_{id}"),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        let mut failed = payment("ab03", 1000, 3_000, "Pizza night");
+        failed.status = PaymentStatus::Failed;
+        let txs = vec![
+            payment("ab01", 1002, 1_000, "Coffee with Satoshi"),
+            payment("cd02", 1001, 2_000, "Monthly rent"),
+            failed,
+            payment("ef04", 1001, 4_000, "Coffee beans"),
+        ];
+
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+        storage.insert_or_update_payments(&txs, false)?;
+        storage.insert_payment_external_info(
+            "cd02",
+            PaymentExternalInfo {
+                ln_address: Some("landlord@getalby.com".to_string()),
+                lnurl_pay_domain: Some("getalby.com".to_string()),
+                ..Default::default()
+            },
+        )?;
+        storage
+            .set_payment_external_metadata("ef04".to_string(), r#"{"tag":"office"}"#.to_string())?;
+
+        let list = |req: ListPaymentsRequest| -> PersistResult<Vec<String>> {
+            Ok(storage
+                .list_payments(req)?
+                .into_iter()
+                .map(|p| p.id)
+                .collect())
+        };
+
+        // Search is case insensitive, matches word prefixes and covers all indexed fields
+        let search = |query: &str| {
+            list(ListPaymentsRequest {
+                search: Some(query.to_string()),
+                include_failures: Some(true),
+                ..Default::default()
+            })
+        };
+        assert_eq!(search("coffee")?, vec!["ab01", "ef04"]);
+        assert_eq!(search("COF sato")?, vec!["ab01"]);
+        assert_eq!(search("getalby.com")?, vec!["cd02"]);
+        assert_eq!(search("landlord")?, vec!["cd02"]);
+        assert_eq!(search("office")?, vec!["ef04"]);
+        assert_eq!(search("groceries")?.len(), 4);
+        assert_eq!(search("\"pizza OR")?, Vec::<String>::new());
+        assert_eq!(search("  ")?.len(), 4);
+
+        assert_eq!(
+            list(ListPaymentsRequest {
+                min_amount_msat: Some(2_000),
+                max_amount_msat: Some(4_000),
+                ..Default::default()
+            })?,
+            vec!["cd02", "ef04"]
+        );
+        assert_eq!(
+            list(ListPaymentsRequest {
+                statuses: Some(vec![PaymentStatus::Failed]),
+                ..Default::default()
+            })?,
+            vec!["ab03"]
+        );
+        assert_eq!(
+            list(ListPaymentsRequest {
+                payment_hash_prefix: Some("AB".to_string()),
+                include_failures: Some(true),
+                ..Default::default()
+            })?,
+            vec!["ab01", "ab03"]
+        );
+        assert_eq!(
+            list(ListPaymentsRequest {
+                counterparty_pubkey: Some("pubkey_cd02".to_string()),
+                ..Default::default()
+            })?,
+            vec!["cd02"]
+        );
+
+        // Paging with a cursor, including over payments made at the same time
+        let mut pages = vec![];
+        let mut cursor = None;
+        loop {
+            let page = storage.list_payments(ListPaymentsRequest {
+                include_failures: Some(true),
+                limit: Some(2),
+                cursor: cursor.clone(),
+                ..Default::default()
+            })?;
+            match page.last() {
+                Some(last) => cursor = Some(PaymentCursor::from(last)),
+                None => break,
+            }
+            pages.push(page.into_iter().map(|p| p.id).collect::<Vec<_>>());
+        }
+        assert_eq!(pages, vec![vec!["ab01", "cd02"], vec!["ef04", "ab03"]]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payment_stats() -> PersistResult<()> {
+        use crate::models::{Payment, PaymentStatsBucket, StatsInterval, StatsRequest};
+        use crate::persist::test_utils;
+
+        const JAN_1: i64 = 1_704_067_200;
+        const DAY: i64 = 86_400;
+        let payment = |id: &str, payment_type, status, time: i64, amount_msat, fee_msat| Payment {
+            id: id.to_string(),
+            payment_type,
+            payment_time: time,
+            amount_msat,
+            fee_msat,
+            status,
+            ..Default::default()
+        };
+        let txs = vec![
+            payment(
+                "s1",
+                PaymentType::Sent,
+                PaymentStatus::Complete,
+                JAN_1 + 3600,
+                10_000,
+                100,
+            ),
+            payment(
+                "r1",
+                PaymentType::Received,
+                PaymentStatus::Complete,
+                JAN_1 + DAY,
+                20_000,
+                0,
+            ),
+            payment(
+                "s2",
+                PaymentType::Sent,
+                PaymentStatus::Failed,
+                JAN_1 + DAY,
+                5_000,
+                0,
+            ),
+            payment(
+                "s3",
+                PaymentType::Sent,
+                PaymentStatus::Pending,
+                JAN_1 + 9 * DAY,
+                1_000,
+                0,
+            ),
+        ];
+
+        let storage = SqliteStorage::new(test_utils::create_test_sql_dir());
+        storage.init()?;
+        storage.insert_or_update_payments(&txs, false)?;
+        storage.insert_open_channel_payment_info("r1", 22_000, "")?;
+
+        let stats = |interval| {
+            storage.payment_stats(StatsRequest {
+                filter: ListPaymentsRequest {
+                    include_failures: Some(true),
+                    ..Default::default()
+                },
+                interval,
+            })
+        };
+
+        let res = stats(None)?;
+        assert_eq!(res.total_sent_msat, 10_000);
+        assert_eq!(res.total_received_msat, 20_000);
+        assert_eq!(res.routing_fees_msat, 100);
+        assert_eq!(res.channel_opening_fees_msat, 2_000);
+        assert_eq!(res.swap_fees_msat, 0);
+        assert_eq!(
+            (res.pending_count, res.complete_count, res.failed_count),
+            (1, 2, 1)
+        );
+        assert!(res.series.is_empty());
+
+        let res = stats(Some(StatsInterval::Day))?;
+        assert_eq!(
+            res.series,
+            vec![
+                PaymentStatsBucket {
+                    start_timestamp: JAN_1,
+                    sent_msat: 10_000,
+                    routing_fees_msat: 100,
+                    complete_count: 1,
+                    ..Default::default()
+                },
+                PaymentStatsBucket {
+                    start_timestamp: JAN_1 + DAY,
+                    received_msat: 20_000,
+                    channel_opening_fees_msat: 2_000,
+                    complete_count: 1,
+                    failed_count: 1,
+                    ..Default::default()
+                },
+                PaymentStatsBucket {
+                    start_timestamp: JAN_1 + 9 * DAY,
+                    pending_count: 1,
+                    ..Default::default()
+                },
+            ]
+        );
+
+        // 2024-01-01 is a Monday
+        let buckets: Vec<i64> = stats(Some(StatsInterval::Week))?
+            .series
+            .iter()
+            .map(|b| b.start_timestamp)
+            .collect();
+        assert_eq!(buckets, vec![JAN_1, JAN_1 + 7 * DAY]);
+        assert_eq!(stats(Some(StatsInterval::Month))?.series.len(), 1);
+
+        // Filters apply like for list_payments
+        let res = storage.payment_stats(StatsRequest {
+            filter: ListPaymentsRequest {
+                filters: Some(vec![PaymentTypeFilter::Sent]),
+                ..Default::default()
+            },
+            interval: None,
+        })?;
+        assert_eq!(res.total_received_msat, 0);
+        assert_eq!(
+            (res.pending_count, res.complete_count, res.failed_count),
+            (1, 1, 0)
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,388 @@
+use thiserror::Error;
+
+use crate::models::{Payment, PaymentStatus, SpendingPolicy};
+use crate::persist::error::PersistError;
+
+const DAY_SEC: i64 = 86_400;
+const WEEK_SEC: i64 = 7 * DAY_SEC;
+
+/// How far back the payments history is needed to evaluate a [SpendingPolicy]
+pub(crate) const SPENDING_POLICY_WINDOW_SEC: i64 = WEEK_SEC;
+
+#[derive(Debug, Error)]
+pub enum SpendingPolicyError {
+    #[error("{0}")]
+    Violation(String),
+
+    #[error(transparent)]
+    Persist(#[from] PersistError),
+}
+
+/// The recipient of an outgoing payment, as known to the spending policy
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Payee {
+    /// Node id, Lightning Address or on-chain address
+    pub ids: Vec<String>,
+    pub domain: Option<String>,
+}
+
+impl Payee {
+    pub(crate) fn node(node_id: &str) -> Self {
+        Payee {
+            ids: vec![node_id.to_string()],
+            domain: None,
+        }
+    }
+
+    /// The payee of an LNURL-pay, identified by its Lightning Address if it has one
+    pub(crate) fn lnurl(ln_address: Option<&str>, domain: &str) -> Self {
+        let domain = ln_address
+            .and_then(|address| address.split_once('@'))
+            .map(|(_, domain)| domain)
+            .unwrap_or(domain);
+        Payee {
+            ids: ln_address.map(str::to_string).into_iter().collect(),
+            domain: Some(domain.to_string()),
+        }
+    }
+}
+
+pub(crate) struct PolicyPayment {
+    pub amount_msat: u64,
+    /// The most the payment may cost in fees
+    pub max_fee_msat: u64,
+    /// Whether `max_fee_msat` is a routing fee limit, which the policy lowers to its own, rather
+    /// than a fee known upfront, which must be within it
+    pub routed: bool,
+    pub payee: Payee,
+}
+
+impl PolicyPayment {
+    /// A Lightning payment, routed with fees of up to `max_fee_msat`
+    pub(crate) fn routed(amount_msat: u64, max_fee_msat: u64, payee: Payee) -> Self {
+        PolicyPayment {
+            amount_msat,
+            max_fee_msat,
+            routed: true,
+            payee,
+        }
+    }
+}
+
+/// Checks an outgoing payment against the policy.
+///
+/// `recent` are the payments sent within [SPENDING_POLICY_WINDOW_SEC] of `now`. Pending ones are
+/// budgeted at the most they may cost in fees, per `max_routing_fee_msat`. Returns the most the
+/// payment may cost in fees, which routed payments must be sent with.
+pub(crate) fn check_payment(
+    policy: &SpendingPolicy,
+    payment: &PolicyPayment,
+    recent: &[Payment],
+    now: i64,
+    max_routing_fee_msat: impl Fn(u64) -> u64,
+) -> Result<u64, SpendingPolicyError> {
+    let matches = |list: &[String], value: &str| list.iter().any(|e| e.eq_ignore_ascii_case(value));
+    let payee = &payment.payee;
+    let domain = payee.domain.as_deref();
+
+    if let Some(id) = payee
+        .ids
+        .iter()
+        .find(|id| matches(&policy.denied_payees, id))
+    {
+        return violation(format!("Payee {id} is denied"));
+    }
+    if let Some(domain) = domain.filter(|d| matches(&policy.denied_domains, d)) {
+        return violation(format!("Domain {domain} is denied"));
+    }
+    let restricted = !policy.allowed_payees.is_empty() || !policy.allowed_domains.is_empty();
+    let allowed = payee
+        .ids
+        .iter()
+        .any(|id| matches(&policy.allowed_payees, id))
+        || domain.is_some_and(|d| matches(&policy.allowed_domains, d));
+    if restricted && !allowed {
+        return violation("Payee is not in the allowed list".to_string());
+    }
+
+    if let Some(max) = policy
+        .max_payment_msat
+        .filter(|max| payment.amount_msat > *max)
+    {
+        return violation(format!(
+            "Amount of {} msat exceeds the payment limit of {max} msat",
+            payment.amount_msat
+        ));
+    }
+    let max_fee_msat = match policy.max_fee_msat {
+        Some(max) if payment.routed => payment.max_fee_msat.min(max),
+        Some(max) if payment.max_fee_msat > max => {
+            return violation(format!(
+                "Fees of {} msat exceed the fee limit of {max} msat",
+                payment.max_fee_msat
+            ));
+        }
+        _ => payment.max_fee_msat,
+    };
+
+    for (budget, window_sec, period) in [
+        (policy.daily_budget_msat, DAY_SEC, "daily"),
+        (policy.weekly_budget_msat, WEEK_SEC, "weekly"),
+    ] {
+        let Some(budget) = budget else {
+            continue;
+        };
+        let spent: u64 = recent
+            .iter()
+            .filter(|p| p.payment_time > now - window_sec)
+            .map(|p| p.amount_msat + budgeted_fee_msat(policy, p, &max_routing_fee_msat))
+            .sum();
+        if spent + payment.amount_msat + max_fee_msat > budget {
+            return violation(format!(
+                "Payment would exceed the {period} budget of {budget} msat, {spent} msat already spent"
+            ));
+        }
+    }
+
+    Ok(max_fee_msat)
+}
+
+/// The fee a sent payment counts with towards the budgets. Pending payments may still cost up to
+/// their routing fee limit.
+fn budgeted_fee_msat(
+    policy: &SpendingPolicy,
+    payment: &Payment,
+    max_routing_fee_msat: impl Fn(u64) -> u64,
+) -> u64 {
+    if payment.status != PaymentStatus::Pending {
+        return payment.fee_msat;
+    }
+    let max_fee_msat = max_routing_fee_msat(payment.amount_msat);
+    policy
+        .max_fee_msat
+        .map_or(max_fee_msat, |max| max_fee_msat.min(max))
+        .max(payment.fee_msat)
+}
+
+fn violation<T>(reason: String) -> Result<T, SpendingPolicyError> {
+    Err(SpendingPolicyError::Violation(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_payment, Payee, PolicyPayment, SpendingPolicyError, DAY_SEC};
+    use crate::models::{Payment, PaymentStatus, PaymentType, SpendingPolicy};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn payment(amount_msat: u64, payee: Payee) -> PolicyPayment {
+        PolicyPayment::routed(amount_msat, 1_000, payee)
+    }
+
+    fn routing_fee(amount_msat: u64) -> u64 {
+        amount_msat / 100
+    }
+
+    fn sent(amount_msat: u64, fee_msat: u64, payment_time: i64) -> Payment {
+        Payment {
+            payment_type: PaymentType::Sent,
+            status: PaymentStatus::Complete,
+            amount_msat,
+            fee_msat,
+            payment_time,
+            ..Default::default()
+        }
+    }
+
+    fn is_violation(res: Result<u64, SpendingPolicyError>) -> bool {
+        matches!(res, Err(SpendingPolicyError::Violation(_)))
+    }
+
+    #[test]
+    fn test_payee_lists() {
+        let policy = SpendingPolicy {
+            allowed_payees: vec!["02aa".to_string()],
+            allowed_domains: vec!["shop.com".to_string()],
+            denied_payees: vec!["mallory@shop.com".to_string()],
+            ..Default::default()
+        };
+
+        assert!(check_payment(
+            &policy,
+            &payment(1, Payee::node("02AA")),
+            &[],
+            NOW,
+            routing_fee
+        )
+        .is_ok());
+        assert!(is_violation(check_payment(
+            &policy,
+            &payment(1, Payee::node("02bb")),
+            &[],
+            NOW,
+            routing_fee
+        )));
+        let alice = Payee::lnurl(Some("alice@shop.com"), "lnurl.shop.com");
+        assert_eq!(alice.domain.as_deref(), Some("shop.com"));
+        assert!(check_payment(&policy, &payment(1, alice), &[], NOW, routing_fee).is_ok());
+        let mallory = Payee::lnurl(Some("Mallory@shop.com"), "shop.com");
+        assert!(is_violation(check_payment(
+            &policy,
+            &payment(1, mallory),
+            &[],
+            NOW,
+            routing_fee
+        )));
+
+        let policy = SpendingPolicy {
+            denied_domains: vec!["casino.com".to_string()],
+            ..Default::default()
+        };
+        assert!(check_payment(
+            &policy,
+            &payment(1, Payee::node("02bb")),
+            &[],
+            NOW,
+            routing_fee
+        )
+        .is_ok());
+        assert!(is_violation(check_payment(
+            &policy,
+            &payment(1, Payee::lnurl(None, "casino.com")),
+            &[],
+            NOW,
+            routing_fee
+        )));
+    }
+
+    #[test]
+    fn test_limits_and_budgets() {
+        let policy = SpendingPolicy {
+            max_payment_msat: Some(10_000),
+            max_fee_msat: Some(1_000),
+            daily_budget_msat: Some(20_000),
+            weekly_budget_msat: Some(45_000),
+            ..Default::default()
+        };
+        let payee = Payee::node("02aa");
+
+        assert!(check_payment(
+            &policy,
+            &payment(10_000, payee.clone()),
+            &[],
+            NOW,
+            routing_fee
+        )
+        .is_ok());
+        assert!(is_violation(check_payment(
+            &policy,
+            &payment(10_001, payee.clone()),
+            &[],
+            NOW,
+            routing_fee
+        )));
+
+        // Routing fees are capped at the fee limit, fees known upfront must be within it
+        let routed = PolicyPayment::routed(1_000, 20_000, payee.clone());
+        assert_eq!(
+            check_payment(&policy, &routed, &[], NOW, routing_fee).unwrap(),
+            1_000
+        );
+        let fixed = PolicyPayment {
+            routed: false,
+            ..PolicyPayment::routed(1_000, 1_001, payee.clone())
+        };
+        assert!(is_violation(check_payment(
+            &policy,
+            &fixed,
+            &[],
+            NOW,
+            routing_fee
+        )));
+
+        // Fees of past payments and the most the new one may cost count towards the budgets
+        let today = vec![sent(14_000, 1_000, NOW - 60)];
+        assert!(check_payment(
+            &policy,
+            &payment(4_000, payee.clone()),
+            &today,
+            NOW,
+            routing_fee
+        )
+        .is_ok());
+        assert!(is_violation(check_payment(
+            &policy,
+            &payment(4_001, payee.clone()),
+            &today,
+            NOW,
+            routing_fee
+        )));
+
+        // Payments older than a day only count towards the weekly budget
+        let this_week = vec![
+            sent(20_000, 0, NOW - 2 * DAY_SEC),
+            sent(20_000, 0, NOW - 3 * DAY_SEC),
+        ];
+        assert!(check_payment(
+            &policy,
+            &payment(4_000, payee.clone()),
+            &this_week,
+            NOW,
+            routing_fee
+        )
+        .is_ok());
+        assert!(is_violation(check_payment(
+            &policy,
+            &payment(4_001, payee),
+            &this_week,
+            NOW,
+            routing_fee
+        )));
+    }
+
+    #[test]
+    fn test_pending_payments_budgeted_at_max_fee() {
+        let policy = SpendingPolicy {
+            daily_budget_msat: Some(20_000),
+            ..Default::default()
+        };
+        let payee = Payee::node("02aa");
+
+        // A pending payment of 10_000 msat may still cost up to 100 msat in routing fees
+        let pending = vec![Payment {
+            status: PaymentStatus::Pending,
+            ..sent(10_000, 0, NOW - 60)
+        }];
+        assert!(check_payment(
+            &policy,
+            &payment(8_900, payee.clone()),
+            &pending,
+            NOW,
+            routing_fee
+        )
+        .is_ok());
+        assert!(is_violation(check_payment(
+            &policy,
+            &payment(8_901, payee.clone()),
+            &pending,
+            NOW,
+            routing_fee
+        )));
+
+        // The policy fee limit caps what a pending payment may cost
+        let policy = SpendingPolicy {
+            max_fee_msat: Some(50),
+            ..policy
+        };
+        let capped = PolicyPayment::routed(9_900, 1_000, payee.clone());
+        assert!(check_payment(&policy, &capped, &pending, NOW, routing_fee).is_ok());
+        let capped = PolicyPayment::routed(9_901, 1_000, payee);
+        assert!(is_violation(check_payment(
+            &policy,
+            &capped,
+            &pending,
+            NOW,
+            routing_fee
+        )));
+    }
+}
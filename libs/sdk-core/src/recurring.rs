@@ -0,0 +1,236 @@
+use chrono::{DateTime, Days, Months, Utc};
+
+use crate::models::{
+    Payment, PaymentStatus, RecurringPayment, RecurringPaymentInterval, RecurringPaymentRun,
+};
+
+/// How many times a scheduled payment is attempted before it is skipped until the next one
+pub(crate) const MAX_RECURRING_PAYMENT_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry, doubled after every failed attempt
+pub(crate) const RETRY_BACKOFF_SEC: i64 = 300;
+
+/// How long an attempt whose outcome is unknown, and which left no payment behind, is
+/// considered in flight. Past this, it never reached the node.
+pub(crate) const IN_FLIGHT_TIMEOUT_SEC: i64 = 3_600;
+
+/// How long the claim of another instance on a scheduled payment is waited on. Past this, the
+/// scheduled payment is skipped, as the other instance is gone or has given up on it.
+pub(crate) const CLAIM_TIMEOUT_SEC: i64 = 86_400;
+
+/// What became of an attempt, given the payment recorded with its label
+#[derive(Debug, PartialEq)]
+pub(crate) enum AttemptOutcome {
+    /// The payment with this id succeeded
+    Paid(String),
+    /// The payment may still succeed, so no new attempt can be made
+    InFlight,
+    Failed,
+}
+
+/// The label of the payment of an attempt, unique to the attempt
+pub(crate) fn attempt_label(run: &RecurringPaymentRun) -> String {
+    format!(
+        "recurring:{}:{}:{}",
+        run.recurring_payment_id, run.scheduled_time, run.attempts
+    )
+}
+
+/// `unsettled` is set when the attempt ended without failing outright, like on a timeout, so
+/// a payment not recorded yet may still be made
+pub(crate) fn attempt_outcome(payment: Option<&Payment>, unsettled: bool) -> AttemptOutcome {
+    match payment {
+        Some(p) if p.status == PaymentStatus::Complete => AttemptOutcome::Paid(p.id.clone()),
+        Some(p) if p.status == PaymentStatus::Pending => AttemptOutcome::InFlight,
+        Some(_) => AttemptOutcome::Failed,
+        None if unsettled => AttemptOutcome::InFlight,
+        None => AttemptOutcome::Failed,
+    }
+}
+
+/// What to do about a recurring payment that is due
+#[derive(Debug, PartialEq)]
+pub(crate) enum RunAction {
+    /// Attempt the payment
+    Pay,
+    /// Wait before the next attempt
+    Wait,
+    /// Move on to the next scheduled payment, either because this one succeeded (possibly on
+    /// another instance) or because it failed too many times
+    Advance,
+}
+
+pub(crate) fn run_action(run: Option<&RecurringPaymentRun>, now: i64) -> RunAction {
+    let Some(run) = run else {
+        return RunAction::Pay;
+    };
+    if run.payment_id.is_some() || run.attempts >= MAX_RECURRING_PAYMENT_ATTEMPTS {
+        return RunAction::Advance;
+    }
+    match now >= retry_time(run) {
+        true => RunAction::Pay,
+        false => RunAction::Wait,
+    }
+}
+
+/// When a failed attempt can be retried
+pub(crate) fn retry_time(run: &RecurringPaymentRun) -> i64 {
+    run.updated_at + (RETRY_BACKOFF_SEC << run.attempts.saturating_sub(1))
+}
+
+/// The first payment time of the schedule after `after`.
+///
+/// Payment times are always computed from the start time, so monthly payments started on the
+/// 31st are made on the last day of shorter months without drifting to an earlier day.
+pub(crate) fn next_payment_time(payment: &RecurringPayment, after: i64) -> i64 {
+    let Some(start) = DateTime::<Utc>::from_timestamp(payment.start_time, 0) else {
+        return i64::MAX;
+    };
+    let nth = |n: u32| -> Option<i64> {
+        let time = match payment.interval {
+            RecurringPaymentInterval::Daily => start.checked_add_days(Days::new(n.into())),
+            RecurringPaymentInterval::Weekly => start.checked_add_days(Days::new(7 * n as u64)),
+            RecurringPaymentInterval::Monthly => start.checked_add_months(Months::new(n)),
+        };
+        time.map(|t| t.timestamp())
+    };
+
+    // Start close to the answer rather than iterating from the start time
+    let period_sec = match payment.interval {
+        RecurringPaymentInterval::Daily => 86_400,
+        RecurringPaymentInterval::Weekly => 7 * 86_400,
+        RecurringPaymentInterval::Monthly => 31 * 86_400,
+    };
+    let mut n = ((after - payment.start_time).max(0) / period_sec) as u32;
+    loop {
+        match nth(n) {
+            Some(time) if time > after => return time,
+            Some(_) => n += 1,
+            None => return i64::MAX,
+        }
+    }
+}
+
+/// Converts a fiat amount to millisatoshis, given the price of one bitcoin
+pub(crate) fn fiat_to_msat(amount: f64, rate: f64) -> u64 {
+    (amount / rate * 100_000_000_000.0).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{
+        attempt_outcome, fiat_to_msat, next_payment_time, retry_time, run_action, AttemptOutcome,
+        RunAction,
+    };
+    use crate::models::{
+        Payment, PaymentStatus, RecurringPayment, RecurringPaymentAmount,
+        RecurringPaymentDestination, RecurringPaymentInterval, RecurringPaymentRun,
+    };
+
+    fn schedule(start_time: i64, interval: RecurringPaymentInterval) -> RecurringPayment {
+        RecurringPayment {
+            id: "1".to_string(),
+            destination: RecurringPaymentDestination::Keysend {
+                node_id: "02aa".to_string(),
+            },
+            amount: RecurringPaymentAmount::Bitcoin { amount_msat: 1_000 },
+            interval,
+            comment: None,
+            start_time,
+            next_payment_time: start_time,
+            paused: false,
+            created_at: start_time,
+            updated_at: start_time,
+        }
+    }
+
+    fn run(attempts: u32, payment_id: Option<&str>) -> RecurringPaymentRun {
+        RecurringPaymentRun {
+            recurring_payment_id: "1".to_string(),
+            scheduled_time: 0,
+            payment_id: payment_id.map(str::to_string),
+            error: None,
+            attempts,
+            updated_at: 1_000,
+            attempt_label: None,
+        }
+    }
+
+    #[test]
+    fn test_next_payment_time() {
+        let jan_31 = Utc
+            .with_ymd_and_hms(2024, 1, 31, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        let monthly = schedule(jan_31, RecurringPaymentInterval::Monthly);
+        let feb_29 = Utc
+            .with_ymd_and_hms(2024, 2, 29, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        let mar_31 = Utc
+            .with_ymd_and_hms(2024, 3, 31, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert_eq!(next_payment_time(&monthly, jan_31), feb_29);
+        assert_eq!(next_payment_time(&monthly, feb_29), mar_31);
+        // Missed payments are skipped
+        assert_eq!(next_payment_time(&monthly, feb_29 + 1), mar_31);
+        assert_eq!(next_payment_time(&monthly, jan_31 - 1), jan_31);
+
+        let weekly = schedule(jan_31, RecurringPaymentInterval::Weekly);
+        assert_eq!(next_payment_time(&weekly, jan_31), jan_31 + 7 * 86_400);
+        let daily = schedule(jan_31, RecurringPaymentInterval::Daily);
+        assert_eq!(
+            next_payment_time(&daily, jan_31 + 10 * 86_400 + 1),
+            jan_31 + 11 * 86_400
+        );
+    }
+
+    #[test]
+    fn test_run_action() {
+        assert_eq!(run_action(None, 0), RunAction::Pay);
+        assert_eq!(
+            run_action(Some(&run(1, Some("hash"))), 0),
+            RunAction::Advance
+        );
+        assert_eq!(run_action(Some(&run(5, None)), 0), RunAction::Advance);
+
+        // Retries back off exponentially
+        assert_eq!(run_action(Some(&run(1, None)), 1_299), RunAction::Wait);
+        assert_eq!(run_action(Some(&run(1, None)), 1_300), RunAction::Pay);
+        assert_eq!(run_action(Some(&run(3, None)), 2_199), RunAction::Wait);
+        assert_eq!(run_action(Some(&run(3, None)), 2_200), RunAction::Pay);
+        assert_eq!(retry_time(&run(3, None)), 2_200);
+    }
+
+    #[test]
+    fn test_attempt_outcome() {
+        let payment = |status| Payment {
+            id: "hash".to_string(),
+            status,
+            ..Default::default()
+        };
+        let complete = payment(PaymentStatus::Complete);
+        assert_eq!(
+            attempt_outcome(Some(&complete), false),
+            AttemptOutcome::Paid("hash".to_string())
+        );
+        let pending = payment(PaymentStatus::Pending);
+        assert_eq!(
+            attempt_outcome(Some(&pending), false),
+            AttemptOutcome::InFlight
+        );
+        let failed = payment(PaymentStatus::Failed);
+        assert_eq!(attempt_outcome(Some(&failed), true), AttemptOutcome::Failed);
+        assert_eq!(attempt_outcome(None, true), AttemptOutcome::InFlight);
+        assert_eq!(attempt_outcome(None, false), AttemptOutcome::Failed);
+    }
+
+    #[test]
+    fn test_fiat_to_msat() {
+        assert_eq!(fiat_to_msat(20.0, 20_000.0), 100_000_000);
+        assert_eq!(fiat_to_msat(0.01, 60_000.0), 16_667);
+    }
+}
@@ -0,0 +1,129 @@
+use gl_client::credentials::{Device, Nobody};
+use gl_client::scheduler::Scheduler;
+use gl_client::signer::model::greenlight::scheduler;
+use gl_client::signer::Signer;
+use sdk_common::prelude::Network;
+use tokio::sync::mpsc;
+
+use super::{SignerAPI, SignerError, SignerResult};
+use crate::bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
+use crate::bitcoin::secp256k1::Secp256k1;
+use crate::GreenlightCredentials;
+
+/// Signs in-process with the Greenlight VLS signer
+pub struct LocalSigner {
+    seed: Vec<u8>,
+    network: Network,
+    signer: Signer,
+}
+
+impl LocalSigner {
+    pub fn new(seed: Vec<u8>, network: Network) -> SignerResult<Self> {
+        let signer = Signer::new(seed.clone(), network.into(), Nobody::new())?;
+        Ok(Self {
+            seed,
+            network,
+            signer,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl SignerAPI for LocalSigner {
+    async fn node_id(&self) -> SignerResult<Vec<u8>> {
+        Ok(self.signer.node_id())
+    }
+
+    async fn derive_bip32_key(&self, path: Vec<ChildNumber>) -> SignerResult<ExtendedPrivKey> {
+        Ok(
+            ExtendedPrivKey::new_master(self.network.into(), &self.signer.bip32_ext_key())?
+                .derive_priv(&Secp256k1::new(), &path)?,
+        )
+    }
+
+    async fn legacy_derive_bip32_key(
+        &self,
+        path: Vec<ChildNumber>,
+    ) -> SignerResult<ExtendedPrivKey> {
+        Ok(
+            ExtendedPrivKey::new_master(self.network.into(), &self.signer.legacy_bip32_ext_key())?
+                .derive_priv(&Secp256k1::new(), &path)?,
+        )
+    }
+
+    async fn sign_message(&self, message: Vec<u8>) -> SignerResult<(Vec<u8>, u8)> {
+        let (signature, recovery_id) = self.signer.sign_message(message)?;
+        Ok((signature.to_vec(), recovery_id))
+    }
+
+    async fn sign_invoice(&self, msg: Vec<u8>) -> SignerResult<Vec<u8>> {
+        Ok(self.signer.sign_invoice(msg)?)
+    }
+
+    async fn register(
+        &self,
+        partner_credentials: Option<GreenlightCredentials>,
+        invite_code: Option<String>,
+    ) -> SignerResult<Vec<u8>> {
+        if invite_code.is_some() && partner_credentials.is_some() {
+            return Err(SignerError::Generic(
+                "Cannot specify both invite code and credentials".to_string(),
+            ));
+        }
+        let creds = match partner_credentials {
+            Some(creds) => {
+                debug!("registering with credentials");
+                Nobody {
+                    cert: creds.developer_cert,
+                    key: creds.developer_key,
+                    ..Default::default()
+                }
+            }
+            None => Nobody::new(),
+        };
+
+        let signer = Signer::new(self.seed.clone(), self.network.into(), creds.clone())?;
+        let scheduler = Scheduler::new(self.network.into(), creds).await?;
+        let register_res: scheduler::RegistrationResponse =
+            scheduler.register(&signer, invite_code).await?;
+        Ok(register_res.creds)
+    }
+
+    async fn recover(&self, device: Option<Vec<u8>>) -> SignerResult<Vec<u8>> {
+        let recover_res: scheduler::RecoveryResponse = match device {
+            Some(device) => {
+                let scheduler =
+                    Scheduler::new(self.network.into(), Device::from_bytes(device)).await?;
+                scheduler.recover(&self.signer).await?
+            }
+            None => {
+                let scheduler = Scheduler::new(self.network.into(), Nobody::new()).await?;
+                scheduler.recover(&self.signer).await?
+            }
+        };
+        Ok(recover_res.creds)
+    }
+
+    async fn upgrade(&self, device: Vec<u8>) -> SignerResult<Vec<u8>> {
+        let device = Device::from_bytes(device);
+        let scheduler = Scheduler::new(self.network.into(), device.clone()).await?;
+        Ok(device.upgrade(&scheduler, &self.signer).await?.to_bytes())
+    }
+
+    async fn create_rune(&self, restrictions: Vec<Vec<String>>) -> SignerResult<String> {
+        let restrictions = restrictions
+            .iter()
+            .map(|alternatives| alternatives.iter().map(String::as_str).collect())
+            .collect();
+        Ok(self.signer.create_rune(None, restrictions)?)
+    }
+
+    async fn run(&self, device: Vec<u8>, shutdown: mpsc::Receiver<()>) -> SignerResult<()> {
+        let signer = Signer::new(
+            self.seed.clone(),
+            self.network.into(),
+            Device::from_bytes(device),
+        )?;
+        Ok(signer.run_forever(shutdown).await?)
+    }
+}
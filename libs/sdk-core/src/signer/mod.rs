@@ -0,0 +1,118 @@
+//! Signing with the keys of the wallet seed, either in-process with [LocalSigner] or in a separate
+//! process reached over gRPC with [RemoteSigner], so the seed can be kept off the SDK host.
+
+use tokio::sync::mpsc;
+
+use crate::bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
+use crate::node_api::NodeError;
+use crate::GreenlightCredentials;
+
+#[cfg(feature = "greenlight")]
+mod local;
+mod remote;
+#[cfg(unix)]
+mod server;
+mod watch_only;
+
+#[cfg(feature = "greenlight")]
+pub use local::LocalSigner;
+pub use remote::RemoteSigner;
+#[cfg(unix)]
+pub use server::{bind, serve};
+pub use watch_only::WatchOnlySigner;
+
+mod proto {
+    tonic::include_proto!("signer");
+}
+
+pub type SignerResult<T, E = SignerError> = Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("{0}")]
+    Generic(String),
+
+    #[error("{0}")]
+    ServiceConnectivity(String),
+}
+
+impl From<anyhow::Error> for SignerError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::Generic(err.to_string())
+    }
+}
+
+impl From<crate::bitcoin::bip32::Error> for SignerError {
+    fn from(err: crate::bitcoin::bip32::Error) -> Self {
+        Self::Generic(err.to_string())
+    }
+}
+
+impl From<tonic::transport::Error> for SignerError {
+    fn from(err: tonic::transport::Error) -> Self {
+        Self::ServiceConnectivity(err.to_string())
+    }
+}
+
+impl From<tonic::Status> for SignerError {
+    fn from(status: tonic::Status) -> Self {
+        match status.code() {
+            tonic::Code::Unavailable => Self::ServiceConnectivity(status.message().to_string()),
+            _ => Self::Generic(status.message().to_string()),
+        }
+    }
+}
+
+impl From<SignerError> for tonic::Status {
+    fn from(err: SignerError) -> Self {
+        match err {
+            SignerError::Generic(err) => Self::internal(err),
+            SignerError::ServiceConnectivity(err) => Self::unavailable(err),
+        }
+    }
+}
+
+impl From<SignerError> for NodeError {
+    fn from(err: SignerError) -> Self {
+        match err {
+            SignerError::Generic(err) => Self::Generic(err),
+            SignerError::ServiceConnectivity(err) => Self::ServiceConnectivity(err),
+        }
+    }
+}
+
+/// Holds the wallet seed and signs with its keys, for the node and for the SDK itself.
+///
+/// The SDK derives its own keys through it too, like the LNURL-auth, swap and backup encryption
+/// keys. Those derived keys are handed out, the seed never leaves the signer.
+#[tonic::async_trait]
+pub trait SignerAPI: Send + Sync {
+    async fn node_id(&self) -> SignerResult<Vec<u8>>;
+    async fn derive_bip32_key(&self, path: Vec<ChildNumber>) -> SignerResult<ExtendedPrivKey>;
+    /// Derives from the legacy master key, to read data encrypted by older SDK versions
+    async fn legacy_derive_bip32_key(
+        &self,
+        path: Vec<ChildNumber>,
+    ) -> SignerResult<ExtendedPrivKey>;
+    /// Signs with the node key, returns the compact signature and its recovery id
+    async fn sign_message(&self, message: Vec<u8>) -> SignerResult<(Vec<u8>, u8)>;
+    /// Signs an hsmd `sign_invoice` message, returns the compact signature followed by the
+    /// recovery id
+    async fn sign_invoice(&self, msg: Vec<u8>) -> SignerResult<Vec<u8>>;
+    /// Registers a new Greenlight node, returns the credentials of the device
+    async fn register(
+        &self,
+        partner_credentials: Option<GreenlightCredentials>,
+        invite_code: Option<String>,
+    ) -> SignerResult<Vec<u8>>;
+    /// Recovers the Greenlight node, which issues new device credentials. When set, `device`
+    /// authenticates the request to the scheduler.
+    async fn recover(&self, device: Option<Vec<u8>>) -> SignerResult<Vec<u8>>;
+    /// Upgrades the device credentials to the latest format
+    async fn upgrade(&self, device: Vec<u8>) -> SignerResult<Vec<u8>>;
+    /// Issues a rune for the node limited by the `restrictions`, all of which must hold. A
+    /// restriction holds when any of its alternatives, like `method^list`, does.
+    async fn create_rune(&self, restrictions: Vec<Vec<String>>) -> SignerResult<String>;
+    /// Answers the signing requests of the node until the shutdown signal is received
+    async fn run(&self, device: Vec<u8>, shutdown: mpsc::Receiver<()>) -> SignerResult<()>;
+}
@@ -0,0 +1,184 @@
+use std::path::PathBuf;
+
+use tokio::sync::mpsc;
+use tonic::transport::Channel;
+
+use super::proto::signer_service_client::SignerServiceClient;
+use super::proto::{
+    CreateRuneRequest, DeriveBip32KeyRequest, NodeIdRequest, PartnerCredentials, RecoverRequest,
+    RegisterRequest, RunRequest, RuneRestriction, SignInvoiceRequest, SignMessageRequest,
+    UpgradeRequest,
+};
+use super::{SignerAPI, SignerError, SignerResult};
+use crate::bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
+use crate::GreenlightCredentials;
+
+/// Forwards the signing requests to a signer served by `signer::serve` in another process
+pub struct RemoteSigner {
+    client: SignerServiceClient<Channel>,
+}
+
+impl RemoteSigner {
+    /// Connects to the signer listening on the Unix socket of the `unix://<path>` url
+    pub async fn connect(url: String) -> SignerResult<Self> {
+        let path = url.strip_prefix("unix://").ok_or(SignerError::Generic(
+            "The remote signer url must be a unix:// socket path".into(),
+        ))?;
+        Ok(Self {
+            client: SignerServiceClient::new(connect_socket(path.into()).await?),
+        })
+    }
+
+    async fn derive(&self, path: Vec<ChildNumber>, legacy: bool) -> SignerResult<ExtendedPrivKey> {
+        let xpriv = self
+            .client
+            .clone()
+            .derive_bip32_key(DeriveBip32KeyRequest {
+                path: path.into_iter().map(u32::from).collect(),
+                legacy,
+            })
+            .await?
+            .into_inner()
+            .xpriv;
+        Ok(ExtendedPrivKey::decode(&xpriv)?)
+    }
+}
+
+#[tonic::async_trait]
+impl SignerAPI for RemoteSigner {
+    async fn node_id(&self) -> SignerResult<Vec<u8>> {
+        Ok(self
+            .client
+            .clone()
+            .node_id(NodeIdRequest {})
+            .await?
+            .into_inner()
+            .node_id)
+    }
+
+    async fn derive_bip32_key(&self, path: Vec<ChildNumber>) -> SignerResult<ExtendedPrivKey> {
+        self.derive(path, false).await
+    }
+
+    async fn legacy_derive_bip32_key(
+        &self,
+        path: Vec<ChildNumber>,
+    ) -> SignerResult<ExtendedPrivKey> {
+        self.derive(path, true).await
+    }
+
+    async fn sign_message(&self, message: Vec<u8>) -> SignerResult<(Vec<u8>, u8)> {
+        let reply = self
+            .client
+            .clone()
+            .sign_message(SignMessageRequest { message })
+            .await?
+            .into_inner();
+        let recovery_id = u8::try_from(reply.recovery_id)
+            .map_err(|_| SignerError::Generic("Invalid recovery id".to_string()))?;
+        Ok((reply.signature, recovery_id))
+    }
+
+    async fn sign_invoice(&self, msg: Vec<u8>) -> SignerResult<Vec<u8>> {
+        Ok(self
+            .client
+            .clone()
+            .sign_invoice(SignInvoiceRequest { msg })
+            .await?
+            .into_inner()
+            .signature)
+    }
+
+    async fn register(
+        &self,
+        partner_credentials: Option<GreenlightCredentials>,
+        invite_code: Option<String>,
+    ) -> SignerResult<Vec<u8>> {
+        let req = RegisterRequest {
+            partner_credentials: partner_credentials.map(|c| PartnerCredentials {
+                developer_key: c.developer_key,
+                developer_cert: c.developer_cert,
+            }),
+            invite_code,
+        };
+        Ok(self.client.clone().register(req).await?.into_inner().device)
+    }
+
+    async fn recover(&self, device: Option<Vec<u8>>) -> SignerResult<Vec<u8>> {
+        Ok(self
+            .client
+            .clone()
+            .recover(RecoverRequest { device })
+            .await?
+            .into_inner()
+            .device)
+    }
+
+    async fn upgrade(&self, device: Vec<u8>) -> SignerResult<Vec<u8>> {
+        Ok(self
+            .client
+            .clone()
+            .upgrade(UpgradeRequest { device })
+            .await?
+            .into_inner()
+            .device)
+    }
+
+    async fn create_rune(&self, restrictions: Vec<Vec<String>>) -> SignerResult<String> {
+        let restrictions = restrictions
+            .into_iter()
+            .map(|alternatives| RuneRestriction { alternatives })
+            .collect();
+        Ok(self
+            .client
+            .clone()
+            .create_rune(CreateRuneRequest { restrictions })
+            .await?
+            .into_inner()
+            .rune)
+    }
+
+    async fn run(&self, device: Vec<u8>, mut shutdown: mpsc::Receiver<()>) -> SignerResult<()> {
+        let mut stream = self
+            .client
+            .clone()
+            .run(RunRequest { device })
+            .await?
+            .into_inner();
+        // The remote signer stops once the stream is dropped
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => return Ok(()),
+                msg = stream.message() => match msg? {
+                    Some(_) => continue,
+                    None => {
+                        return Err(SignerError::ServiceConnectivity(
+                            "Remote signer stopped".to_string(),
+                        ))
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn connect_socket(path: PathBuf) -> SignerResult<Channel> {
+    use tonic::codegen::http::Uri;
+    use tonic::transport::Endpoint;
+    use tower::service_fn;
+
+    // The uri is required but unused, the connector always dials the socket
+    Ok(Endpoint::try_from("http://[::]:50051")?
+        .connect_with_connector(service_fn(move |_: Uri| {
+            tokio::net::UnixStream::connect(path.clone())
+        }))
+        .await?)
+}
+
+#[cfg(not(unix))]
+async fn connect_socket(_path: PathBuf) -> SignerResult<Channel> {
+    Err(SignerError::Generic(
+        "Remote signers are only supported on Unix".into(),
+    ))
+}
@@ -0,0 +1,365 @@
+use std::fs::{self, Permissions};
+use std::future::Future;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::net::UnixListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, UnixListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+use super::proto::signer_service_server::{SignerService, SignerServiceServer};
+use super::proto::{
+    CreateRuneReply, CreateRuneRequest, DeriveBip32KeyReply, DeriveBip32KeyRequest, DeviceReply,
+    NodeIdReply, NodeIdRequest, RecoverRequest, RegisterRequest, RunReply, RunRequest,
+    SignInvoiceReply, SignInvoiceRequest, SignMessageReply, SignMessageRequest, UpgradeRequest,
+};
+use super::{SignerAPI, SignerError, SignerResult};
+use crate::bitcoin::bip32::ChildNumber;
+use crate::GreenlightCredentials;
+
+/// The hardened purposes of the keys the SDK derives for itself: 138 for LNURL-auth, 139 for
+/// the backup and swap keys, 140 for the Greenlight credentials, 141 for NWC, 142 for zaps and
+/// 143 for the app keys. No other key, and in particular not the master key, is ever served.
+const ALLOWED_PURPOSES: [u32; 6] = [138, 139, 140, 141, 142, 143];
+
+/// Binds the Unix socket at `path` for [serve], only accessible by the current user.
+pub fn bind(path: impl AsRef<Path>) -> SignerResult<UnixListener> {
+    let path = path.as_ref();
+    let listener = UnixListener::bind(path)
+        .map_err(|e| SignerError::Generic(format!("Failed to bind {}: {e}", path.display())))?;
+    fs::set_permissions(path, Permissions::from_mode(0o600))
+        .map_err(|e| SignerError::Generic(format!("Failed to restrict the socket: {e}")))?;
+    Ok(listener)
+}
+
+/// Serves `signer` over gRPC on the socket `listener` from [bind] until `shutdown` completes,
+/// for [super::RemoteSigner] clients to connect to.
+///
+/// Only the processes of the user owning the socket are served, the others are disconnected.
+pub async fn serve(
+    signer: Arc<dyn SignerAPI>,
+    listener: UnixListener,
+    shutdown: impl Future<Output = ()>,
+) -> SignerResult<()> {
+    let owner = listener
+        .local_addr()
+        .ok()
+        .and_then(|addr| addr.as_pathname().map(Path::to_path_buf))
+        .and_then(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.uid())
+        .ok_or(SignerError::Generic("The socket has no path".into()))?;
+    // The socket permissions are only set after binding, so the peers are checked as well
+    let incoming = UnixListenerStream::new(listener).filter(move |conn| match conn {
+        Ok(stream) => match stream.peer_cred() {
+            Ok(cred) if cred.uid() == owner => true,
+            Ok(cred) => {
+                warn!("Rejected a signer client of user {}", cred.uid());
+                false
+            }
+            Err(e) => {
+                warn!("Rejected a signer client without credentials: {e}");
+                false
+            }
+        },
+        Err(_) => true,
+    });
+    Server::builder()
+        .add_service(SignerServiceServer::new(SignerServer { signer }))
+        .serve_with_incoming_shutdown(incoming, shutdown)
+        .await?;
+    Ok(())
+}
+
+fn ensure_allowed_path(path: &[ChildNumber]) -> Result<(), Status> {
+    match path.first() {
+        Some(ChildNumber::Hardened { index }) if ALLOWED_PURPOSES.contains(index) => Ok(()),
+        _ => Err(Status::permission_denied(
+            "Only the keys used by the SDK can be derived",
+        )),
+    }
+}
+
+struct SignerServer {
+    signer: Arc<dyn SignerAPI>,
+}
+
+#[tonic::async_trait]
+impl SignerService for SignerServer {
+    type RunStream = Pin<Box<dyn Stream<Item = Result<RunReply, Status>> + Send>>;
+
+    async fn node_id(&self, _: Request<NodeIdRequest>) -> Result<Response<NodeIdReply>, Status> {
+        Ok(Response::new(NodeIdReply {
+            node_id: self.signer.node_id().await?,
+        }))
+    }
+
+    async fn derive_bip32_key(
+        &self,
+        request: Request<DeriveBip32KeyRequest>,
+    ) -> Result<Response<DeriveBip32KeyReply>, Status> {
+        let req = request.into_inner();
+        let path: Vec<ChildNumber> = req.path.into_iter().map(ChildNumber::from).collect();
+        ensure_allowed_path(&path)?;
+        let xpriv = match req.legacy {
+            true => self.signer.legacy_derive_bip32_key(path).await?,
+            false => self.signer.derive_bip32_key(path).await?,
+        };
+        Ok(Response::new(DeriveBip32KeyReply {
+            xpriv: xpriv.encode().to_vec(),
+        }))
+    }
+
+    async fn sign_message(
+        &self,
+        request: Request<SignMessageRequest>,
+    ) -> Result<Response<SignMessageReply>, Status> {
+        let (signature, recovery_id) = self
+            .signer
+            .sign_message(request.into_inner().message)
+            .await?;
+        Ok(Response::new(SignMessageReply {
+            signature,
+            recovery_id: recovery_id.into(),
+        }))
+    }
+
+    async fn sign_invoice(
+        &self,
+        request: Request<SignInvoiceRequest>,
+    ) -> Result<Response<SignInvoiceReply>, Status> {
+        Ok(Response::new(SignInvoiceReply {
+            signature: self.signer.sign_invoice(request.into_inner().msg).await?,
+        }))
+    }
+
+    async fn register(
+        &self,
+        request: Request<RegisterRequest>,
+    ) -> Result<Response<DeviceReply>, Status> {
+        let req = request.into_inner();
+        let partner_credentials = req.partner_credentials.map(|c| GreenlightCredentials {
+            developer_key: c.developer_key,
+            developer_cert: c.developer_cert,
+        });
+        Ok(Response::new(DeviceReply {
+            device: self
+                .signer
+                .register(partner_credentials, req.invite_code)
+                .await?,
+        }))
+    }
+
+    async fn recover(
+        &self,
+        request: Request<RecoverRequest>,
+    ) -> Result<Response<DeviceReply>, Status> {
+        Ok(Response::new(DeviceReply {
+            device: self.signer.recover(request.into_inner().device).await?,
+        }))
+    }
+
+    async fn upgrade(
+        &self,
+        request: Request<UpgradeRequest>,
+    ) -> Result<Response<DeviceReply>, Status> {
+        Ok(Response::new(DeviceReply {
+            device: self.signer.upgrade(request.into_inner().device).await?,
+        }))
+    }
+
+    async fn create_rune(
+        &self,
+        request: Request<CreateRuneRequest>,
+    ) -> Result<Response<CreateRuneReply>, Status> {
+        let restrictions = request
+            .into_inner()
+            .restrictions
+            .into_iter()
+            .map(|restriction| restriction.alternatives)
+            .collect();
+        Ok(Response::new(CreateRuneReply {
+            rune: self.signer.create_rune(restrictions).await?,
+        }))
+    }
+
+    async fn run(&self, request: Request<RunRequest>) -> Result<Response<Self::RunStream>, Status> {
+        let device = request.into_inner().device;
+        let signer = self.signer.clone();
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+            let run = signer.run(device, shutdown_rx);
+            tokio::pin!(run);
+            // The client stops the signer by dropping the stream, which closes the channel
+            let res = tokio::select! {
+                res = &mut run => res,
+                _ = tx.closed() => {
+                    let _ = shutdown_tx.send(()).await;
+                    run.await
+                }
+            };
+            match res {
+                Ok(_) => info!("Remote signer run ended"),
+                Err(e) => {
+                    error!("Remote signer run failed: {e}");
+                    let _ = tx.send(Err(e.into())).await;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Arc;
+
+    use tokio::sync::{mpsc, oneshot};
+
+    use crate::bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
+    use crate::bitcoin::secp256k1::Secp256k1;
+    use crate::bitcoin::Network;
+    use crate::signer::{bind, serve, RemoteSigner, SignerAPI, SignerError, SignerResult};
+    use crate::GreenlightCredentials;
+
+    /// Derives with plain BIP32 from a fixed seed, and runs until shut down
+    struct TestSigner;
+
+    impl TestSigner {
+        fn master(&self, legacy: bool) -> SignerResult<ExtendedPrivKey> {
+            Ok(ExtendedPrivKey::new_master(
+                Network::Bitcoin,
+                &[legacy as u8; 32],
+            )?)
+        }
+    }
+
+    #[tonic::async_trait]
+    impl SignerAPI for TestSigner {
+        async fn node_id(&self) -> SignerResult<Vec<u8>> {
+            Ok(vec![2; 33])
+        }
+
+        async fn derive_bip32_key(&self, path: Vec<ChildNumber>) -> SignerResult<ExtendedPrivKey> {
+            Ok(self.master(false)?.derive_priv(&Secp256k1::new(), &path)?)
+        }
+
+        async fn legacy_derive_bip32_key(
+            &self,
+            path: Vec<ChildNumber>,
+        ) -> SignerResult<ExtendedPrivKey> {
+            Ok(self.master(true)?.derive_priv(&Secp256k1::new(), &path)?)
+        }
+
+        async fn sign_message(&self, message: Vec<u8>) -> SignerResult<(Vec<u8>, u8)> {
+            Ok((message, 1))
+        }
+
+        async fn sign_invoice(&self, _msg: Vec<u8>) -> SignerResult<Vec<u8>> {
+            Err(SignerError::ServiceConnectivity("offline".to_string()))
+        }
+
+        async fn register(
+            &self,
+            _partner_credentials: Option<GreenlightCredentials>,
+            invite_code: Option<String>,
+        ) -> SignerResult<Vec<u8>> {
+            Ok(invite_code.unwrap_or_default().into_bytes())
+        }
+
+        async fn recover(&self, device: Option<Vec<u8>>) -> SignerResult<Vec<u8>> {
+            Ok(device.unwrap_or_default())
+        }
+
+        async fn upgrade(&self, device: Vec<u8>) -> SignerResult<Vec<u8>> {
+            Ok(device)
+        }
+
+        async fn create_rune(&self, restrictions: Vec<Vec<String>>) -> SignerResult<String> {
+            let restrictions: Vec<String> = restrictions.iter().map(|r| r.join("|")).collect();
+            Ok(restrictions.join("&"))
+        }
+
+        async fn run(
+            &self,
+            _device: Vec<u8>,
+            mut shutdown: mpsc::Receiver<()>,
+        ) -> SignerResult<()> {
+            shutdown.recv().await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer() -> SignerResult<()> {
+        let socket = std::env::temp_dir().join(format!("signer-{}.sock", rand::random::<u32>()));
+        let listener = bind(&socket)?;
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve(Arc::new(TestSigner), listener, async {
+            let _ = stop_rx.await;
+        }));
+
+        let remote = RemoteSigner::connect(format!("unix://{}", socket.display())).await?;
+        let path = vec![
+            ChildNumber::from_hardened_idx(139)?,
+            ChildNumber::from_normal_idx(0)?,
+        ];
+        assert_eq!(
+            remote.derive_bip32_key(path.clone()).await?,
+            TestSigner.derive_bip32_key(path.clone()).await?
+        );
+        assert_eq!(
+            remote.legacy_derive_bip32_key(path.clone()).await?,
+            TestSigner.legacy_derive_bip32_key(path).await?
+        );
+        // Neither the master key nor keys outside of the SDK purposes are served
+        assert!(remote.derive_bip32_key(vec![]).await.is_err());
+        assert!(remote.legacy_derive_bip32_key(vec![]).await.is_err());
+        assert!(remote
+            .derive_bip32_key(vec![ChildNumber::from_hardened_idx(84)?])
+            .await
+            .is_err());
+        assert!(remote
+            .derive_bip32_key(vec![ChildNumber::from_normal_idx(139)?])
+            .await
+            .is_err());
+        assert_eq!(remote.node_id().await?, vec![2; 33]);
+        assert_eq!(remote.sign_message(vec![7]).await?, (vec![7], 1));
+        assert_eq!(
+            remote.register(None, Some("code".to_string())).await?,
+            b"code".to_vec()
+        );
+        assert_eq!(remote.recover(Some(vec![1])).await?, vec![1]);
+        assert_eq!(
+            remote
+                .create_rune(vec![
+                    vec!["method^list".to_string(), "method^get".to_string()],
+                    vec!["pnum<10".to_string()],
+                ])
+                .await?,
+            "method^list|method^get&pnum<10"
+        );
+        assert!(matches!(
+            remote.sign_invoice(vec![]).await,
+            Err(SignerError::ServiceConnectivity(_))
+        ));
+
+        let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let run = tokio::spawn(async move { remote.run(vec![], shutdown_rx).await });
+        shutdown_tx.send(()).await.unwrap();
+        assert!(run.await.unwrap().is_ok());
+
+        stop_tx.send(()).unwrap();
+        server.await.unwrap()?;
+        std::fs::remove_file(socket).unwrap();
+        Ok(())
+    }
+}
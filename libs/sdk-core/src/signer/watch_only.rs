@@ -0,0 +1,75 @@
+use tokio::sync::mpsc;
+
+use super::{SignerAPI, SignerError, SignerResult};
+use crate::bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
+use crate::GreenlightCredentials;
+
+/// Stands in for the signer of a watch-only instance, which has no seed. It only knows the node
+/// id, every other request fails.
+pub struct WatchOnlySigner {
+    node_id: Vec<u8>,
+}
+
+impl WatchOnlySigner {
+    pub fn new(node_id: Vec<u8>) -> Self {
+        Self { node_id }
+    }
+}
+
+fn read_only<T>() -> SignerResult<T> {
+    Err(SignerError::Generic(
+        "Watch-only instances have no seed to sign with".to_string(),
+    ))
+}
+
+#[tonic::async_trait]
+impl SignerAPI for WatchOnlySigner {
+    async fn node_id(&self) -> SignerResult<Vec<u8>> {
+        Ok(self.node_id.clone())
+    }
+
+    async fn derive_bip32_key(&self, _path: Vec<ChildNumber>) -> SignerResult<ExtendedPrivKey> {
+        read_only()
+    }
+
+    async fn legacy_derive_bip32_key(
+        &self,
+        _path: Vec<ChildNumber>,
+    ) -> SignerResult<ExtendedPrivKey> {
+        read_only()
+    }
+
+    async fn sign_message(&self, _message: Vec<u8>) -> SignerResult<(Vec<u8>, u8)> {
+        read_only()
+    }
+
+    async fn sign_invoice(&self, _msg: Vec<u8>) -> SignerResult<Vec<u8>> {
+        read_only()
+    }
+
+    async fn register(
+        &self,
+        _partner_credentials: Option<GreenlightCredentials>,
+        _invite_code: Option<String>,
+    ) -> SignerResult<Vec<u8>> {
+        read_only()
+    }
+
+    async fn recover(&self, _device: Option<Vec<u8>>) -> SignerResult<Vec<u8>> {
+        read_only()
+    }
+
+    async fn upgrade(&self, _device: Vec<u8>) -> SignerResult<Vec<u8>> {
+        read_only()
+    }
+
+    async fn create_rune(&self, _restrictions: Vec<Vec<String>>) -> SignerResult<String> {
+        read_only()
+    }
+
+    /// Nothing to answer, the node can't sign while only watch-only instances are connected
+    async fn run(&self, _device: Vec<u8>, mut shutdown: mpsc::Receiver<()>) -> SignerResult<()> {
+        shutdown.recv().await;
+        Ok(())
+    }
+}
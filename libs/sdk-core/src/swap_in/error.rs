@@ -82,6 +82,7 @@ impl From<SdkError> for ReceiveSwapError {
         match e {
             SdkError::Generic { err } => ReceiveSwapError::Generic(err),
             SdkError::ServiceConnectivity { err } => ReceiveSwapError::ServiceConnectivity(err),
+            SdkError::ReadOnly { err } => ReceiveSwapError::Generic(err),
         }
     }
 }
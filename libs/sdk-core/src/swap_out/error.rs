@@ -115,6 +115,7 @@ impl From<SdkError> for ReverseSwapError {
         match value {
             SdkError::Generic { err } => Self::Generic(err),
             SdkError::ServiceConnectivity { err } => Self::ServiceConnectivity(err),
+            SdkError::ReadOnly { err } => Self::Generic(err),
         }
     }
 }
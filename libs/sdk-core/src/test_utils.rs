@@ -46,20 +46,22 @@ use crate::models::{
     ReverseSwapServiceAPI, SwapperAPI, SyncResponse, TlvEntry,
 };
 use crate::node_api::{
-    CreateInvoiceRequest, FetchBolt11Result, IncomingPayment, NodeAPI, NodeError, NodeResult,
+    CreateInvoiceRequest, FetchBolt11Result, HeldHtlcs, HoldInvoiceRequest, IncomingPayment,
+    IssuedDeviceCredentials, NodeAPI, NodeError, NodeLockStatus, NodeResult,
 };
 use crate::swap_in::TaprootSwapperAPI;
 use crate::swap_out::boltzswap::{BoltzApiCreateReverseSwapResponse, BoltzApiReverseSwapStatus};
 use crate::swap_out::error::{ReverseSwapError, ReverseSwapResult};
 use crate::{
-    parse_invoice, BuyBitcoinProvider, Config, CustomMessage, LNInvoice, MaxChannelAmount,
-    NodeCredentials, OpeningFeeParamsMenu, PaymentResponse, PrepareRedeemOnchainFundsRequest,
-    PrepareRedeemOnchainFundsResponse, ReceivePaymentRequest, ReverseSwapPairInfo, RouteHint,
-    RouteHintHop, SwapInfo,
+    parse_invoice, BuyBitcoinProvider, Config, CustomMessage, GreenlightDeviceCredentials,
+    LNInvoice, MaxChannelAmount, NodeCredentials, OpeningFeeParamsMenu, PaymentResponse,
+    PrepareRedeemOnchainFundsRequest, PrepareRedeemOnchainFundsResponse, ReceivePaymentRequest,
+    ReverseSwapPairInfo, RouteHint, RouteHintHop, SwapInfo,
 };
 
 pub const MOCK_REVERSE_SWAP_MIN: u64 = 50_000;
 pub const MOCK_REVERSE_SWAP_MAX: u64 = 1_000_000;
+pub const MOCK_DEVICE_ID: &str = "mock_device";
 
 pub struct MockBackupTransport {
     pub num_pushed: std::sync::Mutex<u32>,
@@ -306,6 +308,10 @@ pub struct MockNodeAPI {
     node_state: NodeState,
     on_send_custom_message: Box<dyn Fn(CustomMessage) -> NodeResult<()> + Sync + Send>,
     on_stream_custom_messages: Mutex<mpsc::Receiver<CustomMessage>>,
+    /// The routing fee limits passed when sending, in order
+    pub max_fees_msat: Mutex<Vec<Option<u64>>>,
+    /// The peers whose channels were closed, in order
+    pub closed_peers: Mutex<Vec<String>>,
 }
 
 #[tonic::async_trait]
@@ -361,7 +367,9 @@ impl NodeAPI for MockNodeAPI {
         bolt11: String,
         _amount_msat: Option<u64>,
         _label: Option<String>,
+        max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment> {
+        self.max_fees_msat.lock().await.push(max_fee_msat);
         let payment = self.add_dummy_payment_for(bolt11, None, None).await?;
         Ok(payment)
     }
@@ -383,7 +391,9 @@ impl NodeAPI for MockNodeAPI {
         _amount_msat: u64,
         _extra_tlvs: Option<Vec<TlvEntry>>,
         _label: Option<String>,
+        max_fee_msat: Option<u64>,
     ) -> NodeResult<Payment> {
+        self.max_fees_msat.lock().await.push(max_fee_msat);
         let payment = self.add_dummy_payment_rand().await?;
         Ok(payment)
     }
@@ -402,9 +412,21 @@ impl NodeAPI for MockNodeAPI {
 
     async fn prepare_redeem_onchain_funds(
         &self,
-        _req: PrepareRedeemOnchainFundsRequest,
+        req: PrepareRedeemOnchainFundsRequest,
     ) -> NodeResult<PrepareRedeemOnchainFundsResponse> {
-        Err(NodeError::Generic("Not implemented".to_string()))
+        // A sweep of the node UTXOs into a single P2WPKH output
+        let inputs = self.node_state.utxos.len().max(1) as u64;
+        let tx_weight = 166 + inputs * 272;
+        let tx_fee_sat = (tx_weight * u64::from(req.sat_per_vbyte)).div_ceil(4);
+        if tx_fee_sat >= self.node_state.onchain_balance_msat / 1_000 {
+            return Err(NodeError::InsufficientFunds(
+                "Insufficient funds to pay fees".to_string(),
+            ));
+        }
+        Ok(PrepareRedeemOnchainFundsResponse {
+            tx_weight,
+            tx_fee_sat,
+        })
     }
 
     async fn start(&self, _shutdown: mpsc::Receiver<()>) {}
@@ -432,8 +454,9 @@ impl NodeAPI for MockNodeAPI {
         Ok(sign_invoice(invoice))
     }
 
-    async fn close_peer_channels(&self, _node_id: String) -> NodeResult<Vec<String>> {
-        Ok(vec![])
+    async fn close_peer_channels(&self, node_id: String) -> NodeResult<Vec<String>> {
+        self.closed_peers.lock().await.push(node_id);
+        Ok(vec![hex::encode(rand_vec_u8(32))])
     }
     async fn stream_incoming_payments(
         &self,
@@ -445,6 +468,56 @@ impl NodeAPI for MockNodeAPI {
         Err(NodeError::Generic("Not implemented".to_string()))
     }
 
+    async fn create_hold_invoice(&self, request: HoldInvoiceRequest) -> NodeResult<String> {
+        let invoice = create_invoice_for_hash(
+            request.description,
+            request.amount_msat,
+            request.payment_hash,
+        );
+        Ok(invoice.bolt11)
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: Vec<u8>) -> NodeResult<()> {
+        Ok(())
+    }
+
+    async fn cancel_hold_invoice(&self, _payment_hash: Vec<u8>) -> NodeResult<()> {
+        Ok(())
+    }
+
+    async fn stream_held_htlcs(&self) -> NodeResult<Pin<Box<dyn Stream<Item = HeldHtlcs> + Send>>> {
+        Err(NodeError::Generic("Not implemented".to_string()))
+    }
+
+    async fn stream_node_lock_status(
+        &self,
+    ) -> NodeResult<Pin<Box<dyn Stream<Item = NodeLockStatus> + Send>>> {
+        Ok(Box::pin(futures::stream::pending()))
+    }
+
+    async fn take_over_node_lock(&self) -> NodeResult<()> {
+        Ok(())
+    }
+
+    async fn device_id(&self) -> NodeResult<String> {
+        Ok(MOCK_DEVICE_ID.to_string())
+    }
+
+    async fn issue_device_credentials(&self) -> NodeResult<IssuedDeviceCredentials> {
+        Ok(IssuedDeviceCredentials {
+            device_id: rand_string(16),
+            credentials: NodeCredentials::Greenlight {
+                credentials: GreenlightDeviceCredentials {
+                    device: rand_vec_u8(32),
+                },
+            },
+        })
+    }
+
+    async fn issue_watch_only_credentials(&self) -> NodeResult<IssuedDeviceCredentials> {
+        self.issue_device_credentials().await
+    }
+
     async fn static_backup(&self) -> NodeResult<Vec<String>> {
         Ok(Vec::new())
     }
@@ -519,6 +592,8 @@ impl MockNodeAPI {
                 let (_, rx) = mpsc::channel(1);
                 Mutex::new(rx)
             },
+            max_fees_msat: Mutex::new(Vec::new()),
+            closed_peers: Mutex::new(Vec::new()),
         }
     }
     /// Creates a (simulated) payment for the specified BOLT11 and adds it to a test-specific
@@ -578,6 +653,11 @@ impl MockNodeAPI {
                     lnurl_success_action: None,
                     lnurl_pay_domain: None,
                     lnurl_pay_comment: None,
+                    lnurl_verify_url: None,
+                    zap_request: None,
+                    zap_receipt: None,
+                    fiat_amount: None,
+                    contact_id: None,
                     lnurl_metadata: None,
                     ln_address: None,
                     lnurl_withdraw_endpoint: None,
@@ -588,6 +668,8 @@ impl MockNodeAPI {
                 },
             },
             metadata: None,
+            fiat_rates: Vec::new(),
+            fiat_rates: Vec::new(),
         };
 
         self.save_payment_for_future_sync_updates(payment).await
@@ -839,6 +921,24 @@ pub fn create_invoice(
     parse_invoice(&sign_invoice(raw_invoice)).unwrap()
 }
 
+pub fn create_invoice_for_hash(
+    description: String,
+    amount_msat: u64,
+    payment_hash: Vec<u8>,
+) -> LNInvoice {
+    let raw_invoice = InvoiceBuilder::new(Currency::Bitcoin)
+        .description(description)
+        .payment_hash(sha256::Hash::from_slice(&payment_hash).unwrap())
+        .timestamp(SystemTime::now())
+        .amount_milli_satoshis(amount_msat)
+        .expiry_time(Duration::new(3600, 0))
+        .payment_secret(PaymentSecret(rand::thread_rng().gen::<[u8; 32]>()))
+        .min_final_cltv_expiry_delta(32)
+        .build_raw()
+        .unwrap();
+    parse_invoice(&sign_invoice(raw_invoice)).unwrap()
+}
+
 fn sign_invoice(invoice: RawBolt11Invoice) -> String {
     let secp = Secp256k1::new();
     let (secret_key, _) = secp.generate_keypair(&mut OsRng);
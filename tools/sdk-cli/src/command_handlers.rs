@@ -92,6 +92,7 @@ impl CommandHandler {
                 partner_key,
                 invite_code,
                 restore_only,
+                remote_signer,
             } => {
                 let mut config = self
                     .persistence
@@ -114,10 +115,16 @@ impl CommandHandler {
                     },
                 };
 
+                // The remote signer holds the seed, none is created locally
+                let seed = match remote_signer {
+                    Some(_) => Vec::new(),
+                    None => self.persistence.get_or_create_seed(),
+                };
                 self.connect(ConnectRequest {
                     config,
-                    seed: self.persistence.get_or_create_seed(),
+                    seed,
                     restore_only: Some(restore_only),
+                    remote_signer_url: remote_signer,
                 })
                 .await?;
                 Ok("Node was connected successfully".to_string())
@@ -38,6 +38,10 @@ pub(crate) enum Commands {
         /// Only restore existing nodes
         #[clap(short = 'r', long = "restore_only")]
         restore_only: bool,
+
+        /// The optional `unix://<path>` socket of a remote signer holding the seed, see `tools/sdk-signer`
+        #[clap(name = "remote_signer", short = 's', long = "remote_signer")]
+        remote_signer: Option<String>,
     },
 
     /// [pay] Send a lightning payment
@@ -0,0 +1,58 @@
+#[macro_use]
+extern crate log;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use bip39::{Language, Mnemonic, Seed};
+use breez_sdk_core::signer::{bind, serve, LocalSigner};
+use breez_sdk_core::Network;
+use clap::Parser;
+
+/// Reference signer serving the keys of a mnemonic to the SDK, connect to it with
+/// `ConnectRequest::remote_signer_url`
+#[derive(Parser)]
+#[command(version, about)]
+struct SignerArgs {
+    /// File containing the BIP39 mnemonic of the node
+    #[clap(long = "phrase_file", default_value = "phrase")]
+    phrase_file: PathBuf,
+
+    /// The network of the node (bitcoin|testnet|signet|regtest)
+    #[clap(long = "network", default_value = "bitcoin")]
+    network: String,
+
+    /// Unix socket to listen on, only accessible by the current user
+    #[clap(long = "socket", default_value = "signer.sock")]
+    socket: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+    let args = SignerArgs::parse();
+
+    let network = match args.network.as_str() {
+        "bitcoin" => Network::Bitcoin,
+        "testnet" => Network::Testnet,
+        "signet" => Network::Signet,
+        "regtest" => Network::Regtest,
+        other => return Err(anyhow!("Unknown network: {other}")),
+    };
+    let phrase = fs::read_to_string(&args.phrase_file)?;
+    let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English)?;
+    let seed = Seed::new(&mnemonic, "").as_bytes().to_vec();
+    let signer = LocalSigner::new(seed, network)?;
+
+    let listener = bind(&args.socket)?;
+    info!("Signer listening on {}", args.socket.display());
+    serve(Arc::new(signer), listener, async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await?;
+    fs::remove_file(&args.socket)?;
+    info!("Signer stopped");
+    Ok(())
+}